@@ -0,0 +1,573 @@
+use ark_accumulation::r1cs_nark_as::r1cs_nark::{
+    matrix_triple_mul, matrix_vec_mul_dispatch, transpose, transposed_matrix_vec_mul, CsrMatrix,
+    MatrixRepr, PoseidonMerkleTree, PrecomputedVerifier, R1CSNark,
+};
+use ark_bn254::{Fq, Fr, G1Affine};
+use ark_ff::{One, PrimeField};
+use ark_relations::{
+    lc,
+    r1cs::{
+        ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, LinearCombination, Matrix,
+        SynthesisError, Variable,
+    },
+};
+use ark_serialize::CanonicalSerialize;
+use ark_sponge::poseidon::PoseidonSponge;
+use ark_std::rand::{rngs::StdRng, RngCore, SeedableRng};
+use ark_std::UniformRand;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+type Nark = R1CSNark<G1Affine, PoseidonSponge<Fq>>;
+
+const SIZES: [usize; 5] = [1 << 10, 1 << 12, 1 << 14, 1 << 16, 1 << 20];
+const BATCH_SIZES: [usize; 3] = [1, 10, 100];
+
+// A satisfiable circuit with `num_constraints` random constraints of the form
+// `(x_1 + ... + x_4) * (y_1 + ... + y_5) = z`, so every row has about 10 non-zero entries. The
+// matrices are determined by `seed`, and the values of the variables by `values_seed`, so that
+// circuits with the same `seed` share an index.
+#[derive(Clone, Copy)]
+struct RandomSparseCircuit {
+    num_constraints: usize,
+    seed: u64,
+    values_seed: u64,
+}
+
+impl RandomSparseCircuit {
+    fn input(&self) -> Vec<Fr> {
+        let rng = &mut StdRng::seed_from_u64(self.values_seed);
+        vec![Fr::one(), Fr::rand(rng)]
+    }
+
+    fn witness(&self) -> Vec<Fr> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        self.generate_constraints(cs.clone()).unwrap();
+        let witness = cs.borrow().unwrap().witness_assignment.clone();
+        witness
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for RandomSparseCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let rng = &mut StdRng::seed_from_u64(self.seed);
+        let values_rng = &mut StdRng::seed_from_u64(self.values_seed);
+        let input_value = F::rand(values_rng);
+        let input = cs.new_input_variable(|| Ok(input_value))?;
+
+        let mut values = Vec::with_capacity(self.num_constraints);
+        let mut variables = Vec::with_capacity(self.num_constraints);
+        values.push(input_value);
+        variables.push(cs.new_witness_variable(|| Ok(input_value))?);
+        cs.enforce_constraint(lc!() + variables[0], lc!() + Variable::One, lc!() + input)?;
+        for _ in 1..self.num_constraints {
+            let value = F::rand(values_rng);
+            values.push(value);
+            variables.push(cs.new_witness_variable(|| Ok(value))?);
+        }
+
+        for _ in 1..self.num_constraints {
+            let mut sample_lc = |num_terms: usize| {
+                let mut lc = LinearCombination::zero();
+                let mut value = F::zero();
+                for _ in 0..num_terms {
+                    let i = rng.next_u32() as usize % values.len();
+                    lc = lc + variables[i];
+                    value += values[i];
+                }
+                (lc, value)
+            };
+
+            let (left, left_value) = sample_lc(4);
+            let (right, right_value) = sample_lc(5);
+            let output_value = left_value * right_value;
+            let output = cs.new_witness_variable(|| Ok(output_value))?;
+            cs.enforce_constraint(left, right, lc!() + output)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn bench_index(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NARK::index");
+    group.sample_size(10);
+    let pp = Nark::setup();
+    for &size in &SIZES {
+        let circuit = RandomSparseCircuit {
+            num_constraints: size,
+            seed: 0,
+            values_seed: 0,
+        };
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size),
+            &circuit,
+            |b, &circuit| b.iter(|| Nark::index(&pp, circuit).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn bench_prove(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NARK::prove");
+    group.sample_size(10);
+    let pp = Nark::setup();
+    for &size in &SIZES {
+        let circuit = RandomSparseCircuit {
+            num_constraints: size,
+            seed: 0,
+            values_seed: 0,
+        };
+        let (ipk, _) = Nark::index(&pp, circuit).unwrap();
+        let rng = &mut ark_std::test_rng();
+
+        let proof = Nark::prove(&ipk, circuit, true, None, Some(rng)).unwrap();
+        println!(
+            "Proof size for {} constraints: {} bytes",
+            size,
+            proof.serialized_size()
+        );
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size),
+            &circuit,
+            |b, &circuit| b.iter(|| Nark::prove(&ipk, circuit, true, None, Some(rng)).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+// Compares `prove_batched`, which commits to a single combination of `z_A`, `z_B`, and `z_C`,
+// with `prove` without zero knowledge, and prints the sizes of their proofs.
+fn bench_prove_batched(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NARK::prove_batched");
+    group.sample_size(10);
+    let pp = Nark::setup();
+    for &size in &SIZES {
+        let circuit = RandomSparseCircuit {
+            num_constraints: size,
+            seed: 0,
+            values_seed: 0,
+        };
+        let (ipk, _) = Nark::index(&pp, circuit).unwrap();
+
+        let proof = Nark::prove(&ipk, circuit, false, None, None).unwrap();
+        let batched_proof = Nark::prove_batched(&ipk, circuit, None).unwrap();
+        println!(
+            "Proof size for {} constraints: {} bytes unbatched, {} bytes batched",
+            size,
+            proof.serialized_size(),
+            batched_proof.serialized_size()
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("batched", size),
+            &circuit,
+            |b, &circuit| b.iter(|| Nark::prove_batched(&ipk, circuit, None).unwrap()),
+        );
+        group.bench_with_input(BenchmarkId::new("non-zk", size), &circuit, |b, &circuit| {
+            b.iter(|| Nark::prove(&ipk, circuit, false, None, None).unwrap())
+        });
+    }
+    group.finish();
+}
+
+// Compares `prove_public`, which reveals the assignment, with `prove` without zero knowledge.
+fn bench_prove_public(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NARK::prove_public");
+    group.sample_size(10);
+    let pp = Nark::setup();
+    for &size in &SIZES {
+        let circuit = RandomSparseCircuit {
+            num_constraints: size,
+            seed: 0,
+            values_seed: 0,
+        };
+        let (ipk, _) = Nark::index(&pp, circuit).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("public", size), &circuit, |b, &circuit| {
+            b.iter(|| Nark::prove_public(&ipk, circuit).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("non-zk", size), &circuit, |b, &circuit| {
+            b.iter(|| Nark::prove(&ipk, circuit, false, None, None).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_verify(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NARK::verify");
+    group.sample_size(10);
+    let pp = Nark::setup();
+    for &size in &SIZES {
+        let circuit = RandomSparseCircuit {
+            num_constraints: size,
+            seed: 0,
+            values_seed: 0,
+        };
+        let (ipk, ivk) = Nark::index(&pp, circuit).unwrap();
+        let rng = &mut ark_std::test_rng();
+        let proof = Nark::prove(&ipk, circuit, true, None, Some(rng)).unwrap();
+        let input = circuit.input();
+
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter(|| assert!(Nark::verify(&ivk, &input, &proof, None).unwrap()))
+        });
+    }
+    group.finish();
+}
+
+fn bench_verify_many(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NARK::verify_many");
+    group.sample_size(10);
+    let pp = Nark::setup();
+    let rng = &mut ark_std::test_rng();
+    let num_proofs = 100;
+    let size = SIZES[0];
+    let (ipk, ivk) = Nark::index(
+        &pp,
+        RandomSparseCircuit {
+            num_constraints: size,
+            seed: 0,
+            values_seed: 0,
+        },
+    )
+    .unwrap();
+
+    let circuits = (0..num_proofs as u64)
+        .map(|values_seed| RandomSparseCircuit {
+            num_constraints: size,
+            seed: 0,
+            values_seed,
+        })
+        .collect::<Vec<_>>();
+    let inputs = circuits.iter().map(|c| c.input()).collect::<Vec<_>>();
+    let proofs = circuits
+        .iter()
+        .map(|&circuit| Nark::prove(&ipk, circuit, true, None, Some(rng)).unwrap())
+        .collect::<Vec<_>>();
+    let instances = inputs
+        .iter()
+        .zip(&proofs)
+        .map(|(input, proof)| (input.as_slice(), proof))
+        .collect::<Vec<_>>();
+
+    group.throughput(Throughput::Elements(num_proofs as u64));
+    group.bench_function("loop", |b| {
+        b.iter(|| {
+            for (input, proof) in &instances {
+                assert!(Nark::verify(&ivk, input, *proof, None).unwrap());
+            }
+        })
+    });
+    group.bench_function("verify_many", |b| {
+        b.iter(|| {
+            let results = Nark::verify_many(&ivk, &instances, PoseidonSponge::new);
+            assert!(results.into_iter().all(|result| result));
+        })
+    });
+    group.finish();
+}
+
+fn bench_verify_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NARK::verify_batch");
+    group.sample_size(10);
+    let pp = Nark::setup();
+    let rng = &mut ark_std::test_rng();
+    let size = SIZES[0];
+    let (ipk, ivk) = Nark::index(
+        &pp,
+        RandomSparseCircuit {
+            num_constraints: size,
+            seed: 0,
+            values_seed: 0,
+        },
+    )
+    .unwrap();
+
+    for &batch_size in &BATCH_SIZES {
+        let circuits = (0..batch_size as u64)
+            .map(|values_seed| RandomSparseCircuit {
+                num_constraints: size,
+                seed: 0,
+                values_seed,
+            })
+            .collect::<Vec<_>>();
+        let inputs = circuits.iter().map(|c| c.input()).collect::<Vec<_>>();
+        let proofs = circuits
+            .iter()
+            .map(|&circuit| Nark::prove(&ipk, circuit, true, None, Some(rng)).unwrap())
+            .collect::<Vec<_>>();
+        let instances = inputs
+            .iter()
+            .zip(&proofs)
+            .map(|(input, proof)| (input.as_slice(), proof))
+            .collect::<Vec<_>>();
+
+        group.throughput(Throughput::Elements(batch_size as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(batch_size),
+            &instances,
+            |b, instances| {
+                b.iter(|| {
+                    let results = Nark::verify_many(&ivk, instances, PoseidonSponge::new);
+                    assert!(results.into_iter().all(|result| result));
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_verify_with_hints(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NARK::verify_with_hints");
+    group.sample_size(10);
+    let pp = Nark::setup();
+    let num_proofs = 1000;
+    let circuit = RandomSparseCircuit {
+        num_constraints: SIZES[0],
+        seed: 0,
+        values_seed: 0,
+    };
+    let (ipk, ivk) = Nark::index(&pp, circuit).unwrap();
+    let rng = &mut ark_std::test_rng();
+    let proof = Nark::prove(&ipk, circuit, true, None, Some(rng)).unwrap();
+    let input = circuit.input();
+
+    group.throughput(Throughput::Elements(num_proofs as u64));
+    group.bench_function("standard", |b| {
+        b.iter(|| {
+            for _ in 0..num_proofs {
+                assert!(Nark::verify(&ivk, &input, &proof, None).unwrap());
+            }
+        })
+    });
+    group.bench_function("hints", |b| {
+        b.iter(|| {
+            let hints = ivk.precompute_hints();
+            for _ in 0..num_proofs {
+                assert!(Nark::verify_with_hints(&ivk, &hints, &input, &proof, None));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn bench_verify_precomputed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NARK::verify_precomputed");
+    group.sample_size(10);
+    let pp = Nark::setup();
+    let num_proofs = 100;
+    let circuit = RandomSparseCircuit {
+        num_constraints: SIZES[0],
+        seed: 0,
+        values_seed: 0,
+    };
+    let (ipk, ivk) = Nark::index(&pp, circuit).unwrap();
+    let precomputed = PrecomputedVerifier::new(ivk.clone());
+    let rng = &mut ark_std::test_rng();
+    let proof = Nark::prove(&ipk, circuit, true, None, Some(rng)).unwrap();
+    let input = circuit.input();
+
+    group.throughput(Throughput::Elements(num_proofs as u64));
+    group.bench_function("standard", |b| {
+        b.iter(|| {
+            for _ in 0..num_proofs {
+                assert!(Nark::verify(&ivk, &input, &proof, None).unwrap());
+            }
+        })
+    });
+    group.bench_function("precomputed", |b| {
+        b.iter(|| {
+            for _ in 0..num_proofs {
+                assert!(precomputed
+                    .verify::<PoseidonSponge<Fq>>(&input, &proof, None)
+                    .unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+fn bench_reprove_incremental(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NARK::reprove_incremental");
+    group.sample_size(10);
+    let pp = Nark::setup();
+    let size = 1 << 16;
+    let circuit = RandomSparseCircuit {
+        num_constraints: size,
+        seed: 0,
+        values_seed: 0,
+    };
+    let (ipk, _) = Nark::index(&pp, circuit).unwrap();
+    let input = circuit.input();
+    let witness = circuit.witness();
+    let old_proof = Nark::prove(&ipk, circuit, false, None, None).unwrap();
+
+    group.bench_function("full", |b| {
+        b.iter(|| Nark::prove(&ipk, circuit, false, None, None).unwrap())
+    });
+    for &num_changed in &[1, 10, 100] {
+        let changed_constraints = (0..num_changed).collect::<Vec<_>>();
+        group.bench_function(BenchmarkId::new("changed", num_changed), |b| {
+            b.iter(|| {
+                Nark::reprove_incremental(
+                    &ipk,
+                    &input,
+                    &old_proof,
+                    &input,
+                    witness.clone(),
+                    &changed_constraints,
+                )
+                .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+// Compares `A * z` with `A^T * v` for the `A` matrix of the same circuit.
+fn bench_transposed_matrix_vec_mul(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NARK::transposed_matrix_vec_mul");
+    group.sample_size(10);
+    let rng = &mut StdRng::seed_from_u64(0);
+    for &size in &SIZES {
+        let circuit = RandomSparseCircuit {
+            num_constraints: size,
+            seed: 0,
+            values_seed: 0,
+        };
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.finalize();
+        let num_variables = cs.num_instance_variables() + cs.num_witness_variables();
+        let a = cs.to_matrices().unwrap().a;
+        let a_t = transpose(&a, num_variables);
+
+        let z = (0..num_variables)
+            .map(|_| Fr::rand(rng))
+            .collect::<Vec<_>>();
+        let v = (0..a.len()).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+        group.bench_function(BenchmarkId::new("forward", size), |b| {
+            b.iter(|| {
+                a.iter()
+                    .map(|row| row.iter().map(|(coeff, i)| *coeff * z[*i]).sum())
+                    .collect::<Vec<Fr>>()
+            })
+        });
+        group.bench_function(BenchmarkId::new("transposed", size), |b| {
+            b.iter(|| transposed_matrix_vec_mul(&a_t, &v))
+        });
+    }
+    group.finish();
+}
+
+// Compares three separate products `A * z`, `B * z`, and `C * z` with `matrix_triple_mul`, which
+// computes them in a single pass over the rows.
+fn bench_matrix_triple_mul(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NARK::matrix_triple_mul");
+    group.sample_size(10);
+    for &size in &SIZES {
+        let circuit = RandomSparseCircuit {
+            num_constraints: size,
+            seed: 0,
+            values_seed: 0,
+        };
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.finalize();
+        let matrices = cs.to_matrices().unwrap();
+        let (a, b, c) = (
+            CsrMatrix::from(matrices.a),
+            CsrMatrix::from(matrices.b),
+            CsrMatrix::from(matrices.c),
+        );
+        let input = circuit.input();
+        let witness = circuit.witness();
+
+        let reprs = [
+            MatrixRepr::Sparse(a.clone()),
+            MatrixRepr::Sparse(b.clone()),
+            MatrixRepr::Sparse(c.clone()),
+        ];
+        group.bench_function(BenchmarkId::new("separate", size), |bench| {
+            bench.iter(|| {
+                reprs
+                    .iter()
+                    .map(|repr| matrix_vec_mul_dispatch(repr, &input, &witness))
+                    .collect::<Vec<_>>()
+            })
+        });
+        group.bench_function(BenchmarkId::new("triple", size), |bench| {
+            bench.iter(|| matrix_triple_mul(&a, &b, &c, &input, &witness))
+        });
+    }
+    group.finish();
+}
+
+// Compares the multiplication of a matrix stored sparsely with that of the representation chosen
+// by `MatrixRepr::from_sparse`, for matrices of increasing density.
+fn bench_matrix_repr(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NARK::matrix_repr");
+    group.sample_size(10);
+    let rng = &mut StdRng::seed_from_u64(0);
+    let size = 1024;
+    let input = vec![Fr::one()];
+    let witness = (1..size).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    for &density in &[10u32, 60, 90] {
+        let matrix: Matrix<Fr> = (0..size)
+            .map(|_| {
+                (0..size)
+                    .filter(|_| rng.next_u32() % 100 < density)
+                    .map(|i| (Fr::rand(rng), i))
+                    .collect()
+            })
+            .collect();
+        let matrix = CsrMatrix::from(matrix);
+        let sparse = MatrixRepr::Sparse(matrix.clone());
+        let chosen = MatrixRepr::from_sparse(&matrix, size);
+
+        group.bench_function(BenchmarkId::new("sparse", density), |b| {
+            b.iter(|| matrix_vec_mul_dispatch(&sparse, &input, &witness))
+        });
+        group.bench_function(BenchmarkId::new("chosen", density), |b| {
+            b.iter(|| matrix_vec_mul_dispatch(&chosen, &input, &witness))
+        });
+    }
+    group.finish();
+}
+
+fn bench_merkle_tree(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NARK::merkle_tree");
+    group.sample_size(10);
+    let rng = &mut StdRng::seed_from_u64(0);
+    for &size in &[1 << 10, 1 << 12, 1 << 14] {
+        let leaves = (0..size).map(|_| Fq::rand(rng)).collect::<Vec<_>>();
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::new("arity_2", size), &leaves, |b, leaves| {
+            b.iter(|| PoseidonMerkleTree::<Fq, 2>::build(leaves).root())
+        });
+        group.bench_with_input(BenchmarkId::new("arity_4", size), &leaves, |b, leaves| {
+            b.iter(|| PoseidonMerkleTree::<Fq, 4>::build(leaves).root())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_index,
+    bench_prove,
+    bench_prove_public,
+    bench_prove_batched,
+    bench_verify,
+    bench_verify_many,
+    bench_verify_batch,
+    bench_verify_with_hints,
+    bench_verify_precomputed,
+    bench_reprove_incremental,
+    bench_transposed_matrix_vec_mul,
+    bench_matrix_triple_mul,
+    bench_matrix_repr,
+    bench_merkle_tree
+);
+criterion_main!(benches);
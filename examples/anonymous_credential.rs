@@ -0,0 +1,196 @@
+// An anonymous credential: a user proves that they are at least 18 years old without revealing
+// their birth year, and a service accumulates the proofs of many users so that it only has to
+// run one expensive check for all of them.
+//
+// PS: test_rng is *insecure*, and only used to make the example deterministic.
+
+use ark_accumulation::r1cs_nark_as::r1cs_nark::R1CSNark;
+use ark_accumulation::r1cs_nark_as::{ASForR1CSNark, InputInstance};
+use ark_accumulation::{AccumulationScheme, Input, MakeZK};
+use ark_ff::{BigInteger, One, PrimeField, Zero};
+use ark_pallas::{Affine, Fq, Fr};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable},
+};
+use ark_sponge::poseidon::PoseidonSponge;
+use ark_sponge::CryptographicSponge;
+
+type Sponge = PoseidonSponge<Fq>;
+type Nark = R1CSNark<Affine, Sponge>;
+type AS = ASForR1CSNark<Affine, Sponge>;
+
+const CURRENT_YEAR: u64 = 2026;
+const MIN_AGE: u64 = 18;
+
+// The number of bits of `age - MIN_AGE`, which bounds the ages that can be proven to 18..=273.
+const AGE_BITS: usize = 8;
+
+// The "user attribute" circuit. The birth year is a secret witness, the current year is a
+// constant of the circuit, and the only public input is the result of the age check.
+//
+// The circuit enforces that `is_adult` is a bit, and that if it is `1`, then
+// `CURRENT_YEAR - birth_year - MIN_AGE` is the sum of `AGE_BITS` bits, and so is not negative.
+#[derive(Clone, Copy)]
+struct AgeCheckCircuit<F: PrimeField> {
+    birth_year: Option<F>,
+    is_adult: Option<bool>,
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for AgeCheckCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let is_adult_value = || {
+            let is_adult = self.is_adult.ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(if is_adult { F::one() } else { F::zero() })
+        };
+        let age_offset_value = || {
+            let birth_year = self.birth_year.ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(F::from(CURRENT_YEAR - MIN_AGE) - birth_year)
+        };
+
+        // Public input: the result of the age check.
+        let is_adult = cs.new_input_variable(is_adult_value)?;
+
+        // Witness: the birth year, and the bits of `age - MIN_AGE`.
+        let birth_year =
+            cs.new_witness_variable(|| self.birth_year.ok_or(SynthesisError::AssignmentMissing))?;
+        let mut bits_sum = lc!();
+        let mut coeff = F::one();
+        for i in 0..AGE_BITS {
+            let bit = cs.new_witness_variable(|| {
+                let age_offset = age_offset_value()?.into_repr();
+                Ok(if age_offset.get_bit(i) {
+                    F::one()
+                } else {
+                    F::zero()
+                })
+            })?;
+
+            // bit * (bit - 1) = 0
+            cs.enforce_constraint(lc!() + bit, lc!() + bit - (F::one(), Variable::One), lc!())?;
+            bits_sum = bits_sum + (coeff, bit);
+            coeff.double_in_place();
+        }
+
+        // is_adult * (is_adult - 1) = 0
+        cs.enforce_constraint(
+            lc!() + is_adult,
+            lc!() + is_adult - (F::one(), Variable::One),
+            lc!(),
+        )?;
+
+        // is_adult * (CURRENT_YEAR - MIN_AGE - birth_year - bits_sum) = 0
+        let age_offset = lc!() + (F::from(CURRENT_YEAR - MIN_AGE), Variable::One) - birth_year;
+        cs.enforce_constraint(lc!() + is_adult, age_offset - bits_sum, lc!())?;
+
+        Ok(())
+    }
+}
+
+fn main() {
+    let rng = &mut ark_std::test_rng();
+
+    // (1) Setup. The NARK needs no trusted setup, and its public parameters are empty.
+    let pp = Nark::setup();
+
+    // (2) Index the circuit. The issuer of the credential does this once, with no assignment,
+    // and publishes the verifier key.
+    let blank_circuit = AgeCheckCircuit::<Fr> {
+        birth_year: None,
+        is_adult: None,
+    };
+    let (ipk, ivk) = Nark::index(&pp, blank_circuit).unwrap();
+
+    // (3) Prove. The user's birth year is the witness. The proof is zero knowledge, so it
+    // reveals nothing about the birth year beyond the result of the age check.
+    let birth_years = [1990u64, 2001, 1975, 2008];
+    let mut proofs = Vec::new();
+    for birth_year in birth_years.iter() {
+        let circuit = AgeCheckCircuit {
+            birth_year: Some(Fr::from(*birth_year)),
+            is_adult: Some(true),
+        };
+
+        // The proofs are made with the sponge that the accumulation scheme expects, so that they
+        // can also be accumulated in step (5).
+        let nark_sponge = AS::nark_sponge(&Sponge::new());
+        let proof = Nark::prove(&ipk, circuit, true, Some(nark_sponge), Some(rng)).unwrap();
+        proofs.push(proof);
+    }
+
+    // (4) Verify. The verifier only sees the result of the age check, with the leading constant
+    // `1` of every R1CS input.
+    let r1cs_input = vec![Fr::one(), Fr::one()];
+    for (birth_year, proof) in birth_years.iter().zip(&proofs) {
+        let nark_sponge = AS::nark_sponge(&Sponge::new());
+        let is_valid = Nark::verify(&ivk, &r1cs_input, proof, Some(nark_sponge)).unwrap();
+        println!("The user born in {} is an adult: {}", birth_year, is_valid);
+    }
+
+    // A user born in 2008 is 18, so their proof verifies. Claiming that a user born in 2010 is
+    // an adult fails, since the age check has no satisfying assignment.
+    let minor = AgeCheckCircuit {
+        birth_year: Some(Fr::from(2010u64)),
+        is_adult: Some(true),
+    };
+    let result = Nark::prove(&ipk, minor, true, None, Some(rng));
+    let minor_proof_verifies = result
+        .map(|proof| Nark::verify(&ivk, &r1cs_input, &proof, None).unwrap_or(false))
+        .unwrap_or(false);
+    println!(
+        "The user born in 2010 is an adult: {}",
+        minor_proof_verifies
+    );
+
+    // (5) Accumulate. Instead of checking every proof in full, a service folds them into a
+    // single accumulator. Each accumulation step is cheap to verify, and one decision at the end
+    // checks all of the accumulated proofs at once.
+    let as_pp = AS::setup(rng).unwrap();
+    let (pk, vk, dk) = AS::index(&as_pp, &(), &(ipk, ivk)).unwrap();
+
+    let inputs: Vec<Input<Fq, Sponge, AS>> = proofs
+        .into_iter()
+        .map(|proof| Input::<Fq, Sponge, AS> {
+            instance: InputInstance {
+                r1cs_input: r1cs_input.clone(),
+                first_round_message: proof.first_msg,
+            },
+            witness: proof.second_msg,
+        })
+        .collect();
+
+    let (accumulator, as_proof) = AS::prove(
+        &pk,
+        Input::<Fq, Sponge, AS>::map_to_refs(&inputs),
+        core::iter::empty(),
+        MakeZK::Enabled(rng),
+        None,
+    )
+    .unwrap();
+
+    // The accumulation verifier only looks at the input instances, not at the proofs' witnesses.
+    let accumulation_is_valid = AS::verify(
+        &vk,
+        Input::<Fq, Sponge, AS>::instances(&inputs),
+        core::iter::empty(),
+        &accumulator.instance,
+        &as_proof,
+        None,
+    )
+    .unwrap();
+    println!(
+        "The accumulation of {} proofs is valid: {}",
+        inputs.len(),
+        accumulation_is_valid
+    );
+
+    // The decider checks the accumulator once, which shows that every accumulated proof is
+    // valid.
+    let all_are_adults = AS::decide(&dk, accumulator.as_ref(), None).unwrap();
+    println!(
+        "All of the accumulated users are adults: {}",
+        all_are_adults
+    );
+
+    assert!(accumulation_is_valid && all_are_adults && !minor_proof_verifies);
+}
@@ -97,7 +97,8 @@ fn profile_nark<R: Rng>(
             &[Fr::one(), v, a, a, a, a],
             &proof,
             Some(PoseidonSponge::new())
-        ));
+        )
+        .unwrap());
         let verifier_time = start.elapsed().as_millis();
         let record = (num_constraints, index_time, prover_time, verifier_time);
         println!(
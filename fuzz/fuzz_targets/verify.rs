@@ -0,0 +1,59 @@
+//! Deserializes arbitrary bytes as an R1CS NARK proof and verifies it. The verifier must reject
+//! every malformed proof with `false` or an error, and never panic.
+
+#![no_main]
+
+use ark_accumulation::r1cs_nark_as::r1cs_nark::{IndexVerifierKey, LazyProof, Proof, R1CSNark};
+use ark_ff::{One, PrimeField};
+use ark_pallas::{Affine, Fq, Fr};
+use ark_relations::lc;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::CanonicalDeserialize;
+use ark_sponge::poseidon::PoseidonSponge;
+use libfuzzer_sys::fuzz_target;
+use std::sync::OnceLock;
+
+type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+// Enforces `x * y = z` for secret `x` and `y` and a public `z`, a few times over.
+#[derive(Clone, Copy)]
+struct ProductCircuit<F> {
+    x: F,
+    y: F,
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for ProductCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let z = cs.new_input_variable(|| Ok(self.x * self.y))?;
+        for _ in 0..4 {
+            let x = cs.new_witness_variable(|| Ok(self.x))?;
+            let y = cs.new_witness_variable(|| Ok(self.y))?;
+            cs.enforce_constraint(lc!() + x, lc!() + y, lc!() + z)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn setup() -> &'static (IndexVerifierKey<Affine>, Vec<Fr>) {
+    static SETUP: OnceLock<(IndexVerifierKey<Affine>, Vec<Fr>)> = OnceLock::new();
+    SETUP.get_or_init(|| {
+        let circuit = ProductCircuit {
+            x: Fr::from(3u64),
+            y: Fr::from(5u64),
+        };
+        let (_, ivk) = Nark::index(&Nark::setup(), circuit).unwrap();
+        (ivk, vec![Fr::one(), Fr::from(15u64)])
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let (ivk, input) = setup();
+
+    if let Ok(proof) = Proof::<Affine>::deserialize(data) {
+        let _ = Nark::verify(ivk, input, &proof, None);
+    }
+
+    let lazy_proof = LazyProof::<Affine>::new(data.to_vec());
+    let _ = Nark::verify(ivk, input, &lazy_proof, None);
+});
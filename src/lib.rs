@@ -245,6 +245,35 @@ pub trait AccumulationScheme<CF: PrimeField, S: CryptographicSponge<CF>>: Sized
     ) -> Result<bool, Self::Error>
     where
         Self: 'a;
+
+    /// Merges two accumulators, such as those accumulated independently by two provers, into one
+    /// that is valid if and only if both are. Calls [`prove`][prove] with no inputs and the two
+    /// accumulators as the old accumulators, so the merge is checked with [`verify`][verify] in
+    /// the same way.
+    ///
+    /// [prove]: AccumulationScheme::prove
+    /// [verify]: AccumulationScheme::verify
+    fn merge_accumulators<'a>(
+        prover_key: &Self::ProverKey,
+        accumulator_1: &'a Accumulator<CF, S, Self>,
+        accumulator_2: &'a Accumulator<CF, S, Self>,
+        make_zk: MakeZK<'_>,
+        sponge: Option<S>,
+    ) -> Result<(Accumulator<CF, S, Self>, Self::Proof), Self::Error>
+    where
+        Self: 'a,
+        S: 'a,
+    {
+        let accumulators =
+            ark_std::iter::once(accumulator_1).chain(ark_std::iter::once(accumulator_2));
+        Self::prove(
+            prover_key,
+            ark_std::iter::empty(),
+            Accumulator::<CF, S, Self>::map_to_refs(accumulators),
+            make_zk,
+            sponge,
+        )
+    }
 }
 
 /// A special case of an [`AccumulationScheme`] that has empty witnesses, so entire
@@ -446,6 +475,64 @@ pub mod tests {
             Ok(())
         }
 
+        /// Tests the merge of two accumulators that each accumulate 5 inputs, one at a time.
+        pub fn merge_accumulators_test(test_params: &I::TestParams) -> Result<(), AS::Error> {
+            let num_inputs_per_accumulator = 5;
+
+            let mut rng = ark_std::test_rng();
+            let public_params = AS::setup(&mut rng)?;
+
+            let (input_params, predicate_params, predicate_index) = I::setup(test_params, &mut rng);
+            let (pk, vk, dk) = AS::index(&public_params, &predicate_params, &predicate_index)?;
+
+            let inputs =
+                I::generate_inputs(&input_params, 2 * num_inputs_per_accumulator, &mut rng);
+
+            let mut accumulators = Vec::with_capacity(2);
+            for inputs in inputs.chunks(num_inputs_per_accumulator) {
+                let mut accumulator = None;
+                for input in inputs {
+                    let (new_accumulator, _) = AS::prove(
+                        &pk,
+                        Input::<CF, S, AS>::map_to_refs(ark_std::iter::once(input)),
+                        Accumulator::<CF, S, AS>::map_to_refs(accumulator.iter()),
+                        if test_params.make_zk() {
+                            MakeZK::Enabled(&mut rng)
+                        } else {
+                            MakeZK::Disabled
+                        },
+                        None::<S>,
+                    )?;
+                    accumulator = Some(new_accumulator);
+                }
+
+                accumulators.push(accumulator.unwrap());
+            }
+
+            let (merged, proof) = AS::merge_accumulators(
+                &pk,
+                &accumulators[0],
+                &accumulators[1],
+                if test_params.make_zk() {
+                    MakeZK::Enabled(&mut rng)
+                } else {
+                    MakeZK::Disabled
+                },
+                None::<S>,
+            )?;
+
+            assert!(AS::verify(
+                &vk,
+                ark_std::iter::empty(),
+                Accumulator::<CF, S, AS>::instances(&accumulators),
+                &merged.instance,
+                &proof,
+                None::<S>,
+            )?);
+            assert!(AS::decide(&dk, merged.as_ref(), None::<S>)?);
+            Ok(())
+        }
+
         /// Tests the initialization of the first accumulator without any inputs.
         pub fn no_inputs_init_test(test_params: &I::TestParams) -> Result<(), AS::Error> {
             let template_params = TemplateParams {
@@ -1,4 +1,4 @@
-use crate::data_structures::{Accumulator, AccumulatorRef, InputRef};
+use crate::data_structures::{Accumulator, AccumulatorRef, Input, InputRef};
 use crate::error::ASError::{MalformedAccumulator, MalformedInput};
 use crate::error::{ASError, BoxedError};
 use crate::hp_as::ASForHadamardProducts;
@@ -124,6 +124,21 @@ where
         base_sponge.fork(HP_AS_PROTOCOL_NAME)
     }
 
+    /// Returns the input that folds the base-case proof of step 0 of an IVC computation, which is
+    /// produced by [`Proof::base_case`][base_case]. Step 0 has no previous proof to verify, so it
+    /// is folded as the zero instance and witness, which satisfy every R1CS relation. At step 1,
+    /// this input is accumulated together with the input of the step-1 proof.
+    ///
+    /// [base_case]: crate::r1cs_nark_as::r1cs_nark::Proof::base_case
+    pub fn base_case_input(prover_key: &ProverKey<G>) -> Input<ConstraintF<G>, S, Self> {
+        let r1cs_input_len = prover_key.nark_pk.index_info.num_instance_variables;
+        let r1cs_witness_len = prover_key.nark_pk.index_info.num_variables - r1cs_input_len;
+        Input::<_, _, Self> {
+            instance: InputInstance::zero(r1cs_input_len, false),
+            witness: InputWitness::zero(r1cs_witness_len, false),
+        }
+    }
+
     /// Check that the input instance is properly structured.
     fn check_input_instance_structure(
         input_instance: &InputInstance<G>,
@@ -1123,6 +1138,7 @@ pub mod tests {
     use crate::tests::*;
     use crate::AccumulationScheme;
     use crate::ConstraintF;
+    use crate::MakeZK;
     use ark_ec::AffineCurve;
     use ark_ff::PrimeField;
     use ark_relations::lc;
@@ -1393,4 +1409,67 @@ pub mod tests {
             make_zk: true,
         })
     }
+
+    #[test]
+    pub fn merge_accumulators_test_no_zk() -> Result<(), BoxedError> {
+        Tests::merge_accumulators_test(&ASForR1CSNarkTestParams {
+            num_inputs: 5,
+            num_constraints: 10,
+            make_zk: false,
+        })
+    }
+
+    #[test]
+    pub fn merge_accumulators_test_zk() -> Result<(), BoxedError> {
+        Tests::merge_accumulators_test(&ASForR1CSNarkTestParams {
+            num_inputs: 5,
+            num_constraints: 10,
+            make_zk: true,
+        })
+    }
+
+    #[test]
+    pub fn base_case_accumulation_test() -> Result<(), BoxedError> {
+        let rng = &mut ark_std::test_rng();
+        for &make_zk in &[false, true] {
+            let test_params = ASForR1CSNarkTestParams {
+                num_inputs: 5,
+                num_constraints: 10,
+                make_zk,
+            };
+            let public_params = AS::setup(rng)?;
+            let (input_params, predicate_params, predicate_index) = I::setup(&test_params, rng);
+            let (pk, vk, dk) = AS::index(&public_params, &predicate_params, &predicate_index)?;
+
+            // Step 1 folds the base case with the proof for step 1.
+            let inputs = vec![
+                AS::base_case_input(&pk),
+                I::generate_inputs(&input_params, 1, rng).pop().unwrap(),
+            ];
+            let (accumulator, proof) = AS::prove(
+                &pk,
+                Input::<CF, Sponge, AS>::map_to_refs(&inputs),
+                vec![],
+                if make_zk {
+                    MakeZK::Enabled(rng)
+                } else {
+                    MakeZK::Disabled
+                },
+                None,
+            )?;
+
+            let input_instances = inputs.iter().map(|input| &input.instance);
+            assert!(AS::verify(
+                &vk,
+                input_instances,
+                vec![],
+                &accumulator.instance,
+                &proof,
+                None
+            )?);
+            assert!(AS::decide(&dk, accumulator.as_ref(), None)?);
+        }
+
+        Ok(())
+    }
 }
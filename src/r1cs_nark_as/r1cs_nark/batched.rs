@@ -0,0 +1,202 @@
+use super::{
+    check_commitment_key, check_protocol_version, IndexProverKey, IndexVerifierKey, NarkError,
+    R1CSNark, R1CSResult,
+};
+use crate::r1cs_nark_as::CHALLENGE_SIZE;
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::{BigInteger, PrimeField};
+use ark_poly_commit::trivial_pc::PedersenCommitment;
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_sponge::{absorb, Absorbable, CryptographicSponge, FieldElementSize};
+use ark_std::io::{Read, Write};
+use ark_std::vec::Vec;
+use ark_std::{cfg_into_iter, cfg_iter};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// A proof of the NARK without zero knowledge in which the commitments to `z_A`, `z_B`, and
+/// `z_C` are replaced by a single commitment to `z_A + r * z_B + r^2 * z_C`, for a challenge `r`
+/// derived from the sponge. This saves two group elements over a [`Proof`][proof].
+///
+/// [proof]: crate::r1cs_nark_as::r1cs_nark::Proof
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BatchedProof<G: AffineCurve> {
+    /// The commitment to `z_A + r * z_B + r^2 * z_C`.
+    pub combined_comm: G,
+
+    /// The witness of the R1CS instance.
+    pub witness: Vec<G::ScalarField>,
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    // Computes the challenge used to combine the commitments to `z_A`, `z_B`, and `z_C`.
+    fn compute_batching_challenge(
+        ipk: &IndexProverKey<G>,
+        input: &[G::ScalarField],
+        witness: &[G::ScalarField],
+        sponge: Option<S>,
+    ) -> G::ScalarField {
+        let mut sponge = Self::init_sponge(ipk, sponge);
+        let to_bytes = |elems: &[G::ScalarField]| {
+            elems
+                .iter()
+                .flat_map(|elem| elem.into_repr().to_bytes_le())
+                .collect::<Vec<_>>()
+        };
+
+        absorb!(
+            &mut sponge,
+            ipk.index_info.matrices_hash.as_ref(),
+            to_bytes(input),
+            to_bytes(witness)
+        );
+
+        sponge
+            .squeeze_nonnative_field_elements_with_sizes(&[FieldElementSize::Truncated(
+                CHALLENGE_SIZE,
+            )])
+            .pop()
+            .unwrap()
+    }
+
+    // Commits to `z_a + r * z_b + r^2 * z_c`.
+    fn commit_combined(
+        ipk: &IndexProverKey<G>,
+        z_a: Vec<G::ScalarField>,
+        z_b: Vec<G::ScalarField>,
+        z_c: Vec<G::ScalarField>,
+        r: G::ScalarField,
+    ) -> G {
+        let r_squared = r.square();
+        let combined: Vec<_> = cfg_into_iter!(z_a)
+            .zip(z_b)
+            .zip(z_c)
+            .map(|((a, b), c)| a + r * b + r_squared * c)
+            .collect();
+        PedersenCommitment::commit(&ipk.ck, &combined, None)
+    }
+
+    /// Proves that some R1CS relation holds with a [`BatchedProof`]. The proof reveals the
+    /// witness, as a proof of [`R1CSNark::prove`] without zero knowledge does. Returns an error
+    /// if the commitment key of `ipk` is too short for the instance.
+    pub fn prove_batched<C: ConstraintSynthesizer<G::ScalarField>>(
+        ipk: &IndexProverKey<G>,
+        r1cs: C,
+        sponge: Option<S>,
+    ) -> R1CSResult<BatchedProof<G>> {
+        check_commitment_key(ipk)?;
+
+        let (input, witness) = Self::synthesize(ipk, r1cs)?;
+        let (z_a, z_b, z_c) = ipk.matrix_vec_muls(&input, &witness);
+        let r = Self::compute_batching_challenge(ipk, &input, &witness, sponge);
+        let combined_comm = Self::commit_combined(ipk, z_a, z_b, z_c, r);
+        Ok(BatchedProof {
+            combined_comm,
+            witness,
+        })
+    }
+
+    /// Verifies a [`BatchedProof`] by checking that the witness satisfies the R1CS instance and
+    /// that the combined commitment opens to the matrix-vector products combined with the
+    /// re-derived challenge. Returns an error if the input or the witness do not have the
+    /// lengths expected by `ivk`, or if the commitment key of `ivk` is too short for the
+    /// instance.
+    pub fn verify_batched(
+        ivk: &IndexVerifierKey<G>,
+        input: &[G::ScalarField],
+        proof: &BatchedProof<G>,
+        sponge: Option<S>,
+    ) -> R1CSResult<bool> {
+        let index_info = &ivk.index_info;
+        check_protocol_version(index_info)?;
+        check_commitment_key(ivk)?;
+        if input.len() != index_info.num_instance_variables {
+            return Err(NarkError::MalformedInput {
+                expected_len: index_info.num_instance_variables,
+                got: input.len(),
+            });
+        }
+
+        let witness_len = index_info.num_variables - index_info.num_instance_variables;
+        if proof.witness.len() != witness_len {
+            return Err(NarkError::MalformedWitness {
+                expected_len: witness_len,
+                got: proof.witness.len(),
+            });
+        }
+
+        let init_time = start_timer!(|| "NARK::BatchedVerifier");
+        let (z_a, z_b, z_c) = ivk.matrix_vec_muls(input, &proof.witness);
+        let is_satisfied = cfg_iter!(z_a)
+            .zip(&z_b)
+            .zip(&z_c)
+            .all(|((a, b), c)| *a * b == *c);
+        if !is_satisfied {
+            end_timer!(init_time);
+            return Ok(false);
+        }
+
+        let r = Self::compute_batching_challenge(ivk, input, &proof.witness, sponge);
+        let reconstructed_comm = Self::commit_combined(ivk, z_a, z_b, z_c, r);
+        end_timer!(init_time);
+        Ok(proof.combined_comm == reconstructed_comm)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_batched_proof() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let proof = Nark::prove_batched(&ipk, c, None).unwrap();
+        assert!(Nark::verify_batched(&ivk, &r1cs_input, &proof, None).unwrap());
+
+        let unbatched_proof = Nark::prove(&ipk, c, false, None, None).unwrap();
+        assert!(proof.serialized_size() < unbatched_proof.serialized_size());
+
+        let wrong_input = vec![Fr::one(), Fr::rand(rng)];
+        assert!(!Nark::verify_batched(&ivk, &wrong_input, &proof, None).unwrap());
+
+        let mut wrong_comm = proof.clone();
+        wrong_comm.combined_comm = unbatched_proof.first_msg.comm_a;
+        assert!(!Nark::verify_batched(&ivk, &r1cs_input, &wrong_comm, None).unwrap());
+
+        let mut sponge = PoseidonSponge::<Fq>::new();
+        sponge.absorb(&b"another state".as_ref());
+        assert!(!Nark::verify_batched(&ivk, &r1cs_input, &proof, Some(sponge)).unwrap());
+
+        let mut short_witness = proof;
+        short_witness.witness.pop();
+        assert!(matches!(
+            Nark::verify_batched(&ivk, &r1cs_input, &short_witness, None),
+            Err(NarkError::MalformedWitness { .. })
+        ));
+    }
+}
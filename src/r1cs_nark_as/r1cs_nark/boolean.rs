@@ -0,0 +1,142 @@
+use super::normalize_matrices;
+
+use ark_ff::PrimeField;
+use ark_relations::r1cs::Matrix;
+use ark_std::vec;
+
+/// Enforces that the variables at the columns `bool_vars` are Boolean, by adding the constraint
+/// `x_i * (x_i - 1) = 0` for each of them, and then removes the constraints that
+/// [`normalize_matrices`] finds trivially satisfied because one of their operands is the constant
+/// `0` or `1`. A variable that is already constrained to be Boolean, by `x_i * (x_i - 1) = 0` or
+/// `x_i * x_i = x_i`, gets no new constraint.
+pub fn reduce_to_binary_field<F: PrimeField>(
+    mut a: Matrix<F>,
+    mut b: Matrix<F>,
+    mut c: Matrix<F>,
+    bool_vars: &[usize],
+) -> (Matrix<F>, Matrix<F>, Matrix<F>) {
+    for &i in bool_vars {
+        let is_enforced = (0..a.len()).any(|row| enforces_boolean(&a[row], &b[row], &c[row], i));
+        if !is_enforced {
+            a.push(vec![(F::one(), i)]);
+            b.push(vec![(-F::one(), 0), (F::one(), i)]);
+            c.push(vec![]);
+        }
+    }
+
+    let (a, b, c, _) = normalize_matrices(a, b, c);
+    (a, b, c)
+}
+
+// Checks whether the constraint with rows `a_row`, `b_row`, and `c_row` is `x_i * (x_i - 1) = 0`
+// or `x_i * x_i = x_i`, up to the order of the operands.
+fn enforces_boolean<F: PrimeField>(
+    a_row: &[(F, usize)],
+    b_row: &[(F, usize)],
+    c_row: &[(F, usize)],
+    i: usize,
+) -> bool {
+    let x = [(F::one(), i)];
+    let mut x_minus_one = [(-F::one(), 0), (F::one(), i)];
+    let sorted = |row: &[(F, usize)]| {
+        let mut row = row.to_vec();
+        row.sort_by_key(|(_, i)| *i);
+        row
+    };
+    x_minus_one.sort_by_key(|(_, i)| *i);
+
+    let (a_row, b_row) = (sorted(a_row), sorted(b_row));
+    let is_product = |left: &[(F, usize)], right: &[(F, usize)]| {
+        (a_row == left && b_row == right) || (a_row == right && b_row == left)
+    };
+    (c_row.is_empty() && is_product(&x, &x_minus_one)) || (c_row == x && is_product(&x, &x))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::{first_unsatisfied_row, CsrMatrix};
+    use ark_ff::One;
+    use ark_pallas::Fr;
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSystem, ConstraintSystemRef, Variable};
+    use ark_std::vec::Vec;
+
+    // Allocates 5 witness bits and enforces that they are the binary decomposition of the public
+    // input, which does not force them to be Boolean.
+    fn decomposition(bits: [u64; 5]) -> ConstraintSystemRef<Fr> {
+        let value: u64 = bits.iter().enumerate().map(|(i, bit)| bit << i).sum();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let input = cs.new_input_variable(|| Ok(Fr::from(value))).unwrap();
+        let mut sum = lc!();
+        for (i, bit) in bits.iter().enumerate() {
+            let var = cs.new_witness_variable(|| Ok(Fr::from(*bit))).unwrap();
+            sum = sum + (Fr::from(1u64 << i), var);
+        }
+        cs.enforce_constraint(sum, lc!() + Variable::One, lc!() + input)
+            .unwrap();
+        cs.finalize();
+        cs
+    }
+
+    fn is_satisfied(
+        cs: &ConstraintSystemRef<Fr>,
+        a: Matrix<Fr>,
+        b: Matrix<Fr>,
+        c: Matrix<Fr>,
+    ) -> bool {
+        let cs = cs.borrow().unwrap();
+        first_unsatisfied_row(
+            &CsrMatrix::from(a),
+            &CsrMatrix::from(b),
+            &CsrMatrix::from(c),
+            &cs.instance_assignment,
+            &cs.witness_assignment,
+        )
+        .is_none()
+    }
+
+    #[test]
+    fn test_reduce_to_binary_field() {
+        let bits = [1, 0, 1, 1, 0];
+        let cs = decomposition(bits);
+        let matrices = cs.to_matrices().unwrap();
+        let bool_vars: Vec<_> = (2..7).collect();
+        let (a, b, c) = reduce_to_binary_field(
+            matrices.a.clone(),
+            matrices.b.clone(),
+            matrices.c.clone(),
+            &bool_vars,
+        );
+        assert_eq!(a.len(), matrices.a.len() + 5);
+        assert!(is_satisfied(&cs, a.clone(), b.clone(), c.clone()));
+
+        // A decomposition with a non-Boolean "bit" satisfies the original constraint only.
+        let cs = decomposition([3, 0, 0, 1, 0]);
+        assert!(cs.is_satisfied().unwrap());
+        assert!(!is_satisfied(&cs, a.clone(), b.clone(), c.clone()));
+
+        // Reducing again adds no constraints, since the variables are already Boolean.
+        let (a_again, _, _) = reduce_to_binary_field(a.clone(), b, c, &bool_vars);
+        assert_eq!(a_again.len(), a.len());
+    }
+
+    #[test]
+    fn test_reduce_removes_trivial_constraints() {
+        let (one, x, y, z) = (
+            vec![(Fr::one(), 0)],
+            vec![(Fr::one(), 1)],
+            vec![(Fr::one(), 2)],
+            vec![(Fr::one(), 3)],
+        );
+        let a = vec![one, x.clone()];
+        let b = vec![x.clone(), y.clone()];
+        let c = vec![x.clone(), z.clone()];
+
+        // `1 * x = x` is removed, `x * y = z` is kept, and `x * (x - 1) = 0` is added.
+        let (a, b, c) = reduce_to_binary_field(a, b, c, &[1]);
+        assert_eq!(a, vec![x.clone(), x.clone()]);
+        assert_eq!(b, vec![y, vec![(-Fr::one(), 0), (Fr::one(), 1)]]);
+        assert_eq!(c, vec![z, vec![]]);
+    }
+}
@@ -0,0 +1,104 @@
+use super::{IndexProverKey, Proof, R1CSNark, R1CSResult};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::{BigInteger, PrimeField};
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_sponge::{Absorbable, CryptographicSponge};
+use blake2::{digest::VariableOutput, VarBlake2b};
+use std::collections::HashMap;
+
+const PROOF_CACHE_DOMAIN: &[u8] = b"R1CS-NARK-2020-PROOF-CACHE";
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Proves that some R1CS relation holds, reusing a proof from `cache` if one was already
+    /// generated for the same index and public input. Proofs are keyed by a hash of the index and
+    /// the public input, so `cache` should only ever be used with the same `sponge`.
+    ///
+    /// Only proofs without zero-knowledge are generated, since they are deterministic and a
+    /// cached proof is indistinguishable from a fresh one.
+    pub fn prove_or_cache<C: ConstraintSynthesizer<G::ScalarField>>(
+        ipk: &IndexProverKey<G>,
+        r1cs: C,
+        cache: &mut HashMap<[u8; 32], Proof<G>>,
+        sponge: Option<S>,
+    ) -> R1CSResult<Proof<G>> {
+        let (input, witness) = Self::synthesize(ipk, r1cs)?;
+        let key = Self::cache_key(ipk, &input);
+        if let Some(proof) = cache.get(&key) {
+            return Ok(proof.clone());
+        }
+
+        let proof = Self::prove_with_assignment(ipk, input, witness, false, sponge, None);
+        cache.insert(key, proof.clone());
+        Ok(proof)
+    }
+
+    // Hashes the index and the public input into the key of a cached proof.
+    fn cache_key(ipk: &IndexProverKey<G>, input: &[G::ScalarField]) -> [u8; 32] {
+        let mut hasher = VarBlake2b::new(32).unwrap();
+        digest::Update::update(&mut hasher, PROOF_CACHE_DOMAIN);
+        digest::Update::update(&mut hasher, &ipk.index_info.matrices_hash);
+        for inp in input {
+            digest::Update::update(&mut hasher, &inp.into_repr().to_bytes_le());
+        }
+
+        let mut key = [0u8; 32];
+        hasher.finalize_variable(|res| key.copy_from_slice(res));
+        key
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_serialize::CanonicalSerialize;
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    fn proof_bytes(proof: &Proof<Affine>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        proof.serialize(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_prove_or_cache() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 1000,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let mut cache = HashMap::new();
+
+        let proof = Nark::prove_or_cache(&ipk, c, &mut cache, None).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert!(Nark::verify(&ivk, &r1cs_input, &proof, None).unwrap());
+
+        let cached_proof = Nark::prove_or_cache(&ipk, c, &mut cache, None).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(proof_bytes(&proof), proof_bytes(&cached_proof));
+
+        let other = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            ..c
+        };
+        Nark::prove_or_cache(&ipk, other, &mut cache, None).unwrap();
+        assert_eq!(cache.len(), 2);
+    }
+}
@@ -0,0 +1,315 @@
+use super::{
+    FullAssignment, IndexProverKey, IndexVerifierKey, NarkError, PublicParameters, R1CSNark,
+    R1CSResult,
+};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::{BigInteger, FpParameters, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::string::ToString;
+use ark_std::vec::Vec;
+use std::path::Path;
+
+const R1CS_MAGIC: &[u8; 4] = b"r1cs";
+const WTNS_MAGIC: &[u8; 4] = b"wtns";
+
+const R1CS_HEADER_SECTION: u32 = 1;
+const R1CS_CONSTRAINTS_SECTION: u32 = 2;
+const WTNS_HEADER_SECTION: u32 = 1;
+const WTNS_VALUES_SECTION: u32 = 2;
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Indexes the R1CS instance of a binary `.r1cs` file produced by Circom. The file must be
+    /// over the scalar field of `G`, e.g. compiled with `--prime pallas` for the Pallas curve.
+    ///
+    /// Circom places the constant `1`, the public outputs, and the public inputs before every
+    /// other signal, so these become the public input of the index, in that order, and the
+    /// remaining signals become the witness.
+    pub fn index_from_circom(
+        pp: &PublicParameters,
+        path: &Path,
+    ) -> R1CSResult<(IndexProverKey<G>, IndexVerifierKey<G>)> {
+        let bytes = read_file(path)?;
+        let sections = read_sections(&bytes, R1CS_MAGIC)?;
+
+        let mut header = Reader::new(find_section(&sections, R1CS_HEADER_SECTION)?);
+        let n8 = read_prime::<G::ScalarField>(&mut header)?;
+        let num_wires = header.read_u32()? as usize;
+        let num_public_outputs = header.read_u32()? as usize;
+        let num_public_inputs = header.read_u32()? as usize;
+        let _num_private_inputs = header.read_u32()?;
+        let _num_labels = header.read_u64()?;
+        let num_constraints = header.read_u32()? as usize;
+
+        let num_instance_variables = 1 + num_public_outputs + num_public_inputs;
+        if num_instance_variables > num_wires {
+            return Err(circom_error("more public signals than signals"));
+        }
+
+        // Each constraint takes at least 12 bytes, for the numbers of terms of its three rows.
+        let mut constraints = Reader::new(find_section(&sections, R1CS_CONSTRAINTS_SECTION)?);
+        let capacity = constraints.capacity(num_constraints, 12);
+        let mut a = Vec::with_capacity(capacity);
+        let mut b = Vec::with_capacity(capacity);
+        let mut c = Vec::with_capacity(capacity);
+        for _ in 0..num_constraints {
+            a.push(read_row(&mut constraints, n8)?);
+            b.push(read_row(&mut constraints, n8)?);
+            c.push(read_row(&mut constraints, n8)?);
+        }
+
+        Self::index_from_matrices(
+            pp,
+            a,
+            b,
+            c,
+            num_instance_variables,
+            num_wires - num_instance_variables,
+        )
+    }
+
+    /// Loads the assignment of a binary `.wtns` file produced by Circom for the R1CS instance of
+    /// `ipk`, which must have been indexed by [`R1CSNark::index_from_circom`]. Returns an error if
+    /// the file has a different number of signals than the index has variables.
+    pub fn load_wtns(
+        ipk: &IndexProverKey<G>,
+        path: &Path,
+    ) -> R1CSResult<FullAssignment<G::ScalarField>> {
+        let bytes = read_file(path)?;
+        let sections = read_sections(&bytes, WTNS_MAGIC)?;
+
+        let mut header = Reader::new(find_section(&sections, WTNS_HEADER_SECTION)?);
+        let n8 = read_prime::<G::ScalarField>(&mut header)?;
+        let num_values = header.read_u32()? as usize;
+
+        let index_info = &ipk.index_info;
+        let num_instance_variables = index_info.num_instance_variables;
+        if num_values != index_info.num_variables {
+            return Err(NarkError::MalformedWitness {
+                expected_len: index_info.num_variables - num_instance_variables,
+                got: num_values.saturating_sub(num_instance_variables),
+            });
+        }
+
+        let mut values = Reader::new(find_section(&sections, WTNS_VALUES_SECTION)?);
+        let mut input = (0..num_values)
+            .map(|_| read_field_element(&mut values, n8))
+            .collect::<R1CSResult<Vec<_>>>()?;
+        let witness = input.split_off(num_instance_variables);
+
+        Ok(FullAssignment { input, witness })
+    }
+}
+
+fn circom_error(msg: &str) -> NarkError {
+    NarkError::CircomFile(msg.to_string())
+}
+
+fn read_file(path: &Path) -> R1CSResult<Vec<u8>> {
+    std::fs::read(path).map_err(|err| NarkError::CircomFile(err.to_string()))
+}
+
+// Splits a Circom binary file into its `(section type, section data)` pairs, after checking its
+// magic bytes. Both `.r1cs` and `.wtns` files share this layout.
+fn read_sections<'a>(bytes: &'a [u8], magic: &[u8; 4]) -> R1CSResult<Vec<(u32, &'a [u8])>> {
+    let mut reader = Reader::new(bytes);
+    if reader.read_bytes(4)? != magic {
+        return Err(circom_error("unexpected magic bytes"));
+    }
+
+    let _version = reader.read_u32()?;
+    let num_sections = reader.read_u32()?;
+    let mut sections = Vec::with_capacity(reader.capacity(num_sections as usize, 12));
+    for _ in 0..num_sections {
+        let section_type = reader.read_u32()?;
+        let section_len = reader.read_u64()? as usize;
+        sections.push((section_type, reader.read_bytes(section_len)?));
+    }
+
+    Ok(sections)
+}
+
+fn find_section<'a>(sections: &[(u32, &'a [u8])], section_type: u32) -> R1CSResult<&'a [u8]> {
+    sections
+        .iter()
+        .find(|(ty, _)| *ty == section_type)
+        .map(|(_, data)| *data)
+        .ok_or_else(|| circom_error("missing section"))
+}
+
+// Reads the field size and prime of a header, and checks that the prime is the modulus of `F`.
+// Returns the size of the field elements of the file.
+fn read_prime<F: PrimeField>(reader: &mut Reader) -> R1CSResult<usize> {
+    let n8 = reader.read_u32()? as usize;
+    let mut prime = reader.read_bytes(n8)?.to_vec();
+    let mut modulus = <F::Params as FpParameters>::MODULUS.to_bytes_le();
+
+    let len = prime.len().max(modulus.len());
+    prime.resize(len, 0);
+    modulus.resize(len, 0);
+    if prime != modulus {
+        return Err(circom_error(
+            "the prime is not the modulus of the scalar field",
+        ));
+    }
+
+    if n8 != F::zero().serialized_size() {
+        return Err(circom_error("unsupported field element size"));
+    }
+
+    Ok(n8)
+}
+
+fn read_field_element<F: PrimeField>(reader: &mut Reader, n8: usize) -> R1CSResult<F> {
+    F::deserialize(reader.read_bytes(n8)?)
+        .map_err(|_| circom_error("field element is not reduced modulo the prime"))
+}
+
+// Reads one linear combination of a constraint, given as its number of terms followed by
+// `(wire, coefficient)` pairs.
+fn read_row<F: PrimeField>(reader: &mut Reader, n8: usize) -> R1CSResult<Vec<(F, usize)>> {
+    let num_terms = reader.read_u32()?;
+    let mut row = Vec::with_capacity(reader.capacity(num_terms as usize, 4 + n8));
+    for _ in 0..num_terms {
+        let wire = reader.read_u32()? as usize;
+        let coeff: F = read_field_element(reader, n8)?;
+        if !coeff.is_zero() {
+            row.push((coeff, wire));
+        }
+    }
+
+    Ok(row)
+}
+
+// A cursor over the little-endian encoding of a Circom binary file.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn read_bytes(&mut self, len: usize) -> R1CSResult<&'a [u8]> {
+        if len > self.bytes.len() {
+            return Err(circom_error("unexpected end of file"));
+        }
+
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    // Returns the capacity to reserve for `count` items of at least `item_len` bytes each, which
+    // is capped by the number of such items that fit in the remaining bytes, so that a corrupted
+    // count cannot cause a huge allocation.
+    fn capacity(&self, count: usize, item_len: usize) -> usize {
+        count.min(self.bytes.len() / item_len)
+    }
+
+    fn read_u32(&mut self) -> R1CSResult<u32> {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(self.read_bytes(4)?);
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn read_u64(&mut self) -> R1CSResult<u64> {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(self.read_bytes(8)?);
+        Ok(u64::from_le_bytes(buf))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::Zero;
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    #[test]
+    fn test_read_sections() {
+        let mut bytes = WTNS_MAGIC.to_vec();
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        for (ty, data) in &[(2u32, &b"abc"[..]), (1u32, &b"de"[..])] {
+            bytes.extend_from_slice(&ty.to_le_bytes());
+            bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(data);
+        }
+
+        let sections = read_sections(&bytes, WTNS_MAGIC).unwrap();
+        assert_eq!(find_section(&sections, 1).unwrap(), b"de");
+        assert_eq!(find_section(&sections, 2).unwrap(), b"abc");
+        assert!(find_section(&sections, 3).is_err());
+
+        assert!(read_sections(&bytes, R1CS_MAGIC).is_err());
+        assert!(read_sections(&bytes[..bytes.len() - 1], WTNS_MAGIC).is_err());
+    }
+
+    #[test]
+    fn test_read_prime() {
+        let modulus = <<Fr as PrimeField>::Params as FpParameters>::MODULUS.to_bytes_le();
+        let mut header = (modulus.len() as u32).to_le_bytes().to_vec();
+        header.extend_from_slice(&modulus);
+        assert_eq!(
+            read_prime::<Fr>(&mut Reader::new(&header)).unwrap(),
+            modulus.len()
+        );
+
+        header[4] ^= 1;
+        assert!(read_prime::<Fr>(&mut Reader::new(&header)).is_err());
+    }
+
+    #[test]
+    fn test_huge_counts() {
+        // Huge counts in truncated files are rejected without reserving memory for them.
+        let mut bytes = R1CS_MAGIC.to_vec();
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(read_sections(&bytes, R1CS_MAGIC).is_err());
+
+        let n8 = Fr::zero().serialized_size();
+        let mut row = u32::MAX.to_le_bytes().to_vec();
+        row.extend_from_slice(&0u32.to_le_bytes());
+        row.extend_from_slice(&vec![0u8; n8]);
+        let mut reader = Reader::new(&row);
+        assert_eq!(reader.capacity(u32::MAX as usize, 4 + n8), 0);
+        assert!(read_row::<Fr>(&mut reader, n8).is_err());
+
+        let modulus = <<Fr as PrimeField>::Params as FpParameters>::MODULUS.to_bytes_le();
+        let mut header = (modulus.len() as u32).to_le_bytes().to_vec();
+        header.extend_from_slice(&modulus);
+        for count in &[1u32, 0, 0, 0] {
+            header.extend_from_slice(&count.to_le_bytes());
+        }
+        header.extend_from_slice(&0u64.to_le_bytes());
+        header.extend_from_slice(&u32::MAX.to_le_bytes());
+
+        let mut bytes = R1CS_MAGIC.to_vec();
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        for (ty, data) in &[
+            (R1CS_HEADER_SECTION, &header[..]),
+            (R1CS_CONSTRAINTS_SECTION, &[][..]),
+        ] {
+            bytes.extend_from_slice(&ty.to_le_bytes());
+            bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(data);
+        }
+
+        let path = std::env::temp_dir().join("ark_accumulation_test_huge_counts.r1cs");
+        std::fs::write(&path, &bytes).unwrap();
+        let result = R1CSNark::<Affine, PoseidonSponge<Fq>>::index_from_circom(&(), &path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(NarkError::CircomFile(_))));
+    }
+}
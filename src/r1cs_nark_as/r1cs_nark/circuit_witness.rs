@@ -0,0 +1,188 @@
+use super::{FullAssignment, NarkError, R1CSResult};
+
+use ark_ff::Field;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError, Variable};
+use ark_std::cell::RefCell;
+use ark_std::ops::Deref;
+use ark_std::rc::Rc;
+use ark_std::vec::Vec;
+
+/// A wrapper around a [`ConstraintSystemRef`] that records the variables allocated through it as
+/// public input or witness variables, and returns the values assigned to them as a
+/// [`FullAssignment`] once synthesis completes. It dereferences to the wrapped constraint system,
+/// so constraints are enforced on it as usual.
+///
+/// # Example
+/// ```
+/// use ark_accumulation::r1cs_nark_as::r1cs_nark::CircuitWitness;
+/// use ark_ff::{One, Field};
+/// use ark_pallas::Fr;
+/// use ark_relations::lc;
+/// use ark_relations::r1cs::{ConstraintSystem, SynthesisMode};
+///
+/// let cs = ConstraintSystem::<Fr>::new_ref();
+/// cs.set_mode(SynthesisMode::Prove {
+///     construct_matrices: false,
+/// });
+///
+/// // Prove knowledge of a square root `x` of the public `y = 4`.
+/// let circuit = CircuitWitness::new(cs);
+/// let x = Fr::from(2u64);
+/// let y = circuit.alloc_input(|| Ok(x.square())).unwrap();
+/// let x = circuit.alloc_witness(|| Ok(x)).unwrap();
+/// circuit.enforce_constraint(lc!() + x, lc!() + x, lc!() + y).unwrap();
+///
+/// let assignment = circuit.to_full_assignment().unwrap();
+/// assert_eq!(assignment.input, vec![Fr::one(), Fr::from(4u64)]);
+/// assert_eq!(assignment.witness, vec![Fr::from(2u64)]);
+/// ```
+#[derive(Clone)]
+pub struct CircuitWitness<F: Field> {
+    cs: ConstraintSystemRef<F>,
+    input_variables: Rc<RefCell<Vec<Variable>>>,
+    witness_variables: Rc<RefCell<Vec<Variable>>>,
+}
+
+impl<F: Field> CircuitWitness<F> {
+    /// Wraps `cs`. Values are only assigned to variables if `cs` is in prove mode.
+    pub fn new(cs: ConstraintSystemRef<F>) -> Self {
+        Self {
+            cs,
+            input_variables: Rc::default(),
+            witness_variables: Rc::default(),
+        }
+    }
+
+    /// Allocates a public input variable with the value returned by `value_fn`.
+    pub fn alloc_input(
+        &self,
+        value_fn: impl FnOnce() -> Result<F, SynthesisError>,
+    ) -> Result<Variable, SynthesisError> {
+        let var = self.cs.new_input_variable(value_fn)?;
+        self.input_variables.borrow_mut().push(var);
+        Ok(var)
+    }
+
+    /// Allocates a witness variable with the value returned by `value_fn`.
+    pub fn alloc_witness(
+        &self,
+        value_fn: impl FnOnce() -> Result<F, SynthesisError>,
+    ) -> Result<Variable, SynthesisError> {
+        let var = self.cs.new_witness_variable(value_fn)?;
+        self.witness_variables.borrow_mut().push(var);
+        Ok(var)
+    }
+
+    /// Returns the public input variables allocated through this wrapper, in allocation order.
+    pub fn input_variables(&self) -> Vec<Variable> {
+        self.input_variables.borrow().clone()
+    }
+
+    /// Returns the witness variables allocated through this wrapper, in allocation order.
+    pub fn witness_variables(&self) -> Vec<Variable> {
+        self.witness_variables.borrow().clone()
+    }
+
+    /// Returns the values assigned to all the variables of the constraint system, including
+    /// those not allocated through this wrapper. Returns an error if the constraint system is not
+    /// in prove mode, in which case no values are assigned.
+    pub fn to_full_assignment(&self) -> R1CSResult<FullAssignment<F>> {
+        if self.cs.is_in_setup_mode() {
+            return Err(NarkError::Synthesis(SynthesisError::AssignmentMissing));
+        }
+
+        let cs = self
+            .cs
+            .borrow()
+            .ok_or(NarkError::Synthesis(SynthesisError::MissingCS))?;
+
+        Ok(FullAssignment {
+            input: cs.instance_assignment.clone(),
+            witness: cs.witness_assignment.clone(),
+        })
+    }
+}
+
+impl<F: Field> Deref for CircuitWitness<F> {
+    type Target = ConstraintSystemRef<F>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.cs
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::R1CSNark;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisMode};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    // Enforces `x * y = z` for secret `x` and `y` and a public `z`.
+    #[derive(Clone, Copy)]
+    struct ProductCircuit<F> {
+        x: F,
+        y: F,
+    }
+
+    impl<F: Field> ConstraintSynthesizer<F> for ProductCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let cs = CircuitWitness::new(cs);
+            let z = cs.alloc_input(|| Ok(self.x * self.y))?;
+            let x = cs.alloc_witness(|| Ok(self.x))?;
+            let y = cs.alloc_witness(|| Ok(self.y))?;
+            cs.enforce_constraint(lc!() + x, lc!() + y, lc!() + z)
+        }
+    }
+
+    #[test]
+    fn test_circuit_witness() {
+        let rng = &mut ark_std::test_rng();
+        let (x, y) = (Fr::rand(rng), Fr::rand(rng));
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        cs.set_mode(SynthesisMode::Prove {
+            construct_matrices: false,
+        });
+        let circuit = CircuitWitness::new(cs);
+        let z_var = circuit.alloc_input(|| Ok(x * y)).unwrap();
+        let x_var = circuit.alloc_witness(|| Ok(x)).unwrap();
+        let y_var = circuit.alloc_witness(|| Ok(y)).unwrap();
+        circuit
+            .enforce_constraint(lc!() + x_var, lc!() + y_var, lc!() + z_var)
+            .unwrap();
+        assert_eq!(circuit.input_variables(), vec![z_var]);
+        assert_eq!(circuit.witness_variables(), vec![x_var, y_var]);
+
+        let assignment = circuit.to_full_assignment().unwrap();
+        assert_eq!(
+            assignment,
+            FullAssignment {
+                input: vec![Fr::one(), x * y],
+                witness: vec![x, y],
+            }
+        );
+
+        // The assignment is that of a circuit built with the wrapper.
+        let c = ProductCircuit { x, y };
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let proof = Nark::prove(&ipk, c, false, None, None).unwrap();
+        assert_eq!(proof.second_msg.blinded_witness, assignment.witness);
+        assert!(Nark::verify(&ivk, &assignment.input, &proof, None).unwrap());
+
+        let setup_cs = ConstraintSystem::<Fr>::new_ref();
+        setup_cs.set_mode(SynthesisMode::Setup);
+        let circuit = CircuitWitness::new(setup_cs);
+        circuit.alloc_witness(|| Ok(x)).unwrap();
+        assert!(matches!(
+            circuit.to_full_assignment(),
+            Err(NarkError::Synthesis(SynthesisError::AssignmentMissing))
+        ));
+    }
+}
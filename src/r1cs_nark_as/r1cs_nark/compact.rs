@@ -0,0 +1,299 @@
+use super::{blake2b, IndexVerifierKey, Proof, R1CSNark, R1CSResult};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::Field;
+use ark_relations::r1cs::Matrix;
+use ark_serialize::CanonicalSerialize;
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::cell::RefCell;
+use ark_std::collections::BTreeMap;
+use ark_std::marker::PhantomData;
+use ark_std::string::String;
+use ark_std::vec::Vec;
+
+/// The `A`, `B`, and `C` matrices of an R1CS instance.
+pub type R1CSMatrices<F> = (Matrix<F>, Matrix<F>, Matrix<F>);
+
+/// A verifier key that identifies an R1CS instance by digests instead of including its matrices,
+/// for settings in which the full [`IndexVerifierKey`] is too large to transmit. The matrices are
+/// fetched with a [`MatrixFetcher`] when verifying with [`R1CSNark::verify_compact`].
+///
+/// Only keys produced by [`R1CSNark::index`] or [`R1CSNark::index_from_matrices`] without further
+/// changes can be reconstructed, since the application domain, the circuit version, and any
+/// padding of the key are not recorded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompactVerifierKey<G: AffineCurve> {
+    /// The Blake2b hash of the serialized commitment key.
+    pub ck_hash: [u8; 32],
+
+    /// The hash of the matrices of the R1CS instance, which identifies it.
+    pub circuit_digest: [u8; 32],
+
+    /// The number of public input (i.e. instance) variables, including the constant `1`. The
+    /// matrices hash does not cover how the columns are split between the input and the witness,
+    /// so the split is fixed by the key.
+    pub num_instance_variables: usize,
+
+    /// The total number of variables, including the public input.
+    pub num_variables: usize,
+
+    /// Where the matrices can be fetched from, if the fetcher needs to be told.
+    pub matrix_source_url: Option<String>,
+
+    _affine: PhantomData<G>,
+}
+
+impl<G: AffineCurve> CompactVerifierKey<G> {
+    /// Compacts `ivk`, whose matrices can be fetched from `matrix_source_url`.
+    pub fn new(ivk: &IndexVerifierKey<G>, matrix_source_url: Option<String>) -> Self {
+        Self {
+            ck_hash: hash_commitment_key(ivk),
+            circuit_digest: ivk.index_info.matrices_hash,
+            num_instance_variables: ivk.index_info.num_instance_variables,
+            num_variables: ivk.index_info.num_variables,
+            matrix_source_url,
+            _affine: PhantomData,
+        }
+    }
+}
+
+// Returns the Blake2b hash of the serialized commitment key of `ivk`.
+fn hash_commitment_key<G: AffineCurve>(ivk: &IndexVerifierKey<G>) -> [u8; 32] {
+    let mut bytes = Vec::new();
+    ivk.ck.serialize(&mut bytes).unwrap();
+    blake2b(&bytes)
+}
+
+/// A trusted source of the matrices of R1CS instances, such as a content-addressed store.
+pub trait MatrixFetcher<F: Field> {
+    /// Returns the matrices of the R1CS instance whose matrices hash to `digest`, or
+    /// [`NarkError::MatricesNotFound`][not_found] if the source does not have them.
+    ///
+    /// [not_found]: crate::r1cs_nark_as::r1cs_nark::NarkError::MatricesNotFound
+    fn fetch(&self, digest: &[u8; 32]) -> R1CSResult<R1CSMatrices<F>>;
+}
+
+/// A [`MatrixFetcher`] that keeps the matrices it has fetched, so that the matrices of each
+/// R1CS instance are only fetched once.
+pub struct CachingMatrixFetcher<F: Field, M: MatrixFetcher<F>> {
+    fetcher: M,
+    cache: RefCell<BTreeMap<[u8; 32], R1CSMatrices<F>>>,
+}
+
+impl<F: Field, M: MatrixFetcher<F>> CachingMatrixFetcher<F, M> {
+    /// Caches the matrices fetched by `fetcher`.
+    pub fn new(fetcher: M) -> Self {
+        Self {
+            fetcher,
+            cache: RefCell::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl<F: Field, M: MatrixFetcher<F>> MatrixFetcher<F> for CachingMatrixFetcher<F, M> {
+    fn fetch(&self, digest: &[u8; 32]) -> R1CSResult<R1CSMatrices<F>> {
+        if let Some(matrices) = self.cache.borrow().get(digest) {
+            return Ok(matrices.clone());
+        }
+
+        let matrices = self.fetcher.fetch(digest)?;
+        self.cache.borrow_mut().insert(*digest, matrices.clone());
+        Ok(matrices)
+    }
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Verifies that some R1CS relation holds under a compact verifier key. The matrices of the
+    /// instance are fetched with `fetcher`, which should be a [`CachingMatrixFetcher`] when many
+    /// proofs are verified, and the verifier key is rebuilt from them.
+    ///
+    /// Returns false if the matrices cannot be fetched, if they do not match the digest or the
+    /// commitment key of `cvk`, if the input or the witness of the proof do not have the lengths
+    /// recorded in `cvk`, or if the proof does not verify.
+    pub fn verify_compact(
+        cvk: &CompactVerifierKey<G>,
+        input: &[G::ScalarField],
+        proof: &Proof<G>,
+        fetcher: &impl MatrixFetcher<G::ScalarField>,
+        sponge: Option<S>,
+    ) -> bool {
+        let num_witness_variables = match cvk.num_variables.checked_sub(cvk.num_instance_variables)
+        {
+            Some(num_witness_variables) => num_witness_variables,
+            None => return false,
+        };
+        if input.len() != cvk.num_instance_variables
+            || proof.second_msg.blinded_witness.len() != num_witness_variables
+        {
+            return false;
+        }
+
+        let (a, b, c) = match fetcher.fetch(&cvk.circuit_digest) {
+            Ok(matrices) => matrices,
+            Err(_) => return false,
+        };
+        let ivk = match Self::index_from_matrices(
+            &(),
+            a,
+            b,
+            c,
+            cvk.num_instance_variables,
+            num_witness_variables,
+        ) {
+            Ok((_, ivk)) => ivk,
+            Err(_) => return false,
+        };
+
+        if ivk.index_info.matrices_hash != cvk.circuit_digest
+            || hash_commitment_key(&ivk) != cvk.ck_hash
+        {
+            return false;
+        }
+
+        Self::verify(&ivk, input, proof, sponge).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use crate::r1cs_nark_as::r1cs_nark::NarkError;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+    use ark_std::cell::Cell;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    // Serves matrices from memory, and counts how many times it was asked for them.
+    struct InMemoryFetcher {
+        matrices: BTreeMap<[u8; 32], R1CSMatrices<Fr>>,
+        num_fetches: Cell<usize>,
+    }
+
+    impl MatrixFetcher<Fr> for InMemoryFetcher {
+        fn fetch(&self, digest: &[u8; 32]) -> R1CSResult<R1CSMatrices<Fr>> {
+            self.num_fetches.set(self.num_fetches.get() + 1);
+            self.matrices
+                .get(digest)
+                .cloned()
+                .ok_or(NarkError::MatricesNotFound { digest: *digest })
+        }
+    }
+
+    fn matrices(ivk: &IndexVerifierKey<Affine>) -> R1CSMatrices<Fr> {
+        (
+            ivk.a.clone().into(),
+            ivk.b.clone().into(),
+            ivk.c.clone().into(),
+        )
+    }
+
+    #[test]
+    fn test_verify_compact() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let other_c = DummyCircuit {
+            num_constraints: 50,
+            ..c
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let (_, other_ivk) = Nark::index(&pp, other_c).unwrap();
+        let proof = Nark::prove(&ipk, c, true, None, Some(rng)).unwrap();
+        let cvk = CompactVerifierKey::new(&ivk, None);
+
+        let mut stored = BTreeMap::new();
+        stored.insert(cvk.circuit_digest, matrices(&ivk));
+        let fetcher = CachingMatrixFetcher::new(InMemoryFetcher {
+            matrices: stored,
+            num_fetches: Cell::new(0),
+        });
+
+        assert!(Nark::verify_compact(
+            &cvk,
+            &r1cs_input,
+            &proof,
+            &fetcher,
+            None
+        ));
+        let wrong_input = vec![Fr::one(), Fr::rand(rng)];
+        assert!(!Nark::verify_compact(
+            &cvk,
+            &wrong_input,
+            &proof,
+            &fetcher,
+            None
+        ));
+        assert_eq!(fetcher.fetcher.num_fetches.get(), 1);
+
+        // Matrices that do not hash to the digest are rejected.
+        let mut stored = BTreeMap::new();
+        stored.insert(cvk.circuit_digest, matrices(&other_ivk));
+        let wrong_fetcher = InMemoryFetcher {
+            matrices: stored,
+            num_fetches: Cell::new(0),
+        };
+        assert!(!Nark::verify_compact(
+            &cvk,
+            &r1cs_input,
+            &proof,
+            &wrong_fetcher,
+            None
+        ));
+
+        let unknown_cvk = CompactVerifierKey::new(&other_ivk, None);
+        assert!(!Nark::verify_compact(
+            &unknown_cvk,
+            &r1cs_input,
+            &proof,
+            &fetcher,
+            None
+        ));
+
+        // The split of the variables between the input and the witness is fixed by the key, even
+        // for an input whose length matches a different split.
+        let mut wrong_split_cvk = cvk.clone();
+        wrong_split_cvk.num_instance_variables += 1;
+        let mut long_input = r1cs_input.clone();
+        long_input.push(c.a.unwrap());
+        assert!(!Nark::verify_compact(
+            &wrong_split_cvk,
+            &long_input,
+            &proof,
+            &fetcher,
+            None
+        ));
+        assert!(!Nark::verify_compact(
+            &cvk,
+            &long_input,
+            &proof,
+            &fetcher,
+            None
+        ));
+
+        let mut wrong_ck_cvk = cvk.clone();
+        wrong_ck_cvk.ck_hash = hash_commitment_key(&other_ivk);
+        assert!(!Nark::verify_compact(
+            &wrong_ck_cvk,
+            &r1cs_input,
+            &proof,
+            &fetcher,
+            None
+        ));
+    }
+}
@@ -0,0 +1,88 @@
+use ark_ff::Field;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::marker::PhantomData;
+
+/// A circuit that generates the constraints of `A` followed by the constraints of `B` in the same
+/// constraint system, so that both are proven jointly.
+#[derive(Clone)]
+pub struct ComposedCircuit<F: Field, A: ConstraintSynthesizer<F>, B: ConstraintSynthesizer<F>> {
+    inner_a: A,
+    inner_b: B,
+    _field: PhantomData<F>,
+}
+
+impl<F, A, B> ComposedCircuit<F, A, B>
+where
+    F: Field,
+    A: ConstraintSynthesizer<F>,
+    B: ConstraintSynthesizer<F>,
+{
+    /// Chains `inner_a` and `inner_b` into a single circuit.
+    pub fn new(inner_a: A, inner_b: B) -> Self {
+        Self {
+            inner_a,
+            inner_b,
+            _field: PhantomData,
+        }
+    }
+}
+
+impl<F, A, B> ConstraintSynthesizer<F> for ComposedCircuit<F, A, B>
+where
+    F: Field,
+    A: ConstraintSynthesizer<F>,
+    B: ConstraintSynthesizer<F>,
+{
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        self.inner_a.generate_constraints(cs.clone())?;
+        self.inner_b.generate_constraints(cs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use crate::r1cs_nark_as::r1cs_nark::R1CSNark;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_composed_circuit() {
+        let rng = &mut ark_std::test_rng();
+        let c1 = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 5,
+            num_constraints: 10,
+        };
+        let c2 = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 20,
+        };
+        let composed = ComposedCircuit::new(c1, c2);
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, composed.clone()).unwrap();
+        // The trivial constraint of each circuit is removed when indexing.
+        assert_eq!(ipk.index_info.num_constraints, 28);
+        assert_eq!(ipk.index_info.num_instance_variables, 3);
+
+        let r1cs_input = vec![
+            Fr::one(),
+            c1.a.unwrap() * c1.b.unwrap(),
+            c2.a.unwrap() * c2.b.unwrap(),
+        ];
+        let proof = Nark::prove(&ipk, composed, true, None, Some(rng)).unwrap();
+        assert!(Nark::verify(&ivk, &r1cs_input, &proof, None).unwrap());
+
+        let mut wrong_input = r1cs_input;
+        wrong_input[2] += Fr::one();
+        assert!(!Nark::verify(&ivk, &wrong_input, &proof, None).unwrap());
+    }
+}
@@ -0,0 +1,59 @@
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::Zero;
+use ark_poly_commit::trivial_pc::{CommitterKey, PedersenCommitment};
+use ark_std::rand::RngCore;
+use ark_std::vec::Vec;
+use ark_std::UniformRand;
+
+/// Computes the same commitment as [`PedersenCommitment::commit`], but never multiplies the
+/// generators by the committed scalars or the blinding directly. Instead, it samples an
+/// independent mask for each scalar and for the blinding from `rng`, commits to the masked
+/// scalars with the masked blinding, and subtracts the commitment to the masks, which is correct
+/// because Pedersen commitments are additively homomorphic. Since the scalars of both
+/// multi-scalar multiplications are uniformly random and independent of `scalars` and
+/// `blinding`, their running time does not depend on them.
+pub fn masked_commit<G: AffineCurve>(
+    ck: &CommitterKey<G>,
+    scalars: &[G::ScalarField],
+    blinding: Option<G::ScalarField>,
+    rng: &mut dyn RngCore,
+) -> G {
+    let masks = (0..scalars.len())
+        .map(|_| G::ScalarField::rand(rng))
+        .collect::<Vec<_>>();
+    let blinding_mask = G::ScalarField::rand(rng);
+
+    let masked_scalars = scalars
+        .iter()
+        .zip(&masks)
+        .map(|(s, mask)| *s + mask)
+        .collect::<Vec<_>>();
+    let masked_blinding = blinding.unwrap_or_else(G::ScalarField::zero) + blinding_mask;
+
+    let masked_comm = PedersenCommitment::commit(ck, &masked_scalars, Some(masked_blinding));
+    let mask_comm = PedersenCommitment::commit(ck, &masks, Some(blinding_mask));
+    (masked_comm.into_projective() - mask_comm.into_projective()).into_affine()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_pallas::{Affine, Fr};
+
+    #[test]
+    fn test_masked_commit() {
+        let rng = &mut ark_std::test_rng();
+        let pp = PedersenCommitment::<Affine>::setup(16);
+        let ck = PedersenCommitment::trim(&pp, 16);
+
+        for &len in &[0, 1, 16] {
+            let scalars = (0..len).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+            for &blinding in &[None, Some(Fr::rand(rng))] {
+                assert_eq!(
+                    masked_commit(&ck, &scalars, blinding, rng),
+                    PedersenCommitment::commit(&ck, &scalars, blinding)
+                );
+            }
+        }
+    }
+}
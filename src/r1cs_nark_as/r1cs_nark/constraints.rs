@@ -0,0 +1,638 @@
+use crate::r1cs_nark_as::constraints::FirstRoundMessageVar;
+use crate::r1cs_nark_as::r1cs_nark::{
+    IndexVerifierKey, Proof, SecondRoundMessage, SecondRoundMessageRandomness,
+    CURRENT_PROTOCOL_VERSION, INPUT_DIGEST_DOMAIN,
+};
+use crate::r1cs_nark_as::CHALLENGE_SIZE;
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::{PrimeField, ToConstraintField};
+use ark_nonnative_field::NonNativeFieldVar;
+use ark_r1cs_std::alloc::{AllocVar, AllocationMode};
+use ark_r1cs_std::bits::boolean::Boolean;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::fields::FieldVar;
+use ark_r1cs_std::groups::CurveVar;
+use ark_r1cs_std::uint8::UInt8;
+use ark_r1cs_std::{ToBitsGadget, ToBytesGadget};
+use ark_relations::r1cs::{ConstraintSystemRef, Matrix, Namespace, SynthesisError};
+use ark_sponge::constraints::{AbsorbableGadget, CryptographicSpongeVar};
+use ark_sponge::{absorb_gadget, Absorbable, CryptographicSponge, FieldElementSize};
+use ark_std::borrow::Borrow;
+use ark_std::marker::PhantomData;
+use ark_std::vec::Vec;
+
+/// A gadget that recomputes [`hash_matrices_poseidon`][hash] inside an R1CS over the field of
+/// the matrices, allowing a circuit to check the structure of another circuit.
+///
+/// [hash]: crate::r1cs_nark_as::r1cs_nark::hash_matrices_poseidon
+pub struct PoseidonMatrixHashGadget<F, S, SV>
+where
+    F: PrimeField,
+    S: CryptographicSponge<F>,
+    SV: CryptographicSpongeVar<F, S>,
+{
+    _field: PhantomData<F>,
+    _sponge: PhantomData<S>,
+    _sponge_var: PhantomData<SV>,
+}
+
+impl<F, S, SV> PoseidonMatrixHashGadget<F, S, SV>
+where
+    F: PrimeField,
+    S: CryptographicSponge<F>,
+    SV: CryptographicSpongeVar<F, S>,
+{
+    /// Computes the hash of the matrices. The dimensions of the matrices are fixed by the
+    /// circuit, while their coefficients and column indices are allocated with `mode`.
+    pub fn hash(
+        cs: ConstraintSystemRef<F>,
+        domain_separator: &[u8],
+        a: &Matrix<F>,
+        b: &Matrix<F>,
+        c: &Matrix<F>,
+        mode: AllocationMode,
+    ) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        let domain_separator: Vec<F> = domain_separator.to_field_elements().unwrap();
+        let mut encoded: Vec<FpVar<F>> =
+            domain_separator.into_iter().map(FpVar::constant).collect();
+
+        for matrix in &[a, b, c] {
+            encoded.push(FpVar::constant(F::from(matrix.len() as u64)));
+            for row in matrix.iter() {
+                encoded.push(FpVar::constant(F::from(row.len() as u64)));
+                for (coeff, index) in row {
+                    encoded.push(FpVar::new_variable(cs.clone(), || Ok(*coeff), mode)?);
+                    encoded.push(FpVar::new_variable(
+                        cs.clone(),
+                        || Ok(F::from(*index as u64)),
+                        mode,
+                    )?);
+                }
+            }
+        }
+
+        let mut sponge = SV::new(cs);
+        sponge.absorb(&encoded)?;
+        sponge.squeeze_bytes(32)
+    }
+}
+
+/// The [`IndexVerifierKey`] of the [`R1CSNarkVerifierGadget`]. The matrices are fixed when the
+/// verification circuit is generated, while the matrices hash and the commitment key are
+/// allocated.
+pub struct IndexVerifierKeyVar<G: AffineCurve, C: CurveVar<G::Projective, ConstraintF<G>>> {
+    /// The number of public input (i.e. instance) variables.
+    pub(crate) num_instance_variables: usize,
+
+    /// The number of witness variables.
+    pub(crate) num_witness_variables: usize,
+
+    /// The `A` matrix of the R1CS instance.
+    pub(crate) a: Matrix<G::ScalarField>,
+
+    /// The `B` matrix of the R1CS instance.
+    pub(crate) b: Matrix<G::ScalarField>,
+
+    /// The `C` matrix of the R1CS instance.
+    pub(crate) c: Matrix<G::ScalarField>,
+
+    /// Hash of the matrices.
+    pub(crate) matrices_hash: Vec<FpVar<ConstraintF<G>>>,
+
+    /// The application domain separation tag, which is fixed like the matrices.
+    pub(crate) app_domain: Vec<u8>,
+
+    /// Whether the verifier receives a digest of the public input instead of the input itself.
+    pub(crate) hash_public_input: bool,
+
+    /// The version of the protocol that the key was generated for.
+    pub(crate) protocol_version: u32,
+
+    /// The version of the circuit, which is absorbed into the sponge if it is non-zero.
+    pub(crate) circuit_version: u32,
+
+    /// The generators of the Pedersen commitment key, one for each constraint.
+    pub(crate) generators: Vec<C>,
+
+    /// The generator of the Pedersen commitment key used for hiding.
+    pub(crate) hiding_generator: C,
+}
+
+impl<G, C> AllocVar<IndexVerifierKey<G>, ConstraintF<G>> for IndexVerifierKeyVar<G, C>
+where
+    G: AffineCurve,
+    C: CurveVar<G::Projective, ConstraintF<G>>,
+{
+    fn new_variable<T: Borrow<IndexVerifierKey<G>>>(
+        cs: impl Into<Namespace<ConstraintF<G>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        f().and_then(|ivk| {
+            let ivk = ivk.borrow();
+            let index_info = &ivk.index_info;
+
+            let matrices_hash = index_info
+                .matrices_hash
+                .as_ref()
+                .to_sponge_field_elements()
+                .into_iter()
+                .map(|f: ConstraintF<G>| FpVar::new_variable(ns.clone(), || Ok(f), mode))
+                .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+            let generators = ivk
+                .ck
+                .generators
+                .iter()
+                .take(index_info.num_constraints)
+                .map(|g| C::new_variable(ns.clone(), || Ok(g.clone()), mode))
+                .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+            let hiding_generator =
+                C::new_variable(ns.clone(), || Ok(ivk.ck.hiding_generator.clone()), mode)?;
+
+            Ok(Self {
+                num_instance_variables: index_info.num_instance_variables,
+                num_witness_variables: index_info.num_variables - index_info.num_instance_variables,
+                a: ivk.a.clone().into(),
+                b: ivk.b.clone().into(),
+                c: ivk.c.clone().into(),
+                matrices_hash,
+                app_domain: ivk.app_domain.clone(),
+                hash_public_input: index_info.hash_public_input,
+                protocol_version: index_info.protocol_version,
+                circuit_version: index_info.circuit_version,
+                generators,
+                hiding_generator,
+            })
+        })
+    }
+}
+
+/// The sigma protocol's prover response randomness.
+pub struct SecondRoundMessageRandomnessVar<G: AffineCurve> {
+    /// The blinded randomness for the Pedersen commitment to the linear combination with the
+    /// `A` matrix.
+    pub(crate) sigma_a: NonNativeFieldVar<G::ScalarField, ConstraintF<G>>,
+
+    /// The blinded randomness for the Pedersen commitment to the linear combination with the
+    /// `B` matrix.
+    pub(crate) sigma_b: NonNativeFieldVar<G::ScalarField, ConstraintF<G>>,
+
+    /// The blinded randomness for the Pedersen commitment to the linear combination with the
+    /// `C` matrix.
+    pub(crate) sigma_c: NonNativeFieldVar<G::ScalarField, ConstraintF<G>>,
+
+    /// The blinded randomness for the Pedersen commitment to the cross terms
+    pub(crate) sigma_o: NonNativeFieldVar<G::ScalarField, ConstraintF<G>>,
+}
+
+impl<G: AffineCurve> AllocVar<SecondRoundMessageRandomness<G::ScalarField>, ConstraintF<G>>
+    for SecondRoundMessageRandomnessVar<G>
+{
+    fn new_variable<T: Borrow<SecondRoundMessageRandomness<G::ScalarField>>>(
+        cs: impl Into<Namespace<ConstraintF<G>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        f().and_then(|randomness| {
+            let randomness = randomness.borrow();
+            let alloc = |elem: G::ScalarField| {
+                NonNativeFieldVar::<G::ScalarField, ConstraintF<G>>::new_variable(
+                    ns.clone(),
+                    || Ok(elem),
+                    mode,
+                )
+            };
+
+            Ok(Self {
+                sigma_a: alloc(randomness.sigma_a)?,
+                sigma_b: alloc(randomness.sigma_b)?,
+                sigma_c: alloc(randomness.sigma_c)?,
+                sigma_o: alloc(randomness.sigma_o)?,
+            })
+        })
+    }
+}
+
+/// The sigma protocol's prover response.
+pub struct SecondRoundMessageVar<G: AffineCurve> {
+    /// The R1CS witness with randomness applied if zero-knowledge is needed.
+    pub(crate) blinded_witness: Vec<NonNativeFieldVar<G::ScalarField, ConstraintF<G>>>,
+
+    /// The randomness used for the response.
+    pub(crate) randomness: Option<SecondRoundMessageRandomnessVar<G>>,
+}
+
+impl<G: AffineCurve> AllocVar<SecondRoundMessage<G::ScalarField>, ConstraintF<G>>
+    for SecondRoundMessageVar<G>
+{
+    fn new_variable<T: Borrow<SecondRoundMessage<G::ScalarField>>>(
+        cs: impl Into<Namespace<ConstraintF<G>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        f().and_then(|second_msg| {
+            let second_msg = second_msg.borrow();
+
+            let blinded_witness = second_msg
+                .blinded_witness
+                .iter()
+                .map(|elem| {
+                    NonNativeFieldVar::<G::ScalarField, ConstraintF<G>>::new_variable(
+                        ns.clone(),
+                        || Ok(*elem),
+                        mode,
+                    )
+                })
+                .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+            let randomness = second_msg
+                .randomness
+                .as_ref()
+                .map(|r| {
+                    SecondRoundMessageRandomnessVar::new_variable(
+                        ns.clone(),
+                        || Ok(r.clone()),
+                        mode,
+                    )
+                })
+                .transpose()?;
+
+            Ok(Self {
+                blinded_witness,
+                randomness,
+            })
+        })
+    }
+}
+
+/// The [`Proof`] of the [`R1CSNarkVerifierGadget`].
+pub struct ProofVar<G: AffineCurve, C: CurveVar<G::Projective, ConstraintF<G>>> {
+    /// The sigma protocol's prove commitment.
+    pub(crate) first_msg: FirstRoundMessageVar<G, C>,
+
+    /// The sigma protocol's prove response.
+    pub(crate) second_msg: SecondRoundMessageVar<G>,
+}
+
+impl<G, C> AllocVar<Proof<G>, ConstraintF<G>> for ProofVar<G, C>
+where
+    G: AffineCurve,
+    C: CurveVar<G::Projective, ConstraintF<G>>,
+{
+    fn new_variable<T: Borrow<Proof<G>>>(
+        cs: impl Into<Namespace<ConstraintF<G>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        f().and_then(|proof| {
+            let proof = proof.borrow();
+
+            let first_msg = FirstRoundMessageVar::new_variable(
+                ns.clone(),
+                || Ok(proof.first_msg.clone()),
+                mode,
+            )?;
+            let second_msg = SecondRoundMessageVar::new_variable(
+                ns.clone(),
+                || Ok(proof.second_msg.clone()),
+                mode,
+            )?;
+
+            Ok(Self {
+                first_msg,
+                second_msg,
+            })
+        })
+    }
+}
+
+/// The verifier gadget of [`R1CSNark`][r1cs_nark], which allows a circuit to check a NARK proof.
+///
+/// [r1cs_nark]: crate::r1cs_nark_as::r1cs_nark::R1CSNark
+pub struct R1CSNarkVerifierGadget<G, C, S, SV>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    C: CurveVar<G::Projective, ConstraintF<G>> + AbsorbableGadget<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+    SV: CryptographicSpongeVar<ConstraintF<G>, S>,
+{
+    _affine: PhantomData<G>,
+    _curve: PhantomData<C>,
+    _sponge: PhantomData<S>,
+    _sponge_var: PhantomData<SV>,
+}
+
+impl<G, C, S, SV> R1CSNarkVerifierGadget<G, C, S, SV>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    C: CurveVar<G::Projective, ConstraintF<G>> + AbsorbableGadget<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+    SV: CryptographicSpongeVar<ConstraintF<G>, S>,
+{
+    /// Computes the gamma challenge of the proof.
+    fn compute_challenge(
+        matrices_hash: &[FpVar<ConstraintF<G>>],
+        input: &[NonNativeFieldVar<G::ScalarField, ConstraintF<G>>],
+        msg: &FirstRoundMessageVar<G, C>,
+        mut sponge: SV,
+    ) -> Result<
+        (
+            NonNativeFieldVar<G::ScalarField, ConstraintF<G>>,
+            Vec<Boolean<ConstraintF<G>>>,
+        ),
+        SynthesisError,
+    > {
+        sponge.absorb(&matrices_hash.to_vec())?;
+
+        let mut input_bytes = Vec::new();
+        for elem in input {
+            input_bytes.append(&mut elem.to_bytes()?);
+        }
+
+        absorb_gadget!(&mut sponge, input_bytes, msg);
+
+        let mut squeezed =
+            sponge.squeeze_nonnative_field_elements_with_sizes(&[FieldElementSize::Truncated(
+                CHALLENGE_SIZE,
+            )])?;
+
+        Ok((squeezed.0.pop().unwrap(), squeezed.1.pop().unwrap()))
+    }
+
+    /// Computes the product of `matrix` with `z`.
+    fn matrix_vec_mul(
+        matrix: &Matrix<G::ScalarField>,
+        z: &[&NonNativeFieldVar<G::ScalarField, ConstraintF<G>>],
+    ) -> Vec<NonNativeFieldVar<G::ScalarField, ConstraintF<G>>> {
+        matrix
+            .iter()
+            .map(|row| {
+                let mut acc = NonNativeFieldVar::zero();
+                for (coeff, i) in row {
+                    acc += &(z[*i] * *coeff);
+                }
+                acc
+            })
+            .collect()
+    }
+
+    /// Computes the Pedersen commitment to `vector` with the optional `randomness`.
+    fn commit(
+        ivk: &IndexVerifierKeyVar<G, C>,
+        vector: &[NonNativeFieldVar<G::ScalarField, ConstraintF<G>>],
+        randomness: Option<&NonNativeFieldVar<G::ScalarField, ConstraintF<G>>>,
+    ) -> Result<C, SynthesisError> {
+        let mut comm = C::zero();
+        for (generator, elem) in ivk.generators.iter().zip(vector) {
+            comm += &generator.scalar_mul_le(elem.to_bits_le()?.iter())?;
+        }
+
+        if let Some(randomness) = randomness {
+            comm += &ivk
+                .hiding_generator
+                .scalar_mul_le(randomness.to_bits_le()?.iter())?;
+        }
+
+        Ok(comm)
+    }
+
+    /// Checks a proof that some R1CS relation holds, mirroring
+    /// [`R1CSNark::verify`][verify]. Returns `Boolean::FALSE` without generating constraints
+    /// if `ivk` was generated for another protocol version, or if the input or the proof are
+    /// not structured as `ivk` expects.
+    ///
+    /// [verify]: crate::r1cs_nark_as::r1cs_nark::R1CSNark::verify
+    #[tracing::instrument(target = "r1cs", skip(cs, ivk, input, proof, sponge))]
+    pub fn verify(
+        cs: ConstraintSystemRef<ConstraintF<G>>,
+        ivk: &IndexVerifierKeyVar<G, C>,
+        input: &[NonNativeFieldVar<G::ScalarField, ConstraintF<G>>],
+        proof: &ProofVar<G, C>,
+        sponge: Option<SV>,
+    ) -> Result<Boolean<ConstraintF<G>>, SynthesisError> {
+        let first_msg = &proof.first_msg;
+        let second_msg = &proof.second_msg;
+        if ivk.protocol_version != CURRENT_PROTOCOL_VERSION
+            || input.len() != ivk.num_instance_variables
+            || second_msg.blinded_witness.len() != ivk.num_witness_variables
+            || first_msg.randomness.is_some() != second_msg.randomness.is_some()
+        {
+            return Ok(Boolean::FALSE);
+        }
+
+        // Step 2 of the scheme's verifier, as detailed in BCLMS20.
+        let mut sponge = sponge.unwrap_or_else(|| SV::new(cs));
+        if !ivk.app_domain.is_empty() {
+            sponge.absorb(&UInt8::constant_vec(&ivk.app_domain))?;
+        }
+        if ivk.hash_public_input {
+            sponge.absorb(&UInt8::constant_vec(INPUT_DIGEST_DOMAIN))?;
+        }
+        if ivk.circuit_version != 0 {
+            sponge.absorb(&UInt8::constant_vec(&ivk.circuit_version.to_le_bytes()))?;
+        }
+        let (gamma, gamma_bits) =
+            Self::compute_challenge(&ivk.matrices_hash, input, first_msg, sponge)?;
+
+        // Step 3 of the scheme's verifier, as detailed in BCLMS20.
+        let z = input
+            .iter()
+            .chain(&second_msg.blinded_witness)
+            .collect::<Vec<_>>();
+        let a_times_blinded_witness = Self::matrix_vec_mul(&ivk.a, &z);
+        let b_times_blinded_witness = Self::matrix_vec_mul(&ivk.b, &z);
+        let c_times_blinded_witness = Self::matrix_vec_mul(&ivk.c, &z);
+
+        // Step 4 of the scheme's verifier, as detailed in BCLMS20.
+        let mut comm_a = first_msg.comm_a.clone();
+        let mut comm_b = first_msg.comm_b.clone();
+        let mut comm_c = first_msg.comm_c.clone();
+        let mut had_prod_comm = first_msg.comm_c.clone();
+        if let Some(randomness) = first_msg.randomness.as_ref() {
+            comm_a += &randomness.comm_r_a.scalar_mul_le(gamma_bits.iter())?;
+            comm_b += &randomness.comm_r_b.scalar_mul_le(gamma_bits.iter())?;
+            comm_c += &randomness.comm_r_c.scalar_mul_le(gamma_bits.iter())?;
+            had_prod_comm += &randomness.comm_1.scalar_mul_le(gamma_bits.iter())?;
+            had_prod_comm += &randomness
+                .comm_2
+                .scalar_mul_le(gamma.square()?.to_bits_le()?.iter())?;
+        }
+
+        let second_msg_randomness = second_msg.randomness.as_ref();
+        let reconstructed_comm_a = Self::commit(
+            ivk,
+            &a_times_blinded_witness,
+            second_msg_randomness.map(|r| &r.sigma_a),
+        )?;
+        let reconstructed_comm_b = Self::commit(
+            ivk,
+            &b_times_blinded_witness,
+            second_msg_randomness.map(|r| &r.sigma_b),
+        )?;
+        let reconstructed_comm_c = Self::commit(
+            ivk,
+            &c_times_blinded_witness,
+            second_msg_randomness.map(|r| &r.sigma_c),
+        )?;
+
+        // Step 5 of the scheme's verifier, as detailed in BCLMS20.
+        let had_prod = a_times_blinded_witness
+            .iter()
+            .zip(&b_times_blinded_witness)
+            .map(|(a, b)| a * b)
+            .collect::<Vec<_>>();
+        let reconstructed_had_prod_comm =
+            Self::commit(ivk, &had_prod, second_msg_randomness.map(|r| &r.sigma_o))?;
+
+        comm_a
+            .is_eq(&reconstructed_comm_a)?
+            .and(&comm_b.is_eq(&reconstructed_comm_b)?)?
+            .and(&comm_c.is_eq(&reconstructed_comm_c)?)?
+            .and(&had_prod_comm.is_eq(&reconstructed_had_prod_comm)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use crate::r1cs_nark_as::r1cs_nark::{hash_matrices_poseidon, R1CSNark, PROTOCOL_NAME};
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::constraints::GVar;
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_r1cs_std::R1CSVar;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_sponge::poseidon::constraints::PoseidonSpongeVar;
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    #[test]
+    fn test_poseidon_matrix_hash_gadget() {
+        let rng = &mut ark_std::test_rng();
+        let a = vec![
+            vec![(Fr::rand(rng), 2)],
+            vec![(Fr::one(), 0), (Fr::rand(rng), 3)],
+        ];
+        let b = vec![vec![(Fr::one(), 3)], vec![]];
+        let c = vec![vec![(Fr::one(), 1)], vec![(Fr::rand(rng), 1)]];
+
+        let expected = hash_matrices_poseidon::<Fr, PoseidonSponge<Fr>>(PROTOCOL_NAME, &a, &b, &c);
+        assert_ne!(
+            expected,
+            hash_matrices_poseidon::<Fr, PoseidonSponge<Fr>>(PROTOCOL_NAME, &b, &a, &c)
+        );
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let hash = PoseidonMatrixHashGadget::<Fr, PoseidonSponge<Fr>, PoseidonSpongeVar<Fr>>::hash(
+            cs.clone(),
+            PROTOCOL_NAME,
+            &a,
+            &b,
+            &c,
+            AllocationMode::Witness,
+        )
+        .unwrap();
+
+        assert_eq!(hash.value().unwrap(), expected.to_vec());
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_r1cs_nark_verifier_gadget() {
+        type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+        type Gadget =
+            R1CSNarkVerifierGadget<Affine, GVar, PoseidonSponge<Fq>, PoseidonSpongeVar<Fq>>;
+
+        let rng = &mut ark_std::test_rng();
+        let circuit = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 5,
+            num_constraints: 4,
+        };
+        let input = vec![Fr::one(), circuit.a.unwrap() * circuit.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, circuit).unwrap();
+
+        for &make_zk in &[false, true] {
+            let proof = Nark::prove(&ipk, circuit, make_zk, None, Some(rng)).unwrap();
+            let mut wrong_input = input.clone();
+            wrong_input[1] += Fr::one();
+
+            for (input, expected) in [(&input, true), (&wrong_input, false)].iter() {
+                assert_eq!(Nark::verify(&ivk, input, &proof, None).unwrap(), *expected);
+
+                let cs = ConstraintSystem::<Fq>::new_ref();
+                let ivk_var =
+                    IndexVerifierKeyVar::<Affine, GVar>::new_constant(cs.clone(), &ivk).unwrap();
+                let input_var = input
+                    .iter()
+                    .map(|elem| {
+                        NonNativeFieldVar::<Fr, Fq>::new_input(cs.clone(), || Ok(*elem)).unwrap()
+                    })
+                    .collect::<Vec<_>>();
+                let proof_var =
+                    ProofVar::<Affine, GVar>::new_witness(cs.clone(), || Ok(proof.clone()))
+                        .unwrap();
+
+                let result =
+                    Gadget::verify(cs.clone(), &ivk_var, &input_var, &proof_var, None).unwrap();
+                assert_eq!(result.value().unwrap(), *expected);
+                assert!(cs.is_satisfied().unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_r1cs_nark_verifier_gadget_with_versions() {
+        type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+        type Gadget =
+            R1CSNarkVerifierGadget<Affine, GVar, PoseidonSponge<Fq>, PoseidonSpongeVar<Fq>>;
+
+        let rng = &mut ark_std::test_rng();
+        let circuit = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 5,
+            num_constraints: 4,
+        };
+        let input = vec![Fr::one(), circuit.a.unwrap() * circuit.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk_1, ivk_1) = Nark::index_with_version(&pp, circuit, 1).unwrap();
+        let (_, ivk_2) = Nark::index_with_version(&pp, circuit, 2).unwrap();
+        let mut old_ivk = ivk_1.clone();
+        old_ivk.index_info.protocol_version = CURRENT_PROTOCOL_VERSION - 1;
+
+        let proof = Nark::prove(&ipk_1, circuit, true, None, Some(rng)).unwrap();
+        for (ivk, expected) in [(&ivk_1, true), (&ivk_2, false), (&old_ivk, false)].iter() {
+            let cs = ConstraintSystem::<Fq>::new_ref();
+            let ivk_var =
+                IndexVerifierKeyVar::<Affine, GVar>::new_constant(cs.clone(), *ivk).unwrap();
+            let input_var = input
+                .iter()
+                .map(|elem| {
+                    NonNativeFieldVar::<Fr, Fq>::new_input(cs.clone(), || Ok(*elem)).unwrap()
+                })
+                .collect::<Vec<_>>();
+            let proof_var =
+                ProofVar::<Affine, GVar>::new_witness(cs.clone(), || Ok(proof.clone())).unwrap();
+
+            let result =
+                Gadget::verify(cs.clone(), &ivk_var, &input_var, &proof_var, None).unwrap();
+            assert_eq!(result.value().unwrap(), *expected);
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use super::{IndexProverKey, IndexVerifierKey, Proof, PublicParameters, R1CSNark, R1CSResult};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_relations::lc;
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable,
+};
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::boxed::Box;
+use ark_std::rand::RngCore;
+use ark_std::vec::Vec;
+
+/// A predicate that does not map neatly onto a single R1CS constraint, and is instead compiled
+/// into R1CS constraints by [`CustomGate::to_constraint`].
+pub trait CustomGate<F: Field> {
+    /// Checks whether the predicate of the gate holds for `inputs`.
+    fn evaluate(&self, inputs: &[F]) -> bool;
+
+    /// Adds the witness variables and R1CS constraints that enforce the gate to `cs`.
+    fn to_constraint(&self, cs: &ConstraintSystemRef<F>) -> Result<(), SynthesisError>;
+}
+
+/// A gate that enforces `0 <= value <= max`, where both sides are compared as integers. `value`
+/// and `max - value` are decomposed into as many bits as `max` has.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RangeGate<F> {
+    /// The value in the range, which is allocated as a witness variable.
+    pub value: F,
+
+    /// The inclusive upper bound of the range.
+    pub max: F,
+}
+
+impl<F: PrimeField> CustomGate<F> for RangeGate<F> {
+    fn evaluate(&self, inputs: &[F]) -> bool {
+        let max = self.max.into_repr();
+        inputs.iter().all(|input| input.into_repr() <= max)
+    }
+
+    fn to_constraint(&self, cs: &ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let num_bits = self.max.into_repr().num_bits() as usize;
+        let value = cs.new_witness_variable(|| Ok(self.value))?;
+        let lower = alloc_bits(cs, self.value, num_bits)?;
+        let upper = alloc_bits(cs, self.max - self.value, num_bits)?;
+
+        cs.enforce_constraint(lower, lc!() + Variable::One, lc!() + value)?;
+        cs.enforce_constraint(
+            upper + value,
+            lc!() + Variable::One,
+            lc!() + (self.max, Variable::One),
+        )
+    }
+}
+
+// Allocates the lowest `num_bits` bits of `value` as Boolean witness variables, and returns the
+// linear combination that recomposes them.
+fn alloc_bits<F: PrimeField>(
+    cs: &ConstraintSystemRef<F>,
+    value: F,
+    num_bits: usize,
+) -> Result<LinearCombination<F>, SynthesisError> {
+    let value = value.into_repr();
+    let mut sum = lc!();
+    let mut coeff = F::one();
+    for i in 0..num_bits {
+        let bit = cs.new_witness_variable(|| {
+            Ok(if value.get_bit(i) {
+                F::one()
+            } else {
+                F::zero()
+            })
+        })?;
+        cs.enforce_constraint(lc!() + bit, lc!() + bit - (F::one(), Variable::One), lc!())?;
+        sum = sum + (coeff, bit);
+        coeff.double_in_place();
+    }
+
+    Ok(sum)
+}
+
+/// A circuit augmented with custom gates, whose constraints are generated after those of the
+/// circuit.
+pub struct CustomGateCircuit<C, F> {
+    /// The circuit that is augmented.
+    pub circuit: C,
+
+    /// The custom gates, in the order in which their constraints are generated.
+    pub gates: Vec<Box<dyn CustomGate<F>>>,
+}
+
+impl<C: ConstraintSynthesizer<F>, F: Field> ConstraintSynthesizer<F> for CustomGateCircuit<C, F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        self.circuit.generate_constraints(cs.clone())?;
+        for gate in &self.gates {
+            gate.to_constraint(&cs)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Outputs a specialized prover and verifier key for some R1CS instance augmented with the
+    /// constraints of the custom gates made by `gate_factory`. Proofs are produced with
+    /// [`R1CSNark::prove_with_custom_gates`] for the same gates, and verified with
+    /// [`R1CSNark::verify`] against the public input of the circuit.
+    pub fn index_with_custom_gates<C, GF>(
+        pp: &PublicParameters,
+        circuit: C,
+        gate_factory: Vec<GF>,
+    ) -> R1CSResult<(IndexProverKey<G>, IndexVerifierKey<G>)>
+    where
+        C: ConstraintSynthesizer<G::ScalarField>,
+        GF: Fn() -> Box<dyn CustomGate<G::ScalarField>>,
+    {
+        Self::index(pp, Self::with_custom_gates(circuit, &gate_factory))
+    }
+
+    /// Proves that some R1CS relation augmented with the custom gates made by `gate_factory`
+    /// holds.
+    pub fn prove_with_custom_gates<C, GF>(
+        ipk: &IndexProverKey<G>,
+        circuit: C,
+        gate_factory: Vec<GF>,
+        make_zk: bool,
+        sponge: Option<S>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> R1CSResult<Proof<G>>
+    where
+        C: ConstraintSynthesizer<G::ScalarField>,
+        GF: Fn() -> Box<dyn CustomGate<G::ScalarField>>,
+    {
+        let circuit = Self::with_custom_gates(circuit, &gate_factory);
+        Self::prove(ipk, circuit, make_zk, sponge, rng)
+    }
+
+    fn with_custom_gates<C, GF>(
+        circuit: C,
+        gate_factory: &[GF],
+    ) -> CustomGateCircuit<C, G::ScalarField>
+    where
+        GF: Fn() -> Box<dyn CustomGate<G::ScalarField>>,
+    {
+        CustomGateCircuit {
+            circuit,
+            gates: gate_factory.iter().map(|make_gate| make_gate()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    fn range_gate(value: u64, max: u64) -> impl Fn() -> Box<dyn CustomGate<Fr>> {
+        move || {
+            Box::new(RangeGate {
+                value: Fr::from(value),
+                max: Fr::from(max),
+            })
+        }
+    }
+
+    #[test]
+    fn test_range_gate() {
+        let gate = RangeGate {
+            value: Fr::from(5u64),
+            max: Fr::from(10u64),
+        };
+        assert!(gate.evaluate(&[Fr::from(0u64), Fr::from(5u64), Fr::from(10u64)]));
+        assert!(!gate.evaluate(&[Fr::from(11u64)]));
+        assert!(!gate.evaluate(&[-Fr::one()]));
+
+        for (value, max, in_range) in [
+            (5u64, 10, true),
+            (10, 10, true),
+            (0, 0, true),
+            (11, 10, false),
+        ]
+        .iter()
+        .copied()
+        {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            range_gate(value, max)().to_constraint(&cs).unwrap();
+            assert_eq!(cs.is_satisfied().unwrap(), in_range);
+        }
+    }
+
+    #[test]
+    fn test_index_with_custom_gates() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (plain_ipk, _) = Nark::index(&pp, c).unwrap();
+        let gates = vec![range_gate(5, 10), range_gate(200, 255)];
+        let (ipk, ivk) = Nark::index_with_custom_gates(&pp, c, gates).unwrap();
+        assert!(ipk.index_info.num_constraints > plain_ipk.index_info.num_constraints);
+
+        let gates = vec![range_gate(5, 10), range_gate(200, 255)];
+        let proof = Nark::prove_with_custom_gates(&ipk, c, gates, true, None, Some(rng)).unwrap();
+        assert!(Nark::verify(&ivk, &r1cs_input, &proof, None).unwrap());
+    }
+}
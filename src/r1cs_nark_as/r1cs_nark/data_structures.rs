@@ -1,11 +1,27 @@
+use super::{
+    hash_matrices, hash_matrices_poseidon, matrices_hash_domain, matrix_triple_mul, matrix_vec_mul,
+    matrix_vec_mul_dispatch, transpose_matrices, validate_matrices, MatrixSymmetries,
+    MatrixVecProducts, NarkError, R1CSResult, PROOF_FORMAT_VERSION,
+};
+
 use ark_ec::AffineCurve;
 use ark_ff::{Field, PrimeField};
 use ark_poly_commit::trivial_pc::CommitterKey;
-use ark_relations::r1cs::Matrix;
+use ark_relations::r1cs::{Matrix, OptimizationGoal};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
-use ark_sponge::{collect_sponge_bytes, collect_sponge_field_elements, Absorbable};
+use ark_sponge::{
+    collect_sponge_bytes, collect_sponge_field_elements, Absorbable, CryptographicSponge,
+};
+use ark_std::convert::TryFrom;
 use ark_std::io::{Read, Write};
+use ark_std::iter::{Chain, Enumerate, Map};
+use ark_std::string::String;
+use ark_std::vec;
 use ark_std::vec::Vec;
+use blake2::{digest::VariableOutput, VarBlake2b};
+
+#[cfg(feature = "serde")]
+use super::CanonicalHex;
 
 /// The public parameters of this NARK.
 pub type PublicParameters = ();
@@ -26,6 +42,42 @@ pub(crate) struct IndexInfo {
 
     /// Hash of the matrices.
     pub(crate) matrices_hash: [u8; 32],
+
+    /// Whether the verifier receives a digest of the public input instead of the input itself.
+    /// The flag is absorbed into the sponge before the challenge is computed.
+    pub(crate) hash_public_input: bool,
+
+    /// The version of the protocol that produced the index.
+    pub(crate) protocol_version: u32,
+
+    /// The version of the circuit, chosen by the user when indexing.
+    pub(crate) circuit_version: u32,
+
+    /// The number of trivially satisfied constraints that were removed from the R1CS instance
+    /// when indexing. The prover accepts circuits with this many more constraints.
+    pub(crate) num_pruned_constraints: usize,
+
+    /// The number of constraints that the circuit generated in setup mode when it was indexed,
+    /// or `0` if the index was produced from matrices. The prover rejects circuits that generate
+    /// fewer constraints.
+    pub(crate) num_circuit_constraints: usize,
+
+    /// Whether the matrices were padded with zero rows and columns by
+    /// [`R1CSNark::index_padded`][padded], or sized for the largest circuit by
+    /// [`R1CSNark::index_dynamic`][dynamic]. Only the prover of a padded index accepts circuits
+    /// with fewer variables or constraints than the index.
+    ///
+    /// [padded]: crate::r1cs_nark_as::r1cs_nark::R1CSNark::index_padded
+    /// [dynamic]: crate::r1cs_nark_as::r1cs_nark::R1CSNark::index_dynamic
+    pub(crate) is_padded: bool,
+
+    /// The number of matrix entries removed by `index_with_threshold`. It is bound into the
+    /// matrices hash, so that a pruned index cannot pass for the index of the original instance.
+    pub(crate) num_pruned_entries: usize,
+
+    /// The symmetries detected in the matrices, which are not serialized, since they can be
+    /// recomputed from the matrices.
+    pub(crate) matrix_symmetries: MatrixSymmetries,
 }
 
 /// The index prover key for our NARK.
@@ -35,37 +87,628 @@ pub struct IndexProverKey<G: AffineCurve> {
     pub(crate) index_info: IndexInfo,
 
     /// The `A` matrix of the R1CS instance.
-    pub(crate) a: Matrix<G::ScalarField>,
+    pub(crate) a: CsrMatrix<G::ScalarField>,
 
     /// The `B` matrix of the R1CS instance.
-    pub(crate) b: Matrix<G::ScalarField>,
+    pub(crate) b: CsrMatrix<G::ScalarField>,
 
     /// The `C` matrix of the R1CS instance.
-    pub(crate) c: Matrix<G::ScalarField>,
+    pub(crate) c: CsrMatrix<G::ScalarField>,
 
     /// Group elements required by the Pedersen commitment.
     pub(crate) ck: CommitterKey<G>,
+
+    /// Representations of the `A`, `B`, and `C` matrices used for matrix-vector multiplication.
+    /// Only present if at least one of the matrices is dense enough to be stored densely.
+    pub(crate) matrix_reprs: Option<Vec<MatrixRepr<G::ScalarField>>>,
+
+    /// The transposes of the `A`, `B`, and `C` matrices, used for multiplication by the
+    /// transposes.
+    pub(crate) transposes: [CsrMatrix<G::ScalarField>; 3],
+
+    /// The strategy used to choose `matrix_reprs`.
+    pub(crate) matrix_strategy: MatrixStrategy,
+
+    /// The labels of the public input variables, excluding the leading constant `1`, in the
+    /// order in which the R1CS instance allocates them.
+    pub(crate) input_labels: Option<Vec<String>>,
+
+    /// The application domain separation tag, which is absorbed into the sponge before the
+    /// challenge is derived. Empty unless the key was produced by `index_with_app_domain`.
+    pub(crate) app_domain: Vec<u8>,
+}
+
+impl CanonicalSerialize for IndexInfo {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.num_variables.serialize(&mut writer)?;
+        self.num_constraints.serialize(&mut writer)?;
+        self.num_instance_variables.serialize(&mut writer)?;
+        writer.write_all(&self.matrices_hash)?;
+        self.hash_public_input.serialize(&mut writer)?;
+        self.protocol_version.serialize(&mut writer)?;
+        self.circuit_version.serialize(&mut writer)?;
+        self.num_pruned_constraints.serialize(&mut writer)?;
+        self.num_circuit_constraints.serialize(&mut writer)?;
+        self.is_padded.serialize(&mut writer)?;
+        self.num_pruned_entries.serialize(&mut writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        self.num_variables.serialized_size()
+            + self.num_constraints.serialized_size()
+            + self.num_instance_variables.serialized_size()
+            + self.matrices_hash.len()
+            + self.hash_public_input.serialized_size()
+            + self.protocol_version.serialized_size()
+            + self.circuit_version.serialized_size()
+            + self.num_pruned_constraints.serialized_size()
+            + self.num_circuit_constraints.serialized_size()
+            + self.is_padded.serialized_size()
+            + self.num_pruned_entries.serialized_size()
+    }
+}
+
+impl CanonicalDeserialize for IndexInfo {
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let num_variables = usize::deserialize(&mut reader)?;
+        let num_constraints = usize::deserialize(&mut reader)?;
+        let num_instance_variables = usize::deserialize(&mut reader)?;
+        let mut matrices_hash = [0u8; 32];
+        reader.read_exact(&mut matrices_hash)?;
+        let hash_public_input = bool::deserialize(&mut reader)?;
+        let protocol_version = u32::deserialize(&mut reader)?;
+        let circuit_version = u32::deserialize(&mut reader)?;
+        let num_pruned_constraints = usize::deserialize(&mut reader)?;
+        let num_circuit_constraints = usize::deserialize(&mut reader)?;
+        let is_padded = bool::deserialize(&mut reader)?;
+        let num_pruned_entries = usize::deserialize(&mut reader)?;
+
+        Ok(Self {
+            num_variables,
+            num_constraints,
+            num_instance_variables,
+            matrices_hash,
+            hash_public_input,
+            protocol_version,
+            circuit_version,
+            num_pruned_constraints,
+            num_circuit_constraints,
+            is_padded,
+            num_pruned_entries,
+            matrix_symmetries: MatrixSymmetries::default(),
+        })
+    }
+}
+
+impl<G: AffineCurve> IndexProverKey<G> {
+    /// Serializes the key into a byte vector, e.g. to cache it on disk.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.serialized_size());
+        self.serialize(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// Sets the strategy used to choose the representations of the `A`, `B`, and `C` matrices
+    /// for matrix-vector multiplication.
+    pub fn set_matrix_strategy(&mut self, strategy: MatrixStrategy) {
+        self.matrix_strategy = strategy;
+        self.matrix_reprs = MatrixRepr::from_matrices(
+            &self.a,
+            &self.b,
+            &self.c,
+            self.index_info.num_variables,
+            strategy,
+        );
+    }
+
+    /// Returns the number of matrix entries that were removed when the key was produced by
+    /// [`R1CSNark::index_with_threshold`][threshold].
+    ///
+    /// [threshold]: crate::r1cs_nark_as::r1cs_nark::R1CSNark::index_with_threshold
+    pub fn num_pruned_entries(&self) -> usize {
+        self.index_info.num_pruned_entries
+    }
+
+    /// Returns the number of trivially satisfied constraints that [`normalize_matrices`][normalize]
+    /// removed from the R1CS instance when the key was produced.
+    ///
+    /// [normalize]: crate::r1cs_nark_as::r1cs_nark::normalize_matrices
+    pub fn num_pruned_constraints(&self) -> usize {
+        self.index_info.num_pruned_constraints
+    }
+
+    /// Deserializes a key produced by [`IndexProverKey::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        Self::deserialize(bytes)
+    }
+
+    // Computes `A * (input || witness)`, `B * (input || witness)`, and `C * (input || witness)`,
+    // skipping the products that the symmetries of the matrices determine.
+    pub(crate) fn matrix_vec_muls(
+        &self,
+        input: &[G::ScalarField],
+        witness: &[G::ScalarField],
+    ) -> MatrixVecProducts<G::ScalarField> {
+        let symmetries = self.index_info.matrix_symmetries;
+        if symmetries == MatrixSymmetries::default() {
+            return match &self.matrix_reprs {
+                Some(reprs) => (
+                    matrix_vec_mul_dispatch(&reprs[0], input, witness),
+                    matrix_vec_mul_dispatch(&reprs[1], input, witness),
+                    matrix_vec_mul_dispatch(&reprs[2], input, witness),
+                ),
+                None => matrix_triple_mul(&self.a, &self.b, &self.c, input, witness),
+            };
+        }
+
+        let mul = |i: usize, matrix: &CsrMatrix<G::ScalarField>| match &self.matrix_reprs {
+            Some(reprs) => matrix_vec_mul_dispatch(&reprs[i], input, witness),
+            None => matrix_vec_mul(matrix, input, witness),
+        };
+        let z_a = mul(0, &self.a);
+        let z_b = if symmetries.a_equals_b {
+            z_a.clone()
+        } else {
+            mul(1, &self.b)
+        };
+        let z_c = if symmetries.c_is_identity {
+            input
+                .iter()
+                .chain(witness)
+                .take(self.index_info.num_constraints)
+                .cloned()
+                .collect()
+        } else {
+            mul(2, &self.c)
+        };
+        (z_a, z_b, z_c)
+    }
+}
+
+// The matrix representations and transposes are not serialized, since they can be recomputed from
+// the matrices.
+impl<G: AffineCurve> CanonicalSerialize for IndexProverKey<G> {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.index_info.serialize(&mut writer)?;
+        self.a.serialize(&mut writer)?;
+        self.b.serialize(&mut writer)?;
+        self.c.serialize(&mut writer)?;
+        self.ck.serialize(&mut writer)?;
+        label_bytes(&self.input_labels).serialize(&mut writer)?;
+        (self.matrix_strategy as u8).serialize(&mut writer)?;
+        self.app_domain.serialize(&mut writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        self.index_info.serialized_size()
+            + self.a.serialized_size()
+            + self.b.serialized_size()
+            + self.c.serialized_size()
+            + self.ck.serialized_size()
+            + label_bytes(&self.input_labels).serialized_size()
+            + (self.matrix_strategy as u8).serialized_size()
+            + self.app_domain.serialized_size()
+    }
+}
+
+/// Keys whose matrices are malformed for the sizes in their index information, or whose matrices
+/// hash does not match the matrices, are rejected as invalid data.
+impl<G: AffineCurve> CanonicalDeserialize for IndexProverKey<G> {
+    fn deserialize<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        Self::deserialize_with_matrices_hash(reader, |index_info, a, b, c| {
+            hash_matrices(&matrices_hash_domain(index_info), a, b, c)
+        })
+    }
+}
+
+impl<G: AffineCurve> IndexProverKey<G> {
+    /// Deserializes a key produced by
+    /// [`R1CSNark::index_with_poseidon_hash`][poseidon] and serialized with
+    /// [`IndexProverKey::to_bytes`], checking its matrices hash with [`hash_matrices_poseidon`].
+    ///
+    /// [poseidon]: crate::r1cs_nark_as::r1cs_nark::R1CSNark::index_with_poseidon_hash
+    pub fn from_bytes_with_poseidon_hash<HS: CryptographicSponge<G::ScalarField>>(
+        bytes: &[u8],
+    ) -> Result<Self, SerializationError>
+    where
+        G::ScalarField: Absorbable<G::ScalarField>,
+    {
+        Self::deserialize_with_matrices_hash(bytes, |index_info, a, b, c| {
+            let (a, b, c): (Matrix<_>, Matrix<_>, Matrix<_>) =
+                (a.clone().into(), b.clone().into(), c.clone().into());
+            hash_matrices_poseidon::<_, HS>(&matrices_hash_domain(index_info), &a, &b, &c)
+        })
+    }
+
+    // Deserializes a key, and checks that its matrices are well formed and that `hash_fn`
+    // outputs its matrices hash on them before deriving anything from the matrices.
+    fn deserialize_with_matrices_hash<R: Read>(
+        mut reader: R,
+        hash_fn: impl FnOnce(
+            &IndexInfo,
+            &CsrMatrix<G::ScalarField>,
+            &CsrMatrix<G::ScalarField>,
+            &CsrMatrix<G::ScalarField>,
+        ) -> [u8; 32],
+    ) -> Result<Self, SerializationError> {
+        let mut index_info = IndexInfo::deserialize(&mut reader)?;
+        let a = Matrix::deserialize(&mut reader)?;
+        let b = Matrix::deserialize(&mut reader)?;
+        let c = Matrix::deserialize(&mut reader)?;
+        if index_info.num_instance_variables == 0
+            || index_info.num_instance_variables > index_info.num_variables
+        {
+            return Err(SerializationError::InvalidData);
+        }
+        validate_matrices(
+            &a,
+            &b,
+            &c,
+            index_info.num_constraints,
+            index_info.num_variables,
+        )
+        .map_err(|_| SerializationError::InvalidData)?;
+        let (a, b, c) = (
+            CsrMatrix::try_from_matrix(a)?,
+            CsrMatrix::try_from_matrix(b)?,
+            CsrMatrix::try_from_matrix(c)?,
+        );
+        if hash_fn(&index_info, &a, &b, &c) != index_info.matrices_hash {
+            return Err(SerializationError::InvalidData);
+        }
+
+        let ck = CommitterKey::deserialize(&mut reader)?;
+        let input_labels = Option::<Vec<Vec<u8>>>::deserialize(&mut reader)?
+            .map(|labels| {
+                labels
+                    .into_iter()
+                    .map(|label| {
+                        String::from_utf8(label).map_err(|_| SerializationError::InvalidData)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?;
+        let matrix_strategy = match u8::deserialize(&mut reader)? {
+            0 => MatrixStrategy::AlwaysSparse,
+            1 => MatrixStrategy::AlwaysDense,
+            2 => MatrixStrategy::AutoDetect,
+            _ => return Err(SerializationError::InvalidData),
+        };
+        let app_domain = Vec::<u8>::deserialize(&mut reader)?;
+        let matrix_reprs =
+            MatrixRepr::from_matrices(&a, &b, &c, index_info.num_variables, matrix_strategy);
+        let transposes = transpose_matrices(&a, &b, &c, index_info.num_variables);
+        index_info.matrix_symmetries = MatrixSymmetries::detect(&a, &b, &c);
+
+        Ok(Self {
+            index_info,
+            a,
+            b,
+            c,
+            ck,
+            matrix_reprs,
+            transposes,
+            matrix_strategy,
+            input_labels,
+            app_domain,
+        })
+    }
+}
+
+// Converts the labels into byte strings, which can be serialized.
+fn label_bytes(labels: &Option<Vec<String>>) -> Option<Vec<Vec<u8>>> {
+    labels.as_ref().map(|labels| {
+        labels
+            .iter()
+            .map(|label| label.as_bytes().to_vec())
+            .collect()
+    })
 }
 
 /// Index verifier key for our NARK.
 pub type IndexVerifierKey<G> = IndexProverKey<G>;
 
+/// An index key whose commitment key was trimmed from a universal
+/// [`CommitmentKey`][ck]. It can be used as both an index prover key and an index verifier key.
+///
+/// [ck]: crate::r1cs_nark_as::r1cs_nark::setup::CommitmentKey
+pub type TrimmedKey<G> = IndexProverKey<G>;
+
+/// Options for indexing and proving with [`R1CSNark`][nark]. The same `optimization_goal` must be
+/// used to index an R1CS instance and to prove it.
+///
+/// [nark]: crate::r1cs_nark_as::r1cs_nark::R1CSNark
+#[derive(Clone, Copy, Debug)]
+pub struct NarkConfig {
+    /// The goal the constraint system optimizes for when the circuit is synthesized. It determines
+    /// the number of constraints, and therefore the size of the commitment key.
+    pub optimization_goal: OptimizationGoal,
+
+    /// Whether the proof should be zero-knowledge.
+    pub zk: bool,
+}
+
+impl Default for NarkConfig {
+    fn default() -> Self {
+        Self {
+            optimization_goal: OptimizationGoal::Constraints,
+            zk: false,
+        }
+    }
+}
+
+/// The strategy for choosing the representation of an R1CS matrix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatrixStrategy {
+    /// Always store the matrix sparsely.
+    AlwaysSparse = 0,
+
+    /// Always store the matrix densely.
+    AlwaysDense = 1,
+
+    /// Store the matrix densely only if enough of its entries are non-zero.
+    AutoDetect = 2,
+}
+
+impl MatrixStrategy {
+    /// Chooses between [`MatrixStrategy::AlwaysSparse`] and [`MatrixStrategy::AlwaysDense`] for
+    /// a matrix with `nnz` non-zero entries out of `num_entries`. The matrix is stored sparsely if
+    /// less than 30% of its entries are non-zero.
+    pub fn auto_detect(nnz: usize, num_entries: usize) -> Self {
+        if 10 * nnz < 3 * num_entries {
+            MatrixStrategy::AlwaysSparse
+        } else {
+            MatrixStrategy::AlwaysDense
+        }
+    }
+}
+
+/// The representation of an R1CS matrix used for matrix-vector multiplication.
+#[derive(Clone)]
+pub enum MatrixRepr<F: Field> {
+    /// A sparse matrix, which stores the non-zero entries of each row along with their columns.
+    Sparse(CsrMatrix<F>),
+
+    /// A dense matrix, which stores every entry of each row, along with the number of columns.
+    Dense(Vec<Vec<F>>, usize),
+}
+
+impl<F: Field> MatrixRepr<F> {
+    /// Chooses the representation of a sparse matrix with `num_columns` columns using
+    /// [`MatrixStrategy::AutoDetect`].
+    pub fn from_sparse(matrix: &CsrMatrix<F>, num_columns: usize) -> Self {
+        Self::with_strategy(matrix, num_columns, MatrixStrategy::AutoDetect)
+    }
+
+    /// Chooses the representation of a sparse matrix with `num_columns` columns according to
+    /// `strategy`.
+    pub fn with_strategy(
+        matrix: &CsrMatrix<F>,
+        num_columns: usize,
+        strategy: MatrixStrategy,
+    ) -> Self {
+        let strategy = match strategy {
+            MatrixStrategy::AutoDetect => {
+                MatrixStrategy::auto_detect(matrix.nnz(), matrix.len() * num_columns)
+            }
+            strategy => strategy,
+        };
+
+        if strategy == MatrixStrategy::AlwaysSparse {
+            return MatrixRepr::Sparse(matrix.clone());
+        }
+
+        let dense_matrix = matrix
+            .rows()
+            .map(|row| {
+                let mut dense_row = vec![F::zero(); num_columns];
+                for (coeff, i) in row.iter() {
+                    dense_row[i] += coeff;
+                }
+                dense_row
+            })
+            .collect();
+
+        MatrixRepr::Dense(dense_matrix, num_columns)
+    }
+
+    // Chooses the representations of the `A`, `B`, and `C` matrices. Returns `None` if all of them
+    // would be stored sparsely.
+    pub(crate) fn from_matrices(
+        a: &CsrMatrix<F>,
+        b: &CsrMatrix<F>,
+        c: &CsrMatrix<F>,
+        num_columns: usize,
+        strategy: MatrixStrategy,
+    ) -> Option<Vec<Self>> {
+        let reprs = [a, b, c]
+            .iter()
+            .map(|matrix| Self::with_strategy(matrix, num_columns, strategy))
+            .collect::<Vec<_>>();
+        if reprs.iter().any(Self::is_dense) {
+            Some(reprs)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether the matrix is stored densely.
+    pub fn is_dense(&self) -> bool {
+        matches!(self, MatrixRepr::Dense(..))
+    }
+}
+
+/// An R1CS matrix in compressed sparse row form. The non-zero entries of all rows are stored
+/// contiguously, and row `i` spans `values[row_ptrs[i]..row_ptrs[i + 1]]` and the same range of
+/// `col_indices`. Unlike a [`Matrix`], which allocates a vector per row, the whole matrix takes
+/// three allocations.
+///
+/// The matrix serializes to the same bytes as the corresponding [`Matrix`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsrMatrix<F: Field> {
+    /// The coefficients of the non-zero entries, row by row.
+    pub(crate) values: Vec<F>,
+
+    /// The column of each entry of `values`.
+    pub(crate) col_indices: Vec<u32>,
+
+    /// The offsets of the rows in `values` and `col_indices`, followed by the number of entries.
+    pub(crate) row_ptrs: Vec<usize>,
+}
+
+/// A row of a [`CsrMatrix`].
+#[derive(Clone, Copy)]
+pub struct CsrRow<'a, F: Field> {
+    values: &'a [F],
+    col_indices: &'a [u32],
+}
+
+impl<'a, F: Field> CsrRow<'a, F> {
+    /// Returns the number of non-zero entries in the row.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns whether the row has no non-zero entries.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns an iterator over the coefficients and columns of the entries of the row.
+    pub fn iter(&self) -> impl Iterator<Item = (F, usize)> + 'a {
+        self.values
+            .iter()
+            .zip(self.col_indices)
+            .map(|(coeff, &i)| (*coeff, i as usize))
+    }
+}
+
+impl<F: Field> CsrMatrix<F> {
+    /// Returns the number of rows of the matrix.
+    pub fn len(&self) -> usize {
+        self.row_ptrs.len() - 1
+    }
+
+    /// Returns whether the matrix has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of non-zero entries of the matrix.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns row `i` of the matrix.
+    pub fn row(&self, i: usize) -> CsrRow<'_, F> {
+        let range = self.row_ptrs[i]..self.row_ptrs[i + 1];
+        CsrRow {
+            values: &self.values[range.clone()],
+            col_indices: &self.col_indices[range],
+        }
+    }
+
+    /// Returns an iterator over the rows of the matrix.
+    pub fn rows(&self) -> impl Iterator<Item = CsrRow<'_, F>> {
+        (0..self.len()).map(move |i| self.row(i))
+    }
+}
+
+/// Panics if a column does not fit in a `u32`.
+impl<F: Field> From<Matrix<F>> for CsrMatrix<F> {
+    fn from(matrix: Matrix<F>) -> Self {
+        let nnz = matrix.iter().map(|row| row.len()).sum();
+        let mut values = Vec::with_capacity(nnz);
+        let mut col_indices = Vec::with_capacity(nnz);
+        let mut row_ptrs = Vec::with_capacity(matrix.len() + 1);
+        row_ptrs.push(0);
+        for row in matrix {
+            for (coeff, i) in row {
+                values.push(coeff);
+                col_indices.push(u32::try_from(i).expect("column index does not fit in a u32"));
+            }
+            row_ptrs.push(values.len());
+        }
+
+        Self {
+            values,
+            col_indices,
+            row_ptrs,
+        }
+    }
+}
+
+impl<F: Field> From<CsrMatrix<F>> for Matrix<F> {
+    fn from(matrix: CsrMatrix<F>) -> Self {
+        matrix.rows().map(|row| row.iter().collect()).collect()
+    }
+}
+
+impl<F: Field> CanonicalSerialize for CsrMatrix<F> {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        (self.len() as u64).serialize(&mut writer)?;
+        for row in self.rows() {
+            (row.len() as u64).serialize(&mut writer)?;
+            for (coeff, i) in row.iter() {
+                coeff.serialize(&mut writer)?;
+                i.serialize(&mut writer)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn serialized_size(&self) -> usize {
+        let entry_size = F::zero().serialized_size() + 0usize.serialized_size();
+        0u64.serialized_size() * (1 + self.len()) + entry_size * self.nnz()
+    }
+}
+
+impl<F: Field> CanonicalDeserialize for CsrMatrix<F> {
+    fn deserialize<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        Self::try_from_matrix(Matrix::<F>::deserialize(reader)?)
+    }
+}
+
+impl<F: Field> CsrMatrix<F> {
+    // Converts `matrix`, rejecting it as invalid data if a column does not fit in a `u32`.
+    fn try_from_matrix(matrix: Matrix<F>) -> Result<Self, SerializationError> {
+        let max_column = u32::MAX as usize;
+        if matrix.iter().flatten().any(|(_, i)| *i > max_column) {
+            return Err(SerializationError::InvalidData);
+        }
+
+        Ok(Self::from(matrix))
+    }
+}
+
 /// The sigma protocol's prover commitment randomness.
+#[cfg_attr(feature = "serde", serde_with::serde_as)]
 #[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct FirstRoundMessageRandomness<G: AffineCurve> {
     /// Pedersen commitment to the vector that blinds the witness in `Az`.
+    #[cfg_attr(feature = "serde", serde_as(as = "CanonicalHex"))]
     pub(crate) comm_r_a: G,
 
     /// Pedersen commitment to the vector that blinds the witness in `Bz`.
+    #[cfg_attr(feature = "serde", serde_as(as = "CanonicalHex"))]
     pub(crate) comm_r_b: G,
 
     /// Pedersen commitment to the vector that blinds the witness in `Cz`.
+    #[cfg_attr(feature = "serde", serde_as(as = "CanonicalHex"))]
     pub(crate) comm_r_c: G,
 
     /// Pedersen commitment to the first cross term randomness vector
+    #[cfg_attr(feature = "serde", serde_as(as = "CanonicalHex"))]
     pub(crate) comm_1: G,
 
     /// Pedersen commitment to the second cross term randomness vector
+    #[cfg_attr(feature = "serde", serde_as(as = "CanonicalHex"))]
     pub(crate) comm_2: G,
 }
 
@@ -97,15 +740,24 @@ where
 }
 
 /// The sigma protocol's prover commitment.
+#[cfg_attr(feature = "serde", serde_with::serde_as)]
 #[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct FirstRoundMessage<G: AffineCurve> {
     /// Pedersen commitment to the `Az` vector.
+    #[cfg_attr(feature = "serde", serde_as(as = "CanonicalHex"))]
     pub(crate) comm_a: G,
 
     /// Pedersen commitment to the `Bz` vector.
+    #[cfg_attr(feature = "serde", serde_as(as = "CanonicalHex"))]
     pub(crate) comm_b: G,
 
     /// Pedersen commitment to the `Cz` vector.
+    #[cfg_attr(feature = "serde", serde_as(as = "CanonicalHex"))]
     pub(crate) comm_c: G,
 
     /// The randomness used for the commitment.
@@ -148,28 +800,45 @@ where
 }
 
 /// The sigma protocol's prover response randomness.
+#[cfg_attr(feature = "serde", serde_with::serde_as)]
 #[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct SecondRoundMessageRandomness<F: Field> {
     /// The blinded randomness for the Pedersen commitment to the linear combination with the
     /// `A` matrix.
+    #[cfg_attr(feature = "serde", serde_as(as = "CanonicalHex"))]
     pub(crate) sigma_a: F,
 
     /// The blinded randomness for the Pedersen commitment to the linear combination with the
     /// `B` matrix.
+    #[cfg_attr(feature = "serde", serde_as(as = "CanonicalHex"))]
     pub(crate) sigma_b: F,
 
     /// The blinded randomness for the Pedersen commitment to the linear combination with the
     /// `C` matrix.
+    #[cfg_attr(feature = "serde", serde_as(as = "CanonicalHex"))]
     pub(crate) sigma_c: F,
 
     /// The blinded randomness for the Pedersen commitment to the cross terms
+    #[cfg_attr(feature = "serde", serde_as(as = "CanonicalHex"))]
     pub(crate) sigma_o: F,
 }
 
 /// The sigma protocol's prover response.
+#[cfg_attr(feature = "serde", serde_with::serde_as)]
 #[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct SecondRoundMessage<F: Field> {
     /// The R1CS witness with randomness applied if zero-knowledge is needed.
+    #[cfg_attr(feature = "serde", serde_as(as = "Vec<CanonicalHex>"))]
     pub(crate) blinded_witness: Vec<F>,
 
     /// The randomness used for the response.
@@ -195,7 +864,12 @@ impl<F: Field> SecondRoundMessage<F> {
 }
 
 /// The proof for our NARK.
-#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Proof<G: AffineCurve> {
     /// The sigma protocol's prove commitment.
     pub first_msg: FirstRoundMessage<G>,
@@ -203,3 +877,448 @@ pub struct Proof<G: AffineCurve> {
     /// The sigma protocol's prove response.
     pub second_msg: SecondRoundMessage<G::ScalarField>,
 }
+
+/// A proof is serialized as [`PROOF_FORMAT_VERSION`], followed by its messages.
+impl<G: AffineCurve> CanonicalSerialize for Proof<G> {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        PROOF_FORMAT_VERSION.serialize(&mut writer)?;
+        self.first_msg.serialize(&mut writer)?;
+        self.second_msg.serialize(&mut writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        PROOF_FORMAT_VERSION.serialized_size()
+            + self.first_msg.serialized_size()
+            + self.second_msg.serialized_size()
+    }
+}
+
+/// Proofs in a format version other than [`PROOF_FORMAT_VERSION`] are rejected as invalid data.
+impl<G: AffineCurve> CanonicalDeserialize for Proof<G> {
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        match u8::deserialize(&mut reader)? {
+            PROOF_FORMAT_VERSION => Ok(Self {
+                first_msg: FirstRoundMessage::deserialize(&mut reader)?,
+                second_msg: SecondRoundMessage::deserialize(&mut reader)?,
+            }),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
+impl<G: AffineCurve> Proof<G> {
+    /// Outputs the canonical proof for step 0 of an IVC computation with initial input `input`,
+    /// which has no previous proof to verify. The proof has zero commitments and carries `input`
+    /// in place of the witness.
+    ///
+    /// The proof is only accepted by [`R1CSNark::verify_ivc_step`][verify_ivc_step] when the
+    /// caller says that it is verifying step 0, and is rejected by every other verifier.
+    ///
+    /// [verify_ivc_step]: crate::r1cs_nark_as::r1cs_nark::R1CSNark::verify_ivc_step
+    pub fn base_case(input: &[G::ScalarField]) -> Self {
+        Self {
+            first_msg: FirstRoundMessage::zero(false),
+            second_msg: SecondRoundMessage {
+                blinded_witness: input.to_vec(),
+                randomness: None,
+            },
+        }
+    }
+}
+
+/// A full assignment to the variables of an R1CS instance.
+#[cfg_attr(feature = "serde", serde_with::serde_as)]
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
+pub struct FullAssignment<F: Field> {
+    /// The public input, including the leading constant `1`.
+    #[cfg_attr(feature = "serde", serde_as(as = "Vec<CanonicalHex>"))]
+    pub input: Vec<F>,
+
+    /// The witness.
+    #[cfg_attr(feature = "serde", serde_as(as = "Vec<CanonicalHex>"))]
+    pub witness: Vec<F>,
+}
+
+/// A view of the assignment `z = input || witness` to the variables of an R1CS instance, which
+/// resolves the column of a variable in the constraint matrices to its value. Columns below
+/// `input.len()` are public input variables, starting with the constant `1`, and the remaining
+/// columns are witness variables.
+#[derive(Clone, Copy, Debug)]
+pub struct VariableAssignment<'a, F: Field> {
+    input: &'a [F],
+    witness: &'a [F],
+}
+
+impl<'a, F: Field> VariableAssignment<'a, F> {
+    /// Creates the assignment `input || witness`, where `input` includes the leading constant
+    /// `1` and `witness` does not.
+    pub fn new(input: &'a [F], witness: &'a [F]) -> Self {
+        Self { input, witness }
+    }
+
+    /// The number of public input variables, including the constant `1`.
+    pub fn num_instance_variables(&self) -> usize {
+        self.input.len()
+    }
+
+    /// The total number of variables.
+    pub fn len(&self) -> usize {
+        self.input.len() + self.witness.len()
+    }
+
+    /// Returns whether there are no variables.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the value of the variable at column `idx`.
+    ///
+    /// # Panics
+    /// Panics if `idx` is not less than [`VariableAssignment::len`].
+    pub fn get(&self, idx: usize) -> F {
+        match idx.checked_sub(self.input.len()) {
+            None => self.input[idx],
+            Some(witness_idx) => self.witness[witness_idx],
+        }
+    }
+}
+
+/// The position of a variable in a [`FullAssignment`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssignmentIndex {
+    /// An index into the public input, where `0` is the leading constant `1`.
+    Input(usize),
+
+    /// An index into the witness.
+    Witness(usize),
+}
+
+impl<F: Field> FullAssignment<F> {
+    /// Returns every position at which the assignments `a` and `b` differ, together with the
+    /// values of `a` and `b` at that position. Input positions are listed before witness
+    /// positions. Returns an error if the inputs or the witnesses of the assignments have
+    /// different lengths.
+    pub fn difference(a: &Self, b: &Self) -> R1CSResult<Vec<(AssignmentIndex, F, F)>> {
+        if a.input.len() != b.input.len() {
+            return Err(NarkError::MalformedInput {
+                expected_len: a.input.len(),
+                got: b.input.len(),
+            });
+        }
+        if a.witness.len() != b.witness.len() {
+            return Err(NarkError::MalformedWitness {
+                expected_len: a.witness.len(),
+                got: b.witness.len(),
+            });
+        }
+
+        let input_diff = a
+            .input
+            .iter()
+            .zip(&b.input)
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(i, (a, b))| (AssignmentIndex::Input(i), *a, *b));
+        let witness_diff = a
+            .witness
+            .iter()
+            .zip(&b.witness)
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(i, (a, b))| (AssignmentIndex::Witness(i), *a, *b));
+
+        Ok(input_diff.chain(witness_diff).collect())
+    }
+
+    /// Returns the assignment with `f` applied to every witness element. The public input is
+    /// unchanged.
+    pub fn map_witness(self, f: impl Fn(F) -> F) -> Self {
+        Self {
+            input: self.input,
+            witness: self.witness.into_iter().map(f).collect(),
+        }
+    }
+
+    /// Combines the assignments `a` and `b` element by element with `f`, for both the public
+    /// input and the witness. Returns an error if the inputs or the witnesses of the assignments
+    /// have different lengths.
+    pub fn zip_with(a: Self, b: Self, f: impl Fn(F, F) -> F) -> R1CSResult<Self> {
+        if a.input.len() != b.input.len() {
+            return Err(NarkError::MalformedInput {
+                expected_len: a.input.len(),
+                got: b.input.len(),
+            });
+        }
+        if a.witness.len() != b.witness.len() {
+            return Err(NarkError::MalformedWitness {
+                expected_len: a.witness.len(),
+                got: b.witness.len(),
+            });
+        }
+
+        Ok(Self {
+            input: a
+                .input
+                .into_iter()
+                .zip(b.input)
+                .map(|(a, b)| f(a, b))
+                .collect(),
+            witness: a
+                .witness
+                .into_iter()
+                .zip(b.witness)
+                .map(|(a, b)| f(a, b))
+                .collect(),
+        })
+    }
+
+    /// Returns the Blake2b hash of the serialized public input followed by the serialized
+    /// witness, e.g. to key a cache of proofs. Field elements are serialized in their canonical
+    /// form, so equal assignments have equal hashes however their elements were computed.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut bytes = Vec::new();
+        self.input.serialize(&mut bytes).unwrap();
+        self.witness.serialize(&mut bytes).unwrap();
+        blake2b(&bytes)
+    }
+
+    /// Returns the Blake2b hash of the serialized public input, as [`FullAssignment::hash`] does
+    /// without the witness.
+    pub fn hash_input_only(&self) -> [u8; 32] {
+        let mut bytes = Vec::new();
+        self.input.serialize(&mut bytes).unwrap();
+        blake2b(&bytes)
+    }
+}
+
+// Returns the 32-byte Blake2b hash of `bytes`.
+pub(crate) fn blake2b(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = VarBlake2b::new(32).unwrap();
+    digest::Update::update(&mut hasher, bytes);
+    let mut hash = [0u8; 32];
+    hasher.finalize_variable(|res| hash.copy_from_slice(res));
+    hash
+}
+
+type IndexedAssignmentIter<F> =
+    Map<Enumerate<vec::IntoIter<F>>, fn((usize, F)) -> (AssignmentIndex, F)>;
+
+impl<F: Field> IntoIterator for FullAssignment<F> {
+    type Item = (AssignmentIndex, F);
+    type IntoIter = Chain<IndexedAssignmentIter<F>, IndexedAssignmentIter<F>>;
+
+    /// Iterates over the elements of the assignment with their positions, listing the public
+    /// input before the witness.
+    fn into_iter(self) -> Self::IntoIter {
+        let input: fn((usize, F)) -> (AssignmentIndex, F) =
+            |(i, value)| (AssignmentIndex::Input(i), value);
+        let witness: fn((usize, F)) -> (AssignmentIndex, F) =
+            |(i, value)| (AssignmentIndex::Witness(i), value);
+        let input_iter = self.input.into_iter().enumerate().map(input);
+        let witness_iter = self.witness.into_iter().enumerate().map(witness);
+        input_iter.chain(witness_iter)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::inner_prod;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::Fr;
+
+    #[test]
+    fn test_variable_assignment_boundary() {
+        let rng = &mut ark_std::test_rng();
+        let input = vec![Fr::one(), Fr::rand(rng), Fr::rand(rng)];
+        let witness: Vec<_> = (0..4).map(|_| Fr::rand(rng)).collect();
+        let z = VariableAssignment::new(&input, &witness);
+        let num_instance_variables = z.num_instance_variables();
+        assert_eq!(num_instance_variables, 3);
+        assert_eq!(z.len(), 7);
+
+        // The last input variable and the first witness variable are on either side of the split.
+        assert_eq!(z.get(0), Fr::one());
+        assert_eq!(z.get(num_instance_variables - 1), input[2]);
+        assert_eq!(z.get(num_instance_variables), witness[0]);
+        assert_eq!(z.get(z.len() - 1), witness[3]);
+
+        let row = CsrMatrix::from(vec![vec![
+            (Fr::one(), num_instance_variables - 1),
+            (Fr::one(), num_instance_variables),
+        ]]);
+        assert_eq!(inner_prod(row.row(0), &z), input[2] + witness[0]);
+    }
+
+    #[test]
+    fn test_assignment_difference() {
+        let rng = &mut ark_std::test_rng();
+        let a = FullAssignment {
+            input: vec![Fr::one(), Fr::rand(rng)],
+            witness: (0..8).map(|_| Fr::rand(rng)).collect(),
+        };
+        assert!(FullAssignment::difference(&a, &a).unwrap().is_empty());
+
+        let mut b = a.clone();
+        b.input[1] += Fr::one();
+        b.witness[2] += Fr::one();
+        b.witness[7] = Fr::rand(rng);
+        assert_eq!(
+            FullAssignment::difference(&a, &b).unwrap(),
+            vec![
+                (AssignmentIndex::Input(1), a.input[1], b.input[1]),
+                (AssignmentIndex::Witness(2), a.witness[2], b.witness[2]),
+                (AssignmentIndex::Witness(7), a.witness[7], b.witness[7]),
+            ]
+        );
+
+        let mut shorter = a.clone();
+        shorter.witness.pop();
+        assert!(matches!(
+            FullAssignment::difference(&a, &shorter),
+            Err(NarkError::MalformedWitness {
+                expected_len: 8,
+                got: 7
+            })
+        ));
+    }
+
+    #[test]
+    fn test_assignment_into_iter() {
+        let rng = &mut ark_std::test_rng();
+        let a = FullAssignment {
+            input: vec![Fr::one(), Fr::rand(rng)],
+            witness: vec![Fr::rand(rng)],
+        };
+        assert_eq!(
+            a.clone().into_iter().collect::<Vec<_>>(),
+            vec![
+                (AssignmentIndex::Input(0), a.input[0]),
+                (AssignmentIndex::Input(1), a.input[1]),
+                (AssignmentIndex::Witness(0), a.witness[0]),
+            ]
+        );
+
+        let num_witness_elems = a
+            .into_iter()
+            .filter(|(index, _)| matches!(index, AssignmentIndex::Witness(_)))
+            .count();
+        assert_eq!(num_witness_elems, 1);
+    }
+
+    #[test]
+    fn test_assignment_map_witness() {
+        let rng = &mut ark_std::test_rng();
+        let a = FullAssignment {
+            input: vec![Fr::one(), Fr::rand(rng)],
+            witness: (0..4).map(|_| Fr::rand(rng)).collect(),
+        };
+
+        let doubled = a.clone().map_witness(|value| value.double());
+        assert_eq!(doubled.input, a.input);
+        for (doubled, value) in doubled.witness.iter().zip(&a.witness) {
+            assert_eq!(*doubled, *value + value);
+        }
+    }
+
+    #[test]
+    fn test_assignment_zip_with() {
+        let rng = &mut ark_std::test_rng();
+        let a = FullAssignment {
+            input: vec![Fr::one(), Fr::rand(rng)],
+            witness: (0..4).map(|_| Fr::rand(rng)).collect(),
+        };
+        let b = FullAssignment {
+            input: vec![Fr::one(), Fr::rand(rng)],
+            witness: (0..4).map(|_| Fr::rand(rng)).collect(),
+        };
+
+        let sum = FullAssignment::zip_with(a.clone(), b.clone(), |a, b| a + b).unwrap();
+        assert_eq!(sum.input[1], a.input[1] + b.input[1]);
+        assert_eq!(sum.witness[3], a.witness[3] + b.witness[3]);
+        let diff = FullAssignment::zip_with(sum, b.clone(), |a, b| a - b).unwrap();
+        assert_eq!(diff.witness, a.witness);
+
+        let mut shorter = b;
+        shorter.input.pop();
+        assert!(matches!(
+            FullAssignment::zip_with(a, shorter, |a, b| a + b),
+            Err(NarkError::MalformedInput {
+                expected_len: 2,
+                got: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_assignment_hash() {
+        let six = Fr::from(6u64);
+        let assignment = FullAssignment {
+            input: vec![Fr::one(), six],
+            witness: vec![Fr::from(2u64), Fr::from(3u64)],
+        };
+
+        // The same values, computed through arithmetic instead of conversions.
+        let two = Fr::one() + Fr::one();
+        let other = FullAssignment {
+            input: vec![two - Fr::one(), two * (two + Fr::one())],
+            witness: vec![six.square() / Fr::from(18u64), -Fr::one()],
+        }
+        .map_witness(|value| {
+            if value == -Fr::one() {
+                six / two
+            } else {
+                value
+            }
+        });
+        assert_eq!(other, assignment);
+        assert_eq!(other.hash(), assignment.hash());
+        assert_eq!(other.hash_input_only(), assignment.hash_input_only());
+
+        let mut different_witness = assignment.clone();
+        different_witness.witness[1] += Fr::one();
+        assert_ne!(different_witness.hash(), assignment.hash());
+        assert_eq!(
+            different_witness.hash_input_only(),
+            assignment.hash_input_only()
+        );
+        assert_ne!(assignment.hash(), assignment.hash_input_only());
+
+        // Moving an element between the input and the witness changes the hash.
+        let moved = FullAssignment {
+            input: vec![Fr::one()],
+            witness: vec![six, Fr::from(2u64), Fr::from(3u64)],
+        };
+        assert_ne!(moved.hash(), assignment.hash());
+    }
+
+    #[test]
+    fn test_csr_matrix() {
+        let rng = &mut ark_std::test_rng();
+        let matrix: Matrix<Fr> = vec![
+            vec![(Fr::rand(rng), 0), (Fr::rand(rng), 3)],
+            vec![],
+            vec![(Fr::rand(rng), 2)],
+        ];
+
+        let csr = CsrMatrix::from(matrix.clone());
+        assert_eq!(csr.len(), 3);
+        assert_eq!(csr.nnz(), 3);
+        assert!(csr.row(1).is_empty());
+        assert_eq!(csr.row(0).iter().collect::<Vec<_>>(), matrix[0]);
+        assert_eq!(Matrix::from(csr.clone()), matrix);
+
+        // The serialization is that of the row-based matrix.
+        let mut csr_bytes = Vec::new();
+        csr.serialize(&mut csr_bytes).unwrap();
+        let mut matrix_bytes = Vec::new();
+        matrix.serialize(&mut matrix_bytes).unwrap();
+        assert_eq!(csr_bytes, matrix_bytes);
+        assert_eq!(CsrMatrix::deserialize(&csr_bytes[..]).unwrap(), csr);
+    }
+}
@@ -0,0 +1,310 @@
+use super::{
+    first_unsatisfied_row, FullAssignment, IndexProverKey, NarkError, PublicParameters, R1CSNark,
+    R1CSResult,
+};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::Field;
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, LinearCombination,
+    SynthesisError, SynthesisMode, Variable,
+};
+use ark_sponge::{Absorbable, CryptographicSponge};
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+/// A wrapper around a [`ConstraintSystemRef`] that captures a backtrace each time a constraint
+/// is added with [`InstrumentedConstraintSystem::enforce_constraint`], to find out which line of
+/// a circuit added which constraint.
+///
+/// Constraints added directly to the wrapped constraint system, e.g. by gadgets that take a
+/// [`ConstraintSystemRef`], get a disabled backtrace.
+#[derive(Clone)]
+pub struct InstrumentedConstraintSystem<F: Field> {
+    cs: ConstraintSystemRef<F>,
+    origins: Option<Rc<RefCell<Vec<Backtrace>>>>,
+}
+
+impl<F: Field> InstrumentedConstraintSystem<F> {
+    /// Wraps `cs`, capturing a backtrace for every constraint added through the wrapper.
+    pub fn new(cs: ConstraintSystemRef<F>) -> Self {
+        Self {
+            cs,
+            origins: Some(Rc::new(RefCell::new(Vec::new()))),
+        }
+    }
+
+    // Wraps `cs` without capturing backtraces, for when the origins are not needed.
+    fn untraced(cs: ConstraintSystemRef<F>) -> Self {
+        Self { cs, origins: None }
+    }
+
+    /// Returns the wrapped constraint system.
+    pub fn cs(&self) -> &ConstraintSystemRef<F> {
+        &self.cs
+    }
+
+    /// Allocates a public input variable, as [`ConstraintSystemRef::new_input_variable`] does.
+    pub fn new_input_variable<Func>(&self, f: Func) -> Result<Variable, SynthesisError>
+    where
+        Func: FnOnce() -> Result<F, SynthesisError>,
+    {
+        self.cs.new_input_variable(f)
+    }
+
+    /// Allocates a witness variable, as [`ConstraintSystemRef::new_witness_variable`] does.
+    pub fn new_witness_variable<Func>(&self, f: Func) -> Result<Variable, SynthesisError>
+    where
+        Func: FnOnce() -> Result<F, SynthesisError>,
+    {
+        self.cs.new_witness_variable(f)
+    }
+
+    /// Enforces `a * b = c`, as [`ConstraintSystemRef::enforce_constraint`] does, and captures
+    /// a backtrace of the call.
+    pub fn enforce_constraint(
+        &self,
+        a: LinearCombination<F>,
+        b: LinearCombination<F>,
+        c: LinearCombination<F>,
+    ) -> Result<(), SynthesisError> {
+        let origins = match &self.origins {
+            Some(origins) => origins,
+            None => return self.cs.enforce_constraint(a, b, c),
+        };
+
+        let num_constraints = self.cs.num_constraints();
+        self.cs.enforce_constraint(a, b, c)?;
+
+        let mut origins = origins.borrow_mut();
+        origins.resize_with(num_constraints, Backtrace::disabled);
+        origins.push(Backtrace::force_capture());
+        Ok(())
+    }
+
+    // Returns the backtraces captured so far, one per constraint of the wrapped system.
+    fn take_origins(&self) -> Vec<Backtrace> {
+        let mut origins = match &self.origins {
+            Some(origins) => std::mem::take(&mut *origins.borrow_mut()),
+            None => Vec::new(),
+        };
+        origins.resize_with(self.cs.num_constraints(), Backtrace::disabled);
+        origins
+    }
+}
+
+/// A circuit that generates its constraints through an [`InstrumentedConstraintSystem`], so that
+/// [`R1CSNark::index_instrumented`] can record where each of them was added.
+pub trait TracedCircuit<F: Field> {
+    /// Generates the constraints of the circuit.
+    fn generate_constraints(
+        self,
+        cs: &InstrumentedConstraintSystem<F>,
+    ) -> Result<(), SynthesisError>;
+}
+
+/// A [`TracedCircuit`] that generates its constraints without capturing backtraces, so that it
+/// can be proven with [`R1CSNark::prove`] like any other circuit.
+pub struct Untraced<C>(pub C);
+
+impl<F: Field, C: TracedCircuit<F>> ConstraintSynthesizer<F> for Untraced<C> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        self.0
+            .generate_constraints(&InstrumentedConstraintSystem::untraced(cs))
+    }
+}
+
+/// An index prover key together with the backtraces of the calls that added its constraints.
+pub struct InstrumentedIndexProverKey<G: AffineCurve> {
+    /// The index prover key, which is also the index verifier key.
+    pub ipk: IndexProverKey<G>,
+
+    /// The backtrace of the call that added each constraint, in the order of the rows of the
+    /// matrices of `ipk`.
+    pub constraint_origins: Vec<Backtrace>,
+}
+
+/// An unsatisfied constraint, reported by
+/// [`InstrumentedIndexProverKey::validate_assignment_with_origins`].
+#[derive(Debug)]
+pub struct ConstraintOrigin<'a> {
+    /// The row of the constraint in the matrices.
+    pub row: usize,
+
+    /// The backtrace of the call that added the constraint.
+    pub origin: &'a Backtrace,
+}
+
+impl fmt::Display for ConstraintOrigin<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "constraint {} is unsatisfied; it was added at:\n{}",
+            self.row, self.origin
+        )
+    }
+}
+
+impl<G: AffineCurve> InstrumentedIndexProverKey<G> {
+    /// Checks that `assignment` satisfies the R1CS instance of the key, and otherwise returns
+    /// the first unsatisfied constraint together with where it was added. Returns an error if
+    /// the assignment does not have the lengths expected by the key.
+    pub fn validate_assignment_with_origins(
+        &self,
+        assignment: &FullAssignment<G::ScalarField>,
+    ) -> R1CSResult<Option<ConstraintOrigin<'_>>> {
+        let index_info = &self.ipk.index_info;
+        if assignment.input.len() != index_info.num_instance_variables {
+            return Err(NarkError::MalformedInput {
+                expected_len: index_info.num_instance_variables,
+                got: assignment.input.len(),
+            });
+        }
+
+        let witness_len = index_info.num_variables - index_info.num_instance_variables;
+        if assignment.witness.len() != witness_len {
+            return Err(NarkError::MalformedWitness {
+                expected_len: witness_len,
+                got: assignment.witness.len(),
+            });
+        }
+
+        let ipk = &self.ipk;
+        let row = first_unsatisfied_row(
+            &ipk.a,
+            &ipk.b,
+            &ipk.c,
+            &assignment.input,
+            &assignment.witness,
+        );
+        Ok(row.map(|row| ConstraintOrigin {
+            row,
+            origin: &self.constraint_origins[row],
+        }))
+    }
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Outputs a specialized prover key for some R1CS instance, together with a backtrace of the
+    /// call that added each of its constraints. Proofs are produced for the circuit wrapped in
+    /// [`Untraced`].
+    ///
+    /// Unlike [`R1CSNark::index`], trivially satisfied constraints are not removed, so that row
+    /// `i` of the matrices is the constraint added at `constraint_origins[i]`.
+    pub fn index_instrumented<C: TracedCircuit<G::ScalarField>>(
+        pp: &PublicParameters,
+        circuit: C,
+    ) -> R1CSResult<InstrumentedIndexProverKey<G>> {
+        let cs = ConstraintSystem::new_ref();
+        cs.set_mode(SynthesisMode::Setup);
+        let ics = InstrumentedConstraintSystem::new(cs.clone());
+        circuit.generate_constraints(&ics)?;
+        cs.finalize();
+
+        let constraint_origins = ics.take_origins();
+        let matrices = cs.to_matrices().expect("should not be `None`");
+        let num_circuit_constraints = matrices.a.len();
+        let (mut ipk, _) = Self::index_from_matrices(
+            pp,
+            matrices.a,
+            matrices.b,
+            matrices.c,
+            cs.num_instance_variables(),
+            cs.num_witness_variables(),
+        )?;
+        ipk.index_info.num_circuit_constraints = num_circuit_constraints;
+
+        Ok(InstrumentedIndexProverKey {
+            ipk,
+            constraint_origins,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_relations::lc;
+    use ark_sponge::poseidon::PoseidonSponge;
+    use std::backtrace::BacktraceStatus;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    // Enforces `x * y = z` and `z * z = w` for secret `x`, `y`, and `w` and a public `z`.
+    #[derive(Clone, Copy)]
+    struct ProductCircuit<F> {
+        x: F,
+        y: F,
+    }
+
+    impl<F: Field> TracedCircuit<F> for ProductCircuit<F> {
+        fn generate_constraints(
+            self,
+            cs: &InstrumentedConstraintSystem<F>,
+        ) -> Result<(), SynthesisError> {
+            let z = cs.new_input_variable(|| Ok(self.x * self.y))?;
+            let x = cs.new_witness_variable(|| Ok(self.x))?;
+            let y = cs.new_witness_variable(|| Ok(self.y))?;
+            let w = cs.new_witness_variable(|| Ok((self.x * self.y).square()))?;
+            cs.enforce_constraint(lc!() + x, lc!() + y, lc!() + z)?;
+            cs.cs().enforce_constraint(lc!() + z, lc!() + z, lc!() + w)
+        }
+    }
+
+    #[test]
+    fn test_index_instrumented() {
+        let rng = &mut ark_std::test_rng();
+        let (x, y) = (Fr::rand(rng), Fr::rand(rng));
+        let circuit = ProductCircuit { x, y };
+        let r1cs_input = vec![Fr::one(), x * y];
+
+        let pp = Nark::setup();
+        let iipk = Nark::index_instrumented(&pp, circuit).unwrap();
+        assert_eq!(iipk.constraint_origins.len(), 2);
+        assert_eq!(
+            iipk.constraint_origins[0].status(),
+            BacktraceStatus::Captured
+        );
+        assert_eq!(
+            iipk.constraint_origins[1].status(),
+            BacktraceStatus::Disabled
+        );
+
+        let proof = Nark::prove(&iipk.ipk, Untraced(circuit), false, None, None).unwrap();
+        assert!(Nark::verify(&iipk.ipk, &r1cs_input, &proof, None).unwrap());
+
+        let mut assignment = FullAssignment {
+            input: r1cs_input,
+            witness: vec![x, y, (x * y).square()],
+        };
+        assert!(iipk
+            .validate_assignment_with_origins(&assignment)
+            .unwrap()
+            .is_none());
+
+        assignment.witness[1] += Fr::one();
+        let origin = iipk
+            .validate_assignment_with_origins(&assignment)
+            .unwrap()
+            .unwrap();
+        assert_eq!(origin.row, 0);
+        assert_eq!(origin.origin.status(), BacktraceStatus::Captured);
+        assert!(origin
+            .to_string()
+            .starts_with("constraint 0 is unsatisfied"));
+
+        assignment.witness.pop();
+        let result = iipk.validate_assignment_with_origins(&assignment);
+        assert!(matches!(result, Err(NarkError::MalformedWitness { .. })));
+    }
+}
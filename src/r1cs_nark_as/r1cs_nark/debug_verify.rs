@@ -0,0 +1,191 @@
+use super::{CommitmentCheck, FailedCheck, IndexVerifierKey, NarkError, Proof, R1CSNark};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_serialize::CanonicalSerialize;
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::fmt;
+use ark_std::format;
+use ark_std::string::String;
+use ark_std::vec::Vec;
+
+/// The check that caused [`R1CSNark::verify_debug`] to reject a proof. For the commitment checks,
+/// `expected` is the commitment derived from the proof and `got` is the one reconstructed by the
+/// verifier from the blinded witness.
+#[derive(Debug)]
+pub enum NarkVerifyFailure<G: AffineCurve> {
+    /// Only one of the messages of the proof contains zero-knowledge randomness.
+    Randomness,
+
+    /// The commitment to `A * (input || witness)` does not match.
+    CommitmentA {
+        /// The commitment derived from the proof.
+        expected: G,
+        /// The commitment reconstructed by the verifier.
+        got: G,
+    },
+
+    /// The commitment to `B * (input || witness)` does not match.
+    CommitmentB {
+        /// The commitment derived from the proof.
+        expected: G,
+        /// The commitment reconstructed by the verifier.
+        got: G,
+    },
+
+    /// The commitment to `C * (input || witness)` does not match.
+    CommitmentC {
+        /// The commitment derived from the proof.
+        expected: G,
+        /// The commitment reconstructed by the verifier.
+        got: G,
+    },
+
+    /// The commitment to the Hadamard product of `A * (input || witness)` and
+    /// `B * (input || witness)` does not match.
+    HadamardProduct {
+        /// The commitment derived from the proof.
+        expected: G,
+        /// The commitment reconstructed by the verifier.
+        got: G,
+    },
+
+    /// The input or the proof were malformed.
+    Malformed(NarkError),
+}
+
+impl<G: AffineCurve> From<FailedCheck<G>> for NarkVerifyFailure<G> {
+    fn from(check: FailedCheck<G>) -> Self {
+        match check {
+            FailedCheck::Randomness => NarkVerifyFailure::Randomness,
+            FailedCheck::Commitment(CommitmentCheck::A, expected, got) => {
+                NarkVerifyFailure::CommitmentA { expected, got }
+            }
+            FailedCheck::Commitment(CommitmentCheck::B, expected, got) => {
+                NarkVerifyFailure::CommitmentB { expected, got }
+            }
+            FailedCheck::Commitment(CommitmentCheck::C, expected, got) => {
+                NarkVerifyFailure::CommitmentC { expected, got }
+            }
+            FailedCheck::HadamardProduct(expected, got) => {
+                NarkVerifyFailure::HadamardProduct { expected, got }
+            }
+        }
+    }
+}
+
+impl<G: AffineCurve> From<NarkError> for NarkVerifyFailure<G> {
+    fn from(err: NarkError) -> Self {
+        NarkVerifyFailure::Malformed(err)
+    }
+}
+
+// Encodes the compressed serialization of a group element as a hexadecimal string.
+fn to_hex<G: AffineCurve>(elem: &G) -> String {
+    let mut bytes = Vec::new();
+    elem.serialize(&mut bytes).unwrap();
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl<G: AffineCurve> fmt::Display for NarkVerifyFailure<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, expected, got) = match self {
+            NarkVerifyFailure::Randomness => {
+                return write!(
+                    f,
+                    "Randomness mismatch: only one proof message contains randomness"
+                )
+            }
+            NarkVerifyFailure::Malformed(err) => return write!(f, "Malformed: {}", err),
+            NarkVerifyFailure::CommitmentA { expected, got } => ("CommitmentA", expected, got),
+            NarkVerifyFailure::CommitmentB { expected, got } => ("CommitmentB", expected, got),
+            NarkVerifyFailure::CommitmentC { expected, got } => ("CommitmentC", expected, got),
+            NarkVerifyFailure::HadamardProduct { expected, got } => {
+                ("HadamardProduct", expected, got)
+            }
+        };
+
+        write!(
+            f,
+            "{} mismatch: expected 0x{}, got 0x{}",
+            name,
+            to_hex(expected),
+            to_hex(got)
+        )
+    }
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Verifies that some R1CS relation holds, like [`R1CSNark::verify`], but reports which
+    /// check of the verifier failed instead of returning `false`.
+    pub fn verify_debug(
+        ivk: &IndexVerifierKey<G>,
+        input: &[G::ScalarField],
+        proof: &Proof<G>,
+        sponge: Option<S>,
+    ) -> Result<bool, NarkVerifyFailure<G>> {
+        let failed_check = Self::find_failed_check(ivk, input, proof, sponge, |input, witness| {
+            ivk.matrix_vec_muls(input, witness)
+        })?;
+
+        match failed_check {
+            None => Ok(true),
+            Some(check) => Err(check.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ec::ProjectiveCurve;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_verify_debug() {
+        let rng = &mut ark_std::test_rng();
+        let pp = Nark::setup();
+        let circuit = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let input = vec![Fr::one(), circuit.a.unwrap() * circuit.b.unwrap()];
+        let (ipk, ivk) = Nark::index(&pp, circuit).unwrap();
+
+        for &make_zk in &[false, true] {
+            let proof = Nark::prove(&ipk, circuit, make_zk, None, Some(rng)).unwrap();
+            assert!(Nark::verify_debug(&ivk, &input, &proof, None).unwrap());
+
+            let mut tampered = proof.clone();
+            tampered.first_msg.comm_a = (tampered.first_msg.comm_a.into_projective()
+                + Affine::prime_subgroup_generator().into_projective())
+            .into_affine();
+            let result = Nark::verify_debug(&ivk, &input, &tampered, None);
+            assert!(matches!(result, Err(NarkVerifyFailure::CommitmentA { .. })));
+            assert!(result
+                .unwrap_err()
+                .to_string()
+                .starts_with("CommitmentA mismatch: expected 0x"));
+
+            let result = Nark::verify_debug(&ivk, &input[..1], &proof, None);
+            assert!(matches!(
+                result,
+                Err(NarkVerifyFailure::Malformed(
+                    NarkError::MalformedInput { .. }
+                ))
+            ));
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use super::{matrix_vec_mul, FullAssignment, IndexProverKey, IndexVerifierKey, Proof, R1CSNark};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_poly_commit::trivial_pc::PedersenCommitment;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::io::{Read, Write};
+
+/// An opening of the commitment to `A * (input || witness)` in a [`Proof`], which shows that the
+/// commitment was computed from a stated vector.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct DecommitmentProof<G: AffineCurve> {
+    /// The commitment being opened.
+    pub commitment: G,
+
+    /// The randomness of the commitment. This is `None` for proofs that are not zero-knowledge.
+    pub randomness: Option<G::ScalarField>,
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Opens `proof.first_msg.comm_a` to `A * (input || witness)` for the input and witness of
+    /// `assignment`.
+    ///
+    /// Returns `None` if `proof` is zero-knowledge, since its commitment randomness cannot be
+    /// recovered from the proof and the assignment, or if the commitment was not computed from
+    /// `assignment`.
+    pub fn decommit(
+        ipk: &IndexProverKey<G>,
+        proof: &Proof<G>,
+        assignment: &FullAssignment<G::ScalarField>,
+    ) -> Option<DecommitmentProof<G>> {
+        if proof.first_msg.randomness.is_some() {
+            return None;
+        }
+
+        let a_times_witness = matrix_vec_mul(&ipk.a, &assignment.input, &assignment.witness);
+        let commitment = PedersenCommitment::commit(&ipk.ck, &a_times_witness, None);
+        if commitment != proof.first_msg.comm_a {
+            return None;
+        }
+
+        Some(DecommitmentProof {
+            commitment,
+            randomness: None,
+        })
+    }
+
+    /// Checks that `decommit_proof` opens `proof.first_msg.comm_a` to `opened_value`.
+    pub fn verify_decommitment(
+        ivk: &IndexVerifierKey<G>,
+        proof: &Proof<G>,
+        decommit_proof: &DecommitmentProof<G>,
+        opened_value: &[G::ScalarField],
+    ) -> bool {
+        if opened_value.len() != ivk.index_info.num_constraints
+            || decommit_proof.commitment != proof.first_msg.comm_a
+        {
+            return false;
+        }
+
+        PedersenCommitment::commit(&ivk.ck, opened_value, decommit_proof.randomness)
+            == decommit_proof.commitment
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_decommit() {
+        let rng = &mut ark_std::test_rng();
+        let circuit = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let (a, b) = (circuit.a.unwrap(), circuit.b.unwrap());
+        let assignment = FullAssignment {
+            input: vec![Fr::one(), a * b],
+            witness: [vec![a, b], vec![a; 7]].concat(),
+        };
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, circuit).unwrap();
+        let proof = Nark::prove(&ipk, circuit, false, None, None).unwrap();
+
+        let opened_value = matrix_vec_mul(&ivk.a, &assignment.input, &assignment.witness);
+        let decommit_proof = Nark::decommit(&ipk, &proof, &assignment).unwrap();
+        assert!(Nark::verify_decommitment(
+            &ivk,
+            &proof,
+            &decommit_proof,
+            &opened_value
+        ));
+
+        let mut wrong_value = opened_value.clone();
+        wrong_value[0] += Fr::one();
+        assert!(!Nark::verify_decommitment(
+            &ivk,
+            &proof,
+            &decommit_proof,
+            &wrong_value
+        ));
+
+        let mut tampered = decommit_proof.clone();
+        tampered.randomness = Some(Fr::one());
+        assert!(!Nark::verify_decommitment(
+            &ivk,
+            &proof,
+            &tampered,
+            &opened_value
+        ));
+
+        let mut wrong_assignment = assignment.clone();
+        wrong_assignment.witness[0] += Fr::one();
+        assert!(Nark::decommit(&ipk, &proof, &wrong_assignment).is_none());
+
+        let zk_proof = Nark::prove(&ipk, circuit, true, None, Some(rng)).unwrap();
+        assert!(Nark::decommit(&ipk, &zk_proof, &assignment).is_none());
+    }
+}
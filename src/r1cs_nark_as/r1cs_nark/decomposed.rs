@@ -0,0 +1,277 @@
+use super::{
+    FullAssignment, IndexProverKey, IndexVerifierKey, Proof, PublicParameters, R1CSNark, R1CSResult,
+};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::Field;
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, LinearCombination, Matrix,
+    SynthesisError, Variable,
+};
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::vec::Vec;
+use rayon::prelude::*;
+use rayon::ThreadPool;
+
+/// A contiguous block of the constraints of a circuit, produced by [`R1CSNark::decompose`]. It
+/// allocates every variable of the circuit, so that the proofs of all the blocks can be stitched
+/// together by checking that they share the same witness.
+#[derive(Clone)]
+pub struct SubCircuit<F: Field> {
+    a: Matrix<F>,
+    b: Matrix<F>,
+    c: Matrix<F>,
+    num_instance_variables: usize,
+    num_witness_variables: usize,
+    assignment: Option<FullAssignment<F>>,
+}
+
+impl<F: Field> SubCircuit<F> {
+    /// Returns the number of constraints of the block.
+    pub fn num_constraints(&self) -> usize {
+        self.a.len()
+    }
+
+    /// Returns the same block without its assignment, e.g. to index it.
+    pub fn without_assignment(&self) -> Self {
+        Self {
+            assignment: None,
+            ..self.clone()
+        }
+    }
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for SubCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let assignment = self.assignment.as_ref();
+        let value = |get: fn(&FullAssignment<F>) -> &[F], i: usize| {
+            assignment
+                .map(|assignment| get(assignment)[i])
+                .ok_or(SynthesisError::AssignmentMissing)
+        };
+
+        let num_variables = self.num_instance_variables + self.num_witness_variables;
+        let mut variables = Vec::with_capacity(num_variables);
+        variables.push(Variable::One);
+        for i in 1..self.num_instance_variables {
+            variables.push(cs.new_input_variable(|| value(|a| &a.input, i))?);
+        }
+        for i in 0..self.num_witness_variables {
+            variables.push(cs.new_witness_variable(|| value(|a| &a.witness, i))?);
+        }
+
+        let lc = |row: &[(F, usize)]| {
+            row.iter()
+                .fold(LinearCombination::zero(), |lc, (coeff, i)| {
+                    lc + (*coeff, variables[*i])
+                })
+        };
+        for ((a_row, b_row), c_row) in self.a.iter().zip(&self.b).zip(&self.c) {
+            cs.enforce_constraint(lc(a_row), lc(b_row), lc(c_row))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The proofs of the blocks of a circuit decomposed by [`R1CSNark::decompose`], in the order of
+/// the blocks.
+#[derive(Clone)]
+pub struct DecomposedProof<G: AffineCurve> {
+    /// The proof of each block.
+    pub sub_proofs: Vec<Proof<G>>,
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Splits the constraints of `circuit` into `num_parts` contiguous blocks of as equal sizes
+    /// as possible, which can be indexed and proven independently. `circuit` is synthesized
+    /// with its assignment, which the blocks keep.
+    ///
+    /// # Panics
+    /// Panics if `num_parts` is zero.
+    pub fn decompose<C: ConstraintSynthesizer<G::ScalarField>>(
+        circuit: C,
+        num_parts: usize,
+    ) -> R1CSResult<Vec<SubCircuit<G::ScalarField>>> {
+        assert!(num_parts > 0, "the number of parts must be non-zero");
+
+        let cs = ConstraintSystem::new_ref();
+        circuit.generate_constraints(cs.clone())?;
+        cs.finalize();
+
+        let matrices = cs.to_matrices().expect("should not be `None`");
+        let assignment = {
+            let cs = cs.borrow().unwrap();
+            FullAssignment {
+                input: cs.instance_assignment.clone(),
+                witness: cs.witness_assignment.clone(),
+            }
+        };
+
+        let num_constraints = matrices.a.len();
+        let part_size = ((num_constraints + num_parts - 1) / num_parts).max(1);
+        let chunks = |mat: &Matrix<G::ScalarField>| {
+            mat.chunks(part_size).map(<[_]>::to_vec).collect::<Vec<_>>()
+        };
+        let (a, b, c) = (
+            chunks(&matrices.a),
+            chunks(&matrices.b),
+            chunks(&matrices.c),
+        );
+        let sub_circuits = a
+            .into_iter()
+            .zip(b)
+            .zip(c)
+            .map(|((a, b), c)| SubCircuit {
+                a,
+                b,
+                c,
+                num_instance_variables: matrices.num_instance_variables,
+                num_witness_variables: matrices.num_witness_variables,
+                assignment: Some(assignment.clone()),
+            })
+            .collect();
+
+        Ok(sub_circuits)
+    }
+
+    /// Outputs a specialized prover and verifier key for each block of a decomposed circuit.
+    pub fn index_decomposed(
+        pp: &PublicParameters,
+        sub_circuits: &[SubCircuit<G::ScalarField>],
+    ) -> R1CSResult<Vec<(IndexProverKey<G>, IndexVerifierKey<G>)>> {
+        sub_circuits
+            .iter()
+            .map(|sub_circuit| Self::index(pp, sub_circuit.without_assignment()))
+            .collect()
+    }
+
+    /// Proves every block of a decomposed circuit on `pool`, with `ipks[i]` for block `i`.
+    ///
+    /// The proofs are not zero knowledge, since they are stitched together by
+    /// [`R1CSNark::verify_decomposed`] by checking that they reveal the same witness.
+    ///
+    /// # Panics
+    /// Panics if there is not one key per block.
+    pub fn prove_decomposed(
+        ipks: &[IndexProverKey<G>],
+        sub_circuits: Vec<SubCircuit<G::ScalarField>>,
+        pool: &ThreadPool,
+        sponge: Option<S>,
+    ) -> R1CSResult<DecomposedProof<G>>
+    where
+        S: Send + Sync,
+    {
+        assert_eq!(ipks.len(), sub_circuits.len(), "expected one key per block");
+
+        let sub_proofs = pool.install(|| {
+            sub_circuits
+                .into_par_iter()
+                .zip(ipks)
+                .map(|(sub_circuit, ipk)| {
+                    Self::prove(ipk, sub_circuit, false, sponge.clone(), None)
+                })
+                .collect::<R1CSResult<Vec<_>>>()
+        })?;
+
+        Ok(DecomposedProof { sub_proofs })
+    }
+
+    /// Verifies a proof of a decomposed circuit, with `ivks[i]` for block `i`. Returns false if
+    /// there is not one key per block, if a block does not verify, or if the blocks were proven
+    /// for different witnesses.
+    pub fn verify_decomposed(
+        ivks: &[IndexVerifierKey<G>],
+        input: &[G::ScalarField],
+        proof: &DecomposedProof<G>,
+        sponge: Option<S>,
+    ) -> R1CSResult<bool> {
+        if ivks.len() != proof.sub_proofs.len() {
+            return Ok(false);
+        }
+
+        // The stitch: every block must be satisfied by the same witness.
+        let witness = proof
+            .sub_proofs
+            .first()
+            .map(|sub_proof| &sub_proof.second_msg.blinded_witness);
+        let is_stitched = proof.sub_proofs.iter().all(|sub_proof| {
+            sub_proof.first_msg.randomness.is_none()
+                && Some(&sub_proof.second_msg.blinded_witness) == witness
+        });
+        if !is_stitched {
+            return Ok(false);
+        }
+
+        for (ivk, sub_proof) in ivks.iter().zip(&proof.sub_proofs) {
+            if !Self::verify(ivk, input, sub_proof, sponge.clone())? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+    use rayon::ThreadPoolBuilder;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_prove_decomposed() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let sub_circuits = Nark::decompose(c, 4).unwrap();
+        assert_eq!(sub_circuits.len(), 4);
+        assert!(sub_circuits.iter().all(|sub| sub.num_constraints() == 25));
+
+        let pp = Nark::setup();
+        let keys = Nark::index_decomposed(&pp, &sub_circuits).unwrap();
+        let (ipks, ivks): (Vec<_>, Vec<_>) = keys.into_iter().unzip();
+
+        let pool = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        let proof = Nark::prove_decomposed(&ipks, sub_circuits, &pool, None).unwrap();
+        assert!(Nark::verify_decomposed(&ivks, &r1cs_input, &proof, None).unwrap());
+
+        let wrong_input = vec![Fr::one(), Fr::rand(rng)];
+        assert!(!Nark::verify_decomposed(&ivks, &wrong_input, &proof, None).unwrap());
+
+        // Blocks proven for different witnesses of the same input are not stitched together.
+        let two = Fr::one() + Fr::one();
+        let other_c = DummyCircuit {
+            a: Some(c.a.unwrap() * two),
+            b: Some(c.b.unwrap() * two.inverse().unwrap()),
+            ..c
+        };
+        let other_sub_circuits = Nark::decompose(other_c, 4).unwrap();
+        let other_proof = Nark::prove_decomposed(&ipks, other_sub_circuits, &pool, None).unwrap();
+        assert!(Nark::verify_decomposed(&ivks, &r1cs_input, &other_proof, None).unwrap());
+
+        let mut mixed_proof = proof.clone();
+        mixed_proof.sub_proofs[2] = other_proof.sub_proofs[2].clone();
+        assert!(Nark::verify(&ivks[2], &r1cs_input, &mixed_proof.sub_proofs[2], None).unwrap());
+        assert!(!Nark::verify_decomposed(&ivks, &r1cs_input, &mixed_proof, None).unwrap());
+
+        assert!(!Nark::verify_decomposed(&ivks[1..], &r1cs_input, &proof, None).unwrap());
+    }
+}
@@ -0,0 +1,197 @@
+use super::{
+    hash_matrices, matrices_hash_domain, transpose_matrices, CsrMatrix, IndexProverKey, MatrixRepr,
+    MatrixSymmetries, NarkConfig, NarkError, Proof, PublicParameters, R1CSNark, R1CSResult,
+};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_relations::r1cs::{ConstraintSynthesizer, Matrix};
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::rand::RngCore;
+use ark_std::vec::Vec;
+
+/// A key for an R1CS instance of which only some constraints are active in any given proof, such
+/// as a circuit over inputs of variable length. The key holds the instance with all of its
+/// constraints, and its commitment key supports that maximum number of constraints.
+///
+/// Each proof is made for the instance in which the constraints outside an `active_mask` are
+/// replaced by the trivial constraint `0 * 0 = 0`. The mask changes the hash of the matrices, so a
+/// proof only verifies against the mask it was created for.
+#[derive(Clone)]
+pub struct DynamicIndexProverKey<G: AffineCurve> {
+    ipk: IndexProverKey<G>,
+}
+
+/// The verifier key for an R1CS instance with a varying set of active constraints.
+pub type DynamicIndexVerifierKey<G> = DynamicIndexProverKey<G>;
+
+impl<G: AffineCurve> DynamicIndexProverKey<G> {
+    /// The number of constraints of the instance when all of them are active.
+    pub fn max_num_constraints(&self) -> usize {
+        self.ipk.index_info.num_constraints
+    }
+
+    // Outputs the key for the instance in which only the constraints in `active_mask` are active.
+    fn masked(&self, active_mask: &[bool]) -> R1CSResult<IndexProverKey<G>> {
+        if active_mask.len() != self.max_num_constraints() {
+            return Err(NarkError::MalformedMask {
+                expected_len: self.max_num_constraints(),
+                got: active_mask.len(),
+            });
+        }
+
+        let mut ipk = self.ipk.clone();
+        let mask = |matrix: &CsrMatrix<G::ScalarField>| -> CsrMatrix<G::ScalarField> {
+            let mut matrix = Matrix::from(matrix.clone());
+            for (i, _) in active_mask
+                .iter()
+                .enumerate()
+                .filter(|(_, active)| !**active)
+            {
+                matrix[i] = Vec::new();
+            }
+            matrix.into()
+        };
+        ipk.a = mask(&ipk.a);
+        ipk.b = mask(&ipk.b);
+        ipk.c = mask(&ipk.c);
+
+        ipk.index_info.matrices_hash = hash_matrices(
+            &matrices_hash_domain(&ipk.index_info),
+            &ipk.a,
+            &ipk.b,
+            &ipk.c,
+        );
+        ipk.matrix_reprs = MatrixRepr::from_matrices(
+            &ipk.a,
+            &ipk.b,
+            &ipk.c,
+            ipk.index_info.num_variables,
+            ipk.matrix_strategy,
+        );
+        ipk.transposes = transpose_matrices(&ipk.a, &ipk.b, &ipk.c, ipk.index_info.num_variables);
+        ipk.index_info.matrix_symmetries = MatrixSymmetries::detect(&ipk.a, &ipk.b, &ipk.c);
+        Ok(ipk)
+    }
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Outputs a prover and verifier key for an R1CS instance whose number of active constraints
+    /// is only known at prove time. `r1cs_instance` must generate the maximum number of
+    /// constraints. Unlike [`R1CSNark::index`], this keeps trivially satisfied constraints, so
+    /// that the entries of an active mask correspond to the constraints of the circuit.
+    pub fn index_dynamic<C: ConstraintSynthesizer<G::ScalarField>>(
+        pp: &PublicParameters,
+        r1cs_instance: C,
+    ) -> R1CSResult<(DynamicIndexProverKey<G>, DynamicIndexVerifierKey<G>)> {
+        let (a, b, c, num_input_variables, num_witness_variables) =
+            Self::constraint_matrices(r1cs_instance, &NarkConfig::default())?;
+        let (mut ipk, _) =
+            Self::index_from_matrices(pp, a, b, c, num_input_variables, num_witness_variables)?;
+        ipk.index_info.is_padded = true;
+        let ipk = DynamicIndexProverKey { ipk };
+        let ivk = ipk.clone();
+        Ok((ipk, ivk))
+    }
+
+    /// Proves that the constraints of `r1cs` selected by `active_mask` hold. The circuit may
+    /// generate fewer constraints and variables than the maximum, in which case the witness is
+    /// padded with zeros. Returns an error if `active_mask` does not have one entry per
+    /// constraint of the key.
+    pub fn prove_dynamic<C: ConstraintSynthesizer<G::ScalarField>>(
+        ipk: &DynamicIndexProverKey<G>,
+        r1cs: C,
+        active_mask: &[bool],
+        make_zk: bool,
+        sponge: Option<S>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> R1CSResult<Proof<G>> {
+        Self::prove(&ipk.masked(active_mask)?, r1cs, make_zk, sponge, rng)
+    }
+
+    /// Verifies that the constraints selected by `active_mask` hold. Returns an error if
+    /// `active_mask` does not have one entry per constraint of the key, or under the same
+    /// conditions as [`R1CSNark::verify`].
+    pub fn verify_dynamic(
+        ivk: &DynamicIndexVerifierKey<G>,
+        input: &[G::ScalarField],
+        proof: &Proof<G>,
+        active_mask: &[bool],
+        sponge: Option<S>,
+    ) -> R1CSResult<bool> {
+        Self::verify(&ivk.masked(active_mask)?, input, proof, sponge)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::{One, PrimeField};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError, Variable};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    const MAX_LEN: usize = 10;
+
+    // Enforces `x_i = 1` for each of `len` witness variables, standing in for a circuit that
+    // processes an input of length `len`.
+    #[derive(Clone, Copy)]
+    struct VariableLengthCircuit {
+        len: usize,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for VariableLengthCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            for _ in 0..self.len {
+                let x = cs.new_witness_variable(|| Ok(F::one()))?;
+                cs.enforce_constraint(lc!() + x, lc!() + Variable::One, lc!() + Variable::One)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_dynamic_constraints() {
+        let rng = &mut ark_std::test_rng();
+        let input = vec![Fr::one()];
+        let circuit = VariableLengthCircuit { len: 5 };
+        let active_mask = (0..MAX_LEN).map(|i| i < 5).collect::<Vec<_>>();
+        let all_active = vec![true; MAX_LEN];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index_dynamic(&pp, VariableLengthCircuit { len: MAX_LEN }).unwrap();
+        assert_eq!(ipk.max_num_constraints(), MAX_LEN);
+
+        for &make_zk in &[false, true] {
+            let proof =
+                Nark::prove_dynamic(&ipk, circuit, &active_mask, make_zk, None, Some(rng)).unwrap();
+            assert!(Nark::verify_dynamic(&ivk, &input, &proof, &active_mask, None).unwrap());
+
+            // The padded witness does not satisfy the inactive constraints.
+            assert!(!Nark::verify_dynamic(&ivk, &input, &proof, &all_active, None).unwrap());
+        }
+
+        assert!(matches!(
+            Nark::verify_dynamic(
+                &ivk,
+                &input,
+                &Proof::base_case(&input),
+                &active_mask[..5],
+                None
+            ),
+            Err(NarkError::MalformedMask {
+                expected_len: MAX_LEN,
+                got: 5
+            })
+        ));
+    }
+}
@@ -0,0 +1,149 @@
+use super::{PublicParameters, R1CSNark};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::rand::RngCore;
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Tests whether two circuits compute the same function, e.g. before and after a refactoring.
+    /// `circuit1` and `circuit2` build each circuit for a given test input, and each circuit
+    /// allocates its public input and outputs as instance variables.
+    ///
+    /// Both circuits are indexed once, and for each of `test_inputs`, both are proven and verified
+    /// against their own public input. Returns `true` if every proof is accepted and both circuits
+    /// produce the same public input and outputs for every test input. This is a test on the given
+    /// inputs only, not a proof of equivalence.
+    pub fn check_equivalence<C1, C2>(
+        pp: &PublicParameters,
+        circuit1: impl Fn(&[G::ScalarField]) -> C1,
+        circuit2: impl Fn(&[G::ScalarField]) -> C2,
+        test_inputs: &[&[G::ScalarField]],
+        rng: &mut dyn RngCore,
+    ) -> bool
+    where
+        C1: ConstraintSynthesizer<G::ScalarField>,
+        C2: ConstraintSynthesizer<G::ScalarField>,
+    {
+        let first_input = match test_inputs.first() {
+            Some(input) => *input,
+            None => return true,
+        };
+        let (ipk1, ivk1) = match Self::index(pp, circuit1(first_input)) {
+            Ok(keys) => keys,
+            Err(_) => return false,
+        };
+        let (ipk2, ivk2) = match Self::index(pp, circuit2(first_input)) {
+            Ok(keys) => keys,
+            Err(_) => return false,
+        };
+
+        test_inputs.iter().all(|&test_input| {
+            let (public1, _) = match Self::synthesize(&ipk1, circuit1(test_input)) {
+                Ok(assignment) => assignment,
+                Err(_) => return false,
+            };
+            let (public2, _) = match Self::synthesize(&ipk2, circuit2(test_input)) {
+                Ok(assignment) => assignment,
+                Err(_) => return false,
+            };
+            if public1 != public2 {
+                return false;
+            }
+
+            let verified1 = Self::prove(&ipk1, circuit1(test_input), true, None, Some(&mut *rng))
+                .and_then(|proof| Self::verify(&ivk1, &public1, &proof, None));
+            let verified2 = Self::prove(&ipk2, circuit2(test_input), true, None, Some(&mut *rng))
+                .and_then(|proof| Self::verify(&ivk2, &public2, &proof, None));
+            verified1.unwrap_or(false) && verified2.unwrap_or(false)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::{PrimeField, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError, Variable};
+    use ark_sponge::poseidon::PoseidonSponge;
+    use ark_std::vec::Vec;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    // Computes the public output `x * y` of the public inputs `x` and `y`, optionally through an
+    // intermediate witness variable as a refactored circuit might, or computes `x + y` instead.
+    #[derive(Clone, Copy)]
+    struct MulCircuit<F> {
+        x: F,
+        y: F,
+        refactored: bool,
+        add_instead: bool,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for MulCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let x = cs.new_input_variable(|| Ok(self.x))?;
+            let y = cs.new_input_variable(|| Ok(self.y))?;
+            let output = if self.add_instead {
+                self.x + self.y
+            } else {
+                self.x * self.y
+            };
+            let z = cs.new_input_variable(|| Ok(output))?;
+
+            if self.add_instead {
+                cs.enforce_constraint(lc!() + x + y, lc!() + Variable::One, lc!() + z)?;
+            } else if self.refactored {
+                let t = cs.new_witness_variable(|| Ok(output))?;
+                cs.enforce_constraint(lc!() + x, lc!() + y, lc!() + t)?;
+                cs.enforce_constraint(lc!() + t, lc!() + Variable::One, lc!() + z)?;
+            } else {
+                cs.enforce_constraint(lc!() + x, lc!() + y, lc!() + z)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    fn circuit(input: &[Fr], refactored: bool, add_instead: bool) -> MulCircuit<Fr> {
+        MulCircuit {
+            x: input[0],
+            y: input[1],
+            refactored,
+            add_instead,
+        }
+    }
+
+    #[test]
+    fn test_check_equivalence() {
+        let rng = &mut ark_std::test_rng();
+        let inputs = (0..5)
+            .map(|_| vec![Fr::rand(rng), Fr::rand(rng)])
+            .collect::<Vec<_>>();
+        let test_inputs = inputs.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+        let pp = Nark::setup();
+        assert!(Nark::check_equivalence(
+            &pp,
+            |input| circuit(input, false, false),
+            |input| circuit(input, true, false),
+            &test_inputs,
+            rng,
+        ));
+        assert!(!Nark::check_equivalence(
+            &pp,
+            |input| circuit(input, false, false),
+            |input| circuit(input, false, true),
+            &test_inputs,
+            rng,
+        ));
+    }
+}
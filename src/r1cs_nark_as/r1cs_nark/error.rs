@@ -0,0 +1,274 @@
+use crate::error::BoxedError;
+
+use ark_relations::r1cs::SynthesisError;
+use ark_serialize::SerializationError;
+use ark_std::error::Error;
+use ark_std::format;
+use ark_std::string::String;
+use core::time::Duration;
+
+/// Errors that can occur when indexing, proving, or verifying with [`R1CSNark`][nark].
+///
+/// [nark]: crate::r1cs_nark_as::r1cs_nark::R1CSNark
+#[derive(Debug)]
+pub enum NarkError {
+    /// The public input does not have the length expected by the index.
+    MalformedInput {
+        /// The length expected by the index, including the constant `1`.
+        expected_len: usize,
+
+        /// The length of the public input.
+        got: usize,
+    },
+
+    /// A witness given directly, or the witness in a proof, does not have the length expected by
+    /// the index.
+    MalformedWitness {
+        /// The length expected by the index.
+        expected_len: usize,
+
+        /// The length of the witness.
+        got: usize,
+    },
+
+    /// A share of a witness given to `R1CSNark::prove_from_shares` does not have the length of
+    /// the witness expected by the index.
+    MalformedShare {
+        /// The position of the share among the shares.
+        position: usize,
+
+        /// The length of the witness expected by the index.
+        expected_len: usize,
+
+        /// The length of the share.
+        got: usize,
+    },
+
+    /// A share of a witness given to `R1CSNark::prove_from_shares` has a party index that is out
+    /// of range or taken by another share, or a number of parties other than the number of
+    /// shares.
+    InconsistentShares {
+        /// The position of the share among the shares.
+        position: usize,
+    },
+
+    /// A mask of active constraints does not have one entry per constraint of the index.
+    MalformedMask {
+        /// The number of constraints of the index.
+        expected_len: usize,
+
+        /// The length of the mask.
+        got: usize,
+    },
+
+    /// The circuit passed to the prover allocates a number of variables other than the index's,
+    /// or more than a padded index supports.
+    WitnessSizeMismatch {
+        /// The number of variables of the index, including the public input.
+        expected: usize,
+
+        /// The number of variables allocated by the circuit, including the public input.
+        got: usize,
+
+        /// The hash of the matrices of the index, which identifies the indexed circuit.
+        circuit_hash: [u8; 32],
+    },
+
+    /// The circuit passed to the prover has a number of constraints other than the index's, or
+    /// more than a padded index supports.
+    ConstraintCountMismatch {
+        /// The number of constraints of the index.
+        expected: usize,
+
+        /// The number of constraints of the circuit.
+        got: usize,
+
+        /// The hash of the matrices of the index, which identifies the indexed circuit.
+        circuit_hash: [u8; 32],
+    },
+
+    /// The circuit passed to the prover generated fewer constraints than when it was indexed,
+    /// e.g. because it skips constraints depending on its synthesis mode.
+    ModeMismatch {
+        /// The number of constraints generated by the circuit in setup mode.
+        index_constraints: usize,
+
+        /// The number of constraints generated by the circuit in prove mode.
+        prove_constraints: usize,
+    },
+
+    /// The assignment does not satisfy one of the constraints.
+    UnsatisfiedConstraint {
+        /// The index of the first unsatisfied constraint.
+        row: usize,
+    },
+
+    /// The commitment key cannot commit to as many elements as the index requires.
+    CommitmentKeyTooShort {
+        /// The number of elements the index requires.
+        needed: usize,
+
+        /// The number of elements the commitment key supports.
+        have: usize,
+    },
+
+    /// The key was produced by a version of the protocol other than
+    /// [`CURRENT_PROTOCOL_VERSION`][version].
+    ///
+    /// [version]: crate::r1cs_nark_as::r1cs_nark::CURRENT_PROTOCOL_VERSION
+    VersionMismatch {
+        /// The current protocol version.
+        expected: u32,
+
+        /// The protocol version of the key.
+        got: u32,
+    },
+
+    /// The bytes of a proof could not be decoded.
+    MalformedProof(SerializationError),
+
+    /// The bytes of a proof are in a format other than
+    /// [`PROOF_FORMAT_VERSION`][version].
+    ///
+    /// [version]: crate::r1cs_nark_as::r1cs_nark::PROOF_FORMAT_VERSION
+    UnsupportedProofVersion {
+        /// The format version of the proof.
+        version: u8,
+    },
+
+    /// The prover did not finish within the time limit given to `R1CSNark::prove_with_timeout`.
+    ProverTimeout {
+        /// The time that elapsed before the prover was abandoned.
+        elapsed: Duration,
+    },
+
+    /// A Circom `.r1cs` or `.wtns` file could not be read or parsed.
+    CircomFile(String),
+
+    /// A `MatrixFetcher` has no matrices for the R1CS instance with the given digest.
+    MatricesNotFound {
+        /// The hash of the matrices that were requested.
+        digest: [u8; 32],
+    },
+
+    /// A `WitnessOracle` returned an assignment for a public input other than the one it was
+    /// asked for.
+    OracleInputMismatch {
+        /// The identifier of the oracle.
+        oracle_id: [u8; 32],
+    },
+
+    /// The polynomial commitment scheme of `KzgR1CSNark` failed to set up, commit, or open.
+    PolynomialCommitment(String),
+
+    /// An error occurred while generating or processing the constraints.
+    Synthesis(SynthesisError),
+}
+
+impl From<SynthesisError> for NarkError {
+    fn from(err: SynthesisError) -> Self {
+        NarkError::Synthesis(err)
+    }
+}
+
+impl From<NarkError> for BoxedError {
+    fn from(err: NarkError) -> Self {
+        BoxedError::new(err)
+    }
+}
+
+impl core::fmt::Display for NarkError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let error_text = match self {
+            NarkError::MalformedInput { expected_len, got } => format!(
+                "MalformedInput: expected {} elements, got {}",
+                expected_len, got
+            ),
+            NarkError::MalformedWitness { expected_len, got } => format!(
+                "MalformedWitness: expected {} elements, got {}",
+                expected_len, got
+            ),
+            NarkError::MalformedShare {
+                position,
+                expected_len,
+                got,
+            } => format!(
+                "MalformedShare: expected share {} to have {} elements, got {}",
+                position, expected_len, got
+            ),
+            NarkError::InconsistentShares { position } => format!(
+                "InconsistentShares: share {} has an invalid party index or party count",
+                position
+            ),
+            NarkError::MalformedMask { expected_len, got } => format!(
+                "MalformedMask: expected {} entries, got {}",
+                expected_len, got
+            ),
+            NarkError::WitnessSizeMismatch {
+                expected,
+                got,
+                circuit_hash,
+            } => format!(
+                "WitnessSizeMismatch: expected {} variables, got {} (circuit 0x{})",
+                expected,
+                got,
+                to_hex(circuit_hash)
+            ),
+            NarkError::ConstraintCountMismatch {
+                expected,
+                got,
+                circuit_hash,
+            } => format!(
+                "ConstraintCountMismatch: expected {} constraints, got {} (circuit 0x{})",
+                expected,
+                got,
+                to_hex(circuit_hash)
+            ),
+            NarkError::ModeMismatch {
+                index_constraints,
+                prove_constraints,
+            } => format!(
+                "ModeMismatch: indexed with {} constraints, proving with {}",
+                index_constraints, prove_constraints
+            ),
+            NarkError::UnsatisfiedConstraint { row } => {
+                format!("UnsatisfiedConstraint: constraint {} is not satisfied", row)
+            }
+            NarkError::CommitmentKeyTooShort { needed, have } => format!(
+                "CommitmentKeyTooShort: needed {} elements, have {}",
+                needed, have
+            ),
+            NarkError::VersionMismatch { expected, got } => format!(
+                "VersionMismatch: expected protocol version {}, got {}",
+                expected, got
+            ),
+            NarkError::MalformedProof(err) => format!("MalformedProof: {}", err),
+            NarkError::UnsupportedProofVersion { version } => format!(
+                "UnsupportedProofVersion: proof format version {} is not supported",
+                version
+            ),
+            NarkError::ProverTimeout { elapsed } => {
+                format!("ProverTimeout: no proof after {:?}", elapsed)
+            }
+            NarkError::CircomFile(err) => format!("CircomFile: {}", err),
+            NarkError::MatricesNotFound { digest } => format!(
+                "MatricesNotFound: no matrices for circuit 0x{}",
+                to_hex(digest)
+            ),
+            NarkError::OracleInputMismatch { oracle_id } => format!(
+                "OracleInputMismatch: oracle 0x{} computed a witness for another input",
+                to_hex(oracle_id)
+            ),
+            NarkError::PolynomialCommitment(err) => format!("PolynomialCommitment: {}", err),
+            NarkError::Synthesis(err) => format!("Synthesis: {}", err),
+        };
+
+        write!(f, "{}", error_text)
+    }
+}
+
+impl Error for NarkError {}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
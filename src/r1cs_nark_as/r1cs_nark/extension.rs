@@ -0,0 +1,518 @@
+use super::{
+    hash_matrices, matrix_vec_mul, CsrMatrix, FirstRoundMessage, FirstRoundMessageRandomness,
+    IndexInfo, MatrixSymmetries, PublicParameters, R1CSNark, R1CSResult,
+    SecondRoundMessageRandomness, CURRENT_PROTOCOL_VERSION, PROTOCOL_NAME,
+};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::{Field, ToConstraintField, Zero};
+use ark_poly_commit::trivial_pc::{CommitterKey, PedersenCommitment};
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisMode,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::io::{Read, Write};
+use ark_std::rand::RngCore;
+use ark_std::vec;
+use ark_std::vec::Vec;
+use ark_std::{cfg_into_iter, cfg_iter, marker::PhantomData, UniformRand};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// The index prover key for [`ExtensionR1CSNark`].
+#[derive(Clone)]
+pub struct ExtensionIndexProverKey<G: AffineCurve, Ext: Field> {
+    /// Information about the index.
+    pub(crate) index_info: IndexInfo,
+
+    /// The `A` matrix of the R1CS instance, defined over the extension field.
+    pub(crate) a: CsrMatrix<Ext>,
+
+    /// The `B` matrix of the R1CS instance, defined over the extension field.
+    pub(crate) b: CsrMatrix<Ext>,
+
+    /// The `C` matrix of the R1CS instance, defined over the extension field.
+    pub(crate) c: CsrMatrix<Ext>,
+
+    /// Group elements required by the Pedersen commitment. Each extension field element occupies
+    /// `Ext::extension_degree()` generators.
+    pub(crate) ck: CommitterKey<G>,
+}
+
+/// Index verifier key for [`ExtensionR1CSNark`].
+pub type ExtensionIndexVerifierKey<G, Ext> = ExtensionIndexProverKey<G, Ext>;
+
+/// The sigma protocol's prover response for [`ExtensionR1CSNark`].
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ExtensionSecondRoundMessage<F: Field, Ext: Field> {
+    /// The R1CS witness over the extension field with randomness applied if zero-knowledge is
+    /// needed.
+    pub(crate) blinded_witness: Vec<Ext>,
+
+    /// The randomness used for the response.
+    pub(crate) randomness: Option<SecondRoundMessageRandomness<F>>,
+}
+
+/// The proof for [`ExtensionR1CSNark`].
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ExtensionProof<G: AffineCurve, Ext: Field> {
+    /// The sigma protocol's prove commitment.
+    pub first_msg: FirstRoundMessage<G>,
+
+    /// The sigma protocol's prove response.
+    pub second_msg: ExtensionSecondRoundMessage<G::ScalarField, Ext>,
+}
+
+/// A variant of [`R1CSNark`] for R1CS instances whose witnesses live in an extension field `Ext`
+/// of the scalar field of `G`.
+///
+/// Vectors over `Ext` are committed to by re-encoding every element as its
+/// `Ext::extension_degree()` coordinates over the scalar field. The encoding is linear over the
+/// scalar field, so the Fiat-Shamir challenge is still sampled from the scalar field and the
+/// verifier's checks carry over unchanged from [`R1CSNark`].
+pub struct ExtensionR1CSNark<G, Ext, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    Ext: Field<BasePrimeField = G::ScalarField> + ToConstraintField<G::ScalarField>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    _affine: PhantomData<G>,
+    _extension: PhantomData<Ext>,
+    _sponge: PhantomData<S>,
+}
+
+impl<G, Ext, S> ExtensionR1CSNark<G, Ext, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    Ext: Field<BasePrimeField = G::ScalarField> + ToConstraintField<G::ScalarField>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    // Re-encodes a vector over `Ext` as a vector over the scalar field.
+    fn encode(elems: &[Ext]) -> Vec<G::ScalarField> {
+        elems
+            .iter()
+            .flat_map(|elem| elem.to_field_elements().unwrap())
+            .collect()
+    }
+
+    // Embeds a scalar field element into `Ext`.
+    fn lift(elem: G::ScalarField) -> Ext {
+        let mut coeffs = vec![G::ScalarField::zero(); Ext::extension_degree() as usize];
+        coeffs[0] = elem;
+        Ext::from_base_prime_field_elems(&coeffs).unwrap()
+    }
+
+    fn commit(ck: &CommitterKey<G>, elems: &[Ext], blinder: Option<G::ScalarField>) -> G {
+        PedersenCommitment::commit(ck, &Self::encode(elems), blinder)
+    }
+
+    /// Performs a setup for R1CS over an extension field. This function does not currently do
+    /// anything meaningful.
+    pub fn setup() -> PublicParameters {}
+
+    /// Outputs a specialized prover and verifier key for some R1CS instance over `Ext`.
+    pub fn index<C: ConstraintSynthesizer<Ext>>(
+        _pp: &PublicParameters,
+        r1cs_instance: C,
+    ) -> R1CSResult<(
+        ExtensionIndexProverKey<G, Ext>,
+        ExtensionIndexVerifierKey<G, Ext>,
+    )> {
+        let constraint_time = start_timer!(|| "Generating constraints");
+
+        let ics = ConstraintSystem::new_ref();
+        ics.set_optimization_goal(OptimizationGoal::Constraints);
+        ics.set_mode(SynthesisMode::Setup);
+        r1cs_instance.generate_constraints(ics.clone())?;
+
+        end_timer!(constraint_time);
+
+        let matrix_processing_time = start_timer!(|| "Processing matrices");
+        ics.finalize();
+
+        let matrices = ics.to_matrices().expect("should not be `None`");
+        let (a, b, c) = (matrices.a, matrices.b, matrices.c);
+        let (num_input_variables, num_witness_variables, num_constraints) = (
+            ics.num_instance_variables(),
+            ics.num_witness_variables(),
+            ics.num_constraints(),
+        );
+
+        end_timer!(matrix_processing_time);
+
+        let (a, b, c) = (CsrMatrix::from(a), CsrMatrix::from(b), CsrMatrix::from(c));
+        let matrices_hash = hash_matrices(PROTOCOL_NAME, &a, &b, &c);
+
+        let num_committed_elems = num_constraints * Ext::extension_degree() as usize;
+        let pp = PedersenCommitment::setup(num_committed_elems);
+        let ck = PedersenCommitment::trim(&pp, num_committed_elems);
+        let index_info = IndexInfo {
+            num_variables: num_input_variables + num_witness_variables,
+            num_constraints,
+            num_instance_variables: num_input_variables,
+            matrices_hash,
+            hash_public_input: false,
+            protocol_version: CURRENT_PROTOCOL_VERSION,
+            circuit_version: 0,
+            num_pruned_constraints: 0,
+            num_circuit_constraints: 0,
+            is_padded: false,
+            num_pruned_entries: 0,
+            matrix_symmetries: MatrixSymmetries::default(),
+        };
+        let ipk = ExtensionIndexProverKey {
+            index_info,
+            a,
+            b,
+            c,
+            ck,
+        };
+        let ivk = ipk.clone();
+        Ok((ipk, ivk))
+    }
+
+    /// Proves that some R1CS relation over `Ext` holds.
+    pub fn prove<C: ConstraintSynthesizer<Ext>>(
+        ipk: &ExtensionIndexProverKey<G, Ext>,
+        r1cs: C,
+        make_zk: bool,
+        sponge: Option<S>,
+        mut rng: Option<&mut dyn RngCore>,
+    ) -> R1CSResult<ExtensionProof<G, Ext>> {
+        let init_time = start_timer!(|| "ExtensionNARK::Prover");
+
+        let constraint_time = start_timer!(|| "Generating constraints and witnesses");
+        let pcs = ConstraintSystem::new_ref();
+        pcs.set_optimization_goal(OptimizationGoal::Constraints);
+        pcs.set_mode(SynthesisMode::Prove {
+            construct_matrices: false,
+        });
+        r1cs.generate_constraints(pcs.clone())?;
+        end_timer!(constraint_time);
+
+        pcs.finalize();
+        let (input, witness, num_constraints) = {
+            let pcs = pcs.borrow().unwrap();
+            (
+                pcs.instance_assignment.as_slice().to_vec(),
+                pcs.witness_assignment.as_slice().to_vec(),
+                pcs.num_constraints,
+            )
+        };
+
+        let num_input_variables = input.len();
+        let num_witness_variables = witness.len();
+
+        assert_eq!(
+            ipk.index_info.num_variables,
+            num_input_variables + num_witness_variables
+        );
+        assert_eq!(ipk.index_info.num_constraints, num_constraints);
+
+        let r = if make_zk {
+            let rng = rng.as_mut().unwrap();
+            Some(
+                (0..num_witness_variables)
+                    .map(|_| Ext::rand(rng))
+                    .collect::<Vec<_>>(),
+            )
+        } else {
+            None
+        };
+
+        let eval_z_m_time = start_timer!(|| "Evaluating z_M");
+        let z_a = matrix_vec_mul(&ipk.a, &input, &witness);
+        let z_b = matrix_vec_mul(&ipk.b, &input, &witness);
+        let z_c = matrix_vec_mul(&ipk.c, &input, &witness);
+        end_timer!(eval_z_m_time);
+
+        let (mut a_blinder, mut b_blinder, mut c_blinder) = (None, None, None);
+        if make_zk {
+            let rng = rng.as_mut().unwrap();
+            a_blinder = Some(G::ScalarField::rand(rng));
+            b_blinder = Some(G::ScalarField::rand(rng));
+            c_blinder = Some(G::ScalarField::rand(rng));
+        }
+
+        let commit_time = start_timer!(|| "Committing to z_A, z_B, and z_C");
+        let comm_a = Self::commit(&ipk.ck, &z_a, a_blinder);
+        let comm_b = Self::commit(&ipk.ck, &z_b, b_blinder);
+        let comm_c = Self::commit(&ipk.ck, &z_c, c_blinder);
+        end_timer!(commit_time);
+
+        let (mut r_a_blinder, mut r_b_blinder, mut r_c_blinder) = (None, None, None);
+        let (mut blinder_1, mut blinder_2) = (None, None);
+        let first_round_randomness = if make_zk {
+            let rng = rng.as_mut().unwrap();
+            let r_ref = r.as_ref().unwrap();
+            let zeros = vec![Ext::zero(); num_input_variables];
+
+            let eval_r_m_time = start_timer!(|| "Evaluating r_M");
+            let r_a = matrix_vec_mul(&ipk.a, &zeros, r_ref);
+            let r_b = matrix_vec_mul(&ipk.b, &zeros, r_ref);
+            let r_c = matrix_vec_mul(&ipk.c, &zeros, r_ref);
+            end_timer!(eval_r_m_time);
+
+            r_a_blinder = Some(G::ScalarField::rand(rng));
+            r_b_blinder = Some(G::ScalarField::rand(rng));
+            r_c_blinder = Some(G::ScalarField::rand(rng));
+
+            let commit_time = start_timer!(|| "Committing to r_A, r_B, r_C");
+            let comm_r_a = Self::commit(&ipk.ck, &r_a, r_a_blinder);
+            let comm_r_b = Self::commit(&ipk.ck, &r_b, r_b_blinder);
+            let comm_r_c = Self::commit(&ipk.ck, &r_c, r_c_blinder);
+            end_timer!(commit_time);
+
+            // Commit to z_a ○ r_b + z_b ○ r_a, computed over `Ext`.
+            let cross_product: Vec<_> = cfg_iter!(z_a)
+                .zip(&r_b)
+                .zip(cfg_iter!(z_b).zip(&r_a))
+                .map(|((z_a, r_b), (z_b, r_a))| *z_a * r_b + *z_b * r_a)
+                .collect();
+            blinder_1 = Some(G::ScalarField::rand(rng));
+            let comm_1 = Self::commit(&ipk.ck, &cross_product, blinder_1);
+
+            // Commit to r_a ○ r_b, computed over `Ext`.
+            let r_a_r_b_product: Vec<_> = cfg_iter!(r_a)
+                .zip(&r_b)
+                .map(|(r_a, r_b)| *r_a * r_b)
+                .collect();
+            blinder_2 = Some(G::ScalarField::rand(rng));
+            let comm_2 = Self::commit(&ipk.ck, &r_a_r_b_product, blinder_2);
+
+            Some(FirstRoundMessageRandomness {
+                comm_r_a,
+                comm_r_b,
+                comm_r_c,
+                comm_1,
+                comm_2,
+            })
+        } else {
+            None
+        };
+
+        let first_msg = FirstRoundMessage {
+            comm_a,
+            comm_b,
+            comm_c,
+            randomness: first_round_randomness,
+        };
+
+        let gamma = R1CSNark::<G, S>::compute_challenge(
+            &ipk.index_info.matrices_hash,
+            &Self::encode(&input),
+            &first_msg,
+            sponge.unwrap_or_else(|| S::new()),
+        );
+
+        let mut blinded_witness = witness;
+        let second_round_randomness = if make_zk {
+            let gamma_ext = Self::lift(gamma);
+            ark_std::cfg_iter_mut!(blinded_witness)
+                .zip(r.unwrap())
+                .for_each(|(s, r)| *s += gamma_ext * r);
+
+            let sigma_a = a_blinder.unwrap() + gamma * r_a_blinder.unwrap();
+            let sigma_b = b_blinder.unwrap() + gamma * r_b_blinder.unwrap();
+            let sigma_c = c_blinder.unwrap() + gamma * r_c_blinder.unwrap();
+            let sigma_o = c_blinder.unwrap()
+                + gamma * blinder_1.unwrap()
+                + gamma.square() * blinder_2.unwrap();
+
+            Some(SecondRoundMessageRandomness {
+                sigma_a,
+                sigma_b,
+                sigma_c,
+                sigma_o,
+            })
+        } else {
+            None
+        };
+
+        let proof = ExtensionProof {
+            first_msg,
+            second_msg: ExtensionSecondRoundMessage {
+                blinded_witness,
+                randomness: second_round_randomness,
+            },
+        };
+
+        end_timer!(init_time);
+        Ok(proof)
+    }
+
+    /// Verifies that some R1CS relation over `Ext` holds.
+    pub fn verify(
+        ivk: &ExtensionIndexVerifierKey<G, Ext>,
+        input: &[Ext],
+        proof: &ExtensionProof<G, Ext>,
+        sponge: Option<S>,
+    ) -> bool {
+        let init_time = start_timer!(|| "ExtensionNARK::Verifier");
+        if proof.first_msg.randomness.is_some() != proof.second_msg.randomness.is_some() {
+            return false;
+        }
+
+        let gamma = R1CSNark::<G, S>::compute_challenge(
+            &ivk.index_info.matrices_hash,
+            &Self::encode(input),
+            &proof.first_msg,
+            sponge.unwrap_or_else(|| S::new()),
+        );
+
+        let blinded_witness = &proof.second_msg.blinded_witness;
+        let a_times_blinded_witness = matrix_vec_mul(&ivk.a, input, blinded_witness);
+        let b_times_blinded_witness = matrix_vec_mul(&ivk.b, input, blinded_witness);
+        let c_times_blinded_witness = matrix_vec_mul(&ivk.c, input, blinded_witness);
+
+        let mut comm_a = proof.first_msg.comm_a.into_projective();
+        let mut comm_b = proof.first_msg.comm_b.into_projective();
+        let mut comm_c = proof.first_msg.comm_c.into_projective();
+        if let Some(first_msg_randomness) = proof.first_msg.randomness.as_ref() {
+            comm_a += first_msg_randomness.comm_r_a.mul(gamma);
+            comm_b += first_msg_randomness.comm_r_b.mul(gamma);
+            comm_c += first_msg_randomness.comm_r_c.mul(gamma);
+        }
+
+        let randomness = proof.second_msg.randomness.as_ref();
+        let a_equal = comm_a
+            == Self::commit(
+                &ivk.ck,
+                &a_times_blinded_witness,
+                randomness.map(|r| r.sigma_a),
+            )
+            .into_projective();
+        let b_equal = comm_b
+            == Self::commit(
+                &ivk.ck,
+                &b_times_blinded_witness,
+                randomness.map(|r| r.sigma_b),
+            )
+            .into_projective();
+        let c_equal = comm_c
+            == Self::commit(
+                &ivk.ck,
+                &c_times_blinded_witness,
+                randomness.map(|r| r.sigma_c),
+            )
+            .into_projective();
+
+        let had_prod: Vec<_> = cfg_into_iter!(a_times_blinded_witness)
+            .zip(b_times_blinded_witness)
+            .map(|(a, b)| a * b)
+            .collect();
+        let reconstructed_had_prod_comm =
+            Self::commit(&ivk.ck, &had_prod, randomness.map(|r| r.sigma_o));
+
+        let mut had_prod_comm = proof.first_msg.comm_c.into_projective();
+        if let Some(first_msg_randomness) = proof.first_msg.randomness.as_ref() {
+            had_prod_comm += first_msg_randomness.comm_1.mul(gamma);
+            had_prod_comm += first_msg_randomness.comm_2.mul(gamma.square());
+        }
+        let had_prod_equal = had_prod_comm == reconstructed_had_prod_comm.into_projective();
+
+        end_timer!(init_time);
+        a_equal & b_equal & c_equal & had_prod_equal
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::{field_new, Fp2, Fp2Parameters, One};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_relations::{
+        lc,
+        r1cs::{ConstraintSystemRef, SynthesisError},
+    };
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    pub struct Fr2Parameters;
+
+    impl Fp2Parameters for Fr2Parameters {
+        type Fp = Fr;
+
+        // 5 generates the multiplicative group of `Fr`, so it is a quadratic non-residue.
+        const NONRESIDUE: Fr = field_new!(Fr, "5");
+
+        // The norm of `u` is `-5`, which is a non-residue because `-1` is a residue in `Fr`.
+        const QUADRATIC_NONRESIDUE: (Fr, Fr) = (field_new!(Fr, "0"), field_new!(Fr, "1"));
+
+        const FROBENIUS_COEFF_FP2_C1: &'static [Fr] = &[field_new!(Fr, "1"), field_new!(Fr, "-1")];
+    }
+
+    type Fr2 = Fp2<Fr2Parameters>;
+    type Nark = ExtensionR1CSNark<Affine, Fr2, PoseidonSponge<Fq>>;
+
+    #[derive(Copy, Clone)]
+    struct ExtensionCircuit<F: Field> {
+        a: Option<F>,
+        b: Option<F>,
+        num_constraints: usize,
+    }
+
+    impl<F: Field> ConstraintSynthesizer<F> for ExtensionCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+            let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+            let c = cs.new_input_variable(|| {
+                let a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+                let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+
+                Ok(a * b)
+            })?;
+
+            for _ in 0..self.num_constraints {
+                cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_extension_field_circuit() {
+        let rng = &mut ark_std::test_rng();
+        let c = ExtensionCircuit {
+            a: Some(Fr2::rand(rng)),
+            b: Some(Fr2::rand(rng)),
+            num_constraints: 10,
+        };
+
+        let input = vec![Fr2::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+
+        for make_zk in [false, true].iter() {
+            let proof = Nark::prove(
+                &ipk,
+                c,
+                *make_zk,
+                Some(PoseidonSponge::<Fq>::new()),
+                Some(rng),
+            )
+            .unwrap();
+
+            assert!(Nark::verify(
+                &ivk,
+                &input,
+                &proof,
+                Some(PoseidonSponge::<Fq>::new()),
+            ));
+
+            let wrong_input = vec![Fr2::one(), c.a.unwrap()];
+            assert!(!Nark::verify(
+                &ivk,
+                &wrong_input,
+                &proof,
+                Some(PoseidonSponge::<Fq>::new()),
+            ));
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use super::{is_satisfied, FullAssignment, IndexVerifierKey, Proof, R1CSNark};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::Field;
+use ark_serialize::CanonicalSerialize;
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::vec::Vec;
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Checks that `assignment` satisfies the R1CS instance of `ivk`.
+    pub fn validate_assignment(
+        ivk: &IndexVerifierKey<G>,
+        assignment: &FullAssignment<G::ScalarField>,
+    ) -> bool {
+        let index_info = &ivk.index_info;
+        if assignment.input.len() != index_info.num_instance_variables
+            || assignment.input.len() + assignment.witness.len() != index_info.num_variables
+        {
+            return false;
+        }
+
+        is_satisfied(
+            &ivk.a,
+            &ivk.b,
+            &ivk.c,
+            &assignment.input,
+            &assignment.witness,
+        )
+    }
+
+    /// Extracts a satisfying assignment from two accepting transcripts that share the same first
+    /// message but were verified with different sponge states, and therefore have different
+    /// challenges. This rewinding extractor exists for security analysis and testing only.
+    ///
+    /// Returns `None` if either transcript is rejected, the first messages differ, the
+    /// challenges coincide, or the extracted assignment does not satisfy the R1CS instance.
+    pub fn extract(
+        ivk: &IndexVerifierKey<G>,
+        input: &[G::ScalarField],
+        transcripts: [(&Proof<G>, S); 2],
+    ) -> Option<FullAssignment<G::ScalarField>> {
+        let [(proof_1, sponge_1), (proof_2, sponge_2)] = transcripts;
+
+        let mut first_msg_1 = Vec::new();
+        let mut first_msg_2 = Vec::new();
+        proof_1.first_msg.serialize(&mut first_msg_1).unwrap();
+        proof_2.first_msg.serialize(&mut first_msg_2).unwrap();
+        if first_msg_1 != first_msg_2 {
+            return None;
+        }
+
+        let matrices_hash = &ivk.index_info.matrices_hash;
+        let gamma_1 =
+            Self::compute_challenge(matrices_hash, input, &proof_1.first_msg, sponge_1.clone());
+        let gamma_2 =
+            Self::compute_challenge(matrices_hash, input, &proof_2.first_msg, sponge_2.clone());
+        if gamma_1 == gamma_2
+            || !Self::verify(ivk, input, proof_1, Some(sponge_1)).unwrap_or(false)
+            || !Self::verify(ivk, input, proof_2, Some(sponge_2)).unwrap_or(false)
+        {
+            return None;
+        }
+
+        let blinded_witness_1 = &proof_1.second_msg.blinded_witness;
+        let blinded_witness_2 = &proof_2.second_msg.blinded_witness;
+        if blinded_witness_1.len() != blinded_witness_2.len() {
+            return None;
+        }
+
+        // Each blinded witness is `w + gamma_i * r`, so `w` is recovered by eliminating `r`.
+        let denominator_inv = (gamma_2 - gamma_1).inverse()?;
+        let witness = blinded_witness_1
+            .iter()
+            .zip(blinded_witness_2)
+            .map(|(s_1, s_2)| (gamma_2 * s_1 - gamma_1 * s_2) * denominator_inv)
+            .collect();
+
+        let assignment = FullAssignment {
+            input: input.to_vec(),
+            witness,
+        };
+
+        if Self::validate_assignment(ivk, &assignment) {
+            Some(assignment)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_extract() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+
+        let sponge_1 = PoseidonSponge::<Fq>::new();
+        let mut sponge_2 = PoseidonSponge::<Fq>::new();
+        sponge_2.absorb(&Fq::one());
+
+        // Rewind the prover by running it twice with the same randomness.
+        let proof_1 = Nark::prove(
+            &ipk,
+            c,
+            true,
+            Some(sponge_1.clone()),
+            Some(&mut ark_std::test_rng()),
+        )
+        .unwrap();
+        let proof_2 = Nark::prove(
+            &ipk,
+            c,
+            true,
+            Some(sponge_2.clone()),
+            Some(&mut ark_std::test_rng()),
+        )
+        .unwrap();
+
+        let assignment = Nark::extract(
+            &ivk,
+            &input,
+            [(&proof_1, sponge_1.clone()), (&proof_2, sponge_2)],
+        )
+        .unwrap();
+        assert_eq!(assignment.input, input);
+        assert_eq!(assignment.witness[0], c.a.unwrap());
+        assert_eq!(assignment.witness[1], c.b.unwrap());
+        assert!(Nark::validate_assignment(&ivk, &assignment));
+
+        // Both transcripts have the same challenge, so nothing can be extracted.
+        assert!(Nark::extract(
+            &ivk,
+            &input,
+            [(&proof_1, sponge_1.clone()), (&proof_1, sponge_1)],
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,84 @@
+use ark_ff::Field;
+use ark_relations::r1cs::Matrix;
+
+/// The numbers of multiplication and addition gates of an R1CS instance, as counted by
+/// [`count_gates`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GateCount {
+    /// The number of constraints that multiply two linear combinations of variables.
+    pub mul_gates: usize,
+
+    /// The number of constraints that only scale a linear combination of variables by a
+    /// constant, which amounts to a linear relation between the variables.
+    pub add_gates: usize,
+
+    /// The number of constraints.
+    pub total_constraints: usize,
+}
+
+/// Classifies each constraint of an R1CS instance as an addition gate, if its row of `b` is a
+/// single non-zero multiple of the constant `1` at column `0`, or as a multiplication gate
+/// otherwise.
+pub fn count_gates<F: Field>(a: &Matrix<F>, b: &Matrix<F>, c: &Matrix<F>) -> GateCount {
+    assert!(
+        a.len() == b.len() && b.len() == c.len(),
+        "the matrices must have the same number of rows"
+    );
+
+    let add_gates = b
+        .iter()
+        .filter(|row| matches!(row.as_slice(), [(coeff, 0)] if !coeff.is_zero()))
+        .count();
+    GateCount {
+        mul_gates: a.len() - add_gates,
+        add_gates,
+        total_constraints: a.len(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_pallas::Fr;
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSystem, Variable};
+
+    #[test]
+    fn test_count_gates() {
+        let rng = &mut ark_std::test_rng();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        for _ in 0..10 {
+            let (x, y) = (Fr::rand(rng), Fr::rand(rng));
+            let x_var = cs.new_witness_variable(|| Ok(x)).unwrap();
+            let y_var = cs.new_witness_variable(|| Ok(y)).unwrap();
+            let z_var = cs.new_witness_variable(|| Ok(x * y)).unwrap();
+            cs.enforce_constraint(lc!() + x_var, lc!() + y_var, lc!() + z_var)
+                .unwrap();
+        }
+        for _ in 0..5 {
+            let (x, y) = (Fr::rand(rng), Fr::rand(rng));
+            let x_var = cs.new_witness_variable(|| Ok(x)).unwrap();
+            let y_var = cs.new_witness_variable(|| Ok(y)).unwrap();
+            let sum_var = cs.new_input_variable(|| Ok(x + y)).unwrap();
+            cs.enforce_constraint(
+                lc!() + x_var + y_var,
+                lc!() + (Fr::from(2u64), Variable::One),
+                lc!() + (Fr::from(2u64), sum_var),
+            )
+            .unwrap();
+        }
+        assert!(cs.is_satisfied().unwrap());
+
+        cs.finalize();
+        let matrices = cs.to_matrices().unwrap();
+        assert_eq!(
+            count_gates(&matrices.a, &matrices.b, &matrices.c),
+            GateCount {
+                mul_gates: 10,
+                add_gates: 5,
+                total_constraints: 15,
+            }
+        );
+    }
+}
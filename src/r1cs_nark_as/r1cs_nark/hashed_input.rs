@@ -0,0 +1,307 @@
+use super::{IndexProverKey, IndexVerifierKey, Proof, PublicParameters, R1CSNark, R1CSResult};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::{One, PrimeField, ToConstraintField};
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::{AllocatedFp, FpVar};
+use ark_r1cs_std::fields::FieldVar;
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, LinearCombination,
+    SynthesisError, SynthesisMode, Variable,
+};
+use ark_sponge::constraints::CryptographicSpongeVar;
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::marker::PhantomData;
+use ark_std::rand::RngCore;
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+pub(crate) const INPUT_DIGEST_DOMAIN: &[u8] = b"R1CS-NARK-2020-INPUT-DIGEST";
+
+/// A digest of the public input of an R1CS instance. It is the only input the verifier receives
+/// for indices created with [`R1CSNark::index_with_hashed_input`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PublicInputDigest<F: PrimeField>(pub F);
+
+impl<F: PrimeField + Absorbable<F>> PublicInputDigest<F> {
+    /// Computes the digest of a full public input, including its leading `1`, with the sponge
+    /// `HS` over the field of the R1CS instance.
+    pub fn compute<HS: CryptographicSponge<F>>(input: &[F]) -> Self {
+        let mut encoded: Vec<F> = INPUT_DIGEST_DOMAIN.to_field_elements().unwrap();
+        encoded.extend_from_slice(input);
+
+        let mut sponge = HS::new();
+        sponge.absorb(&encoded);
+        Self(sponge.squeeze_field_elements(1).pop().unwrap())
+    }
+}
+
+/// An R1CS instance whose public input is the [`PublicInputDigest`] of the public input of
+/// `circuit`. The public input of `circuit` is allocated as witness variables, and the digest is
+/// recomputed from them with the sponge gadget `HSV`.
+pub struct HashedInputCircuit<F, C, HS, HSV>
+where
+    F: PrimeField,
+    C: ConstraintSynthesizer<F>,
+    HS: CryptographicSponge<F>,
+    HSV: CryptographicSpongeVar<F, HS>,
+{
+    circuit: C,
+    _field: PhantomData<F>,
+    _sponge: PhantomData<HS>,
+    _sponge_var: PhantomData<HSV>,
+}
+
+impl<F, C, HS, HSV> HashedInputCircuit<F, C, HS, HSV>
+where
+    F: PrimeField,
+    C: ConstraintSynthesizer<F>,
+    HS: CryptographicSponge<F>,
+    HSV: CryptographicSpongeVar<F, HS>,
+{
+    /// Wraps `circuit`.
+    pub fn new(circuit: C) -> Self {
+        Self {
+            circuit,
+            _field: PhantomData,
+            _sponge: PhantomData,
+            _sponge_var: PhantomData,
+        }
+    }
+}
+
+impl<F, C, HS, HSV> ConstraintSynthesizer<F> for HashedInputCircuit<F, C, HS, HSV>
+where
+    F: PrimeField + Absorbable<F>,
+    C: ConstraintSynthesizer<F>,
+    HS: CryptographicSponge<F>,
+    HSV: CryptographicSpongeVar<F, HS>,
+{
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        // The wrapped circuit is synthesized on its own, so that its public input variables can
+        // be reallocated as witness variables.
+        let inner_cs = ConstraintSystem::new_ref();
+        inner_cs.set_mode(if cs.is_in_setup_mode() {
+            SynthesisMode::Setup
+        } else {
+            SynthesisMode::Prove {
+                construct_matrices: true,
+            }
+        });
+        self.circuit.generate_constraints(inner_cs.clone())?;
+        inner_cs.finalize();
+        let matrices = inner_cs
+            .to_matrices()
+            .ok_or(SynthesisError::AssignmentMissing)?;
+        let inner_cs = inner_cs
+            .into_inner()
+            .ok_or(SynthesisError::AssignmentMissing)?;
+
+        let input = &inner_cs.instance_assignment;
+        let digest =
+            FpVar::new_input(cs.clone(), || Ok(PublicInputDigest::compute::<HS>(input).0))?;
+
+        let mut variables = vec![Variable::One];
+        let mut input_vars = vec![FpVar::one()];
+        for i in 1..inner_cs.num_instance_variables {
+            let value = input.get(i).copied();
+            let variable =
+                cs.new_witness_variable(|| value.ok_or(SynthesisError::AssignmentMissing))?;
+            variables.push(variable);
+            input_vars.push(FpVar::Var(AllocatedFp::new(value, variable, cs.clone())));
+        }
+        for i in 0..inner_cs.num_witness_variables {
+            let value = inner_cs.witness_assignment.get(i).copied();
+            variables
+                .push(cs.new_witness_variable(|| value.ok_or(SynthesisError::AssignmentMissing))?);
+        }
+
+        let to_lc = |row: &Vec<(F, usize)>| -> LinearCombination<F> {
+            LinearCombination(
+                row.iter()
+                    .map(|(coeff, index)| (*coeff, variables[*index]))
+                    .collect(),
+            )
+        };
+        for ((a, b), c) in matrices.a.iter().zip(&matrices.b).zip(&matrices.c) {
+            cs.enforce_constraint(to_lc(a), to_lc(b), to_lc(c))?;
+        }
+
+        let domain: Vec<F> = INPUT_DIGEST_DOMAIN.to_field_elements().unwrap();
+        let mut encoded: Vec<FpVar<F>> = domain.into_iter().map(FpVar::constant).collect();
+        encoded.extend(input_vars);
+
+        let mut sponge = HSV::new(cs);
+        sponge.absorb(&encoded)?;
+        let computed_digest = sponge.squeeze_field_elements(1)?.pop().unwrap();
+        computed_digest.enforce_equal(&digest)
+    }
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Outputs a specialized prover and verifier key for some R1CS instance whose verifier only
+    /// receives a [`PublicInputDigest`] of the public input, computed with `HS`. The keys are
+    /// those of the [`HashedInputCircuit`] of `r1cs_instance`.
+    pub fn index_with_hashed_input<C, HS, HSV>(
+        pp: &PublicParameters,
+        r1cs_instance: C,
+    ) -> R1CSResult<(IndexProverKey<G>, IndexVerifierKey<G>)>
+    where
+        C: ConstraintSynthesizer<G::ScalarField>,
+        G::ScalarField: Absorbable<G::ScalarField>,
+        HS: CryptographicSponge<G::ScalarField>,
+        HSV: CryptographicSpongeVar<G::ScalarField, HS>,
+    {
+        let circuit = HashedInputCircuit::<_, _, HS, HSV>::new(r1cs_instance);
+        let (mut ipk, mut ivk) = Self::index(pp, circuit)?;
+        ipk.index_info.hash_public_input = true;
+        ivk.index_info.hash_public_input = true;
+        Ok((ipk, ivk))
+    }
+
+    /// Proves that some R1CS relation holds for an index created with
+    /// [`R1CSNark::index_with_hashed_input`], and outputs the proof with the digest of the public
+    /// input. The proof does not contain the public input.
+    pub fn prove_with_hashed_input<C, HS, HSV>(
+        ipk: &IndexProverKey<G>,
+        r1cs: C,
+        make_zk: bool,
+        sponge: Option<S>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> R1CSResult<(Proof<G>, PublicInputDigest<G::ScalarField>)>
+    where
+        C: ConstraintSynthesizer<G::ScalarField>,
+        G::ScalarField: Absorbable<G::ScalarField>,
+        HS: CryptographicSponge<G::ScalarField>,
+        HSV: CryptographicSpongeVar<G::ScalarField, HS>,
+    {
+        if !ipk.index_info.hash_public_input {
+            return Err(SynthesisError::MalformedVerifyingKey.into());
+        }
+
+        let circuit = HashedInputCircuit::<_, _, HS, HSV>::new(r1cs);
+        let (input, witness) = Self::synthesize(ipk, circuit)?;
+        let digest = PublicInputDigest(input[1]);
+        let proof = Self::prove_with_assignment(ipk, input, witness, make_zk, sponge, rng);
+
+        Ok((proof, digest))
+    }
+
+    /// Verifies that some R1CS relation holds, given only the [`PublicInputDigest`] of the
+    /// public input. The digest is absorbed into the sponge as the public input of the
+    /// [`HashedInputCircuit`].
+    pub fn verify_with_hashed_input(
+        ivk: &IndexVerifierKey<G>,
+        digest: &PublicInputDigest<G::ScalarField>,
+        proof: &Proof<G>,
+        sponge: Option<S>,
+    ) -> bool {
+        if !ivk.index_info.hash_public_input {
+            return false;
+        }
+
+        let input = [G::ScalarField::one(), digest.0];
+        Self::verify(ivk, &input, proof, sponge).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_relations::lc;
+    use ark_sponge::poseidon::constraints::PoseidonSpongeVar;
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+    type HS = PoseidonSponge<Fr>;
+    type HSV = PoseidonSpongeVar<Fr>;
+
+    #[derive(Clone)]
+    struct ManyInputsCircuit<F: PrimeField> {
+        inputs: Vec<F>,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for ManyInputsCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            for inp in self.inputs {
+                let x = cs.new_input_variable(|| Ok(inp))?;
+                let w = cs.new_witness_variable(|| Ok(inp))?;
+                cs.enforce_constraint(lc!() + x, lc!() + Variable::One, lc!() + w)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_hashed_input() {
+        let rng = &mut ark_std::test_rng();
+        let circuit = ManyInputsCircuit {
+            inputs: (0..1000).map(|_| Fr::rand(rng)).collect(),
+        };
+
+        let mut full_input = vec![Fr::one()];
+        full_input.extend_from_slice(&circuit.inputs);
+        let digest = PublicInputDigest::compute::<HS>(&full_input);
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index_with_hashed_input::<_, HS, HSV>(&pp, circuit.clone()).unwrap();
+        assert_eq!(ivk.index_info.num_instance_variables, 2);
+
+        for &make_zk in &[false, true] {
+            let (proof, proof_digest) = Nark::prove_with_hashed_input::<_, HS, HSV>(
+                &ipk,
+                circuit.clone(),
+                make_zk,
+                None,
+                Some(rng),
+            )
+            .unwrap();
+            assert_eq!(proof_digest, digest);
+            assert!(Nark::verify_with_hashed_input(&ivk, &digest, &proof, None));
+            assert!(!Nark::verify_with_hashed_input(
+                &ivk,
+                &PublicInputDigest(digest.0 + Fr::one()),
+                &proof,
+                None
+            ));
+        }
+
+        // A proof for a different public input does not verify against the digest of the
+        // original one.
+        let mut other_circuit = circuit.clone();
+        other_circuit.inputs[0] += Fr::one();
+        let (other_proof, _) =
+            Nark::prove_with_hashed_input::<_, HS, HSV>(&ipk, other_circuit, false, None, None)
+                .unwrap();
+        assert!(!Nark::verify_with_hashed_input(
+            &ivk,
+            &digest,
+            &other_proof,
+            None
+        ));
+
+        // The verifier key of the plain index of the circuit does not accept hashed inputs.
+        let (plain_ipk, plain_ivk) = Nark::index(&pp, circuit.clone()).unwrap();
+        let plain_proof = Nark::prove(&plain_ipk, circuit.clone(), false, None, None).unwrap();
+        assert!(!Nark::verify_with_hashed_input(
+            &plain_ivk,
+            &digest,
+            &plain_proof,
+            None
+        ));
+        assert!(Nark::prove_with_hashed_input::<_, HS, HSV>(
+            &plain_ipk, circuit, false, None, None
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use super::{CsrMatrix, IndexProverKey, IndexVerifierKey, Proof, R1CSNark};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::Field;
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::cfg_into_iter;
+use ark_std::vec::Vec;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+// A matrix whose entries are split by whether they multiply the input or the witness, so that
+// multiplying it by `input || witness` needs no per-entry branch on the column.
+#[derive(Clone)]
+struct SplitMatrix<F: Field> {
+    // The offsets of the rows in `input_entries` and `witness_entries`.
+    input_offsets: Vec<usize>,
+    witness_offsets: Vec<usize>,
+
+    // The entries of the input columns, and of the witness columns indexed relative to the first
+    // witness column.
+    input_entries: Vec<(F, usize)>,
+    witness_entries: Vec<(F, usize)>,
+}
+
+impl<F: Field> SplitMatrix<F> {
+    fn new(matrix: &CsrMatrix<F>, num_instance_variables: usize) -> Self {
+        let mut input_offsets = Vec::with_capacity(matrix.len() + 1);
+        let mut witness_offsets = Vec::with_capacity(matrix.len() + 1);
+        let mut input_entries = Vec::new();
+        let mut witness_entries = Vec::new();
+        input_offsets.push(0);
+        witness_offsets.push(0);
+        for row in matrix.rows() {
+            for (coeff, i) in row.iter() {
+                if i < num_instance_variables {
+                    input_entries.push((coeff, i));
+                } else {
+                    witness_entries.push((coeff, i - num_instance_variables));
+                }
+            }
+            input_offsets.push(input_entries.len());
+            witness_offsets.push(witness_entries.len());
+        }
+
+        Self {
+            input_offsets,
+            witness_offsets,
+            input_entries,
+            witness_entries,
+        }
+    }
+
+    // Computes `matrix * (input || witness)`.
+    fn mul(&self, input: &[F], witness: &[F]) -> Vec<F> {
+        let num_rows = self.input_offsets.len() - 1;
+        cfg_into_iter!(0..num_rows)
+            .map(|row| {
+                let input_entries =
+                    &self.input_entries[self.input_offsets[row]..self.input_offsets[row + 1]];
+                let witness_entries =
+                    &self.witness_entries[self.witness_offsets[row]..self.witness_offsets[row + 1]];
+
+                let mut acc = F::zero();
+                for &(ref coeff, i) in input_entries {
+                    acc += &(*coeff * input[i]);
+                }
+                for &(ref coeff, i) in witness_entries {
+                    acc += &(*coeff * witness[i]);
+                }
+                acc
+            })
+            .collect()
+    }
+}
+
+/// Values that [`R1CSNark::verify`] derives from an index verifier key alone, computed once by
+/// [`IndexProverKey::precompute_hints`] and reused by [`R1CSNark::verify_with_hints`] across
+/// many proofs for the same R1CS instance.
+#[derive(Clone)]
+pub struct VerificationHints<G: AffineCurve> {
+    matrices_hash: [u8; 32],
+    a: SplitMatrix<G::ScalarField>,
+    b: SplitMatrix<G::ScalarField>,
+    c: SplitMatrix<G::ScalarField>,
+}
+
+impl<G: AffineCurve> IndexProverKey<G> {
+    /// Precomputes the [`VerificationHints`] for this key. The rows of each matrix are laid out
+    /// contiguously, with the entries that multiply the input stored apart from those that
+    /// multiply the witness.
+    pub fn precompute_hints(&self) -> VerificationHints<G> {
+        let num_instance_variables = self.index_info.num_instance_variables;
+        VerificationHints {
+            matrices_hash: self.index_info.matrices_hash,
+            a: SplitMatrix::new(&self.a, num_instance_variables),
+            b: SplitMatrix::new(&self.b, num_instance_variables),
+            c: SplitMatrix::new(&self.c, num_instance_variables),
+        }
+    }
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Verifies that some R1CS relation holds using `hints` precomputed from `ivk`. This is
+    /// equivalent to [`R1CSNark::verify`], except that a malformed input or proof, or hints for
+    /// another index, are rejected rather than reported as an error.
+    pub fn verify_with_hints(
+        ivk: &IndexVerifierKey<G>,
+        hints: &VerificationHints<G>,
+        input: &[G::ScalarField],
+        proof: &Proof<G>,
+        sponge: Option<S>,
+    ) -> bool {
+        if hints.matrices_hash != ivk.index_info.matrices_hash {
+            return false;
+        }
+
+        Self::verify_with_matrix_vec_muls(ivk, input, proof, sponge, |input, witness| {
+            (
+                hints.a.mul(input, witness),
+                hints.b.mul(input, witness),
+                hints.c.mul(input, witness),
+            )
+        })
+        .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_verify_with_hints() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let hints = ivk.precompute_hints();
+
+        for &make_zk in &[false, true] {
+            let proof = Nark::prove(&ipk, c, make_zk, None, Some(rng)).unwrap();
+            assert!(Nark::verify_with_hints(
+                &ivk,
+                &hints,
+                &r1cs_input,
+                &proof,
+                None
+            ));
+
+            let mut wrong_input = r1cs_input.clone();
+            wrong_input[1] += Fr::one();
+            assert!(!Nark::verify_with_hints(
+                &ivk,
+                &hints,
+                &wrong_input,
+                &proof,
+                None
+            ));
+            assert!(!Nark::verify_with_hints(
+                &ivk,
+                &hints,
+                &r1cs_input[..1],
+                &proof,
+                None
+            ));
+        }
+
+        // Hints for another index are rejected.
+        let other_c = DummyCircuit {
+            num_constraints: 50,
+            ..c
+        };
+        let (_, other_ivk) = Nark::index(&pp, other_c).unwrap();
+        let proof = Nark::prove(&ipk, c, false, None, None).unwrap();
+        assert!(!Nark::verify_with_hints(
+            &ivk,
+            &other_ivk.precompute_hints(),
+            &r1cs_input,
+            &proof,
+            None
+        ));
+    }
+}
@@ -0,0 +1,184 @@
+use super::{
+    inner_prod, CsrMatrix, FirstRoundMessage, IndexProverKey, Proof, R1CSNark, R1CSResult,
+    SecondRoundMessage, VariableAssignment,
+};
+use crate::ConstraintF;
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::Zero;
+use ark_poly_commit::trivial_pc::PedersenCommitment;
+use ark_relations::r1cs::SynthesisError;
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Updates a proof without zero-knowledge for `(old_input, old_witness)` into a proof for
+    /// `(input, new_witness)` under the same index. Only the rows in `changed_constraints` are
+    /// recomputed, and the commitments of `old_proof` are shifted by the change in those rows, so
+    /// the cost grows with `changed_constraints.len()` instead of with the number of constraints.
+    ///
+    /// `changed_constraints` must contain every row of `A`, `B`, or `C` whose product with the
+    /// assignment changes; otherwise the resulting proof does not verify. Returns an error if
+    /// `old_proof` is zero-knowledge, since its witness cannot be recovered from it, or if a row
+    /// is out of range.
+    pub fn reprove_incremental(
+        ipk: &IndexProverKey<G>,
+        old_input: &[G::ScalarField],
+        old_proof: &Proof<G>,
+        input: &[G::ScalarField],
+        new_witness: Vec<G::ScalarField>,
+        changed_constraints: &[usize],
+    ) -> R1CSResult<Proof<G>> {
+        let init_time = start_timer!(|| "NARK::IncrementalProver");
+        if old_proof.first_msg.randomness.is_some() || old_proof.second_msg.randomness.is_some() {
+            return Err(SynthesisError::AssignmentMissing.into());
+        }
+
+        let num_constraints = ipk.index_info.num_constraints;
+        if changed_constraints.iter().any(|&i| i >= num_constraints) {
+            return Err(SynthesisError::Unsatisfiable.into());
+        }
+
+        let old_witness = &old_proof.second_msg.blinded_witness;
+        let first_msg = match changed_constraints.iter().max() {
+            Some(&max_row) => {
+                let update_commitment = |comm: G, matrix: &CsrMatrix<G::ScalarField>| {
+                    let mut delta = vec![G::ScalarField::zero(); max_row + 1];
+                    let new_z = VariableAssignment::new(input, &new_witness);
+                    let old_z = VariableAssignment::new(old_input, old_witness);
+                    for &i in changed_constraints {
+                        delta[i] =
+                            inner_prod(matrix.row(i), &new_z) - inner_prod(matrix.row(i), &old_z);
+                    }
+
+                    let delta_comm = PedersenCommitment::commit(&ipk.ck, &delta, None);
+                    (comm.into_projective() + delta_comm.into_projective()).into_affine()
+                };
+
+                let old_first_msg = &old_proof.first_msg;
+                FirstRoundMessage {
+                    comm_a: update_commitment(old_first_msg.comm_a, &ipk.a),
+                    comm_b: update_commitment(old_first_msg.comm_b, &ipk.b),
+                    comm_c: update_commitment(old_first_msg.comm_c, &ipk.c),
+                    randomness: None,
+                }
+            }
+            None => old_proof.first_msg.clone(),
+        };
+
+        end_timer!(init_time);
+        Ok(Proof {
+            first_msg,
+            second_msg: SecondRoundMessage {
+                blinded_witness: new_witness,
+                randomness: None,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::{PrimeField, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_relations::{
+        lc,
+        r1cs::{ConstraintSynthesizer, ConstraintSystemRef},
+    };
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    // Enforces `x_i * x_i = y_i` for every value `x_i`, with `y_0` as the only public input.
+    #[derive(Clone)]
+    struct SquaresCircuit<F: PrimeField> {
+        values: Vec<F>,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for SquaresCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            for (i, value) in self.values.into_iter().enumerate() {
+                let x = cs.new_witness_variable(|| Ok(value))?;
+                let y = if i == 0 {
+                    cs.new_input_variable(|| Ok(value.square()))?
+                } else {
+                    cs.new_witness_variable(|| Ok(value.square()))?
+                };
+                cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + y)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_reprove_incremental() {
+        let rng = &mut ark_std::test_rng();
+        let circuit = SquaresCircuit {
+            values: (0..100).map(|_| Fr::rand(rng)).collect(),
+        };
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, circuit.clone()).unwrap();
+        let (old_input, old_witness) = Nark::synthesize(&ipk, circuit.clone()).unwrap();
+        let old_proof =
+            Nark::prove_with_assignment(&ipk, old_input.clone(), old_witness, false, None, None);
+
+        let mut new_circuit = circuit.clone();
+        new_circuit.values[0] = Fr::rand(rng);
+        new_circuit.values[42] = Fr::rand(rng);
+        let (input, new_witness) = Nark::synthesize(&ipk, new_circuit).unwrap();
+        assert_ne!(input, old_input);
+
+        let proof = Nark::reprove_incremental(
+            &ipk,
+            &old_input,
+            &old_proof,
+            &input,
+            new_witness.clone(),
+            &[0, 42],
+        )
+        .unwrap();
+        assert!(Nark::verify(&ivk, &input, &proof, None).unwrap());
+
+        // The proof does not verify if a changed constraint is omitted.
+        let proof = Nark::reprove_incremental(
+            &ipk,
+            &old_input,
+            &old_proof,
+            &input,
+            new_witness.clone(),
+            &[0],
+        )
+        .unwrap();
+        assert!(!Nark::verify(&ivk, &input, &proof, None).unwrap());
+
+        assert!(Nark::reprove_incremental(
+            &ipk,
+            &old_input,
+            &old_proof,
+            &input,
+            new_witness.clone(),
+            &[100],
+        )
+        .is_err());
+
+        let zk_proof = Nark::prove(&ipk, circuit, true, None, Some(rng)).unwrap();
+        assert!(Nark::reprove_incremental(
+            &ipk,
+            &old_input,
+            &zk_proof,
+            &input,
+            new_witness,
+            &[0, 42]
+        )
+        .is_err());
+    }
+}
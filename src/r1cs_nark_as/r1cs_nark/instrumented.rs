@@ -0,0 +1,233 @@
+use ark_ff::Field;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::cell::RefCell;
+use ark_std::rc::Rc;
+use ark_std::string::String;
+use ark_std::vec::Vec;
+
+/// The constraints and witness variables added by one instrumented sub-circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionReport {
+    /// The name the sub-circuit was instrumented with.
+    pub name: String,
+
+    /// The number of instrumented sub-circuits that enclose this one.
+    pub depth: usize,
+
+    /// The index of the first constraint added by the sub-circuit.
+    pub first_constraint: usize,
+
+    /// The number of constraints added by the sub-circuit, including those of nested
+    /// sub-circuits.
+    pub num_constraints: usize,
+
+    /// The number of witness variables added by the sub-circuit, including those of nested
+    /// sub-circuits.
+    pub num_witness_variables: usize,
+}
+
+/// The costs of the instrumented sub-circuits of a circuit, in the order in which they started
+/// generating constraints.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CircuitReport {
+    /// One entry per instrumented sub-circuit.
+    pub sections: Vec<SectionReport>,
+
+    // The depth of the sub-circuit that is currently generating constraints.
+    depth: usize,
+}
+
+impl CircuitReport {
+    /// Returns the report of the first sub-circuit named `name`, if any.
+    pub fn section(&self, name: &str) -> Option<&SectionReport> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+}
+
+/// A wrapper around a circuit that records the constraints and witness variables it adds in a
+/// [`CircuitReport`], to find out which sub-circuits are expensive. Sub-circuits wrapped with
+/// [`InstrumentedCircuit::instrument`] share the report, and a sub-circuit that is synthesized
+/// while another one is generating constraints is nested in it.
+///
+/// Synthesizing an outermost sub-circuit clears the report, so the report always describes the
+/// latest synthesis, e.g. by [`R1CSNark::index`][index] or [`R1CSNark::prove`][prove].
+///
+/// [index]: crate::r1cs_nark_as::r1cs_nark::R1CSNark::index
+/// [prove]: crate::r1cs_nark_as::r1cs_nark::R1CSNark::prove
+#[derive(Clone)]
+pub struct InstrumentedCircuit<C> {
+    name: String,
+    circuit: C,
+    report: Rc<RefCell<CircuitReport>>,
+}
+
+impl<C> InstrumentedCircuit<C> {
+    /// Wraps `circuit` under `name`, with a new report.
+    pub fn new(name: impl Into<String>, circuit: C) -> Self {
+        Self {
+            name: name.into(),
+            circuit,
+            report: Rc::default(),
+        }
+    }
+
+    /// Wraps `circuit` under `name`, sharing the report of `self`.
+    pub fn instrument<D>(&self, name: impl Into<String>, circuit: D) -> InstrumentedCircuit<D> {
+        InstrumentedCircuit {
+            name: name.into(),
+            circuit,
+            report: self.report.clone(),
+        }
+    }
+
+    /// Returns the report of the latest synthesis.
+    pub fn report(&self) -> CircuitReport {
+        self.report.borrow().clone()
+    }
+}
+
+impl<F: Field, C: ConstraintSynthesizer<F>> ConstraintSynthesizer<F> for InstrumentedCircuit<C> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let num_constraints = cs.num_constraints();
+        let num_witness_variables = cs.num_witness_variables();
+        let section_index = {
+            let mut report = self.report.borrow_mut();
+            if report.depth == 0 {
+                report.sections.clear();
+            }
+
+            let depth = report.depth;
+            report.depth += 1;
+            report.sections.push(SectionReport {
+                name: self.name,
+                depth,
+                first_constraint: num_constraints,
+                num_constraints: 0,
+                num_witness_variables: 0,
+            });
+            report.sections.len() - 1
+        };
+
+        let result = self.circuit.generate_constraints(cs.clone());
+
+        let mut report = self.report.borrow_mut();
+        report.depth -= 1;
+        let section = &mut report.sections[section_index];
+        section.num_constraints = cs.num_constraints() - num_constraints;
+        section.num_witness_variables = cs.num_witness_variables() - num_witness_variables;
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::R1CSNark;
+    use ark_ff::{PrimeField, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_relations::lc;
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    // Enforces `x * x = y` for a secret `x` and a public `y`.
+    #[derive(Clone, Copy)]
+    struct SquareCircuit<F> {
+        x: F,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for SquareCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let x = cs.new_witness_variable(|| Ok(self.x))?;
+            let y = cs.new_input_variable(|| Ok(self.x.square()))?;
+            cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + y)?;
+            Ok(())
+        }
+    }
+
+    // Squares a secret `x` `len` times, keeping every power secret.
+    #[derive(Clone, Copy)]
+    struct ChainCircuit<F> {
+        x: F,
+        len: usize,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for ChainCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let mut value = self.x;
+            let mut var = cs.new_witness_variable(|| Ok(value))?;
+            for _ in 0..self.len {
+                value.square_in_place();
+                let next_var = cs.new_witness_variable(|| Ok(value))?;
+                cs.enforce_constraint(lc!() + var, lc!() + var, lc!() + next_var)?;
+                var = next_var;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[derive(Clone)]
+    struct ComposedCircuit<F> {
+        square: InstrumentedCircuit<SquareCircuit<F>>,
+        chain: InstrumentedCircuit<ChainCircuit<F>>,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for ComposedCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            self.square.generate_constraints(cs.clone())?;
+            self.chain.generate_constraints(cs)
+        }
+    }
+
+    fn section(
+        name: &str,
+        depth: usize,
+        first_constraint: usize,
+        num_constraints: usize,
+        num_witness_variables: usize,
+    ) -> SectionReport {
+        SectionReport {
+            name: name.into(),
+            depth,
+            first_constraint,
+            num_constraints,
+            num_witness_variables,
+        }
+    }
+
+    #[test]
+    fn test_instrumented_circuit() {
+        let rng = &mut ark_std::test_rng();
+        let x = Fr::rand(rng);
+
+        let square = InstrumentedCircuit::new("square", SquareCircuit { x });
+        let chain = square.instrument("chain", ChainCircuit { x, len: 3 });
+        let circuit = square.instrument(
+            "composed",
+            ComposedCircuit {
+                square: square.clone(),
+                chain,
+            },
+        );
+
+        let expected = vec![
+            section("composed", 0, 0, 4, 5),
+            section("square", 1, 0, 1, 1),
+            section("chain", 1, 1, 3, 4),
+        ];
+
+        let pp = Nark::setup();
+        let (ipk, _) = Nark::index(&pp, circuit.clone()).unwrap();
+        assert_eq!(circuit.report().sections, expected);
+        assert_eq!(
+            circuit.report().section("chain").unwrap().num_constraints,
+            3
+        );
+        assert!(circuit.report().section("missing").is_none());
+
+        // Proving synthesizes the circuit again, which replaces the report.
+        Nark::prove(&ipk, circuit.clone(), false, None, None).unwrap();
+        assert_eq!(circuit.report().sections, expected);
+    }
+}
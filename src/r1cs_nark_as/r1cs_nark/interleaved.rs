@@ -0,0 +1,257 @@
+use super::{
+    check_commitment_key, first_unsatisfied_row, normalize_matrices, FullAssignment,
+    IndexProverKey, IndexVerifierKey, NarkConfig, NarkError, Proof, PublicParameters, R1CSNark,
+    R1CSResult,
+};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::Field;
+use ark_relations::r1cs::{ConstraintSynthesizer, Matrix};
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::rand::RngCore;
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+/// Interleaves the witnesses of several assignments of the same circuit into the assignment of
+/// the batched circuit indexed by [`R1CSNark::index_batched`], in which witness variable `j` of
+/// copy `k` is witness variable `j * witnesses.len() + k`. The copies share the public input.
+///
+/// # Panics
+/// Panics if `witnesses` is empty, or if the assignments have different public inputs or
+/// witnesses of different lengths.
+pub fn interleave_witnesses<F: Field>(witnesses: Vec<FullAssignment<F>>) -> FullAssignment<F> {
+    let first = witnesses.first().expect("no assignments to interleave");
+    let (input, witness_len) = (first.input.clone(), first.witness.len());
+    assert!(
+        witnesses
+            .iter()
+            .all(|w| w.input == input && w.witness.len() == witness_len),
+        "the assignments do not belong to copies of the same circuit"
+    );
+
+    let batch_size = witnesses.len();
+    let mut witness = vec![F::zero(); witness_len * batch_size];
+    for (k, assignment) in witnesses.into_iter().enumerate() {
+        for (j, value) in assignment.witness.into_iter().enumerate() {
+            witness[j * batch_size + k] = value;
+        }
+    }
+
+    FullAssignment { input, witness }
+}
+
+// Returns the matrix of `batch_size` copies of the constraints of `mat`, with the rows and the
+// witness columns of the copies interleaved as the witnesses are by `interleave_witnesses`. The
+// first `num_instance_variables` columns are shared by all copies.
+fn interleave_matrix<F: Field>(
+    mat: &Matrix<F>,
+    num_instance_variables: usize,
+    batch_size: usize,
+) -> Matrix<F> {
+    let mut batched = Vec::with_capacity(mat.len() * batch_size);
+    for row in mat {
+        for k in 0..batch_size {
+            let row = row.iter().map(|(coeff, j)| {
+                if *j < num_instance_variables {
+                    (*coeff, *j)
+                } else {
+                    let j = (j - num_instance_variables) * batch_size + k;
+                    (*coeff, num_instance_variables + j)
+                }
+            });
+            batched.push(row.collect());
+        }
+    }
+
+    batched
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Outputs a specialized prover and verifier key for `batch_size` copies of some R1CS
+    /// instance that share its public input, so that a single proof produced by
+    /// [`R1CSNark::prove_interleaved`] covers all of them. The number of constraints and of
+    /// witness variables grows linearly with `batch_size`. Proofs are verified with
+    /// [`R1CSNark::verify`] against the shared public input.
+    ///
+    /// # Panics
+    /// Panics if `batch_size` is zero.
+    pub fn index_batched<C: ConstraintSynthesizer<G::ScalarField>>(
+        pp: &PublicParameters,
+        circuit: C,
+        batch_size: usize,
+    ) -> R1CSResult<(IndexProverKey<G>, IndexVerifierKey<G>)> {
+        assert!(batch_size > 0, "the batch size must be non-zero");
+
+        let (a, b, c, num_input_variables, num_witness_variables) =
+            Self::constraint_matrices(circuit, &NarkConfig::default())?;
+        let num_circuit_constraints = a.len() * batch_size;
+        let (a, b, c, num_pruned_constraints) = normalize_matrices(a, b, c);
+
+        let interleave = |mat| interleave_matrix(&mat, num_input_variables, batch_size);
+        let (mut ipk, mut ivk) = Self::index_from_matrices(
+            pp,
+            interleave(a),
+            interleave(b),
+            interleave(c),
+            num_input_variables,
+            num_witness_variables * batch_size,
+        )?;
+        ipk.index_info.num_pruned_constraints = num_pruned_constraints * batch_size;
+        ivk.index_info.num_pruned_constraints = num_pruned_constraints * batch_size;
+        ipk.index_info.num_circuit_constraints = num_circuit_constraints;
+        ivk.index_info.num_circuit_constraints = num_circuit_constraints;
+        Ok((ipk, ivk))
+    }
+
+    /// Proves that every assignment in `witnesses` satisfies the circuit batched by
+    /// [`R1CSNark::index_batched`], with a single proof for the assignment interleaved by
+    /// [`interleave_witnesses`]. Returns an error if the interleaved assignment does not have
+    /// the lengths expected by `ipk`, or does not satisfy the batched instance.
+    ///
+    /// # Panics
+    /// Panics under the same conditions as [`interleave_witnesses`].
+    pub fn prove_interleaved(
+        ipk: &IndexProverKey<G>,
+        witnesses: Vec<FullAssignment<G::ScalarField>>,
+        make_zk: bool,
+        sponge: Option<S>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> R1CSResult<Proof<G>> {
+        check_commitment_key(ipk)?;
+
+        let index_info = &ipk.index_info;
+        let FullAssignment { input, witness } = interleave_witnesses(witnesses);
+        if input.len() != index_info.num_instance_variables {
+            return Err(NarkError::MalformedInput {
+                expected_len: index_info.num_instance_variables,
+                got: input.len(),
+            });
+        }
+
+        let witness_len = index_info.num_variables - index_info.num_instance_variables;
+        if witness.len() != witness_len {
+            return Err(NarkError::MalformedWitness {
+                expected_len: witness_len,
+                got: witness.len(),
+            });
+        }
+
+        if let Some(row) = first_unsatisfied_row(&ipk.a, &ipk.b, &ipk.c, &input, &witness) {
+            return Err(NarkError::UnsatisfiedConstraint { row });
+        }
+
+        Ok(Self::prove_with_assignment(
+            ipk, input, witness, make_zk, sponge, rng,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::CircuitWitness;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_relations::lc;
+    use ark_relations::r1cs::{
+        ConstraintSystem, ConstraintSystemRef, SynthesisError, SynthesisMode,
+    };
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    // Enforces `x * y = z` and `z * z = w` for secret `x`, `y`, and `w` and a public `z`.
+    #[derive(Clone, Copy)]
+    struct ProductCircuit<F> {
+        x: F,
+        y: F,
+    }
+
+    impl<F: Field> ConstraintSynthesizer<F> for ProductCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let z = cs.new_input_variable(|| Ok(self.x * self.y))?;
+            let x = cs.new_witness_variable(|| Ok(self.x))?;
+            let y = cs.new_witness_variable(|| Ok(self.y))?;
+            let w = cs.new_witness_variable(|| Ok((self.x * self.y).square()))?;
+            cs.enforce_constraint(lc!() + x, lc!() + y, lc!() + z)?;
+            cs.enforce_constraint(lc!() + z, lc!() + z, lc!() + w)
+        }
+    }
+
+    fn assignment(c: ProductCircuit<Fr>) -> FullAssignment<Fr> {
+        let cs = ConstraintSystem::new_ref();
+        cs.set_mode(SynthesisMode::Prove {
+            construct_matrices: false,
+        });
+        c.generate_constraints(cs.clone()).unwrap();
+        CircuitWitness::new(cs).to_full_assignment().unwrap()
+    }
+
+    #[test]
+    fn test_interleave_witnesses() {
+        let witnesses = (0..3u64)
+            .map(|k| FullAssignment {
+                input: vec![Fr::one()],
+                witness: vec![Fr::from(k), Fr::from(k + 10)],
+            })
+            .collect();
+        let interleaved = interleave_witnesses(witnesses);
+        assert_eq!(interleaved.input, vec![Fr::one()]);
+        let expected: Vec<_> = [0u64, 1, 2, 10, 11, 12]
+            .iter()
+            .map(|v| Fr::from(*v))
+            .collect();
+        assert_eq!(interleaved.witness, expected);
+    }
+
+    #[test]
+    fn test_prove_interleaved() {
+        let rng = &mut ark_std::test_rng();
+        let batch_size = 5;
+
+        // Every copy has a different witness for the same public `z`.
+        let z = Fr::rand(rng);
+        let circuits: Vec<_> = (0..batch_size)
+            .map(|_| {
+                let x = Fr::rand(rng);
+                ProductCircuit {
+                    x,
+                    y: z * x.inverse().unwrap(),
+                }
+            })
+            .collect();
+        let r1cs_input = vec![Fr::one(), z];
+
+        let pp = Nark::setup();
+        let (ipk, _) = Nark::index(&pp, circuits[0]).unwrap();
+        let (batched_ipk, batched_ivk) = Nark::index_batched(&pp, circuits[0], batch_size).unwrap();
+        let index_info = &batched_ivk.index_info;
+        assert_eq!(
+            index_info.num_constraints,
+            ipk.index_info.num_constraints * batch_size
+        );
+        assert_eq!(
+            index_info.num_variables - index_info.num_instance_variables,
+            (ipk.index_info.num_variables - ipk.index_info.num_instance_variables) * batch_size
+        );
+
+        let witnesses: Vec<_> = circuits.iter().map(|c| assignment(*c)).collect();
+        let proof = Nark::prove_interleaved(&batched_ipk, witnesses.clone(), true, None, Some(rng))
+            .unwrap();
+        assert!(Nark::verify(&batched_ivk, &r1cs_input, &proof, None).unwrap());
+
+        let wrong_input = vec![Fr::one(), Fr::rand(rng)];
+        assert!(!Nark::verify(&batched_ivk, &wrong_input, &proof, None).unwrap());
+
+        // A batch with a copy missing does not match the key.
+        let result =
+            Nark::prove_interleaved(&batched_ipk, witnesses[1..].to_vec(), false, None, None);
+        assert!(matches!(result, Err(NarkError::MalformedWitness { .. })));
+    }
+}
@@ -0,0 +1,160 @@
+use super::{IndexVerifierKey, Proof, R1CSNark};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::{Field, PrimeField};
+use ark_relations::lc;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::CanonicalSerialize;
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use ark_std::string::String;
+use ark_std::vec::Vec;
+use ark_std::UniformRand;
+use serde::{Deserialize, Serialize};
+
+/// A known-answer test vector for [`R1CSNark`], which lets other implementations check that they
+/// index, prove, and verify the same way. Every value is hex encoded from its
+/// [`CanonicalSerialize`] bytes, and the vector serializes to JSON with `serde_json`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Kat {
+    /// The hash of the matrices of the index.
+    pub circuit_hash: String,
+
+    /// The public input, including the leading constant `1`.
+    pub input: Vec<String>,
+
+    /// The witness.
+    pub witness: Vec<String>,
+
+    /// The zero-knowledge proof.
+    pub proof_bytes: String,
+
+    /// Whether the proof is accepted by [`R1CSNark::verify`].
+    pub verify_result: bool,
+}
+
+// Squares a secret `x_0` repeatedly, enforcing `x_i * x_i = x_{i + 1}` for each of
+// `num_constraints` constraints, and makes the last power public.
+#[derive(Clone, Copy)]
+struct KatCircuit<F: Field> {
+    x: F,
+    num_constraints: usize,
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for KatCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let mut value = self.x;
+        let mut var = cs.new_witness_variable(|| Ok(value))?;
+        for i in 0..self.num_constraints {
+            let next_value = value.square();
+            let next_var = if i + 1 == self.num_constraints {
+                cs.new_input_variable(|| Ok(next_value))?
+            } else {
+                cs.new_witness_variable(|| Ok(next_value))?
+            };
+            cs.enforce_constraint(lc!() + var, lc!() + var, lc!() + next_var)?;
+
+            value = next_value;
+            var = next_var;
+        }
+
+        Ok(())
+    }
+}
+
+/// Generates the [`Kat`] for a circuit with `circuit_size` constraints. All randomness, including
+/// the secret of the circuit and the blinding of the proof, is derived from `seed`, so the output
+/// only depends on `seed`, `circuit_size`, and the protocol version.
+///
+/// Panics if `circuit_size` is zero.
+pub fn generate_kat<G, S>(circuit_size: usize, seed: &[u8; 32]) -> Kat
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    assert!(circuit_size > 0, "the circuit must have a constraint");
+
+    let rng = &mut StdRng::from_seed(*seed);
+    let circuit = KatCircuit {
+        x: G::ScalarField::rand(rng),
+        num_constraints: circuit_size,
+    };
+
+    let pp = R1CSNark::<G, S>::setup();
+    let (ipk, ivk): (_, IndexVerifierKey<G>) = R1CSNark::<G, S>::index(&pp, circuit).unwrap();
+    let (input, witness) = R1CSNark::<G, S>::synthesize(&ipk, circuit).unwrap();
+    let proof: Proof<G> = R1CSNark::<G, S>::prove(&ipk, circuit, true, None, Some(rng)).unwrap();
+    let verify_result = R1CSNark::<G, S>::verify(&ivk, &input, &proof, None).unwrap();
+
+    Kat {
+        circuit_hash: to_hex(&ipk.index_info.matrices_hash),
+        input: input.iter().map(canonical_hex).collect(),
+        witness: witness.iter().map(canonical_hex).collect(),
+        proof_bytes: canonical_hex(&proof),
+        verify_result,
+    }
+}
+
+// Hex encodes the canonical serialization of `value`.
+fn canonical_hex<T: CanonicalSerialize>(value: &T) -> String {
+    let mut bytes = Vec::with_capacity(value.serialized_size());
+    value.serialize(&mut bytes).unwrap();
+    to_hex(&bytes)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_serialize::CanonicalDeserialize;
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_generate_kat() {
+        let seed = [7u8; 32];
+        let kat = generate_kat::<Affine, PoseidonSponge<Fq>>(16, &seed);
+        assert!(kat.verify_result);
+        assert_eq!(kat.input.len(), 2);
+        assert_eq!(kat.witness.len(), 16);
+
+        // The vector is reproducible, and depends on the seed.
+        assert_eq!(generate_kat::<Affine, PoseidonSponge<Fq>>(16, &seed), kat);
+        assert_ne!(
+            generate_kat::<Affine, PoseidonSponge<Fq>>(16, &[8u8; 32]),
+            kat
+        );
+
+        // The vector survives a JSON round trip, and its proof verifies against its input.
+        let json = serde_json::to_string(&kat).unwrap();
+        assert_eq!(serde_json::from_str::<Kat>(&json).unwrap(), kat);
+
+        let input = kat
+            .input
+            .iter()
+            .map(|inp| Fr::deserialize(&from_hex(inp)[..]).unwrap())
+            .collect::<Vec<_>>();
+        let proof = Proof::<Affine>::deserialize(&from_hex(&kat.proof_bytes)[..]).unwrap();
+        let circuit = KatCircuit {
+            x: Fr::deserialize(&from_hex(&kat.witness[0])[..]).unwrap(),
+            num_constraints: 16,
+        };
+        let (_, ivk) = Nark::index(&Nark::setup(), circuit).unwrap();
+        assert_eq!(to_hex(&ivk.index_info.matrices_hash), kat.circuit_hash);
+        assert!(Nark::verify(&ivk, &input, &proof, None).unwrap());
+    }
+}
@@ -0,0 +1,358 @@
+use super::{
+    check_protocol_version, IndexProverKey, IndexVerifierKey, NarkError, PublicParameters,
+    R1CSNark, R1CSResult,
+};
+use crate::r1cs_nark_as::CHALLENGE_SIZE;
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::{to_bytes, BigInteger, Field, PrimeField, Zero};
+use ark_poly::polynomial::univariate::DensePolynomial;
+use ark_poly::{EvaluationDomain, Evaluations, GeneralEvaluationDomain, Polynomial};
+use ark_poly_commit::{LabeledCommitment, LabeledPolynomial, PolynomialCommitment};
+use ark_relations::r1cs::{ConstraintSynthesizer, SynthesisError};
+use ark_sponge::{absorb, Absorbable, CryptographicSponge, FieldElementSize};
+use ark_std::format;
+use ark_std::marker::PhantomData;
+use ark_std::rand::RngCore;
+use ark_std::string::ToString;
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+// The labels of the committed polynomials, in the order in which they are opened.
+const POLYNOMIAL_LABELS: [&str; 4] = ["z_a", "z_b", "z_c", "t"];
+
+/// A variant of [`R1CSNark`] that commits to `z_A`, `z_B`, and `z_C` with a polynomial
+/// commitment scheme `P`, such as `MarlinKZG10`, instead of Pedersen commitments.
+///
+/// Each vector is encoded as the polynomial that interpolates it over a multiplicative subgroup
+/// `H` with as many elements as the next power of two after the number of constraints. The
+/// prover commits to these polynomials and to the quotient `t = (z_A * z_B - z_C) / Z_H`, and
+/// opens all four at a random point `beta` with a single batched proof, so the proof has a
+/// constant number of commitments.
+///
+/// This is a sketch: the proof still reveals the witness, and the verifier checks that the
+/// openings are consistent with it by evaluating the matrix-vector products at `beta`, which
+/// takes time linear in the size of the circuit. Making the verifier succinct requires proving
+/// these linear relations instead, e.g. with a holographic sumcheck.
+pub struct KzgR1CSNark<G, P, S>
+where
+    G: AffineCurve,
+    P: PolynomialCommitment<G::ScalarField, DensePolynomial<G::ScalarField>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    _affine: PhantomData<G>,
+    _pc: PhantomData<P>,
+    _sponge: PhantomData<S>,
+}
+
+/// The prover key of [`KzgR1CSNark`].
+pub struct KzgIndexProverKey<G, P>
+where
+    G: AffineCurve,
+    P: PolynomialCommitment<G::ScalarField, DensePolynomial<G::ScalarField>>,
+{
+    /// The index prover key of the R1CS instance. Its Pedersen commitment key is not used.
+    pub ipk: IndexProverKey<G>,
+
+    /// The key to commit to the polynomials of the prover.
+    pub kzg_ck: P::CommitterKey,
+}
+
+/// The verifier key of [`KzgR1CSNark`].
+pub struct KzgIndexVerifierKey<G, P>
+where
+    G: AffineCurve,
+    P: PolynomialCommitment<G::ScalarField, DensePolynomial<G::ScalarField>>,
+{
+    /// The index verifier key of the R1CS instance. Its Pedersen commitment key is not used.
+    pub ivk: IndexVerifierKey<G>,
+
+    /// The key to check the openings of the polynomials of the prover.
+    pub kzg_vk: P::VerifierKey,
+}
+
+/// A proof of [`KzgR1CSNark`].
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""))]
+pub struct KzgProof<G, P>
+where
+    G: AffineCurve,
+    P: PolynomialCommitment<G::ScalarField, DensePolynomial<G::ScalarField>>,
+{
+    /// The commitments to the polynomials of `z_A`, `z_B`, `z_C`, and `t`.
+    pub commitments: Vec<LabeledCommitment<P::Commitment>>,
+
+    /// The evaluations of the committed polynomials at `beta`, in the same order.
+    pub evaluations: Vec<G::ScalarField>,
+
+    /// The batched opening of the committed polynomials at `beta`.
+    pub opening: P::Proof,
+
+    /// The witness of the R1CS instance.
+    pub witness: Vec<G::ScalarField>,
+}
+
+impl<G, P, S> KzgR1CSNark<G, P, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    P: PolynomialCommitment<G::ScalarField, DensePolynomial<G::ScalarField>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Generates the universal parameters of the polynomial commitment scheme, which support
+    /// circuits with up to `max_degree` constraints.
+    pub fn setup(max_degree: usize, rng: &mut impl RngCore) -> R1CSResult<P::UniversalParams> {
+        P::setup(max_degree, None, rng).map_err(pc_error)
+    }
+
+    /// Outputs a specialized prover and verifier key for some R1CS instance. Returns an error if
+    /// `pc_pp` does not support as many constraints as the instance has.
+    pub fn index<C: ConstraintSynthesizer<G::ScalarField>>(
+        pp: &PublicParameters,
+        pc_pp: &P::UniversalParams,
+        r1cs_instance: C,
+    ) -> R1CSResult<(KzgIndexProverKey<G, P>, KzgIndexVerifierKey<G, P>)> {
+        let (ipk, ivk) = R1CSNark::<G, S>::index(pp, r1cs_instance)?;
+        let domain = domain(ipk.index_info.num_constraints)?;
+        let (kzg_ck, kzg_vk) = P::trim(pc_pp, domain.size(), 0, None).map_err(pc_error)?;
+
+        Ok((
+            KzgIndexProverKey { ipk, kzg_ck },
+            KzgIndexVerifierKey { ivk, kzg_vk },
+        ))
+    }
+
+    /// Proves that some R1CS relation holds. The proof is not zero knowledge.
+    pub fn prove<C: ConstraintSynthesizer<G::ScalarField>>(
+        ipk: &KzgIndexProverKey<G, P>,
+        r1cs: C,
+        sponge: Option<S>,
+    ) -> R1CSResult<KzgProof<G, P>> {
+        let init_time = start_timer!(|| "KzgNARK::Prover");
+
+        let (input, witness) = R1CSNark::<G, S>::synthesize(&ipk.ipk, r1cs)?;
+        let (z_a, z_b, z_c) = ipk.ipk.matrix_vec_muls(&input, &witness);
+
+        // Step 1: interpolate the matrix-vector products over `H`, and divide out the vanishing
+        // polynomial of `H` from `z_A * z_B - z_C`, which is only possible if the assignment
+        // satisfies every constraint.
+        let domain = domain(ipk.ipk.index_info.num_constraints)?;
+        let interpolate = |mut values: Vec<G::ScalarField>| {
+            values.resize(domain.size(), G::ScalarField::zero());
+            Evaluations::from_vec_and_domain(values, domain).interpolate()
+        };
+        let (poly_a, poly_b, poly_c) = (interpolate(z_a), interpolate(z_b), interpolate(z_c));
+        let (poly_t, remainder) = (&(&poly_a * &poly_b) - &poly_c)
+            .divide_by_vanishing_poly(domain)
+            .expect("the domain should be the one the polynomials were interpolated over");
+        if !remainder.is_zero() {
+            return Err(SynthesisError::Unsatisfiable.into());
+        }
+
+        let polynomials = vec![poly_a, poly_b, poly_c, poly_t]
+            .into_iter()
+            .zip(&POLYNOMIAL_LABELS)
+            .map(|(poly, label)| LabeledPolynomial::new(label.to_string(), poly, None, None))
+            .collect::<Vec<_>>();
+
+        // Step 2: commit to the polynomials, and derive `beta` and the batching challenge from
+        // the commitments.
+        let (commitments, randomness) =
+            P::commit(&ipk.kzg_ck, &polynomials, None).map_err(pc_error)?;
+        let sponge = R1CSNark::<G, S>::init_sponge(&ipk.ipk, sponge);
+        let (beta, batching_challenge) =
+            Self::compute_challenges(&ipk.ipk.matrices_hash, &input, &commitments, sponge);
+
+        // Step 3: open the polynomials at `beta`.
+        let evaluations = polynomials
+            .iter()
+            .map(|poly| poly.evaluate(&beta))
+            .collect();
+        let opening = P::open_individual_opening_challenges(
+            &ipk.kzg_ck,
+            &polynomials,
+            &commitments,
+            &beta,
+            &|i| batching_challenge.pow(&[i]),
+            &randomness,
+            None,
+        )
+        .map_err(pc_error)?;
+
+        end_timer!(init_time);
+        Ok(KzgProof {
+            commitments,
+            evaluations,
+            opening,
+            witness,
+        })
+    }
+
+    /// Verifies that some R1CS relation holds. Returns an error if the input or the witness do
+    /// not have the lengths expected by `ivk`.
+    pub fn verify(
+        ivk: &KzgIndexVerifierKey<G, P>,
+        input: &[G::ScalarField],
+        proof: &KzgProof<G, P>,
+        sponge: Option<S>,
+    ) -> R1CSResult<bool> {
+        let index_info = &ivk.ivk.index_info;
+        check_protocol_version(index_info)?;
+        if input.len() != index_info.num_instance_variables {
+            return Err(NarkError::MalformedInput {
+                expected_len: index_info.num_instance_variables,
+                got: input.len(),
+            });
+        }
+
+        let witness_len = index_info.num_variables - index_info.num_instance_variables;
+        if proof.witness.len() != witness_len {
+            return Err(NarkError::MalformedWitness {
+                expected_len: witness_len,
+                got: proof.witness.len(),
+            });
+        }
+
+        if proof.commitments.len() != POLYNOMIAL_LABELS.len()
+            || proof.evaluations.len() != POLYNOMIAL_LABELS.len()
+        {
+            return Ok(false);
+        }
+
+        let init_time = start_timer!(|| "KzgNARK::Verifier");
+        let sponge = R1CSNark::<G, S>::init_sponge(&ivk.ivk, sponge);
+        let (beta, batching_challenge) =
+            Self::compute_challenges(&ivk.ivk.matrices_hash, input, &proof.commitments, sponge);
+
+        // Step 1: check the openings of the committed polynomials at `beta`.
+        let opening_is_valid = P::check_individual_opening_challenges(
+            &ivk.kzg_vk,
+            &proof.commitments,
+            &beta,
+            proof.evaluations.iter().cloned(),
+            &proof.opening,
+            &|i| batching_challenge.pow(&[i]),
+            None,
+        )
+        .map_err(pc_error)?;
+
+        // Step 2: check that `z_A * z_B - z_C = t * Z_H` at `beta`, which shows that
+        // `z_A ∘ z_B = z_C` on `H`.
+        let domain = domain(index_info.num_constraints)?;
+        let (eval_a, eval_b, eval_c, eval_t) = (
+            proof.evaluations[0],
+            proof.evaluations[1],
+            proof.evaluations[2],
+            proof.evaluations[3],
+        );
+        let quotient_is_valid =
+            eval_a * eval_b - eval_c == eval_t * domain.evaluate_vanishing_polynomial(beta);
+
+        // Step 3: check that the committed polynomials interpolate the matrix-vector products of
+        // the witness, by evaluating the products at `beta`.
+        let lagrange_coeffs = domain.evaluate_all_lagrange_coefficients(beta);
+        let (z_a, z_b, z_c) = ivk.ivk.matrix_vec_muls(input, &proof.witness);
+        let evaluate = |values: &[G::ScalarField]| -> G::ScalarField {
+            values
+                .iter()
+                .zip(&lagrange_coeffs)
+                .map(|(value, coeff)| *value * coeff)
+                .sum()
+        };
+        let products_are_valid =
+            evaluate(&z_a) == eval_a && evaluate(&z_b) == eval_b && evaluate(&z_c) == eval_c;
+
+        end_timer!(init_time);
+        Ok(opening_is_valid && quotient_is_valid && products_are_valid)
+    }
+
+    // Derives the evaluation point `beta` and the challenge that batches the openings.
+    fn compute_challenges(
+        matrices_hash: &[u8; 32],
+        input: &[G::ScalarField],
+        commitments: &[LabeledCommitment<P::Commitment>],
+        mut sponge: S,
+    ) -> (G::ScalarField, G::ScalarField) {
+        sponge.absorb(&matrices_hash.as_ref());
+
+        let input_bytes = input
+            .iter()
+            .flat_map(|inp| inp.into_repr().to_bytes_le())
+            .collect::<Vec<_>>();
+        let commitment_bytes = commitments
+            .iter()
+            .flat_map(|comm| to_bytes![comm.commitment()].unwrap())
+            .collect::<Vec<_>>();
+        absorb!(&mut sponge, input_bytes, commitment_bytes);
+
+        let mut challenges = sponge.squeeze_nonnative_field_elements_with_sizes(&[
+            FieldElementSize::Truncated(CHALLENGE_SIZE),
+            FieldElementSize::Truncated(CHALLENGE_SIZE),
+        ]);
+        let batching_challenge = challenges.pop().unwrap();
+        let beta = challenges.pop().unwrap();
+        (beta, batching_challenge)
+    }
+}
+
+// Returns the multiplicative subgroup over which the matrix-vector products are interpolated.
+fn domain<F: PrimeField>(num_constraints: usize) -> R1CSResult<GeneralEvaluationDomain<F>> {
+    GeneralEvaluationDomain::new(num_constraints.max(1))
+        .ok_or_else(|| SynthesisError::PolynomialDegreeTooLarge.into())
+}
+
+fn pc_error(err: impl ark_std::error::Error) -> NarkError {
+    NarkError::PolynomialCommitment(format!("{}", err))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_bn254::{Bn254, Fq, Fr, G1Affine};
+    use ark_ff::{One, UniformRand};
+    use ark_poly_commit::marlin_pc::MarlinKZG10;
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type PC = MarlinKZG10<Bn254, DensePolynomial<Fr>>;
+    type KzgNark = KzgR1CSNark<G1Affine, PC, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_kzg_proof() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = R1CSNark::<G1Affine, PoseidonSponge<Fq>>::setup();
+        let pc_pp = KzgNark::setup(128, rng).unwrap();
+        let (ipk, ivk) = KzgNark::index(&pp, &pc_pp, c).unwrap();
+        let proof = KzgNark::prove(&ipk, c, None).unwrap();
+        assert_eq!(proof.commitments.len(), 4);
+        assert!(KzgNark::verify(&ivk, &r1cs_input, &proof, None).unwrap());
+
+        let wrong_input = vec![Fr::one(), Fr::rand(rng)];
+        assert!(!KzgNark::verify(&ivk, &wrong_input, &proof, None).unwrap());
+
+        let mut wrong_eval = proof.clone();
+        wrong_eval.evaluations[3] += Fr::one();
+        assert!(!KzgNark::verify(&ivk, &r1cs_input, &wrong_eval, None).unwrap());
+
+        let mut wrong_witness = proof.clone();
+        wrong_witness.witness[0] += Fr::one();
+        assert!(!KzgNark::verify(&ivk, &r1cs_input, &wrong_witness, None).unwrap());
+
+        // A circuit with more constraints than the parameters support cannot be indexed.
+        let large_c = DummyCircuit {
+            num_constraints: 200,
+            ..c
+        };
+        let result = KzgNark::index(&pp, &pc_pp, large_c);
+        assert!(matches!(result, Err(NarkError::PolynomialCommitment(_))));
+    }
+}
@@ -0,0 +1,181 @@
+use super::{IndexProverKey, IndexVerifierKey, Proof, PublicParameters, R1CSNark, R1CSResult};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::Field;
+use ark_relations::r1cs::{ConstraintSynthesizer, SynthesisError};
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::rand::RngCore;
+use ark_std::string::{String, ToString};
+use ark_std::vec::Vec;
+
+/// A public input whose values are identified by labels instead of by their position.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LabeledInput<F: Field> {
+    /// The labels of the input values.
+    pub labels: Vec<String>,
+
+    /// The input values, in the same order as `labels`.
+    pub values: Vec<F>,
+}
+
+impl<F: Field> LabeledInput<F> {
+    /// Arranges the values in the order given by `order`, prepended with the constant `1`.
+    /// Returns `None` if the labels of the input are not a permutation of `order`.
+    pub fn ordered(&self, order: &[String]) -> Option<Vec<F>> {
+        if self.labels.len() != self.values.len() || self.labels.len() != order.len() {
+            return None;
+        }
+
+        let mut input = Vec::with_capacity(order.len() + 1);
+        input.push(F::one());
+        for label in order {
+            let position = self.labels.iter().position(|l| l == label)?;
+            input.push(self.values[position]);
+        }
+
+        Some(input)
+    }
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Outputs a specialized prover and verifier key for some R1CS instance whose public input
+    /// variables are named by `labels`, in the order in which the instance allocates them.
+    /// Returns an error if the number of labels does not match the number of public inputs or
+    /// if a label is repeated.
+    pub fn index_labeled<C: ConstraintSynthesizer<G::ScalarField>>(
+        pp: &PublicParameters,
+        r1cs_instance: C,
+        labels: &[&str],
+    ) -> R1CSResult<(IndexProverKey<G>, IndexVerifierKey<G>)> {
+        let (mut ipk, _) = Self::index(pp, r1cs_instance)?;
+        let has_duplicates = labels
+            .iter()
+            .enumerate()
+            .any(|(i, label)| labels[..i].contains(label));
+        if labels.len() + 1 != ipk.index_info.num_instance_variables || has_duplicates {
+            return Err(SynthesisError::MalformedVerifyingKey.into());
+        }
+
+        ipk.input_labels = Some(labels.iter().map(|label| label.to_string()).collect());
+        let ivk = ipk.clone();
+        Ok((ipk, ivk))
+    }
+
+    /// Proves that some R1CS relation holds for an index created with
+    /// [`R1CSNark::index_labeled`]. Returns an error if the public input synthesized from `r1cs`
+    /// does not match `input`.
+    pub fn prove_labeled<C: ConstraintSynthesizer<G::ScalarField>>(
+        ipk: &IndexProverKey<G>,
+        r1cs: C,
+        input: &LabeledInput<G::ScalarField>,
+        make_zk: bool,
+        sponge: Option<S>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> R1CSResult<Proof<G>> {
+        let expected_input = ipk
+            .input_labels
+            .as_ref()
+            .and_then(|order| input.ordered(order))
+            .ok_or(SynthesisError::MalformedVerifyingKey)?;
+
+        let (r1cs_input, witness) = Self::synthesize(ipk, r1cs)?;
+        if r1cs_input != expected_input {
+            return Err(SynthesisError::Unsatisfiable.into());
+        }
+
+        Ok(Self::prove_with_assignment(
+            ipk, r1cs_input, witness, make_zk, sponge, rng,
+        ))
+    }
+
+    /// Verifies that some R1CS relation holds for a labeled public input. The values are
+    /// arranged in the order of the labels of `ivk` before the proof is checked.
+    pub fn verify_labeled(
+        ivk: &IndexVerifierKey<G>,
+        input: &LabeledInput<G::ScalarField>,
+        proof: &Proof<G>,
+        sponge: Option<S>,
+    ) -> bool {
+        match ivk
+            .input_labels
+            .as_ref()
+            .and_then(|order| input.ordered(order))
+        {
+            Some(r1cs_input) => Self::verify(ivk, &r1cs_input, proof, sponge).unwrap_or(false),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::{One, PrimeField, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_relations::{
+        lc,
+        r1cs::{ConstraintSystemRef, Variable},
+    };
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    // Proves knowledge of a `secret` such that `root = secret^2` and `nullifier = secret + 1`.
+    #[derive(Clone, Copy)]
+    struct NamedInputsCircuit<F: PrimeField> {
+        secret: F,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for NamedInputsCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let root = cs.new_input_variable(|| Ok(self.secret.square()))?;
+            let nullifier = cs.new_input_variable(|| Ok(self.secret + F::one()))?;
+            let secret = cs.new_witness_variable(|| Ok(self.secret))?;
+            cs.enforce_constraint(lc!() + secret, lc!() + secret, lc!() + root)?;
+            cs.enforce_constraint(
+                lc!() + secret + Variable::One,
+                lc!() + Variable::One,
+                lc!() + nullifier,
+            )?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_labeled_input() {
+        let rng = &mut ark_std::test_rng();
+        let circuit = NamedInputsCircuit {
+            secret: Fr::rand(rng),
+        };
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index_labeled(&pp, circuit, &["root", "nullifier"]).unwrap();
+        assert!(Nark::index_labeled(&pp, circuit, &["root"]).is_err());
+        assert!(Nark::index_labeled(&pp, circuit, &["root", "root"]).is_err());
+
+        // The values are given in a different order than the circuit allocates them.
+        let input = LabeledInput {
+            labels: vec!["nullifier".to_string(), "root".to_string()],
+            values: vec![circuit.secret + Fr::one(), circuit.secret.square()],
+        };
+
+        let proof = Nark::prove_labeled(&ipk, circuit, &input, true, None, Some(rng)).unwrap();
+        assert!(Nark::verify_labeled(&ivk, &input, &proof, None));
+
+        let mut swapped_input = input.clone();
+        swapped_input.values.swap(0, 1);
+        assert!(!Nark::verify_labeled(&ivk, &swapped_input, &proof, None));
+        assert!(Nark::prove_labeled(&ipk, circuit, &swapped_input, false, None, None).is_err());
+
+        let mut renamed_input = input;
+        renamed_input.labels[0] = "timestamp".to_string();
+        assert!(!Nark::verify_labeled(&ivk, &renamed_input, &proof, None));
+    }
+}
@@ -0,0 +1,199 @@
+use super::{
+    check_proof_format_version, FirstRoundMessage, NarkError, Proof, R1CSResult, SecondRoundMessage,
+};
+
+use ark_ec::AffineCurve;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::cell::OnceCell;
+use ark_std::vec::Vec;
+
+// The length of the format version that precedes the messages of a serialized proof.
+const VERSION_LEN: usize = 1;
+
+/// A [`Proof`] kept as its canonical serialization, whose messages are only deserialized when
+/// they are first accessed. This avoids the cost of deserialization for proofs that are received
+/// but then discarded without being verified, e.g. due to rate limiting.
+#[derive(Clone)]
+pub struct LazyProof<G: AffineCurve> {
+    bytes: Vec<u8>,
+    first_msg: OnceCell<FirstRoundMessage<G>>,
+    second_msg: OnceCell<SecondRoundMessage<G::ScalarField>>,
+}
+
+impl<G: AffineCurve> LazyProof<G> {
+    /// Wraps the canonical serialization of a [`Proof`] without deserializing it.
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes,
+            first_msg: OnceCell::new(),
+            second_msg: OnceCell::new(),
+        }
+    }
+
+    /// Returns the first message of the proof, deserializing it on the first call. Returns an
+    /// error if the bytes are in an unsupported format version or do not encode the message.
+    pub fn first_msg(&self) -> R1CSResult<&FirstRoundMessage<G>> {
+        let version = u8::deserialize(&self.bytes[..]).map_err(NarkError::MalformedProof)?;
+        check_proof_format_version(version)?;
+        get_or_deserialize(&self.first_msg, &self.bytes[VERSION_LEN..])
+    }
+
+    /// Returns the second message of the proof, deserializing it and the first message on the
+    /// first call. Returns an error if the bytes do not encode the messages.
+    pub fn second_msg(&self) -> R1CSResult<&SecondRoundMessage<G::ScalarField>> {
+        let offset = VERSION_LEN + self.first_msg()?.serialized_size();
+        get_or_deserialize(&self.second_msg, &self.bytes[offset..])
+    }
+}
+
+// Returns the value in `cell`, after deserializing it from `bytes` if the cell is empty. A value
+// that fails to deserialize is not cached.
+fn get_or_deserialize<'a, T: CanonicalDeserialize>(
+    cell: &'a OnceCell<T>,
+    bytes: &[u8],
+) -> R1CSResult<&'a T> {
+    if let Some(value) = cell.get() {
+        return Ok(value);
+    }
+
+    let value = T::deserialize(bytes).map_err(NarkError::MalformedProof)?;
+    Ok(cell.get_or_init(|| value))
+}
+
+/// A reference to either an eagerly deserialized [`Proof`] or a [`LazyProof`], which lets the
+/// verifier accept both.
+#[derive(Clone, Copy)]
+pub enum ProofRef<'a, G: AffineCurve> {
+    /// A reference to a deserialized proof.
+    Eager(&'a Proof<G>),
+
+    /// A reference to a proof that is deserialized on access.
+    Lazy(&'a LazyProof<G>),
+}
+
+impl<'a, G: AffineCurve> ProofRef<'a, G> {
+    /// Returns the first message of the proof.
+    pub fn first_msg(self) -> R1CSResult<&'a FirstRoundMessage<G>> {
+        match self {
+            ProofRef::Eager(proof) => Ok(&proof.first_msg),
+            ProofRef::Lazy(proof) => proof.first_msg(),
+        }
+    }
+
+    /// Returns the second message of the proof.
+    pub fn second_msg(self) -> R1CSResult<&'a SecondRoundMessage<G::ScalarField>> {
+        match self {
+            ProofRef::Eager(proof) => Ok(&proof.second_msg),
+            ProofRef::Lazy(proof) => proof.second_msg(),
+        }
+    }
+
+    // Checks that the proof is the one produced by `Proof::base_case` for `input`.
+    pub(crate) fn is_valid_base_case(self, input: &[G::ScalarField]) -> R1CSResult<bool> {
+        let (first_msg, second_msg) = (self.first_msg()?, self.second_msg()?);
+        Ok(first_msg.comm_a.is_zero()
+            && first_msg.comm_b.is_zero()
+            && first_msg.comm_c.is_zero()
+            && first_msg.randomness.is_none()
+            && second_msg.randomness.is_none()
+            && second_msg.blinded_witness == input)
+    }
+}
+
+impl<'a, G: AffineCurve> From<&'a Proof<G>> for ProofRef<'a, G> {
+    fn from(proof: &'a Proof<G>) -> Self {
+        ProofRef::Eager(proof)
+    }
+}
+
+impl<'a, G: AffineCurve> From<&'a LazyProof<G>> for ProofRef<'a, G> {
+    fn from(proof: &'a LazyProof<G>) -> Self {
+        ProofRef::Lazy(proof)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use crate::r1cs_nark_as::r1cs_nark::{R1CSNark, PROOF_FORMAT_VERSION};
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_lazy_proof() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let proof = Nark::prove(&ipk, c, true, None, Some(rng)).unwrap();
+        let mut bytes = Vec::new();
+        proof.serialize(&mut bytes).unwrap();
+
+        // A proof that is dropped without being accessed is never deserialized.
+        let lazy = LazyProof::<Affine>::new(bytes.clone());
+        assert!(lazy.first_msg.get().is_none());
+        assert!(lazy.second_msg.get().is_none());
+        drop(lazy);
+
+        let lazy = LazyProof::<Affine>::new(bytes.clone());
+        lazy.first_msg().unwrap();
+        assert!(lazy.second_msg.get().is_none());
+        assert!(Nark::verify(&ivk, &r1cs_input, &lazy, None).unwrap());
+        assert!(Nark::verify(&ivk, &r1cs_input, &proof, None).unwrap());
+
+        let wrong_input = vec![Fr::one(), Fr::rand(rng)];
+        assert!(!Nark::verify(&ivk, &wrong_input, &lazy, None).unwrap());
+
+        let truncated = LazyProof::<Affine>::new(bytes[..bytes.len() - 1].to_vec());
+        assert!(truncated.first_msg().is_ok());
+        assert!(matches!(
+            Nark::verify(&ivk, &r1cs_input, &truncated, None),
+            Err(NarkError::MalformedProof(_))
+        ));
+    }
+
+    #[test]
+    fn test_proof_format_version() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let proof = Nark::prove(&ipk, c, true, None, Some(rng)).unwrap();
+        let mut bytes = Vec::new();
+        proof.serialize(&mut bytes).unwrap();
+        assert_eq!(bytes[0], PROOF_FORMAT_VERSION);
+        assert_eq!(bytes.len(), proof.serialized_size());
+
+        let decoded = Proof::<Affine>::deserialize(&bytes[..]).unwrap();
+        assert!(Nark::verify(&ivk, &r1cs_input, &decoded, None).unwrap());
+        let lazy = LazyProof::<Affine>::new(bytes.clone());
+        assert!(Nark::verify(&ivk, &r1cs_input, &lazy, None).unwrap());
+
+        // A proof in an unknown format is reported as such rather than misread.
+        bytes[0] = PROOF_FORMAT_VERSION + 1;
+        assert!(Proof::<Affine>::deserialize(&bytes[..]).is_err());
+        let lazy = LazyProof::<Affine>::new(bytes);
+        assert!(matches!(
+            Nark::verify(&ivk, &r1cs_input, &lazy, None),
+            Err(NarkError::UnsupportedProofVersion { version: 2 })
+        ));
+    }
+}
@@ -0,0 +1,153 @@
+use ark_ff::PrimeField;
+use ark_relations::r1cs::Matrix;
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+/// One of the three matrices of an R1CS instance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum R1CSMatrix {
+    /// The `A` matrix.
+    A,
+
+    /// The `B` matrix.
+    B,
+
+    /// The `C` matrix.
+    C,
+}
+
+/// A likely mistake in an R1CS instance, found by [`lint_circuit`]. Variables are identified by
+/// their column in the matrices, where the public input, including the constant `1` at column
+/// `0`, precedes the witness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LintWarning {
+    /// A witness variable that appears in no constraint, so any value satisfies the instance.
+    UnconstrainedWitness(usize),
+
+    /// A public input variable other than the constant `1` that appears in the `C` matrix, which
+    /// fixes its value and may make the instance unsatisfiable for other inputs.
+    OverconstrainedInput(usize),
+
+    /// A row of a matrix without non-zero entries.
+    ZeroRow(R1CSMatrix, usize),
+}
+
+/// Checks the matrices of an R1CS instance with `num_variables` variables, the first
+/// `num_instance_variables` of which are public, for common constraint mistakes. Returns every
+/// warning found, ordered by kind and then by position.
+pub fn lint_circuit<F: PrimeField>(
+    a: &Matrix<F>,
+    b: &Matrix<F>,
+    c: &Matrix<F>,
+    num_instance_variables: usize,
+    num_variables: usize,
+) -> Vec<LintWarning> {
+    let matrices = [(R1CSMatrix::A, a), (R1CSMatrix::B, b), (R1CSMatrix::C, c)];
+
+    let mut is_used = vec![false; num_variables];
+    for (_, matrix) in &matrices {
+        for (_, column) in matrix
+            .iter()
+            .flatten()
+            .filter(|(coeff, _)| !coeff.is_zero())
+        {
+            if let Some(used) = is_used.get_mut(*column) {
+                *used = true;
+            }
+        }
+    }
+
+    let mut warnings = (num_instance_variables..num_variables)
+        .filter(|&column| !is_used[column])
+        .map(LintWarning::UnconstrainedWitness)
+        .collect::<Vec<_>>();
+
+    let mut overconstrained_inputs = c
+        .iter()
+        .flatten()
+        .filter(|(coeff, column)| {
+            !coeff.is_zero() && 0 < *column && *column < num_instance_variables
+        })
+        .map(|(_, column)| *column)
+        .collect::<Vec<_>>();
+    overconstrained_inputs.sort_unstable();
+    overconstrained_inputs.dedup();
+    warnings.extend(
+        overconstrained_inputs
+            .into_iter()
+            .map(LintWarning::OverconstrainedInput),
+    );
+
+    for (id, matrix) in &matrices {
+        for (i, row) in matrix.iter().enumerate() {
+            if row.iter().all(|(coeff, _)| coeff.is_zero()) {
+                warnings.push(LintWarning::ZeroRow(*id, i));
+            }
+        }
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::{One, Zero};
+    use ark_pallas::Fr;
+
+    // The matrices of `w_1 * w_2 = x_1`, over the variables `(1, x_1, w_1, w_2)`.
+    fn matrices() -> (Matrix<Fr>, Matrix<Fr>, Matrix<Fr>) {
+        (
+            vec![vec![(Fr::one(), 2)]],
+            vec![vec![(Fr::one(), 3)]],
+            vec![vec![(Fr::one(), 0)]],
+        )
+    }
+
+    #[test]
+    fn test_clean_circuit() {
+        let (a, b, c) = matrices();
+        assert!(lint_circuit(&a, &b, &c, 2, 4).is_empty());
+    }
+
+    #[test]
+    fn test_unconstrained_witness() {
+        let (a, b, c) = matrices();
+        assert_eq!(
+            lint_circuit(&a, &b, &c, 2, 5),
+            vec![LintWarning::UnconstrainedWitness(4)]
+        );
+
+        // An entry with a zero coefficient does not constrain its variable.
+        let a = vec![vec![(Fr::one(), 2), (Fr::zero(), 4)]];
+        assert_eq!(
+            lint_circuit(&a, &b, &c, 2, 5),
+            vec![LintWarning::UnconstrainedWitness(4)]
+        );
+    }
+
+    #[test]
+    fn test_overconstrained_input() {
+        let (a, b, _) = matrices();
+        let c = vec![vec![(Fr::one(), 1), (Fr::one(), 0)]];
+        assert_eq!(
+            lint_circuit(&a, &b, &c, 2, 4),
+            vec![LintWarning::OverconstrainedInput(1)]
+        );
+    }
+
+    #[test]
+    fn test_zero_row() {
+        let (mut a, mut b, mut c) = matrices();
+        a.push(vec![]);
+        b.push(vec![(Fr::zero(), 2)]);
+        c.push(vec![(Fr::one(), 0)]);
+        assert_eq!(
+            lint_circuit(&a, &b, &c, 2, 4),
+            vec![
+                LintWarning::ZeroRow(R1CSMatrix::A, 1),
+                LintWarning::ZeroRow(R1CSMatrix::B, 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use super::FullAssignment;
+
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_sponge::poseidon::PoseidonSponge;
+use ark_sponge::CryptographicSponge;
+use ark_std::io::{Read, Write};
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+/// A Merkle tree with `ARITY` children per internal node, where each internal node is the
+/// Poseidon hash of its children. Binary (`ARITY = 2`) and 4-ary (`ARITY = 4`) trees are the
+/// intended instantiations; a 4-ary tree over the same leaves has half the depth of a binary one.
+#[derive(Clone, Debug)]
+pub struct PoseidonMerkleTree<F: PrimeField, const ARITY: usize> {
+    /// The layers of the tree, from the (padded) leaves up to the single root.
+    layers: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField, const ARITY: usize> PoseidonMerkleTree<F, ARITY> {
+    /// Builds the tree over `leaves`. The leaves are padded with zeros up to the next power of
+    /// `ARITY`.
+    pub fn build(leaves: &[F]) -> Self {
+        assert!(ARITY >= 2, "the arity of a Merkle tree must be at least 2");
+
+        let mut num_leaves = 1;
+        while num_leaves < leaves.len() {
+            num_leaves *= ARITY;
+        }
+
+        let mut layer = leaves.to_vec();
+        layer.resize(num_leaves, F::zero());
+
+        let mut layers = vec![layer];
+        while layers.last().unwrap().len() > 1 {
+            let parents = layers
+                .last()
+                .unwrap()
+                .chunks(ARITY)
+                .map(Self::hash_children)
+                .collect();
+            layers.push(parents);
+        }
+
+        Self { layers }
+    }
+
+    /// The root of the tree.
+    pub fn root(&self) -> F {
+        self.layers.last().unwrap()[0]
+    }
+
+    /// The number of layers of internal nodes, which is zero for a tree with a single leaf.
+    pub fn depth(&self) -> usize {
+        self.layers.len() - 1
+    }
+
+    // Computes an internal node from its children.
+    fn hash_children(children: &[F]) -> F {
+        let mut sponge = PoseidonSponge::<F>::new();
+        sponge.absorb(&children.to_vec());
+        sponge.squeeze_field_elements(1).pop().unwrap()
+    }
+}
+
+/// A commitment to a [`FullAssignment`], given by the root of a [`PoseidonMerkleTree`] over the
+/// leaves `input || witness`.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CommitmentFullAssignment<F: PrimeField> {
+    /// The root of the Merkle tree.
+    pub root: F,
+}
+
+impl<F: PrimeField> FullAssignment<F> {
+    /// Commits to the assignment with a [`PoseidonMerkleTree`] of arity `ARITY`.
+    pub fn commit<const ARITY: usize>(&self) -> CommitmentFullAssignment<F> {
+        let leaves = [self.input.as_slice(), self.witness.as_slice()].concat();
+        CommitmentFullAssignment {
+            root: PoseidonMerkleTree::<F, ARITY>::build(&leaves).root(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::{One, UniformRand, Zero};
+    use ark_pallas::Fq;
+
+    #[test]
+    fn test_merkle_tree_arities() {
+        let rng = &mut ark_std::test_rng();
+        let leaves: Vec<Fq> = (0..16).map(|_| Fq::rand(rng)).collect();
+
+        let binary_tree = PoseidonMerkleTree::<Fq, 2>::build(&leaves);
+        let quaternary_tree = PoseidonMerkleTree::<Fq, 4>::build(&leaves);
+        assert_eq!(binary_tree.depth(), 4);
+        assert_eq!(quaternary_tree.depth(), 2);
+        assert_ne!(binary_tree.root(), quaternary_tree.root());
+
+        // Building the same tree twice gives the same root.
+        assert_eq!(
+            PoseidonMerkleTree::<Fq, 2>::build(&leaves).root(),
+            binary_tree.root()
+        );
+
+        // A leaf count that is not a power of the arity is padded with zeros.
+        let odd_tree = PoseidonMerkleTree::<Fq, 4>::build(&leaves[..13]);
+        let padded_leaves = [&leaves[..13], &[Fq::zero(); 3]].concat();
+        assert_eq!(odd_tree.depth(), 2);
+        assert_eq!(
+            odd_tree.root(),
+            PoseidonMerkleTree::<Fq, 4>::build(&padded_leaves).root()
+        );
+
+        let single_leaf_tree = PoseidonMerkleTree::<Fq, 2>::build(&leaves[..1]);
+        assert_eq!(single_leaf_tree.depth(), 0);
+        assert_eq!(single_leaf_tree.root(), leaves[0]);
+    }
+
+    #[test]
+    fn test_commit_full_assignment() {
+        let rng = &mut ark_std::test_rng();
+        let assignment = FullAssignment {
+            input: vec![Fq::one(), Fq::rand(rng)],
+            witness: (0..6).map(|_| Fq::rand(rng)).collect(),
+        };
+
+        let leaves = [assignment.input.as_slice(), assignment.witness.as_slice()].concat();
+        let commitment = assignment.commit::<2>();
+        assert_eq!(
+            commitment.root,
+            PoseidonMerkleTree::<Fq, 2>::build(&leaves).root()
+        );
+        assert_ne!(commitment, assignment.commit::<4>());
+
+        let mut tampered = assignment.clone();
+        tampered.witness[5] += Fq::one();
+        assert_ne!(tampered.commit::<2>(), commitment);
+    }
+}
@@ -0,0 +1,231 @@
+use super::constraints::{IndexVerifierKeyVar, ProofVar, R1CSNarkVerifierGadget};
+use super::{
+    FirstRoundMessage, IndexVerifierKey, Proof, PublicParameters, R1CSNark, R1CSResult,
+    SecondRoundMessage,
+};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::One;
+use ark_nonnative_field::NonNativeFieldVar;
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::bits::boolean::Boolean;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::groups::CurveVar;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_sponge::constraints::{AbsorbableGadget, CryptographicSpongeVar};
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::marker::PhantomData;
+use ark_std::rand::RngCore;
+use ark_std::vec::Vec;
+
+/// A proof of knowledge of a valid [`R1CSNark`] proof, produced by [`R1CSNark::prove_meta`].
+///
+/// The meta-proof is an [`R1CSNark`] proof over a curve `H` whose scalar field is the base field
+/// of the curve of the hidden proof, such as Vesta for a proof over Pallas.
+#[derive(Clone)]
+pub struct MetaProof<H: AffineCurve> {
+    /// The proof that the verifier circuit of the NARK accepts the hidden proof.
+    pub proof: Proof<H>,
+
+    /// Whether the hidden proof is zero knowledge, which fixes the shape of the verifier circuit.
+    pub inner_is_zk: bool,
+}
+
+// The circuit that accepts exactly the proofs that `R1CSNark::verify` accepts for `ivk` and
+// `input`, which are constants of the circuit. The proof is the only witness.
+struct MetaVerifierCircuit<'a, G, C, S, SV>
+where
+    G: AffineCurve,
+{
+    ivk: &'a IndexVerifierKey<G>,
+    input: &'a [G::ScalarField],
+    proof: Proof<G>,
+    _curve: PhantomData<C>,
+    _sponge: PhantomData<S>,
+    _sponge_var: PhantomData<SV>,
+}
+
+impl<'a, G, C, S, SV> MetaVerifierCircuit<'a, G, C, S, SV>
+where
+    G: AffineCurve,
+{
+    fn new(ivk: &'a IndexVerifierKey<G>, input: &'a [G::ScalarField], proof: Proof<G>) -> Self {
+        Self {
+            ivk,
+            input,
+            proof,
+            _curve: PhantomData,
+            _sponge: PhantomData,
+            _sponge_var: PhantomData,
+        }
+    }
+
+    // The circuit with a placeholder proof of the right shape, which is enough to index it.
+    fn blank(ivk: &'a IndexVerifierKey<G>, input: &'a [G::ScalarField], is_zk: bool) -> Self {
+        let index_info = &ivk.index_info;
+        let witness_len = index_info.num_variables - index_info.num_instance_variables;
+        let proof = Proof {
+            first_msg: FirstRoundMessage::zero(is_zk),
+            second_msg: SecondRoundMessage::zero(witness_len, is_zk),
+        };
+        Self::new(ivk, input, proof)
+    }
+}
+
+impl<'a, G, C, S, SV> ConstraintSynthesizer<ConstraintF<G>> for MetaVerifierCircuit<'a, G, C, S, SV>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    C: CurveVar<G::Projective, ConstraintF<G>> + AbsorbableGadget<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+    SV: CryptographicSpongeVar<ConstraintF<G>, S>,
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF<G>>,
+    ) -> Result<(), SynthesisError> {
+        let ivk = IndexVerifierKeyVar::<G, C>::new_constant(cs.clone(), self.ivk)?;
+        let input = self
+            .input
+            .iter()
+            .map(|elem| NonNativeFieldVar::new_constant(cs.clone(), elem))
+            .collect::<Result<Vec<_>, _>>()?;
+        let proof = ProofVar::<G, C>::new_witness(cs.clone(), || Ok(&self.proof))?;
+
+        let is_valid =
+            R1CSNarkVerifierGadget::<G, C, S, SV>::verify(cs, &ivk, &input, &proof, None)?;
+        is_valid.enforce_equal(&Boolean::TRUE)
+    }
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Proves knowledge of a `proof` that [`R1CSNark::verify`] accepts for `ivk` and `input`,
+    /// without revealing it. The verifier of the NARK is run as a circuit with
+    /// [`R1CSNarkVerifierGadget`], which is proven with an [`R1CSNark`] over `H` and `sponge`.
+    /// The meta-proof is zero knowledge if `rng` is provided.
+    ///
+    /// The hidden proof must verify with the default sponge, since the verifier circuit does not
+    /// take one. A meta-proof of a proof that does not verify is rejected by
+    /// [`R1CSNark::verify_meta`].
+    pub fn prove_meta<H, T, C, SV>(
+        pp: &PublicParameters,
+        ivk: &IndexVerifierKey<G>,
+        proof: &Proof<G>,
+        input: &[G::ScalarField],
+        sponge: Option<T>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> R1CSResult<MetaProof<H>>
+    where
+        H: AffineCurve<ScalarField = ConstraintF<G>> + Absorbable<ConstraintF<H>>,
+        ConstraintF<H>: Absorbable<ConstraintF<H>>,
+        T: CryptographicSponge<ConstraintF<H>>,
+        C: CurveVar<G::Projective, ConstraintF<G>> + AbsorbableGadget<ConstraintF<G>>,
+        SV: CryptographicSpongeVar<ConstraintF<G>, S>,
+    {
+        let inner_is_zk = proof.first_msg.randomness.is_some();
+        let blank = MetaVerifierCircuit::<G, C, S, SV>::blank(ivk, input, inner_is_zk);
+        let (meta_ipk, _) = R1CSNark::<H, T>::index(pp, blank)?;
+
+        let circuit = MetaVerifierCircuit::<G, C, S, SV>::new(ivk, input, proof.clone());
+        let make_zk = rng.is_some();
+        let proof = R1CSNark::<H, T>::prove(&meta_ipk, circuit, make_zk, sponge, rng)?;
+
+        Ok(MetaProof { proof, inner_is_zk })
+    }
+
+    /// Verifies a meta-proof produced by [`R1CSNark::prove_meta`] for `ivk` and `input`, which
+    /// shows that its prover knows a proof that [`R1CSNark::verify`] accepts for them. Returns
+    /// false if the verifier circuit cannot be indexed.
+    pub fn verify_meta<H, T, C, SV>(
+        pp: &PublicParameters,
+        ivk: &IndexVerifierKey<G>,
+        input: &[G::ScalarField],
+        meta_proof: &MetaProof<H>,
+        sponge: Option<T>,
+    ) -> bool
+    where
+        H: AffineCurve<ScalarField = ConstraintF<G>> + Absorbable<ConstraintF<H>>,
+        ConstraintF<H>: Absorbable<ConstraintF<H>>,
+        T: CryptographicSponge<ConstraintF<H>>,
+        C: CurveVar<G::Projective, ConstraintF<G>> + AbsorbableGadget<ConstraintF<G>>,
+        SV: CryptographicSpongeVar<ConstraintF<G>, S>,
+    {
+        // The circuit depends on `ivk` and `input`, so the meta-proof only verifies under the
+        // index of the statement that it was proven for.
+        let blank = MetaVerifierCircuit::<G, C, S, SV>::blank(ivk, input, meta_proof.inner_is_zk);
+        let meta_ivk = match R1CSNark::<H, T>::index(pp, blank) {
+            Ok((_, meta_ivk)) => meta_ivk,
+            Err(_) => return false,
+        };
+
+        let meta_input = [ConstraintF::<G>::one()];
+        R1CSNark::<H, T>::verify(&meta_ivk, &meta_input, &meta_proof.proof, sponge).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::constraints::GVar;
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::constraints::PoseidonSpongeVar;
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+    type Vesta = ark_vesta::Affine;
+
+    #[test]
+    fn test_prove_meta() {
+        let rng = &mut ark_std::test_rng();
+        let circuit = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 5,
+            num_constraints: 4,
+        };
+        let input = vec![Fr::one(), circuit.a.unwrap() * circuit.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, circuit).unwrap();
+        let proof = Nark::prove(&ipk, circuit, true, None, Some(rng)).unwrap();
+        assert!(Nark::verify(&ivk, &input, &proof, None).unwrap());
+
+        let meta_proof =
+            Nark::prove_meta::<Vesta, PoseidonSponge<Fr>, GVar, PoseidonSpongeVar<Fq>>(
+                &pp,
+                &ivk,
+                &proof,
+                &input,
+                None,
+                Some(rng),
+            )
+            .unwrap();
+        assert!(meta_proof.inner_is_zk);
+
+        let verify_meta = |input: &[Fr], meta_proof: &MetaProof<Vesta>| {
+            Nark::verify_meta::<Vesta, PoseidonSponge<Fr>, GVar, PoseidonSpongeVar<Fq>>(
+                &pp, &ivk, input, meta_proof, None,
+            )
+        };
+        assert!(verify_meta(&input, &meta_proof));
+
+        let wrong_input = vec![Fr::one(), input[1] + Fr::one()];
+        assert!(!verify_meta(&wrong_input, &meta_proof));
+
+        let mut tampered = meta_proof.clone();
+        tampered.proof.second_msg.blinded_witness[0] += Fq::one();
+        assert!(!verify_meta(&input, &tampered));
+
+        let mut tampered = meta_proof;
+        tampered.inner_is_zk = false;
+        assert!(!verify_meta(&input, &tampered));
+    }
+}
@@ -0,0 +1,151 @@
+use super::{IndexProverKey, IndexVerifierKey, Proof, R1CSNark, R1CSResult};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_serialize::CanonicalSerialize;
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::rand::RngCore;
+use std::time::Instant;
+
+/// Statistics about the generation or verification of a proof, for performance analysis and
+/// audit logs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofMetadata {
+    /// The hash of the matrices of the index.
+    pub circuit_hash: [u8; 32],
+
+    /// The time taken to generate the proof, in milliseconds, or `0` if it was not generated.
+    pub prove_time_ms: u64,
+
+    /// The time taken to verify the proof, in milliseconds, or `0` if it was not verified.
+    pub verify_time_ms: u64,
+
+    /// The size of the canonical serialization of the proof.
+    pub proof_size_bytes: usize,
+
+    /// The number of constraints of the index.
+    pub num_constraints: usize,
+
+    /// The number of threads available to the prover or verifier.
+    pub prover_thread_count: usize,
+}
+
+impl ProofMetadata {
+    fn new<G: AffineCurve>(ivk: &IndexVerifierKey<G>, proof: &Proof<G>) -> Self {
+        Self {
+            circuit_hash: ivk.index_info.matrices_hash,
+            prove_time_ms: 0,
+            verify_time_ms: 0,
+            proof_size_bytes: proof.serialized_size(),
+            num_constraints: ivk.index_info.num_constraints,
+            prover_thread_count: thread_count(),
+        }
+    }
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Proves that some R1CS relation holds as [`R1CSNark::prove`] does, and records the
+    /// [`ProofMetadata`] of the proof.
+    pub fn prove_with_metadata<C: ConstraintSynthesizer<G::ScalarField>>(
+        ipk: &IndexProverKey<G>,
+        r1cs: C,
+        make_zk: bool,
+        sponge: Option<S>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> R1CSResult<(Proof<G>, ProofMetadata)> {
+        let start = Instant::now();
+        let proof = Self::prove(ipk, r1cs, make_zk, sponge, rng)?;
+        let prove_time_ms = start.elapsed().as_millis() as u64;
+
+        let metadata = ProofMetadata {
+            prove_time_ms,
+            ..ProofMetadata::new(ipk, &proof)
+        };
+        Ok((proof, metadata))
+    }
+
+    /// Verifies that some R1CS relation holds as [`R1CSNark::verify`] does, and records the
+    /// [`ProofMetadata`] of the verification. A malformed input or proof is rejected rather than
+    /// reported as an error.
+    pub fn verify_with_metadata(
+        ivk: &IndexVerifierKey<G>,
+        input: &[G::ScalarField],
+        proof: &Proof<G>,
+        sponge: Option<S>,
+    ) -> (bool, ProofMetadata) {
+        let start = Instant::now();
+        let result = Self::verify(ivk, input, proof, sponge).unwrap_or(false);
+        let verify_time_ms = start.elapsed().as_millis() as u64;
+
+        let metadata = ProofMetadata {
+            verify_time_ms,
+            ..ProofMetadata::new(ivk, proof)
+        };
+        (result, metadata)
+    }
+}
+
+// The number of threads that parallel iterators run on.
+fn thread_count() -> usize {
+    #[cfg(feature = "parallel")]
+    {
+        rayon::current_num_threads()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        1
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_proof_metadata() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let (proof, prove_metadata) =
+            Nark::prove_with_metadata(&ipk, c, true, None, Some(rng)).unwrap();
+        assert_eq!(
+            prove_metadata.num_constraints,
+            ipk.index_info.num_constraints
+        );
+        assert_eq!(prove_metadata.circuit_hash, ipk.index_info.matrices_hash);
+        assert_eq!(prove_metadata.proof_size_bytes, proof.serialized_size());
+        assert_eq!(prove_metadata.verify_time_ms, 0);
+
+        let (result, verify_metadata) = Nark::verify_with_metadata(&ivk, &r1cs_input, &proof, None);
+        assert!(result);
+        assert_eq!(
+            verify_metadata.num_constraints,
+            ivk.index_info.num_constraints
+        );
+        assert_eq!(verify_metadata.prove_time_ms, 0);
+
+        let wrong_input = vec![Fr::one(), Fr::rand(rng)];
+        assert!(!Nark::verify_with_metadata(&ivk, &wrong_input, &proof, None).0);
+    }
+}
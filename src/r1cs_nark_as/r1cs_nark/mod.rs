@@ -2,7 +2,7 @@ use crate::r1cs_nark_as::CHALLENGE_SIZE;
 use crate::ConstraintF;
 
 use ark_ec::AffineCurve;
-use ark_ff::{BigInteger, Field, PrimeField, Zero};
+use ark_ff::{BigInteger, Field, PrimeField, ToConstraintField, Zero};
 use ark_poly_commit::trivial_pc::PedersenCommitment;
 use ark_relations::r1cs::{
     ConstraintSynthesizer, ConstraintSystem, Matrix, OptimizationGoal, SynthesisError,
@@ -13,7 +13,7 @@ use ark_sponge::{absorb, Absorbable, CryptographicSponge, FieldElementSize};
 use ark_std::rand::RngCore;
 use ark_std::vec;
 use ark_std::vec::Vec;
-use ark_std::{cfg_into_iter, cfg_iter, marker::PhantomData, UniformRand};
+use ark_std::{cfg_into_iter, cfg_iter, marker::PhantomData};
 use blake2::{digest::VariableOutput, VarBlake2b};
 
 #[cfg(feature = "parallel")]
@@ -22,10 +22,238 @@ use rayon::prelude::*;
 mod data_structures;
 pub use data_structures::*;
 
-type R1CSResult<T> = Result<T, SynthesisError>;
+mod error;
+pub use error::*;
+
+mod extension;
+pub use extension::*;
+
+mod hashed_input;
+pub use hashed_input::*;
+
+mod padding;
+pub use padding::*;
+
+mod dynamic;
+pub use dynamic::*;
+
+mod extractor;
+
+mod precomputed;
+pub use precomputed::*;
+
+mod hints;
+pub use hints::*;
+
+mod shared;
+pub use shared::*;
+
+mod labeled;
+pub use labeled::*;
+
+mod witness_builder;
+pub use witness_builder::*;
+
+mod composition;
+pub use composition::*;
+
+mod decommitment;
+pub use decommitment::*;
+
+mod public_coin;
+pub use public_coin::*;
+
+mod merkle;
+pub use merkle::*;
+
+mod on_chain;
+pub use on_chain::*;
+
+mod streaming;
+pub use streaming::*;
+
+mod non_private;
+pub use non_private::*;
+
+mod equivalence;
+
+mod lint;
+pub use lint::*;
+
+mod gates;
+pub use gates::*;
+
+mod transcript;
+pub use transcript::*;
+
+mod instrumented;
+pub use instrumented::*;
+
+mod lazy;
+pub use lazy::*;
+
+mod batched;
+pub use batched::*;
+
+mod circuit_witness;
+pub use circuit_witness::*;
+
+mod transpose;
+pub use transpose::*;
+
+mod test_rng;
+
+mod boolean;
+pub use boolean::*;
+
+mod interleaved;
+pub use interleaved::*;
+
+mod symmetry;
+pub use symmetry::*;
+
+mod piggyback;
+pub use piggyback::*;
+
+mod custom_gate;
+pub use custom_gate::*;
+
+mod transition;
+pub use transition::*;
+
+mod sumcheck;
+pub use sumcheck::*;
+
+mod oracle;
+pub use oracle::*;
+
+mod pedersen_vector;
+pub use pedersen_vector::*;
+
+#[cfg(feature = "parallel")]
+mod decomposed;
+#[cfg(feature = "parallel")]
+pub use decomposed::*;
+
+mod output;
+
+#[cfg(feature = "std")]
+mod cache;
+
+#[cfg(feature = "std")]
+mod metadata;
+#[cfg(feature = "std")]
+pub use metadata::*;
+
+#[cfg(feature = "std")]
+mod timeout;
+
+#[cfg(feature = "debug-verify")]
+mod debug_verify;
+#[cfg(feature = "debug-verify")]
+pub use debug_verify::*;
+
+#[cfg(feature = "debug-trace")]
+mod debug_trace;
+#[cfg(feature = "debug-trace")]
+pub use debug_trace::*;
+
+#[cfg(feature = "profiling")]
+mod profiler;
+#[cfg(feature = "profiling")]
+pub use profiler::*;
+
+#[cfg(feature = "serde")]
+mod serde_hex;
+#[cfg(feature = "serde")]
+pub use serde_hex::*;
+
+#[cfg(feature = "constant-time")]
+mod constant_time;
+#[cfg(feature = "constant-time")]
+pub use constant_time::*;
+
+#[cfg(feature = "circom-compat")]
+mod circom;
+
+#[cfg(feature = "kat")]
+mod kat;
+#[cfg(feature = "kat")]
+pub use kat::*;
+
+#[cfg(feature = "pki")]
+mod pki;
+#[cfg(feature = "pki")]
+pub use pki::*;
+
+#[cfg(feature = "recursive")]
+mod meta;
+#[cfg(feature = "recursive")]
+pub use meta::*;
+
+#[cfg(feature = "kzg")]
+mod kzg;
+#[cfg(feature = "kzg")]
+pub use kzg::*;
+
+#[cfg(feature = "compact-verifier")]
+mod compact;
+#[cfg(feature = "compact-verifier")]
+pub use compact::*;
+
+mod incremental;
+
+#[cfg(test)]
+mod reference;
+
+#[cfg(test)]
+mod tests;
+
+/// A universal setup for the commitment keys used by [`R1CSNark`].
+pub mod setup;
+
+/// A brute-force search for satisfying witnesses of small R1CS instances. This is meant for
+/// testing circuits during development, and is far too slow for anything else.
+pub mod solver;
+use setup::CommitmentKey;
+
+/// Constraints for recomputing the hash of an R1CS instance's matrices.
+#[cfg(feature = "r1cs")]
+pub mod constraints;
+
+type R1CSResult<T> = Result<T, NarkError>;
+
+// The products of the `A`, `B`, and `C` matrices with a vector.
+pub(crate) type MatrixVecProducts<F> = (Vec<F>, Vec<F>, Vec<F>);
+
+// The matrix whose commitment is checked by the verifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "debug-verify"), allow(dead_code))]
+pub(crate) enum CommitmentCheck {
+    A,
+    B,
+    C,
+}
+
+// A check of the verifier that failed. The commitments are the one derived from the proof and
+// the one reconstructed by the verifier, in that order.
+#[cfg_attr(not(feature = "debug-verify"), allow(dead_code))]
+pub(crate) enum FailedCheck<G: AffineCurve> {
+    Randomness,
+    Commitment(CommitmentCheck, G, G),
+    HadamardProduct(G, G),
+}
 
 pub(crate) const PROTOCOL_NAME: &[u8] = b"R1CS-NARK-2020";
 
+/// The version of the protocol implemented by [`R1CSNark`]. It is stored in every index, and is
+/// incremented with every change that makes existing keys or proofs incompatible.
+pub const CURRENT_PROTOCOL_VERSION: u32 = 2;
+
+/// The format version of serialized [`Proof`]s, which is written as their first byte so that the
+/// format can change while proofs in older formats are still recognized.
+pub const PROOF_FORMAT_VERSION: u8 = 1;
+
 /// A simple non-interactive argument of knowledge for R1CS.
 /// The construction is described in detail in Section 8 of [\[BCLMS20\]][bclms20].
 ///
@@ -71,18 +299,130 @@ where
         out
     }
 
+    // Returns `sponge`, or a new sponge if it is `None`, after absorbing the application domain
+    // and the circuit version of `ipk` if they are set.
+    pub(crate) fn init_sponge(ipk: &IndexProverKey<G>, sponge: Option<S>) -> S {
+        let mut sponge = sponge.unwrap_or_else(|| S::new());
+        if !ipk.app_domain.is_empty() {
+            sponge.absorb(&ipk.app_domain);
+        }
+        if ipk.index_info.hash_public_input {
+            sponge.absorb(&INPUT_DIGEST_DOMAIN);
+        }
+        if ipk.index_info.circuit_version != 0 {
+            sponge.absorb(&ipk.index_info.circuit_version.to_le_bytes().as_ref());
+        }
+        sponge
+    }
+
     /// Performs a setup for R1CS. This function does not currently do anything meaning.
     pub fn setup() -> PublicParameters {}
 
     /// Outputs a specialized prover and verifier key for some R1CS instance.
     pub fn index<C: ConstraintSynthesizer<G::ScalarField>>(
-        _pp: &PublicParameters,
+        pp: &PublicParameters,
+        r1cs_instance: C,
+    ) -> R1CSResult<(IndexProverKey<G>, IndexVerifierKey<G>)> {
+        Self::index_with_config(pp, r1cs_instance, &NarkConfig::default())
+    }
+
+    /// Outputs a specialized prover and verifier key for some R1CS instance, synthesized with the
+    /// optimization goal of `config`. Trivially satisfied constraints are removed from the
+    /// instance with [`normalize_matrices`].
+    pub fn index_with_config<C: ConstraintSynthesizer<G::ScalarField>>(
+        pp: &PublicParameters,
         r1cs_instance: C,
+        config: &NarkConfig,
     ) -> R1CSResult<(IndexProverKey<G>, IndexVerifierKey<G>)> {
+        let (a, b, c, num_input_variables, num_witness_variables) =
+            Self::constraint_matrices(r1cs_instance, config)?;
+        let num_circuit_constraints = a.len();
+        let (a, b, c, num_pruned_constraints) = normalize_matrices(a, b, c);
+
+        let (mut ipk, mut ivk) =
+            Self::index_from_matrices(pp, a, b, c, num_input_variables, num_witness_variables)?;
+        ipk.index_info.num_pruned_constraints = num_pruned_constraints;
+        ivk.index_info.num_pruned_constraints = num_pruned_constraints;
+        ipk.index_info.num_circuit_constraints = num_circuit_constraints;
+        ivk.index_info.num_circuit_constraints = num_circuit_constraints;
+        Ok((ipk, ivk))
+    }
+
+    /// Outputs a specialized prover and verifier key for version `circuit_version` of some R1CS
+    /// instance. The prover and verifier absorb a non-zero version into the sponge, so a
+    /// zero-knowledge proof for one version of a circuit does not verify under the key of another.
+    pub fn index_with_version<C: ConstraintSynthesizer<G::ScalarField>>(
+        pp: &PublicParameters,
+        r1cs_instance: C,
+        circuit_version: u32,
+    ) -> R1CSResult<(IndexProverKey<G>, IndexVerifierKey<G>)> {
+        let (mut ipk, mut ivk) = Self::index(pp, r1cs_instance)?;
+        ipk.index_info.circuit_version = circuit_version;
+        ivk.index_info.circuit_version = circuit_version;
+        Ok((ipk, ivk))
+    }
+
+    /// Outputs a specialized prover and verifier key for some R1CS instance, after removing every
+    /// matrix entry whose coefficient is zero or has a magnitude less than that of `threshold`,
+    /// where the magnitude of `x` is `min(x, p - x)` for `x` viewed as an integer in `[0, p)`.
+    /// This is meant for instances imported from external formats that contain spurious entries.
+    /// As in [`index`][Self::index], trivially satisfied constraints are then removed with
+    /// [`normalize_matrices`]. The keys record the number of removed entries, which is returned
+    /// by [`IndexProverKey::num_pruned_entries`] and bound into the matrices hash, so a verifier
+    /// can tell a pruned index from the index of the original instance.
+    ///
+    /// Removing an entry changes the relation unless the variable it multiplies is always zero,
+    /// so the threshold must only cover entries that are known to be spurious.
+    pub fn index_with_threshold<C: ConstraintSynthesizer<G::ScalarField>>(
+        pp: &PublicParameters,
+        r1cs_instance: C,
+        threshold: G::ScalarField,
+    ) -> R1CSResult<(IndexProverKey<G>, IndexVerifierKey<G>)> {
+        let (mut a, mut b, mut c, num_input_variables, num_witness_variables) =
+            Self::constraint_matrices(r1cs_instance, &NarkConfig::default())?;
+
+        let magnitude = |x: G::ScalarField| ark_std::cmp::min(x.into_repr(), (-x).into_repr());
+        let threshold = magnitude(threshold);
+        let mut num_pruned_entries = 0;
+        for row in a.iter_mut().chain(b.iter_mut()).chain(c.iter_mut()) {
+            let len = row.len();
+            row.retain(|(coeff, _)| !coeff.is_zero() && magnitude(*coeff) >= threshold);
+            num_pruned_entries += len - row.len();
+        }
+        let num_circuit_constraints = a.len();
+        let (a, b, c, num_pruned_constraints) = normalize_matrices(a, b, c);
+
+        let (mut ipk, _) =
+            Self::index_from_matrices(pp, a, b, c, num_input_variables, num_witness_variables)?;
+        ipk.index_info.num_pruned_constraints = num_pruned_constraints;
+        ipk.index_info.num_circuit_constraints = num_circuit_constraints;
+        ipk.index_info.num_pruned_entries = num_pruned_entries;
+        ipk.index_info.matrices_hash = hash_matrices(
+            &matrices_hash_domain(&ipk.index_info),
+            &ipk.a,
+            &ipk.b,
+            &ipk.c,
+        );
+        let ivk = ipk.clone();
+        Ok((ipk, ivk))
+    }
+
+    // Generates the matrices of `r1cs_instance` with the optimization goal of `config`, and
+    // returns them with the numbers of instance and witness variables.
+    fn constraint_matrices<C: ConstraintSynthesizer<G::ScalarField>>(
+        r1cs_instance: C,
+        config: &NarkConfig,
+    ) -> R1CSResult<(
+        Matrix<G::ScalarField>,
+        Matrix<G::ScalarField>,
+        Matrix<G::ScalarField>,
+        usize,
+        usize,
+    )> {
         let constraint_time = start_timer!(|| "Generating constraints");
 
         let ics = ConstraintSystem::new_ref();
-        ics.set_optimization_goal(OptimizationGoal::Constraints);
+        ics.set_optimization_goal(config.optimization_goal);
         ics.set_mode(SynthesisMode::Setup);
         r1cs_instance.generate_constraints(ics.clone())?;
 
@@ -100,381 +440,753 @@ where
         );
 
         end_timer!(matrix_processing_time);
+        if num_constraints != a.len() {
+            return Err(SynthesisError::MalformedVerifyingKey.into());
+        }
+
+        Ok((a, b, c, num_input_variables, num_witness_variables))
+    }
+
+    /// Outputs a specialized prover and verifier key for some R1CS instance that is bound to the
+    /// application domain `app_domain`. The prover and verifier absorb `app_domain` into the
+    /// sponge before anything else, so a proof for one domain does not verify under another, even
+    /// if the R1CS instances are identical.
+    ///
+    /// Note that only zero-knowledge proofs depend on the challenge. A proof that is not
+    /// zero-knowledge reveals the witness, so it verifies under any domain.
+    pub fn index_with_app_domain<C: ConstraintSynthesizer<G::ScalarField>>(
+        pp: &PublicParameters,
+        r1cs_instance: C,
+        app_domain: &[u8],
+    ) -> R1CSResult<(IndexProverKey<G>, IndexVerifierKey<G>)> {
+        let (mut ipk, mut ivk) = Self::index(pp, r1cs_instance)?;
+        ipk.app_domain = app_domain.to_vec();
+        ivk.app_domain = app_domain.to_vec();
+        Ok((ipk, ivk))
+    }
+
+    /// Outputs a specialized prover and verifier key for an R1CS instance given directly by its
+    /// matrices. The first `num_instance_variables` columns of the matrices correspond to the
+    /// public input, including the constant `1`, and the next `num_witness_variables` columns to
+    /// the witness.
+    pub fn index_from_matrices(
+        _pp: &PublicParameters,
+        a: Matrix<G::ScalarField>,
+        b: Matrix<G::ScalarField>,
+        c: Matrix<G::ScalarField>,
+        num_instance_variables: usize,
+        num_witness_variables: usize,
+    ) -> R1CSResult<(IndexProverKey<G>, IndexVerifierKey<G>)> {
+        let num_constraints = a.len();
+        let num_variables = num_instance_variables + num_witness_variables;
+        validate_matrices(&a, &b, &c, num_constraints, num_variables)?;
 
-        let matrices_hash = hash_matrices(PROTOCOL_NAME, &a, &b, &c);
+        let (a, b, c) = (CsrMatrix::from(a), CsrMatrix::from(b), CsrMatrix::from(c));
 
-        let num_variables = num_input_variables + num_witness_variables;
         let pp = PedersenCommitment::setup(num_constraints);
         let ck = PedersenCommitment::trim(&pp, num_constraints);
-        let index_info = IndexInfo {
+        let mut index_info = IndexInfo {
             num_variables,
             num_constraints,
-            num_instance_variables: num_input_variables,
-            matrices_hash,
+            num_instance_variables,
+            matrices_hash: [0u8; 32],
+            hash_public_input: false,
+            protocol_version: CURRENT_PROTOCOL_VERSION,
+            circuit_version: 0,
+            num_pruned_constraints: 0,
+            num_circuit_constraints: 0,
+            is_padded: false,
+            num_pruned_entries: 0,
+            matrix_symmetries: MatrixSymmetries::detect(&a, &b, &c),
         };
+        index_info.matrices_hash = hash_matrices(&matrices_hash_domain(&index_info), &a, &b, &c);
+        let matrix_strategy = MatrixStrategy::AutoDetect;
+        let matrix_reprs = MatrixRepr::from_matrices(&a, &b, &c, num_variables, matrix_strategy);
+        let transposes = transpose_matrices(&a, &b, &c, num_variables);
+
         let ipk = IndexProverKey {
             index_info,
             a,
             b,
             c,
             ck,
+            matrix_reprs,
+            transposes,
+            matrix_strategy,
+            input_labels: None,
+            app_domain: Vec::new(),
         };
         let ivk = ipk.clone();
         Ok((ipk, ivk))
     }
 
-    /// Proves that some R1CS relation holds.
+    /// Reduces a universal commitment key to the exact size required by the R1CS instance of
+    /// `ipk`. The outputted key can be used by both the prover and verifier in place of `ipk`.
+    /// Returns an error if the instance has more constraints than `ck` supports.
+    pub fn trim(ck: &CommitmentKey<G>, ipk: &IndexProverKey<G>) -> R1CSResult<TrimmedKey<G>> {
+        let num_constraints = ipk.index_info.num_constraints;
+        if num_constraints > ck.max_degree {
+            return Err(NarkError::CommitmentKeyTooShort {
+                needed: num_constraints,
+                have: ck.max_degree,
+            });
+        }
+
+        Ok(IndexProverKey {
+            ck: PedersenCommitment::trim(&ck.pp, num_constraints),
+            ..ipk.clone()
+        })
+    }
+
+    // Generates the input and witness assignments of `r1cs`.
+    pub(crate) fn synthesize<C: ConstraintSynthesizer<G::ScalarField>>(
+        ipk: &IndexProverKey<G>,
+        r1cs: C,
+    ) -> R1CSResult<(Vec<G::ScalarField>, Vec<G::ScalarField>)> {
+        PubCoinR1CSNark::synthesize(ipk, r1cs, OptimizationGoal::Constraints)
+    }
+
+    /// Proves that some R1CS relation holds. Returns an error if the commitment key of `ipk` is
+    /// too short for the instance.
     pub fn prove<C: ConstraintSynthesizer<G::ScalarField>>(
         ipk: &IndexProverKey<G>,
         r1cs: C,
         make_zk: bool,
         sponge: Option<S>,
-        mut rng: Option<&mut dyn RngCore>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> R1CSResult<Proof<G>> {
+        let config = NarkConfig {
+            zk: make_zk,
+            ..NarkConfig::default()
+        };
+        Self::prove_with_config(ipk, r1cs, &config, sponge, rng)
+    }
+
+    /// Proves that some R1CS relation holds according to `config`. `ipk` must have been produced
+    /// by [`R1CSNark::index_with_config`] with the same optimization goal.
+    pub fn prove_with_config<C: ConstraintSynthesizer<G::ScalarField>>(
+        ipk: &IndexProverKey<G>,
+        r1cs: C,
+        config: &NarkConfig,
+        sponge: Option<S>,
+        rng: Option<&mut dyn RngCore>,
     ) -> R1CSResult<Proof<G>> {
+        // The key is checked before the circuit is run, since computing the witness may be
+        // expensive.
+        check_prover_key(ipk)?;
+
         let init_time = start_timer!(|| "NARK::Prover");
 
         // Step 1 of the scheme's prover, as detailed in BCLMS20.
-        let constraint_time = start_timer!(|| "Generating constraints and witnesses");
-        let pcs = ConstraintSystem::new_ref();
-        pcs.set_optimization_goal(OptimizationGoal::Constraints);
-        pcs.set_mode(ark_relations::r1cs::SynthesisMode::Prove {
-            construct_matrices: false,
-        });
-        r1cs.generate_constraints(pcs.clone())?;
-        end_timer!(constraint_time);
+        let (input, witness) = PubCoinR1CSNark::synthesize(ipk, r1cs, config.optimization_goal)?;
 
-        pcs.finalize();
-        let (input, witness, num_constraints) = {
-            let pcs = pcs.borrow().unwrap();
-            (
-                pcs.instance_assignment.as_slice().to_vec(),
-                pcs.witness_assignment.as_slice().to_vec(),
-                pcs.num_constraints,
-            )
-        };
+        // Catch witnesses that do not satisfy the R1CS instance during development.
+        #[cfg(debug_assertions)]
+        {
+            if let Some(row) = first_unsatisfied_row(&ipk.a, &ipk.b, &ipk.c, &input, &witness) {
+                return Err(NarkError::UnsatisfiedConstraint { row });
+            }
+        }
 
-        let num_input_variables = input.len();
-        let num_witness_variables = witness.len();
-        let num_variables = num_input_variables + num_witness_variables;
+        let proof = Self::prove_with_assignment(ipk, input, witness, config.zk, sponge, rng);
+
+        end_timer!(init_time);
+        Ok(proof)
+    }
+
+    /// Proves that some R1CS relation holds for the assignment returned by `witness_fn`, without
+    /// running a circuit. `witness_fn` is only called once `ipk` has been checked, so that an
+    /// expensive witness computation is skipped when the key is unusable. The proof is zero
+    /// knowledge if `rng` is provided.
+    ///
+    /// Returns an error if the assignment does not have the lengths expected by `ipk`. The
+    /// witness may be shorter than expected if `ipk` was padded, in which case it is padded with
+    /// zeros.
+    pub fn prove_lazy(
+        ipk: &IndexProverKey<G>,
+        witness_fn: impl FnOnce() -> R1CSResult<FullAssignment<G::ScalarField>>,
+        sponge: Option<S>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> R1CSResult<Proof<G>> {
+        check_prover_key(ipk)?;
 
-        assert_eq!(ipk.index_info.num_variables, num_variables);
-        assert_eq!(ipk.index_info.num_constraints, num_constraints);
+        let init_time = start_timer!(|| "NARK::LazyProver");
+        let mut assignment = witness_fn()?;
 
-        // Step 2 of the scheme's prover, as detailed in BCLMS20.
-        let r = if make_zk {
-            // Sample r
-            let randomizer_time = start_timer!(|| "Sampling randomizer r");
+        let index_info = &ipk.index_info;
+        if assignment.input.len() != index_info.num_instance_variables {
+            return Err(NarkError::MalformedInput {
+                expected_len: index_info.num_instance_variables,
+                got: assignment.input.len(),
+            });
+        }
+        let witness_len = index_info.num_variables - index_info.num_instance_variables;
+        if assignment.witness.len() > witness_len {
+            return Err(NarkError::MalformedWitness {
+                expected_len: witness_len,
+                got: assignment.witness.len(),
+            });
+        }
+        pad_assignment(&mut assignment, index_info.num_variables);
 
-            let rng = rng.as_mut().unwrap();
-            let mut r = Vec::with_capacity(num_witness_variables);
-            for _ in 0..num_witness_variables {
-                r.push(G::ScalarField::rand(rng))
+        let FullAssignment { input, witness } = assignment;
+        #[cfg(debug_assertions)]
+        {
+            if let Some(row) = first_unsatisfied_row(&ipk.a, &ipk.b, &ipk.c, &input, &witness) {
+                return Err(NarkError::UnsatisfiedConstraint { row });
             }
+        }
 
-            end_timer!(randomizer_time);
-
-            Some(r)
-        } else {
-            None
-        };
-
-        // Step 3 of the scheme's prover, as detailed in BCLMS20.
-        let eval_z_m_time = start_timer!(|| "Evaluating z_M");
-        let z_a = matrix_vec_mul(&ipk.a, &input, &witness);
-        let z_b = matrix_vec_mul(&ipk.b, &input, &witness);
-        let z_c = matrix_vec_mul(&ipk.c, &input, &witness);
-        end_timer!(eval_z_m_time);
-
-        let (r_a, r_b, r_c) = if make_zk {
-            let r_ref = r.as_ref().unwrap();
-            let zeros = vec![G::ScalarField::zero(); num_input_variables];
-
-            // Compute r_a, r_b, r_c.
-            let eval_r_m_time = start_timer!(|| "Evaluating r_M");
-            let r_a = matrix_vec_mul(&ipk.a, &zeros, r_ref);
-            let r_b = matrix_vec_mul(&ipk.b, &zeros, r_ref);
-            let r_c = matrix_vec_mul(&ipk.c, &zeros, r_ref);
-            end_timer!(eval_r_m_time);
-
-            (Some(r_a), Some(r_b), Some(r_c))
-        } else {
-            (None, None, None)
-        };
-
-        // Step 4 of the scheme's prover, as detailed in BCLMS20.
-        // Sample blinders for z_a, z_b, z_c.
-        let (mut a_blinder, mut b_blinder, mut c_blinder) = (None, None, None);
-        if make_zk {
-            let rng = rng.as_mut().unwrap();
-            a_blinder = Some(G::ScalarField::rand(rng));
-            b_blinder = Some(G::ScalarField::rand(rng));
-            c_blinder = Some(G::ScalarField::rand(rng));
-        }
-
-        let commit_time = start_timer!(|| "Committing to z_A, z_B, and z_C");
-        // Compute hiding commitments to z_a, z_b, z_c.
-        let comm_a = PedersenCommitment::commit(&ipk.ck, &z_a, a_blinder);
-        let comm_b = PedersenCommitment::commit(&ipk.ck, &z_b, b_blinder);
-        let comm_c = PedersenCommitment::commit(&ipk.ck, &z_c, c_blinder);
-
-        end_timer!(commit_time);
-
-        let (mut r_a_blinder, mut r_b_blinder, mut r_c_blinder) = (None, None, None);
-        let (mut blinder_1, mut blinder_2) = (None, None);
-        let first_round_randomness = if make_zk {
-            let rng = rng.as_mut().unwrap();
-
-            // Sample blinders for r_a, r_b, r_c.
-            r_a_blinder = Some(G::ScalarField::rand(rng));
-            r_b_blinder = Some(G::ScalarField::rand(rng));
-            r_c_blinder = Some(G::ScalarField::rand(rng));
-
-            // Commit to r_a, r_b, r_c.
-            let commit_time = start_timer!(|| "Committing to r_A, r_B, r_C");
-            let comm_r_a = PedersenCommitment::commit(&ipk.ck, r_a.as_ref().unwrap(), r_a_blinder);
-            let comm_r_b = PedersenCommitment::commit(&ipk.ck, r_b.as_ref().unwrap(), r_b_blinder);
-            let comm_r_c = PedersenCommitment::commit(&ipk.ck, r_c.as_ref().unwrap(), r_c_blinder);
-            end_timer!(commit_time);
-
-            // Step 5 of the scheme's prover, as detailed in BCLMS20.
-            // Commit to z_a ○ r_b + z_b ○ r_a.
-            let cross_prod_time = start_timer!(|| "Computing cross product z_a ○ r_b + z_b ○ r_a");
-            let z_a_times_r_b = cfg_iter!(z_a).zip(r_b.as_ref().unwrap());
-            let z_b_times_r_a = cfg_iter!(z_b).zip(r_a.as_ref().unwrap());
-            let cross_product: Vec<_> = z_a_times_r_b
-                .zip(z_b_times_r_a)
-                .map(|((z_a, r_b), (z_b, r_a))| *z_a * r_b + *z_b * r_a)
-                .collect();
-            end_timer!(cross_prod_time);
-            blinder_1 = Some(G::ScalarField::rand(rng));
-            let commit_time = start_timer!(|| "Committing to cross product");
-            let comm_1 = PedersenCommitment::commit(&ipk.ck, &cross_product, blinder_1);
-            end_timer!(commit_time);
-
-            // Commit to r_a ○ r_b.
-            let commit_time = start_timer!(|| "Committing to r_a ○ r_b");
-            let r_a_r_b_product: Vec<_> = cfg_iter!(r_a.as_ref().unwrap())
-                .zip(r_b.unwrap())
-                .map(|(r_a, r_b)| r_b * r_a)
-                .collect();
-            blinder_2 = Some(G::ScalarField::rand(rng));
-            let comm_2 = PedersenCommitment::commit(&ipk.ck, &r_a_r_b_product, blinder_2);
-            end_timer!(commit_time);
-
-            Some(FirstRoundMessageRandomness {
-                comm_r_a,
-                comm_r_b,
-                comm_r_c,
-                comm_1,
-                comm_2,
-            })
-        } else {
-            None
-        };
+        let make_zk = rng.is_some();
+        let proof = Self::prove_with_assignment(ipk, input, witness, make_zk, sponge, rng);
 
-        // Step 6 of the scheme's prover, as detailed in BCLMS20.
-        let first_msg = FirstRoundMessage {
-            comm_a,
-            comm_b,
-            comm_c,
-            randomness: first_round_randomness,
-        };
+        end_timer!(init_time);
+        Ok(proof)
+    }
+
+    // Proves that the R1CS relation holds for an already generated input and witness.
+    pub(crate) fn prove_with_assignment(
+        ipk: &IndexProverKey<G>,
+        input: Vec<G::ScalarField>,
+        witness: Vec<G::ScalarField>,
+        make_zk: bool,
+        sponge: Option<S>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Proof<G> {
+        Self::prove_with_assignment_and_challenge(ipk, input, witness, make_zk, sponge, rng).0
+    }
+
+    // Proves that the R1CS relation holds for an already generated input and witness, and returns
+    // the proof with the Fiat-Shamir challenge of the prover.
+    pub(crate) fn prove_with_assignment_and_challenge(
+        ipk: &IndexProverKey<G>,
+        input: Vec<G::ScalarField>,
+        witness: Vec<G::ScalarField>,
+        make_zk: bool,
+        sponge: Option<S>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> (Proof<G>, G::ScalarField) {
+        // Steps 2-6 of the scheme's prover, as detailed in BCLMS20.
+        let (first_msg, state) =
+            PubCoinR1CSNark::first_msg_with_assignment(ipk, &input, witness, make_zk, rng);
 
         // Step 7 of the scheme's prover, as detailed in BCLMS20.
         let gamma = Self::compute_challenge(
             &ipk.index_info.matrices_hash,
             &input,
             &first_msg,
-            sponge.unwrap_or_else(|| S::new()),
-        );
-
-        let mut blinded_witness = witness;
-        let second_round_randomness = if make_zk {
-            // Step 8 of the scheme's prover, as detailed in BCLMS20.
-            ark_std::cfg_iter_mut!(blinded_witness)
-                .zip(r.unwrap())
-                .for_each(|(s, r)| *s += gamma * r);
-
-            // Step 9 of the scheme's prover, as detailed in BCLMS20.
-            let sigma_a = a_blinder.unwrap() + gamma * r_a_blinder.unwrap();
-            let sigma_b = b_blinder.unwrap() + gamma * r_b_blinder.unwrap();
-            let sigma_c = c_blinder.unwrap() + gamma * r_c_blinder.unwrap();
-
-            // Step 10 of the scheme's prover, as detailed in BCLMS20.
-            let sigma_o = c_blinder.unwrap()
-                + gamma * blinder_1.unwrap()
-                + gamma.square() * blinder_2.unwrap();
-
-            Some(SecondRoundMessageRandomness {
-                sigma_a,
-                sigma_b,
-                sigma_c,
-                sigma_o,
-            })
-        } else {
-            None
-        };
+            Self::init_sponge(ipk, sponge),
+        );
 
-        // Step 11 of the scheme's prover, as detailed in BCLMS20.
-        let second_msg = SecondRoundMessage {
-            blinded_witness,
-            randomness: second_round_randomness,
-        };
+        // Steps 8-11 of the scheme's prover, as detailed in BCLMS20.
+        let second_msg = PubCoinR1CSNark::prover_second_msg(state, gamma);
 
         // Step 12 of the scheme's prover, as detailed in BCLMS20.
         let proof = Proof {
             first_msg,
             second_msg,
         };
+        (proof, gamma)
+    }
 
-        end_timer!(init_time);
-        Ok(proof)
+    /// Verifies that some R1CS relation holds. Returns an error if the input or the witness in
+    /// `proof` do not have the lengths expected by `ivk`, or if the commitment key of `ivk` is too
+    /// short for the instance.
+    ///
+    /// `proof` may be a [`Proof`] or a [`LazyProof`], which is deserialized as it is verified and
+    /// rejected with [`NarkError::MalformedProof`] if its bytes are malformed.
+    pub fn verify<'a>(
+        ivk: &IndexVerifierKey<G>,
+        input: &[G::ScalarField],
+        proof: impl Into<ProofRef<'a, G>>,
+        sponge: Option<S>,
+    ) -> R1CSResult<bool> {
+        Self::verify_with_matrix_vec_muls(ivk, input, proof, sponge, |input, witness| {
+            ivk.matrix_vec_muls(input, witness)
+        })
     }
 
-    /// Verifies that some R1CS relation holds.
-    pub fn verify(
+    /// Verifies the proof for one step of an IVC computation. If `is_base_case` is true, the
+    /// proof must be the one produced by [`Proof::base_case`] for `input`, and is otherwise
+    /// verified like [`R1CSNark::verify`].
+    ///
+    /// Whether a step is the base case is known to the caller from the position of the step in
+    /// the computation, and is never read from the proof.
+    pub fn verify_ivc_step<'a>(
         ivk: &IndexVerifierKey<G>,
         input: &[G::ScalarField],
-        proof: &Proof<G>,
+        proof: impl Into<ProofRef<'a, G>>,
+        is_base_case: bool,
         sponge: Option<S>,
-    ) -> bool {
-        let init_time = start_timer!(|| "NARK::Verifier");
-        if proof.first_msg.randomness.is_some() != proof.second_msg.randomness.is_some() {
-            return false;
+    ) -> R1CSResult<bool> {
+        if !is_base_case {
+            return Self::verify(ivk, input, proof, sponge);
+        }
+
+        check_protocol_version(&ivk.index_info)?;
+        if input.len() != ivk.index_info.num_instance_variables {
+            return Err(NarkError::MalformedInput {
+                expected_len: ivk.index_info.num_instance_variables,
+                got: input.len(),
+            });
+        }
+        proof.into().is_valid_base_case(input)
+    }
+
+    /// Verifies many proofs for the same R1CS instance, with each proof checked against its own
+    /// public input and a fresh sponge from `sponge_factory`. The proofs are verified in parallel
+    /// when the `parallel` feature is enabled. Returns one result per instance, in order, where a
+    /// malformed input or proof counts as invalid.
+    pub fn verify_many(
+        ivk: &IndexVerifierKey<G>,
+        instances: &[(&[G::ScalarField], &Proof<G>)],
+        sponge_factory: impl Fn() -> S + Sync,
+    ) -> Vec<bool> {
+        let verify_time = start_timer!(|| "NARK::VerifyMany");
+        let results = cfg_iter!(instances)
+            .map(|(input, proof)| {
+                Self::verify(ivk, input, *proof, Some(sponge_factory())).unwrap_or(false)
+            })
+            .collect();
+        end_timer!(verify_time);
+        results
+    }
+
+    // Verifies that some R1CS relation holds, using `matrix_vec_muls` to compute
+    // `A * (input || witness)`, `B * (input || witness)`, and `C * (input || witness)`.
+    pub(crate) fn verify_with_matrix_vec_muls<'a>(
+        ivk: &IndexVerifierKey<G>,
+        input: &[G::ScalarField],
+        proof: impl Into<ProofRef<'a, G>>,
+        sponge: Option<S>,
+        matrix_vec_muls: impl Fn(
+            &[G::ScalarField],
+            &[G::ScalarField],
+        ) -> MatrixVecProducts<G::ScalarField>,
+    ) -> R1CSResult<bool> {
+        let failed_check = Self::find_failed_check(ivk, input, proof, sponge, matrix_vec_muls)?;
+        Ok(failed_check.is_none())
+    }
+
+    // Runs the checks of the verifier in order and returns the first one that fails, if any.
+    pub(crate) fn find_failed_check<'a>(
+        ivk: &IndexVerifierKey<G>,
+        input: &[G::ScalarField],
+        proof: impl Into<ProofRef<'a, G>>,
+        sponge: Option<S>,
+        matrix_vec_muls: impl Fn(
+            &[G::ScalarField],
+            &[G::ScalarField],
+        ) -> MatrixVecProducts<G::ScalarField>,
+    ) -> R1CSResult<Option<FailedCheck<G>>> {
+        let index_info = &ivk.index_info;
+        check_protocol_version(index_info)?;
+        check_commitment_key(ivk)?;
+        if input.len() != index_info.num_instance_variables {
+            return Err(NarkError::MalformedInput {
+                expected_len: index_info.num_instance_variables,
+                got: input.len(),
+            });
+        }
+
+        let proof = proof.into();
+        let (first_msg, second_msg) = (proof.first_msg()?, proof.second_msg()?);
+        let witness_len = index_info.num_variables - index_info.num_instance_variables;
+        if second_msg.blinded_witness.len() != witness_len {
+            return Err(NarkError::MalformedWitness {
+                expected_len: witness_len,
+                got: second_msg.blinded_witness.len(),
+            });
         }
 
+        let init_time = start_timer!(|| "NARK::Verifier");
+
         // Step 2 of the scheme's verifier, as detailed in BCLMS20.
         let gamma = Self::compute_challenge(
             &ivk.index_info.matrices_hash,
             &input,
-            &proof.first_msg,
-            sponge.unwrap_or_else(|| S::new()),
-        );
-
-        // Step 3 of the scheme's verifier, as detailed in BCLMS20.
-        let mat_vec_mul_time = start_timer!(|| "Computing M * blinded_witness");
-        let a_times_blinded_witness =
-            matrix_vec_mul(&ivk.a, &input, &proof.second_msg.blinded_witness);
-        let b_times_blinded_witness =
-            matrix_vec_mul(&ivk.b, &input, &proof.second_msg.blinded_witness);
-        let c_times_blinded_witness =
-            matrix_vec_mul(&ivk.c, &input, &proof.second_msg.blinded_witness);
-        end_timer!(mat_vec_mul_time);
-
-        // Step 4 of the scheme's verifier, as detailed in BCLMS20.
-        let mut comm_a = proof.first_msg.comm_a.into_projective();
-        let mut comm_b = proof.first_msg.comm_b.into_projective();
-        let mut comm_c = proof.first_msg.comm_c.into_projective();
-        if let Some(first_msg_randomness) = proof.first_msg.randomness.as_ref() {
-            comm_a += first_msg_randomness.comm_r_a.mul(gamma);
-            comm_b += first_msg_randomness.comm_r_b.mul(gamma);
-            comm_c += first_msg_randomness.comm_r_c.mul(gamma);
-        }
-
-        let commit_time = start_timer!(|| "Reconstructing c_A, c_B, c_C commitments");
-        let reconstructed_comm_a = PedersenCommitment::commit(
-            &ivk.ck,
-            &a_times_blinded_witness,
-            proof.second_msg.randomness.as_ref().map(|r| r.sigma_a),
-        );
-        let reconstructed_comm_b = PedersenCommitment::commit(
-            &ivk.ck,
-            &b_times_blinded_witness,
-            proof.second_msg.randomness.as_ref().map(|r| r.sigma_b),
-        );
-        let reconstructed_comm_c = PedersenCommitment::commit(
-            &ivk.ck,
-            &c_times_blinded_witness,
-            proof.second_msg.randomness.as_ref().map(|r| r.sigma_c),
-        );
-
-        let a_equal = comm_a == reconstructed_comm_a.into_projective();
-        let b_equal = comm_b == reconstructed_comm_b.into_projective();
-        let c_equal = comm_c == reconstructed_comm_c.into_projective();
-        drop(c_times_blinded_witness);
-        end_timer!(commit_time);
-
-        // Step 5 of the scheme's verifier, as detailed in BCLMS20.
-        let had_prod_time = start_timer!(|| "Computing Hadamard product and commitment to it");
-        let had_prod: Vec<_> = cfg_into_iter!(a_times_blinded_witness)
-            .zip(b_times_blinded_witness)
-            .map(|(a, b)| a * b)
-            .collect();
-        let reconstructed_had_prod_comm = PedersenCommitment::commit(
-            &ivk.ck,
-            &had_prod,
-            proof.second_msg.randomness.as_ref().map(|r| r.sigma_o),
+            first_msg,
+            Self::init_sponge(ivk, sponge),
         );
-        end_timer!(had_prod_time);
 
-        let mut had_prod_comm = proof.first_msg.comm_c.into_projective();
-        if let Some(first_msg_randomness) = proof.first_msg.randomness.as_ref() {
-            had_prod_comm += first_msg_randomness.comm_1.mul(gamma);
-            had_prod_comm += first_msg_randomness.comm_2.mul(gamma.square());
-        }
-        let had_prod_equal = had_prod_comm == reconstructed_had_prod_comm.into_projective();
-        add_to_trace!(|| "Verifier result", || format!("A equal: {}, B equal: {}, C equal: {}, Hadamard Product equal: {}", a_equal, b_equal, c_equal, had_prod_equal));
+        // Steps 3-5 of the scheme's verifier, as detailed in BCLMS20.
+        let failed_check = PubCoinR1CSNark::find_failed_check(
+            ivk,
+            input,
+            first_msg,
+            second_msg,
+            gamma,
+            matrix_vec_muls,
+        );
         end_timer!(init_time);
-        a_equal & b_equal & c_equal & had_prod_equal
+        Ok(failed_check)
     }
 }
 
-pub(crate) fn hash_matrices<F: Field>(
-    domain_separator: &[u8],
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    G::ScalarField: Absorbable<G::ScalarField>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Outputs a specialized prover and verifier key for some R1CS instance, identifying the
+    /// instance by [`hash_matrices_poseidon`] instead of the default Blake2b-based hash. This
+    /// allows a circuit over the scalar field to recompute the hash of the index with
+    /// [`PoseidonMatrixHashGadget`][gadget]. The keys are deserialized with
+    /// [`IndexProverKey::from_bytes_with_poseidon_hash`].
+    ///
+    /// [gadget]: crate::r1cs_nark_as::r1cs_nark::constraints::PoseidonMatrixHashGadget
+    pub fn index_with_poseidon_hash<
+        C: ConstraintSynthesizer<G::ScalarField>,
+        HS: CryptographicSponge<G::ScalarField>,
+    >(
+        pp: &PublicParameters,
+        r1cs_instance: C,
+    ) -> R1CSResult<(IndexProverKey<G>, IndexVerifierKey<G>)> {
+        let (mut ipk, _) = Self::index(pp, r1cs_instance)?;
+        let (a, b, c): (Matrix<_>, Matrix<_>, Matrix<_>) = (
+            ipk.a.clone().into(),
+            ipk.b.clone().into(),
+            ipk.c.clone().into(),
+        );
+        let domain = matrices_hash_domain(&ipk.index_info);
+        ipk.index_info.matrices_hash = hash_matrices_poseidon::<_, HS>(&domain, &a, &b, &c);
+        let ivk = ipk.clone();
+        Ok((ipk, ivk))
+    }
+}
+
+/// Removes the constraints of an R1CS instance that every assignment satisfies, namely those of
+/// the form `0 * x = 0`, `x * 0 = 0`, `x * 1 = x`, and `1 * x = x`, where the constant `1` is
+/// the variable at column `0`. Returns the remaining matrices and the number of removed
+/// constraints. Since the removed constraints never fail, the relation is unchanged.
+pub fn normalize_matrices<F: Field>(
+    a: Matrix<F>,
+    b: Matrix<F>,
+    c: Matrix<F>,
+) -> (Matrix<F>, Matrix<F>, Matrix<F>, usize) {
+    let sorted = |row: &[(F, usize)]| {
+        let mut row = row.to_vec();
+        row.sort_by_key(|(_, i)| *i);
+        row
+    };
+    let is_one = |row: &[(F, usize)]| row == [(F::one(), 0)];
+    let is_trivial = |a_row: &[(F, usize)], b_row: &[(F, usize)], c_row: &[(F, usize)]| {
+        (c_row.is_empty() && (a_row.is_empty() || b_row.is_empty()))
+            || (is_one(b_row) && sorted(a_row) == sorted(c_row))
+            || (is_one(a_row) && sorted(b_row) == sorted(c_row))
+    };
+
+    let num_constraints = a.len();
+    let (mut normalized_a, mut normalized_b, mut normalized_c) =
+        (Vec::new(), Vec::new(), Vec::new());
+    for ((a_row, b_row), c_row) in a.into_iter().zip(b).zip(c) {
+        if !is_trivial(&a_row, &b_row, &c_row) {
+            normalized_a.push(a_row);
+            normalized_b.push(b_row);
+            normalized_c.push(c_row);
+        }
+    }
+
+    let num_pruned_constraints = num_constraints - normalized_a.len();
+    (
+        normalized_a,
+        normalized_b,
+        normalized_c,
+        num_pruned_constraints,
+    )
+}
+
+// Checks that `a`, `b`, and `c` have `num_constraints` rows each, and that every row refers to
+// distinct columns less than `num_variables`. There is no dedicated `SynthesisError` variant for
+// malformed matrices, so `MalformedVerifyingKey` is returned if any check fails.
+pub(crate) fn validate_matrices<F: Field>(
     a: &Matrix<F>,
     b: &Matrix<F>,
     c: &Matrix<F>,
-) -> [u8; 32] {
-    let mut serialized_matrices = domain_separator.to_vec();
-    a.serialize(&mut serialized_matrices).unwrap();
-    b.serialize(&mut serialized_matrices).unwrap();
-    c.serialize(&mut serialized_matrices).unwrap();
-
-    let mut hasher = VarBlake2b::new(32).unwrap();
-    digest::Update::update(&mut hasher, &serialized_matrices);
+    num_constraints: usize,
+    num_variables: usize,
+) -> R1CSResult<()> {
+    let is_well_formed = |matrix: &Matrix<F>| {
+        matrix.len() == num_constraints
+            && matrix.iter().all(|row| {
+                let mut columns = row.iter().map(|(_, i)| *i).collect::<Vec<_>>();
+                columns.sort_unstable();
+                columns.windows(2).all(|w| w[0] != w[1])
+                    && columns.last().map_or(true, |i| *i < num_variables)
+            })
+    };
 
-    let mut matrices_hash = [0u8; 32];
-    hasher.finalize_variable(|res| matrices_hash.copy_from_slice(res));
+    if is_well_formed(a) && is_well_formed(b) && is_well_formed(c) {
+        Ok(())
+    } else {
+        Err(SynthesisError::MalformedVerifyingKey.into())
+    }
+}
 
-    matrices_hash
+/// Computes the hash of the matrices of an R1CS instance one row at a time, so that the matrices
+/// of a circuit that is built incrementally never need to be held or serialized in full.
+///
+/// The hash of an index is obtained by absorbing, for each constraint in order, its rows of `A`,
+/// `B`, and `C`, with the matrix IDs `0`, `1`, and `2`.
+pub struct MatrixHasher {
+    hasher: VarBlake2b,
 }
 
-// Computes `matrix * (input || witness)`.
-pub(crate) fn matrix_vec_mul<F: Field>(matrix: &Matrix<F>, input: &[F], witness: &[F]) -> Vec<F> {
-    ark_std::cfg_iter!(matrix)
-        .map(|row| inner_prod(row, input, witness))
-        .collect()
+impl MatrixHasher {
+    /// Creates a hasher for the domain `domain_separator`.
+    pub fn new(domain_separator: &[u8]) -> Self {
+        let mut hasher = VarBlake2b::new(32).unwrap();
+        digest::Update::update(&mut hasher, domain_separator);
+        Self { hasher }
+    }
+
+    /// Absorbs the next row of the matrix identified by `matrix_id`.
+    pub fn absorb_row<F: Field>(&mut self, matrix_id: u8, row: &[(F, usize)]) {
+        let mut serialized_row = vec![matrix_id];
+        (row.len() as u64).serialize(&mut serialized_row).unwrap();
+        for (coeff, index) in row {
+            coeff.serialize(&mut serialized_row).unwrap();
+            (*index as u64).serialize(&mut serialized_row).unwrap();
+        }
+
+        digest::Update::update(&mut self.hasher, &serialized_row);
+    }
+
+    /// Outputs the hash of the rows absorbed so far.
+    pub fn finalize(self) -> [u8; 32] {
+        let mut matrices_hash = [0u8; 32];
+        self.hasher
+            .finalize_variable(|res| matrices_hash.copy_from_slice(res));
+        matrices_hash
+    }
 }
 
-// Computes the inner product of `row` and `input || witness`
-fn inner_prod<F: Field>(row: &[(F, usize)], input: &[F], witness: &[F]) -> F {
-    let mut acc = F::zero();
-    for &(ref coeff, i) in row {
-        let tmp = if i < input.len() {
-            input[i]
-        } else {
-            witness[i - input.len()]
-        };
+// The domain separator of the matrices hash of an index. It extends `PROTOCOL_NAME` with the
+// number of entries pruned by `index_with_threshold`, if any, so that the hash of a pruned index
+// differs from that of the original instance.
+pub(crate) fn matrices_hash_domain(index_info: &IndexInfo) -> Vec<u8> {
+    let mut domain = PROTOCOL_NAME.to_vec();
+    if index_info.num_pruned_entries != 0 {
+        domain.extend_from_slice(b"-PRUNED");
+        domain.extend_from_slice(&(index_info.num_pruned_entries as u64).to_le_bytes());
+    }
+    domain
+}
 
-        acc += &(if coeff.is_one() { tmp } else { tmp * coeff });
+pub(crate) fn hash_matrices<F: Field>(
+    domain_separator: &[u8],
+    a: &CsrMatrix<F>,
+    b: &CsrMatrix<F>,
+    c: &CsrMatrix<F>,
+) -> [u8; 32] {
+    assert!(a.len() == b.len() && b.len() == c.len());
+
+    let mut hasher = MatrixHasher::new(domain_separator);
+    let mut row = Vec::new();
+    for i in 0..a.len() {
+        for (matrix_id, matrix) in [a, b, c].iter().enumerate() {
+            row.clear();
+            row.extend(matrix.row(i).iter());
+            hasher.absorb_row(matrix_id as u8, &row);
+        }
     }
-    acc
+
+    hasher.finalize()
+}
+
+/// Hashes the matrices of an R1CS instance with a sponge over the field of the matrices. Unlike
+/// the Blake2b-based matrix hash, this hash can be recomputed cheaply inside a circuit over the
+/// same field using [`PoseidonMatrixHashGadget`][gadget].
+///
+/// [gadget]: crate::r1cs_nark_as::r1cs_nark::constraints::PoseidonMatrixHashGadget
+pub fn hash_matrices_poseidon<F: PrimeField + Absorbable<F>, S: CryptographicSponge<F>>(
+    domain_separator: &[u8],
+    a: &Matrix<F>,
+    b: &Matrix<F>,
+    c: &Matrix<F>,
+) -> [u8; 32] {
+    let mut encoded: Vec<F> = domain_separator.to_field_elements().unwrap();
+    for matrix in &[a, b, c] {
+        encoded.push(F::from(matrix.len() as u64));
+        for row in matrix.iter() {
+            encoded.push(F::from(row.len() as u64));
+            for (coeff, index) in row {
+                encoded.push(*coeff);
+                encoded.push(F::from(*index as u64));
+            }
+        }
+    }
+
+    let mut sponge = S::new();
+    sponge.absorb(&encoded);
+
+    let mut matrices_hash = [0u8; 32];
+    matrices_hash.copy_from_slice(&sponge.squeeze_bytes(32));
+    matrices_hash
+}
+
+// Computes `matrix * (input || witness)`.
+pub(crate) fn matrix_vec_mul<F: Field>(
+    matrix: &CsrMatrix<F>,
+    input: &[F],
+    witness: &[F],
+) -> Vec<F> {
+    let z = VariableAssignment::new(input, witness);
+    cfg_into_iter!(0..matrix.len())
+        .map(|i| inner_prod(matrix.row(i), &z))
+        .collect()
+}
+
+/// Computes `A * z`, `B * z`, and `C * z`, where `z = input || witness`, in a single pass over the
+/// rows of the three matrices. Each row of `A`, `B`, and `C` is multiplied while the entries of `z`
+/// it reads are still in cache. The matrices must have the same number of rows.
+pub fn matrix_triple_mul<F: Field>(
+    a: &CsrMatrix<F>,
+    b: &CsrMatrix<F>,
+    c: &CsrMatrix<F>,
+    input: &[F],
+    witness: &[F],
+) -> MatrixVecProducts<F> {
+    assert!(a.len() == b.len() && b.len() == c.len());
+    let z = VariableAssignment::new(input, witness);
+    let products: Vec<_> = cfg_into_iter!(0..a.len())
+        .map(|i| {
+            (
+                inner_prod(a.row(i), &z),
+                inner_prod(b.row(i), &z),
+                inner_prod(c.row(i), &z),
+            )
+        })
+        .collect();
+
+    let mut a_z = Vec::with_capacity(products.len());
+    let mut b_z = Vec::with_capacity(products.len());
+    let mut c_z = Vec::with_capacity(products.len());
+    for (a_i, b_i, c_i) in products {
+        a_z.push(a_i);
+        b_z.push(b_i);
+        c_z.push(c_i);
+    }
+
+    (a_z, b_z, c_z)
+}
+
+/// Computes `matrix * (input || witness)` using the representation of `matrix`.
+pub fn matrix_vec_mul_dispatch<F: Field>(
+    matrix: &MatrixRepr<F>,
+    input: &[F],
+    witness: &[F],
+) -> Vec<F> {
+    match matrix {
+        MatrixRepr::Sparse(matrix) => matrix_vec_mul(matrix, input, witness),
+        MatrixRepr::Dense(matrix, num_columns) => {
+            assert_eq!(*num_columns, input.len() + witness.len());
+            ark_std::cfg_iter!(matrix)
+                .map(|row| {
+                    row.iter()
+                        .zip(input.iter().chain(witness))
+                        .fold(F::zero(), |acc, (coeff, z)| acc + &(*coeff * z))
+                })
+                .collect()
+        }
+    }
+}
+
+// Checks that `(A * z) ○ (B * z) == C * z`, where `z = input || witness`.
+pub(crate) fn is_satisfied<F: Field>(
+    a: &CsrMatrix<F>,
+    b: &CsrMatrix<F>,
+    c: &CsrMatrix<F>,
+    input: &[F],
+    witness: &[F],
+) -> bool {
+    first_unsatisfied_row(a, b, c, input, witness).is_none()
+}
+
+// Returns an error if `index_info` was produced by another version of the protocol.
+pub(crate) fn check_protocol_version(index_info: &IndexInfo) -> R1CSResult<()> {
+    if index_info.protocol_version != CURRENT_PROTOCOL_VERSION {
+        return Err(NarkError::VersionMismatch {
+            expected: CURRENT_PROTOCOL_VERSION,
+            got: index_info.protocol_version,
+        });
+    }
+
+    Ok(())
+}
+
+// Returns an error if `version` is not a proof format version that can be decoded.
+pub(crate) fn check_proof_format_version(version: u8) -> R1CSResult<()> {
+    if version != PROOF_FORMAT_VERSION {
+        return Err(NarkError::UnsupportedProofVersion { version });
+    }
+
+    Ok(())
+}
+
+// Returns an error if `ipk` cannot be used to prove, which only depends on the key and not on
+// the assignment.
+fn check_prover_key<G: AffineCurve>(ipk: &IndexProverKey<G>) -> R1CSResult<()> {
+    check_protocol_version(&ipk.index_info)?;
+    check_commitment_key(ipk)
+}
+
+// Returns an error if the commitment key of `ipk` is too short to commit to the vectors of the
+// instance, which have one entry per constraint.
+pub(crate) fn check_commitment_key<G: AffineCurve>(ipk: &IndexProverKey<G>) -> R1CSResult<()> {
+    let needed = ipk.index_info.num_constraints;
+    let have = ipk.ck.supported_num_elems();
+    if have < needed {
+        return Err(NarkError::CommitmentKeyTooShort { needed, have });
+    }
+
+    Ok(())
+}
+
+// Returns the index of the first row in which `(A * z) ○ (B * z) != C * z`, where
+// `z = input || witness`, if there is one.
+pub(crate) fn first_unsatisfied_row<F: Field>(
+    a: &CsrMatrix<F>,
+    b: &CsrMatrix<F>,
+    c: &CsrMatrix<F>,
+    input: &[F],
+    witness: &[F],
+) -> Option<usize> {
+    let (z_a, z_b, z_c) = matrix_triple_mul(a, b, c, input, witness);
+
+    z_a.into_iter()
+        .zip(z_b)
+        .zip(z_c)
+        .position(|((a, b), c)| a * b != c)
+}
+
+// Computes the inner product of `row` and the assignment `z`.
+pub(crate) fn inner_prod<F: Field>(row: CsrRow<'_, F>, z: &VariableAssignment<'_, F>) -> F {
+    let mut acc = F::zero();
+    for (coeff, i) in row.iter() {
+        let tmp = z.get(i);
+        acc += &(if coeff.is_one() { tmp } else { tmp * coeff });
+    }
+    acc
 }
 
 #[cfg(test)]
 pub(crate) mod test {
     use super::*;
-    use ark_ff::{PrimeField, UniformRand};
+    use ark_ff::{One, PrimeField, UniformRand};
     use ark_pallas::{Affine, Fq, Fr};
     use ark_relations::{
         lc,
-        r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+        r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable},
     };
+    use ark_serialize::SerializationError;
     use ark_sponge::poseidon::PoseidonSponge;
     const NUM_ITERS: usize = 10;
 
     #[derive(Copy, Clone)]
-    struct DummyCircuit<F: PrimeField> {
+    pub(crate) struct DummyCircuit<F: PrimeField> {
         pub a: Option<F>,
         pub b: Option<F>,
         pub num_variables: usize,
@@ -506,6 +1218,1228 @@ pub(crate) mod test {
         }
     }
 
+    #[test]
+    fn test_universal_setup() {
+        let rng = &mut ark_std::test_rng();
+        let ck = setup::universal_setup::<Affine>(128);
+        let pp = R1CSNark::<Affine, PoseidonSponge<Fq>>::setup();
+
+        for &(num_variables, num_constraints) in &[(5, 16), (10, 100)] {
+            let c = DummyCircuit {
+                a: Some(Fr::rand(rng)),
+                b: Some(Fr::rand(rng)),
+                num_variables,
+                num_constraints,
+            };
+            let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+            let (ipk, _) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index(&pp, c).unwrap();
+            let trimmed = R1CSNark::<Affine, PoseidonSponge<Fq>>::trim(&ck, &ipk).unwrap();
+            assert_eq!(trimmed.ck.supported_num_elems(), num_constraints);
+
+            let proof = R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(
+                &trimmed,
+                c,
+                true,
+                Some(PoseidonSponge::<Fq>::new()),
+                Some(rng),
+            )
+            .unwrap();
+
+            assert!(R1CSNark::<Affine, PoseidonSponge<Fq>>::verify(
+                &trimmed,
+                &r1cs_input,
+                &proof,
+                Some(PoseidonSponge::<Fq>::new()),
+            )
+            .unwrap());
+        }
+
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 200,
+        };
+        let (ipk, _) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index(&pp, c).unwrap();
+        assert!(matches!(
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::trim(&ck, &ipk),
+            Err(NarkError::CommitmentKeyTooShort {
+                needed: 199,
+                have: 128
+            })
+        ));
+    }
+
+    #[test]
+    fn test_prove_lazy() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let small_c = DummyCircuit {
+            num_constraints: 10,
+            ..c
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = R1CSNark::<Affine, PoseidonSponge<Fq>>::setup();
+        let (ipk, ivk) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index(&pp, c).unwrap();
+        let (input, witness) = R1CSNark::<Affine, PoseidonSponge<Fq>>::synthesize(&ipk, c).unwrap();
+        let assignment = FullAssignment { input, witness };
+
+        for &make_zk in &[false, true] {
+            let zk_rng: Option<&mut dyn RngCore> = if make_zk { Some(&mut *rng) } else { None };
+            let proof = R1CSNark::<Affine, PoseidonSponge<Fq>>::prove_lazy(
+                &ipk,
+                || Ok(assignment.clone()),
+                None,
+                zk_rng,
+            )
+            .unwrap();
+            assert_eq!(proof.first_msg.randomness.is_some(), make_zk);
+            assert!(R1CSNark::<Affine, PoseidonSponge<Fq>>::verify(
+                &ivk,
+                &r1cs_input,
+                &proof,
+                None
+            )
+            .unwrap());
+        }
+
+        // The witness is not computed if the commitment key is too short.
+        let (small_ipk, _) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index(&pp, small_c).unwrap();
+        let short_ipk = IndexProverKey {
+            ck: small_ipk.ck,
+            ..ipk.clone()
+        };
+        let result = R1CSNark::<Affine, PoseidonSponge<Fq>>::prove_lazy(
+            &short_ipk,
+            || panic!("the witness should not be computed"),
+            None,
+            None,
+        );
+        assert!(matches!(
+            result,
+            Err(NarkError::CommitmentKeyTooShort { .. })
+        ));
+
+        let mut long_assignment = assignment;
+        long_assignment.witness.push(Fr::one());
+        let result = R1CSNark::<Affine, PoseidonSponge<Fq>>::prove_lazy(
+            &ipk,
+            || Ok(long_assignment),
+            None,
+            None,
+        );
+        assert!(matches!(result, Err(NarkError::MalformedWitness { .. })));
+    }
+
+    #[test]
+    fn test_commitment_key_too_short() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let small_c = DummyCircuit {
+            num_constraints: 10,
+            ..c
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = R1CSNark::<Affine, PoseidonSponge<Fq>>::setup();
+        let (ipk, ivk) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index(&pp, c).unwrap();
+        let (small_ipk, _) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index(&pp, small_c).unwrap();
+        let proof =
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(&ipk, c, false, None, None).unwrap();
+
+        let short_ipk = IndexProverKey {
+            ck: small_ipk.ck,
+            ..ipk
+        };
+        let result =
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(&short_ipk, c, false, None, None);
+        assert!(matches!(
+            result,
+            Err(NarkError::CommitmentKeyTooShort {
+                needed: 99,
+                have: 9
+            })
+        ));
+
+        let short_ivk = IndexVerifierKey {
+            ck: short_ipk.ck,
+            ..ivk
+        };
+        let result =
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::verify(&short_ivk, &r1cs_input, &proof, None);
+        assert!(matches!(
+            result,
+            Err(NarkError::CommitmentKeyTooShort {
+                needed: 99,
+                have: 9
+            })
+        ));
+    }
+
+    #[test]
+    fn test_verify_many() {
+        let rng = &mut ark_std::test_rng();
+        let pp = R1CSNark::<Affine, PoseidonSponge<Fq>>::setup();
+        let index_circuit = DummyCircuit {
+            a: None,
+            b: None,
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let (ipk, ivk) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index(&pp, index_circuit).unwrap();
+
+        let mut inputs = Vec::new();
+        let mut proofs = Vec::new();
+        for i in 0..NUM_ITERS {
+            let c = DummyCircuit {
+                a: Some(Fr::rand(rng)),
+                b: Some(Fr::rand(rng)),
+                ..index_circuit
+            };
+            inputs.push(vec![Fr::one(), c.a.unwrap() * c.b.unwrap()]);
+            proofs.push(
+                R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(
+                    &ipk,
+                    c,
+                    i % 2 == 0,
+                    Some(PoseidonSponge::<Fq>::new()),
+                    Some(rng),
+                )
+                .unwrap(),
+            );
+        }
+
+        // Swap the inputs of the last two instances, and truncate one input.
+        inputs.swap(NUM_ITERS - 2, NUM_ITERS - 1);
+        inputs[0].pop();
+
+        let instances = inputs
+            .iter()
+            .zip(&proofs)
+            .map(|(input, proof)| (input.as_slice(), proof))
+            .collect::<Vec<_>>();
+        let results = R1CSNark::<Affine, PoseidonSponge<Fq>>::verify_many(
+            &ivk,
+            &instances,
+            PoseidonSponge::<Fq>::new,
+        );
+
+        assert_eq!(results.len(), NUM_ITERS);
+        for (i, (result, (input, proof))) in results.iter().zip(&instances).enumerate() {
+            let expected = R1CSNark::<Affine, PoseidonSponge<Fq>>::verify(
+                &ivk,
+                input,
+                *proof,
+                Some(PoseidonSponge::<Fq>::new()),
+            )
+            .unwrap_or(false);
+            assert_eq!(*result, expected);
+            assert_eq!(*result, i != 0 && i < NUM_ITERS - 2);
+        }
+    }
+
+    #[test]
+    fn test_nark_errors() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = R1CSNark::<Affine, PoseidonSponge<Fq>>::setup();
+        let (ipk, ivk) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index(&pp, c).unwrap();
+        let proof =
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(&ipk, c, false, None, None).unwrap();
+
+        let result =
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::verify(&ivk, &r1cs_input[..1], &proof, None);
+        assert!(matches!(
+            result,
+            Err(NarkError::MalformedInput {
+                expected_len: 2,
+                got: 1
+            })
+        ));
+
+        let mut short_proof = proof.clone();
+        short_proof.second_msg.blinded_witness.pop();
+        let result =
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::verify(&ivk, &r1cs_input, &short_proof, None);
+        assert!(matches!(
+            result,
+            Err(NarkError::MalformedWitness {
+                expected_len: 9,
+                got: 8
+            })
+        ));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "MalformedWitness: expected 9 elements, got 8"
+        );
+
+        // A proof without a witness is rejected before any matrix is multiplied by it.
+        let mut empty_proof = proof.clone();
+        empty_proof.second_msg.blinded_witness.clear();
+        let result =
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::verify(&ivk, &r1cs_input, &empty_proof, None);
+        assert!(matches!(
+            result,
+            Err(NarkError::MalformedWitness {
+                expected_len: 9,
+                got: 0
+            })
+        ));
+        let result = R1CSNark::<Affine, PoseidonSponge<Fq>>::verify(
+            &ivk,
+            &r1cs_input,
+            &LazyProof::new(Vec::new()),
+            None,
+        );
+        assert!(matches!(result, Err(NarkError::MalformedProof(_))));
+
+        let missing_assignment = DummyCircuit { a: None, ..c };
+        let result = R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(
+            &ipk,
+            missing_assignment,
+            false,
+            None,
+            None,
+        );
+        assert!(matches!(
+            result,
+            Err(NarkError::Synthesis(SynthesisError::AssignmentMissing))
+        ));
+
+        let larger_circuit = DummyCircuit {
+            num_variables: 20,
+            ..c
+        };
+        let result =
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(&ipk, larger_circuit, false, None, None);
+        match result {
+            Err(NarkError::WitnessSizeMismatch {
+                expected: 10,
+                got: 21,
+                circuit_hash,
+            }) => assert_eq!(circuit_hash, ipk.index_info.matrices_hash),
+            _ => panic!("expected a witness size mismatch"),
+        }
+
+        let more_constraints = DummyCircuit {
+            num_constraints: 101,
+            ..c
+        };
+        let result = R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(
+            &ipk,
+            more_constraints,
+            false,
+            None,
+            None,
+        );
+        assert!(matches!(
+            result,
+            Err(NarkError::ConstraintCountMismatch {
+                expected: 100,
+                got: 101,
+                ..
+            })
+        ));
+    }
+
+    // Enforces `a * b = c`, and repeats the constraint only in setup mode, as a circuit might if
+    // it wrongly treats a check as only needed for the matrices.
+    #[derive(Copy, Clone)]
+    struct ModeDependentCircuit<F: PrimeField> {
+        a: F,
+        b: F,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for ModeDependentCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let a = cs.new_witness_variable(|| Ok(self.a))?;
+            let b = cs.new_witness_variable(|| Ok(self.b))?;
+            let c = cs.new_input_variable(|| Ok(self.a * self.b))?;
+
+            cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+            if cs.is_in_setup_mode() {
+                cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_mode_mismatch() {
+        let rng = &mut ark_std::test_rng();
+        let c = ModeDependentCircuit {
+            a: Fr::rand(rng),
+            b: Fr::rand(rng),
+        };
+
+        let pp = R1CSNark::<Affine, PoseidonSponge<Fq>>::setup();
+        let (ipk, _) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index(&pp, c).unwrap();
+        assert_eq!(ipk.index_info.num_circuit_constraints, 2);
+
+        let result = R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(&ipk, c, false, None, None);
+        assert!(matches!(
+            result,
+            Err(NarkError::ModeMismatch {
+                index_constraints: 2,
+                prove_constraints: 1,
+            })
+        ));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "ModeMismatch: indexed with 2 constraints, proving with 1"
+        );
+
+        // Keys produced from matrices do not record the constraints of a circuit.
+        let (a, b, c_matrix, num_instance_variables, num_witness_variables) =
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::constraint_matrices(c, &NarkConfig::default())
+                .unwrap();
+        let (ipk, ivk) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index_from_matrices(
+            &pp,
+            a,
+            b,
+            c_matrix,
+            num_instance_variables,
+            num_witness_variables,
+        )
+        .unwrap();
+        let proof =
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(&ipk, c, false, None, None).unwrap();
+        let r1cs_input = vec![Fr::one(), c.a * c.b];
+        assert!(
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::verify(&ivk, &r1cs_input, &proof, None)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_key_bytes_round_trip() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = R1CSNark::<Affine, PoseidonSponge<Fq>>::setup();
+        let (ipk, ivk) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index(&pp, c).unwrap();
+
+        let ipk_bytes = ipk.to_bytes();
+        let ipk = IndexProverKey::<Affine>::from_bytes(&ipk_bytes).unwrap();
+        assert_eq!(ipk.to_bytes(), ipk_bytes);
+
+        let ivk = IndexVerifierKey::<Affine>::from_bytes(&ivk.to_bytes()).unwrap();
+        assert_eq!(ivk.index_info.matrices_hash, ipk.index_info.matrices_hash);
+
+        let proof =
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(&ipk, c, true, None, Some(rng)).unwrap();
+        assert!(
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::verify(&ivk, &r1cs_input, &proof, None)
+                .unwrap()
+        );
+
+        assert!(IndexProverKey::<Affine>::from_bytes(&ipk_bytes[..ipk_bytes.len() - 1]).is_err());
+
+        // Keys whose matrices do not match their hash are rejected.
+        let mut swapped = ipk.clone();
+        ark_std::mem::swap(&mut swapped.a, &mut swapped.b);
+        assert!(matches!(
+            IndexProverKey::<Affine>::from_bytes(&swapped.to_bytes()),
+            Err(SerializationError::InvalidData)
+        ));
+
+        // Keys whose matrices refer to variables that do not exist are rejected.
+        let mut a: Matrix<Fr> = ipk.a.clone().into();
+        a[0].push((Fr::one(), ipk.index_info.num_variables));
+        let mut out_of_range = ipk.clone();
+        out_of_range.a = a.into();
+        out_of_range.index_info.matrices_hash = hash_matrices(
+            &matrices_hash_domain(&ipk.index_info),
+            &out_of_range.a,
+            &ipk.b,
+            &ipk.c,
+        );
+        assert!(matches!(
+            IndexProverKey::<Affine>::from_bytes(&out_of_range.to_bytes()),
+            Err(SerializationError::InvalidData)
+        ));
+    }
+
+    #[test]
+    fn test_index_from_matrices() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 3,
+            num_constraints: 2,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        // The instance variables are `(1, a * b)` and the witness variables are `(a, b)`.
+        let a = vec![vec![(Fr::one(), 2)], vec![]];
+        let b = vec![vec![(Fr::one(), 3)], vec![]];
+        let c_matrix = vec![vec![(Fr::one(), 1)], vec![]];
+
+        // `index` removes the trivially satisfied second constraint.
+        let (normalized_a, normalized_b, normalized_c, num_pruned_constraints) =
+            normalize_matrices(a.clone(), b, c_matrix.clone());
+        assert_eq!(num_pruned_constraints, 1);
+
+        let pp = R1CSNark::<Affine, PoseidonSponge<Fq>>::setup();
+        let (ipk, ivk) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index_from_matrices(
+            &pp,
+            normalized_a,
+            normalized_b,
+            normalized_c,
+            2,
+            2,
+        )
+        .unwrap();
+        let (expected_ipk, _) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index(&pp, c).unwrap();
+
+        assert!(ipk.a == expected_ipk.a && ipk.b == expected_ipk.b && ipk.c == expected_ipk.c);
+        assert_eq!(
+            ipk.index_info.matrices_hash,
+            expected_ipk.index_info.matrices_hash
+        );
+        assert_eq!(
+            ipk.index_info.num_variables,
+            expected_ipk.index_info.num_variables
+        );
+        assert_eq!(
+            ipk.index_info.num_instance_variables,
+            expected_ipk.index_info.num_instance_variables
+        );
+
+        let proof =
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(&expected_ipk, c, true, None, Some(rng))
+                .unwrap();
+        assert!(
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::verify(&ivk, &r1cs_input, &proof, None,)
+                .unwrap()
+        );
+
+        let out_of_range = vec![vec![(Fr::one(), 4)], vec![]];
+        let duplicate_column = vec![vec![(Fr::one(), 2), (Fr::one(), 2)], vec![]];
+        let missing_row = vec![vec![(Fr::one(), 2)]];
+        for malformed in &[out_of_range, duplicate_column, missing_row] {
+            assert!(R1CSNark::<Affine, PoseidonSponge<Fq>>::index_from_matrices(
+                &pp,
+                a.clone(),
+                malformed.clone(),
+                c_matrix.clone(),
+                2,
+                2,
+            )
+            .is_err());
+        }
+    }
+
+    #[derive(Copy, Clone)]
+    struct UnsatisfiedCircuit<F: PrimeField> {
+        a: F,
+        b: F,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for UnsatisfiedCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let a = cs.new_witness_variable(|| Ok(self.a))?;
+            let b = cs.new_witness_variable(|| Ok(self.b))?;
+            let c = cs.new_input_variable(|| Ok(self.a * self.b + F::one()))?;
+            cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_unsatisfied_witness() {
+        let rng = &mut ark_std::test_rng();
+        let c = UnsatisfiedCircuit {
+            a: Fr::rand(rng),
+            b: Fr::rand(rng),
+        };
+
+        let pp = R1CSNark::<Affine, PoseidonSponge<Fq>>::setup();
+        let (ipk, _) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index(&pp, c).unwrap();
+        let result = R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(&ipk, c, false, None, None);
+        assert!(matches!(
+            result,
+            Err(NarkError::UnsatisfiedConstraint { row: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_index_with_poseidon_hash() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 5,
+            num_constraints: 8,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = R1CSNark::<Affine, PoseidonSponge<Fq>>::setup();
+        let (ipk, ivk) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index_with_poseidon_hash::<
+            _,
+            PoseidonSponge<Fr>,
+        >(&pp, c)
+        .unwrap();
+        let (a, b, c_matrix): (Matrix<Fr>, Matrix<Fr>, Matrix<Fr>) = (
+            ivk.a.clone().into(),
+            ivk.b.clone().into(),
+            ivk.c.clone().into(),
+        );
+        assert_eq!(
+            ivk.index_info.matrices_hash,
+            hash_matrices_poseidon::<Fr, PoseidonSponge<Fr>>(PROTOCOL_NAME, &a, &b, &c_matrix)
+        );
+
+        // The key only deserializes if its hash is checked with Poseidon.
+        let ivk_bytes = ivk.to_bytes();
+        assert!(IndexVerifierKey::<Affine>::from_bytes(&ivk_bytes).is_err());
+        let ivk = IndexVerifierKey::<Affine>::from_bytes_with_poseidon_hash::<PoseidonSponge<Fr>>(
+            &ivk_bytes,
+        )
+        .unwrap();
+
+        let proof =
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(&ipk, c, true, None, Some(rng)).unwrap();
+        assert!(
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::verify(&ivk, &r1cs_input, &proof, None)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_matrix_repr() {
+        let rng = &mut ark_std::test_rng();
+        let size = 1024;
+        let input = vec![Fr::one()];
+        let witness = (1..size).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+
+        for &density in &[10u32, 60, 90] {
+            let matrix: Matrix<Fr> = (0..size)
+                .map(|_| {
+                    (0..size)
+                        .filter(|_| rng.next_u32() % 100 < density)
+                        .map(|i| (Fr::rand(rng), i))
+                        .collect()
+                })
+                .collect();
+            let matrix = CsrMatrix::from(matrix);
+
+            let sparse = MatrixRepr::Sparse(matrix.clone());
+            let repr = MatrixRepr::from_sparse(&matrix, size);
+            assert_eq!(repr.is_dense(), density > 30);
+
+            assert_eq!(
+                matrix_vec_mul_dispatch(&sparse, &input, &witness),
+                matrix_vec_mul_dispatch(&repr, &input, &witness)
+            );
+        }
+    }
+
+    #[test]
+    fn test_matrix_strategy() {
+        assert_eq!(
+            MatrixStrategy::auto_detect(100 * 100, 100 * 100),
+            MatrixStrategy::AlwaysDense
+        );
+        assert_eq!(
+            MatrixStrategy::auto_detect(1000, 1000 * 1000),
+            MatrixStrategy::AlwaysSparse
+        );
+
+        let dense_matrix: Matrix<Fr> = (0..100)
+            .map(|_| (0..100).map(|i| (Fr::one(), i)).collect())
+            .collect();
+        let dense_matrix = CsrMatrix::from(dense_matrix);
+        assert!(MatrixRepr::from_sparse(&dense_matrix, 100).is_dense());
+        assert!(
+            !MatrixRepr::with_strategy(&dense_matrix, 100, MatrixStrategy::AlwaysSparse).is_dense()
+        );
+
+        let sparse_matrix: Matrix<Fr> = (0..1000).map(|i| vec![(Fr::one(), i)]).collect();
+        let sparse_matrix = CsrMatrix::from(sparse_matrix);
+        assert!(!MatrixRepr::from_sparse(&sparse_matrix, 1000).is_dense());
+        assert!(
+            MatrixRepr::with_strategy(&sparse_matrix, 1000, MatrixStrategy::AlwaysDense).is_dense()
+        );
+
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = R1CSNark::<Affine, PoseidonSponge<Fq>>::setup();
+        let (mut ipk, _) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index(&pp, c).unwrap();
+        assert!(ipk.matrix_reprs.is_none());
+
+        ipk.set_matrix_strategy(MatrixStrategy::AlwaysDense);
+        assert!(ipk.matrix_reprs.is_some());
+
+        let proof =
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(&ipk, c, true, None, Some(rng)).unwrap();
+        assert!(
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::verify(&ipk, &r1cs_input, &proof, None)
+                .unwrap()
+        );
+    }
+
+    // Enforces `x * y = z` for public `z` in seven constraints, along with three constraints that
+    // every assignment satisfies.
+    #[derive(Clone, Copy)]
+    struct RedundantCircuit<F: PrimeField> {
+        x: F,
+        y: F,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for RedundantCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let x = cs.new_witness_variable(|| Ok(self.x))?;
+            let y = cs.new_witness_variable(|| Ok(self.y))?;
+            let z = cs.new_input_variable(|| Ok(self.x * self.y))?;
+
+            for _ in 0..7 {
+                cs.enforce_constraint(lc!() + x, lc!() + y, lc!() + z)?;
+            }
+            cs.enforce_constraint(lc!() + x, lc!() + Variable::One, lc!() + x)?;
+            cs.enforce_constraint(lc!(), lc!() + y, lc!())?;
+            cs.enforce_constraint(lc!() + Variable::One, lc!() + y, lc!() + y)?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_normalize_matrices() {
+        let rng = &mut ark_std::test_rng();
+        let circuit = RedundantCircuit {
+            x: Fr::rand(rng),
+            y: Fr::rand(rng),
+        };
+        let r1cs_input = vec![Fr::one(), circuit.x * circuit.y];
+
+        let pp = R1CSNark::<Affine, PoseidonSponge<Fq>>::setup();
+        let (ipk, ivk) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index(&pp, circuit).unwrap();
+        assert_eq!(ipk.index_info.num_constraints, 7);
+        assert_eq!(ipk.num_pruned_constraints(), 3);
+
+        let proof =
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(&ipk, circuit, true, None, Some(rng))
+                .unwrap();
+        assert!(
+            R1CSNark::<Affine, PoseidonSponge<Fq>>::verify(&ivk, &r1cs_input, &proof, None)
+                .unwrap()
+        );
+
+        let ipk = IndexProverKey::<Affine>::from_bytes(&ipk.to_bytes()).unwrap();
+        assert_eq!(ipk.num_pruned_constraints(), 3);
+    }
+
+    #[test]
+    fn test_matrix_hasher() {
+        let rng = &mut ark_std::test_rng();
+        let sample_matrix = |rng: &mut ark_std::rand::rngs::StdRng| -> Matrix<Fr> {
+            (0..20)
+                .map(|i| (0..i % 4).map(|j| (Fr::rand(rng), j)).collect())
+                .collect()
+        };
+        let (a, b, c) = (sample_matrix(rng), sample_matrix(rng), sample_matrix(rng));
+
+        let mut hasher = MatrixHasher::new(PROTOCOL_NAME);
+        for ((a_row, b_row), c_row) in a.iter().zip(&b).zip(&c) {
+            hasher.absorb_row(0, a_row);
+            hasher.absorb_row(1, b_row);
+            hasher.absorb_row(2, c_row);
+        }
+        let (a, b, c) = (CsrMatrix::from(a), CsrMatrix::from(b), CsrMatrix::from(c));
+        assert_eq!(hasher.finalize(), hash_matrices(PROTOCOL_NAME, &a, &b, &c));
+
+        // Swapping two matrices changes the hash.
+        assert_ne!(
+            hash_matrices(PROTOCOL_NAME, &a, &b, &c),
+            hash_matrices(PROTOCOL_NAME, &b, &a, &c)
+        );
+    }
+
+    #[test]
+    fn test_matrix_triple_mul() {
+        let rng = &mut ark_std::test_rng();
+        let size = 1 << 10;
+        let num_inputs = 2;
+        let sample_matrix = |rng: &mut ark_std::rand::rngs::StdRng| -> CsrMatrix<Fr> {
+            let matrix: Matrix<Fr> = (0..size)
+                .map(|_| {
+                    (0..3)
+                        .map(|_| (Fr::rand(rng), rng.next_u32() as usize % size))
+                        .collect()
+                })
+                .collect();
+            matrix.into()
+        };
+        let (a, b, c) = (sample_matrix(rng), sample_matrix(rng), sample_matrix(rng));
+        let input = (0..num_inputs).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+        let witness = (num_inputs..size)
+            .map(|_| Fr::rand(rng))
+            .collect::<Vec<_>>();
+
+        let separate_result = (
+            matrix_vec_mul(&a, &input, &witness),
+            matrix_vec_mul(&b, &input, &witness),
+            matrix_vec_mul(&c, &input, &witness),
+        );
+        assert_eq!(
+            matrix_triple_mul(&a, &b, &c, &input, &witness),
+            separate_result
+        );
+    }
+
+    // Squares the sum of its witnesses several times. The sum is a shared linear combination, so
+    // the circuit has fewer constraints when the combination is inlined than when it is outlined
+    // into its own variable.
+    #[derive(Clone)]
+    struct SquaredSumCircuit<F: PrimeField> {
+        witness: Vec<F>,
+        num_squares: usize,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for SquaredSumCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let sum = self.witness.iter().copied().sum::<F>();
+            let out = cs.new_input_variable(|| Ok(sum.square()))?;
+
+            let mut sum_lc = lc!();
+            for w in self.witness {
+                sum_lc = sum_lc + cs.new_witness_variable(|| Ok(w))?;
+            }
+            let sum = cs.new_lc(sum_lc)?;
+
+            for _ in 0..self.num_squares {
+                cs.enforce_constraint(lc!() + sum, lc!() + sum, lc!() + out)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_optimization_goal() {
+        let rng = &mut ark_std::test_rng();
+        let circuit = SquaredSumCircuit {
+            witness: (0..10).map(|_| Fr::rand(rng)).collect::<Vec<_>>(),
+            num_squares: 10,
+        };
+        let sum = circuit.witness.iter().copied().sum::<Fr>();
+        let r1cs_input = vec![Fr::one(), sum.square()];
+
+        let pp = R1CSNark::<Affine, PoseidonSponge<Fq>>::setup();
+        let mut proof_sizes = Vec::new();
+        for &optimization_goal in &[OptimizationGoal::Constraints, OptimizationGoal::Weight] {
+            let config = NarkConfig {
+                optimization_goal,
+                zk: true,
+            };
+            let (ipk, ivk) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index_with_config(
+                &pp,
+                circuit.clone(),
+                &config,
+            )
+            .unwrap();
+            assert_eq!(ipk.ck.supported_num_elems(), ipk.index_info.num_constraints);
+
+            let proof = R1CSNark::<Affine, PoseidonSponge<Fq>>::prove_with_config(
+                &ipk,
+                circuit.clone(),
+                &config,
+                None,
+                Some(rng),
+            )
+            .unwrap();
+            assert!(R1CSNark::<Affine, PoseidonSponge<Fq>>::verify(
+                &ivk,
+                &r1cs_input,
+                &proof,
+                None
+            )
+            .unwrap());
+            proof_sizes.push(proof.serialized_size());
+        }
+
+        assert_ne!(proof_sizes[0], proof_sizes[1]);
+    }
+
+    // Enforces `(2a + noise * zero) * 2b = 4c` `num_constraints` times, where `zero` is a witness
+    // that is always zero, so the `noise` entries do not change the relation.
+    #[derive(Copy, Clone)]
+    struct NoisyCircuit<F: PrimeField> {
+        a: Option<F>,
+        b: Option<F>,
+        noise: Option<F>,
+        num_constraints: usize,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for NoisyCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+            let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+            let zero = cs.new_witness_variable(|| Ok(F::zero()))?;
+            let c = cs.new_input_variable(|| {
+                let a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+                let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+
+                Ok(a * b)
+            })?;
+
+            let (two, four) = (F::from(2u64), F::from(4u64));
+            for _ in 0..self.num_constraints {
+                let mut left = lc!() + (two, a);
+                if let Some(noise) = self.noise {
+                    left = left + (noise, zero);
+                }
+                cs.enforce_constraint(left, lc!() + (two, b), lc!() + (four, c))?;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_index_with_threshold() {
+        let rng = &mut ark_std::test_rng();
+        let clean = NoisyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            noise: None,
+            num_constraints: 16,
+        };
+        let noisy = NoisyCircuit {
+            noise: Some(Fr::one()),
+            ..clean
+        };
+        let r1cs_input = vec![Fr::one(), clean.a.unwrap() * clean.b.unwrap()];
+
+        type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+        let pp = Nark::setup();
+        let (clean_ipk, _) = Nark::index(&pp, clean).unwrap();
+        let (noisy_ipk, _) = Nark::index(&pp, noisy).unwrap();
+        let (pruned_ipk, pruned_ivk) =
+            Nark::index_with_threshold(&pp, noisy, Fr::from(2u64)).unwrap();
+
+        assert_eq!(noisy_ipk.num_pruned_entries(), 0);
+        assert_eq!(pruned_ipk.num_pruned_entries(), 16);
+        assert_eq!(pruned_ivk.num_pruned_entries(), 16);
+        assert_eq!(pruned_ivk.index_info.num_circuit_constraints, 16);
+        assert_ne!(
+            noisy_ipk.index_info.matrices_hash,
+            clean_ipk.index_info.matrices_hash
+        );
+        assert_ne!(
+            pruned_ipk.index_info.matrices_hash,
+            clean_ipk.index_info.matrices_hash
+        );
+        assert_eq!(pruned_ipk.a, clean_ipk.a);
+        assert_eq!(pruned_ipk.b, clean_ipk.b);
+        assert_eq!(pruned_ipk.c, clean_ipk.c);
+
+        for &make_zk in &[false, true] {
+            let proof = Nark::prove(&pruned_ipk, noisy, make_zk, None, Some(rng)).unwrap();
+            assert!(Nark::verify(&pruned_ivk, &r1cs_input, &proof, None).unwrap());
+        }
+
+        let pruned_ivk = IndexVerifierKey::<Affine>::from_bytes(&pruned_ivk.to_bytes()).unwrap();
+        assert_eq!(pruned_ivk.num_pruned_entries(), 16);
+
+        // A pruned key cannot be passed off as unpruned.
+        let mut unmarked_ivk = pruned_ivk.clone();
+        unmarked_ivk.index_info.num_pruned_entries = 0;
+        assert!(IndexVerifierKey::<Affine>::from_bytes(&unmarked_ivk.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_index_with_threshold_symmetric() {
+        let rng = &mut ark_std::test_rng();
+        let clean = NoisyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            noise: None,
+            num_constraints: 16,
+        };
+        let noisy = NoisyCircuit {
+            noise: Some(-Fr::one()),
+            ..clean
+        };
+
+        type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+        let pp = Nark::setup();
+        let (clean_ipk, _) = Nark::index(&pp, clean).unwrap();
+
+        // Small negative coefficients are pruned like small positive ones, while large ones of
+        // either sign are kept.
+        let (pruned_ipk, _) = Nark::index_with_threshold(&pp, noisy, Fr::from(2u64)).unwrap();
+        assert_eq!(pruned_ipk.num_pruned_entries(), 16);
+        assert_eq!(pruned_ipk.a, clean_ipk.a);
+
+        let (pruned_ipk, _) = Nark::index_with_threshold(&pp, noisy, -Fr::from(2u64)).unwrap();
+        assert_eq!(pruned_ipk.num_pruned_entries(), 16);
+
+        let (pruned_ipk, _) = Nark::index_with_threshold(&pp, clean, Fr::from(3u64)).unwrap();
+        assert_eq!(pruned_ipk.num_pruned_entries(), 32);
+    }
+
+    // Enforces `2a * 2b = 4c`, followed by `num_spurious` constraints `(noise * zero) * 2b = 0`,
+    // where `zero` is a witness that is always zero.
+    #[derive(Copy, Clone)]
+    struct SpuriousConstraintCircuit<F: PrimeField> {
+        a: Option<F>,
+        b: Option<F>,
+        noise: F,
+        num_spurious: usize,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for SpuriousConstraintCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+            let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+            let zero = cs.new_witness_variable(|| Ok(F::zero()))?;
+            let c = cs.new_input_variable(|| {
+                let a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+                let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+
+                Ok(a * b)
+            })?;
+
+            let (two, four) = (F::from(2u64), F::from(4u64));
+            cs.enforce_constraint(lc!() + (two, a), lc!() + (two, b), lc!() + (four, c))?;
+            for _ in 0..self.num_spurious {
+                cs.enforce_constraint(lc!() + (self.noise, zero), lc!() + (two, b), lc!())?;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_index_with_threshold_normalizes() {
+        let rng = &mut ark_std::test_rng();
+        let circuit = SpuriousConstraintCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            noise: Fr::one(),
+            num_spurious: 3,
+        };
+        let r1cs_input = vec![Fr::one(), circuit.a.unwrap() * circuit.b.unwrap()];
+
+        type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index_with_threshold(&pp, circuit, Fr::from(2u64)).unwrap();
+
+        // Removing the `noise` entries leaves the spurious constraints trivially satisfied, so
+        // they are removed as in `index`.
+        assert_eq!(ipk.num_pruned_entries(), 3);
+        assert_eq!(ipk.index_info.num_pruned_constraints, 3);
+        assert_eq!(ipk.index_info.num_circuit_constraints, 4);
+        assert_eq!(ipk.index_info.num_constraints, 1);
+
+        let proof = Nark::prove(&ipk, circuit, true, None, Some(rng)).unwrap();
+        assert!(Nark::verify(&ivk, &r1cs_input, &proof, None).unwrap());
+    }
+
+    // One step of the IVC computation `z_{i + 1} = z_i^2`, with public input `(z_i, z_{i + 1})`.
+    #[derive(Copy, Clone)]
+    struct SquaringStep<F: PrimeField> {
+        z: Option<F>,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for SquaringStep<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let z = self.z.ok_or(SynthesisError::AssignmentMissing);
+            let z_in = cs.new_input_variable(|| z)?;
+            let z_out = cs.new_input_variable(|| z.map(|z| z.square()))?;
+            let w = cs.new_witness_variable(|| z)?;
+            cs.enforce_constraint(lc!() + w, lc!() + Variable::One, lc!() + z_in)?;
+            cs.enforce_constraint(lc!() + w, lc!() + w, lc!() + z_out)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_ivc_base_case() {
+        let rng = &mut ark_std::test_rng();
+        type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, SquaringStep { z: None }).unwrap();
+
+        let z_0 = Fr::rand(rng);
+        let base_input = vec![Fr::one(), z_0, z_0.square()];
+        let base_proof = Proof::<Affine>::base_case(&base_input);
+        assert!(Nark::verify_ivc_step(&ivk, &base_input, &base_proof, true, None).unwrap());
+        let other_input = vec![Fr::one(), z_0 + Fr::one(), z_0.square()];
+        assert!(!Nark::verify_ivc_step(&ivk, &other_input, &base_proof, true, None).unwrap());
+        assert!(matches!(
+            Nark::verify_ivc_step(&ivk, &base_input[..2], &base_proof, true, None),
+            Err(NarkError::MalformedInput {
+                expected_len: 3,
+                got: 2
+            })
+        ));
+
+        // The base-case proof is rejected unless the caller says that it is verifying step 0.
+        assert!(Nark::verify(&ivk, &base_input, &base_proof, None).is_err());
+        assert!(Nark::verify_ivc_step(&ivk, &base_input, &base_proof, false, None).is_err());
+
+        let mut forged = Proof::<Affine>::base_case(&base_input);
+        forged.first_msg.comm_a = Affine::prime_subgroup_generator();
+        assert!(!Nark::verify_ivc_step(&ivk, &base_input, &forged, true, None).unwrap());
+
+        let mut z = z_0;
+        for _ in 1..4 {
+            let step = SquaringStep { z: Some(z) };
+            let input = vec![Fr::one(), z, z.square()];
+            let proof = Nark::prove(&ipk, step, true, None, Some(rng)).unwrap();
+            assert!(Nark::verify_ivc_step(&ivk, &input, &proof, false, None).unwrap());
+
+            // A proof for a later step cannot be passed off as the base case.
+            assert!(!Nark::verify_ivc_step(&ivk, &input, &proof, true, None).unwrap());
+
+            z = z.square();
+        }
+    }
+
+    #[test]
+    fn test_version_mismatch() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        assert_eq!(ivk.index_info.protocol_version, CURRENT_PROTOCOL_VERSION);
+        let proof = Nark::prove(&ipk, c, true, None, Some(rng)).unwrap();
+
+        // Keys from an older version of the protocol are rejected by the prover and the verifier.
+        let mut old_ipk = ipk.clone();
+        old_ipk.index_info.protocol_version = CURRENT_PROTOCOL_VERSION - 1;
+        let old_ivk = IndexVerifierKey::<Affine>::from_bytes(&old_ipk.to_bytes()).unwrap();
+        assert!(matches!(
+            Nark::prove(&old_ipk, c, true, None, Some(rng)),
+            Err(NarkError::VersionMismatch { expected, got })
+                if expected == CURRENT_PROTOCOL_VERSION && got == CURRENT_PROTOCOL_VERSION - 1
+        ));
+        assert!(matches!(
+            Nark::verify(&old_ivk, &r1cs_input, &proof, None),
+            Err(NarkError::VersionMismatch { .. })
+        ));
+
+        // Proofs for one version of a circuit do not verify under another.
+        let (ipk_1, ivk_1) = Nark::index_with_version(&pp, c, 1).unwrap();
+        let (ipk_2, ivk_2) = Nark::index_with_version(&pp, c, 2).unwrap();
+        let proof_1 = Nark::prove(&ipk_1, c, true, None, Some(rng)).unwrap();
+        let proof_2 = Nark::prove(&ipk_2, c, true, None, Some(rng)).unwrap();
+        assert!(Nark::verify(&ivk_1, &r1cs_input, &proof_1, None).unwrap());
+        assert!(Nark::verify(&ivk_2, &r1cs_input, &proof_2, None).unwrap());
+        assert!(!Nark::verify(&ivk_2, &r1cs_input, &proof_1, None).unwrap());
+        assert!(!Nark::verify(&ivk_1, &r1cs_input, &proof_2, None).unwrap());
+    }
+
+    #[test]
+    fn test_app_domain() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+        let pp = Nark::setup();
+        let (voting_ipk, voting_ivk) = Nark::index_with_app_domain(&pp, c, b"voting").unwrap();
+        let (auction_ipk, auction_ivk) = Nark::index_with_app_domain(&pp, c, b"auction").unwrap();
+        assert_eq!(
+            voting_ivk.index_info.matrices_hash,
+            auction_ivk.index_info.matrices_hash
+        );
+
+        let voting_proof = Nark::prove(&voting_ipk, c, true, None, Some(rng)).unwrap();
+        let auction_proof = Nark::prove(&auction_ipk, c, true, None, Some(rng)).unwrap();
+        assert!(Nark::verify(&voting_ivk, &r1cs_input, &voting_proof, None).unwrap());
+        assert!(Nark::verify(&auction_ivk, &r1cs_input, &auction_proof, None).unwrap());
+        assert!(!Nark::verify(&auction_ivk, &r1cs_input, &voting_proof, None).unwrap());
+        assert!(!Nark::verify(&voting_ivk, &r1cs_input, &auction_proof, None).unwrap());
+
+        // The domain survives serialization of the key.
+        let voting_ivk = IndexVerifierKey::<Affine>::from_bytes(&voting_ivk.to_bytes()).unwrap();
+        assert!(Nark::verify(&voting_ivk, &r1cs_input, &voting_proof, None).unwrap());
+        assert!(!Nark::verify(&voting_ivk, &r1cs_input, &auction_proof, None).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_comm_a() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = R1CSNark::<Affine, PoseidonSponge<Fq>>::setup();
+        let (ipk, ivk) = R1CSNark::<Affine, PoseidonSponge<Fq>>::index(&pp, c).unwrap();
+
+        for &make_zk in &[false, true] {
+            let mut proof =
+                R1CSNark::<Affine, PoseidonSponge<Fq>>::prove(&ipk, c, make_zk, None, Some(rng))
+                    .unwrap();
+            proof.first_msg.comm_a = proof.first_msg.comm_b;
+
+            assert!(!R1CSNark::<Affine, PoseidonSponge<Fq>>::verify(
+                &ivk,
+                &r1cs_input,
+                &proof,
+                None
+            )
+            .unwrap());
+        }
+    }
+
     #[test]
     fn test_simple_circuit() {
         let rng = &mut ark_std::test_rng();
@@ -545,7 +2479,8 @@ pub(crate) mod test {
                 &r1cs_input,
                 &proof,
                 Some(PoseidonSponge::<Fq>::new()),
-            ))
+            )
+            .unwrap())
         }
 
         println!(
@@ -0,0 +1,89 @@
+use super::{
+    check_commitment_key, FullAssignment, IndexProverKey, IndexVerifierKey, R1CSNark, R1CSResult,
+};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::io::{Read, Write};
+
+/// A proof for [`R1CSNark`] that reveals the full assignment instead of committing to it. It is
+/// neither zero-knowledge nor succinct, but producing it requires no group operations, which makes
+/// it useful for debugging, benchmarking, and systems in which the witness may be public.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct NonPrivateProof<G: AffineCurve> {
+    /// The public input and the witness, padded to the size of the index.
+    pub assignment: FullAssignment<G::ScalarField>,
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Proves that some R1CS relation holds by revealing its assignment. This skips the
+    /// commitments and blinding of [`R1CSNark::prove`], so it only costs the synthesis of `r1cs`.
+    pub fn prove_public<C: ConstraintSynthesizer<G::ScalarField>>(
+        ipk: &IndexProverKey<G>,
+        r1cs: C,
+    ) -> R1CSResult<NonPrivateProof<G>> {
+        check_commitment_key(ipk)?;
+
+        let (input, witness) = Self::synthesize(ipk, r1cs)?;
+        Ok(NonPrivateProof {
+            assignment: FullAssignment { input, witness },
+        })
+    }
+
+    /// Verifies a [`NonPrivateProof`] by checking that its assignment has the public input
+    /// `input` and satisfies the R1CS instance of `ivk`.
+    pub fn verify_public(
+        ivk: &IndexVerifierKey<G>,
+        input: &[G::ScalarField],
+        proof: &NonPrivateProof<G>,
+    ) -> bool {
+        proof.assignment.input == input && Self::validate_assignment(ivk, &proof.assignment)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+    use ark_std::vec::Vec;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_non_private_proof() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let proof = Nark::prove_public(&ipk, c).unwrap();
+        assert!(Nark::verify_public(&ivk, &r1cs_input, &proof));
+
+        let wrong_input = vec![Fr::one(), Fr::rand(rng)];
+        assert!(!Nark::verify_public(&ivk, &wrong_input, &proof));
+
+        let mut bytes = Vec::new();
+        proof.serialize(&mut bytes).unwrap();
+        let mut tampered = NonPrivateProof::<Affine>::deserialize(&bytes[..]).unwrap();
+        assert_eq!(tampered, proof);
+        tampered.assignment.witness[0] += Fr::one();
+        assert!(!Nark::verify_public(&ivk, &r1cs_input, &tampered));
+    }
+}
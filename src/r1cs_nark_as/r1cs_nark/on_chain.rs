@@ -0,0 +1,115 @@
+use super::{FirstRoundMessage, IndexVerifierKey, Proof, R1CSNark, SecondRoundMessage};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::marker::PhantomData;
+use ark_std::vec::Vec;
+
+/// A [`Proof`] packed into the compressed encoding of its two messages, for posting to a chain.
+///
+/// Every field of the messages is needed to verify the proof, so none is removed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OnChainProof<G: AffineCurve> {
+    /// The compressed serialization of the first and second messages of the proof.
+    pub bytes: Vec<u8>,
+
+    _affine: PhantomData<G>,
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Packs `proof` into an [`OnChainProof`].
+    pub fn finalize_proof_for_chain(proof: &Proof<G>) -> OnChainProof<G> {
+        let mut bytes = Vec::with_capacity(
+            proof.first_msg.serialized_size() + proof.second_msg.serialized_size(),
+        );
+        proof.first_msg.serialize(&mut bytes).unwrap();
+        proof.second_msg.serialize(&mut bytes).unwrap();
+
+        OnChainProof {
+            bytes,
+            _affine: PhantomData,
+        }
+    }
+
+    /// Unpacks `proof` and verifies it like [`R1CSNark::verify`] with the default sponge.
+    /// Returns `false` if the bytes cannot be decoded, contain trailing data, or do not have the
+    /// lengths expected by `ivk`.
+    pub fn verify_on_chain(
+        ivk: &IndexVerifierKey<G>,
+        input: &[G::ScalarField],
+        proof: &OnChainProof<G>,
+    ) -> bool {
+        let mut reader = proof.bytes.as_slice();
+        let first_msg = match FirstRoundMessage::deserialize(&mut reader) {
+            Ok(first_msg) => first_msg,
+            Err(_) => return false,
+        };
+        let second_msg = match SecondRoundMessage::deserialize(&mut reader) {
+            Ok(second_msg) => second_msg,
+            Err(_) => return false,
+        };
+        if !reader.is_empty() {
+            return false;
+        }
+
+        let proof = Proof {
+            first_msg,
+            second_msg,
+        };
+        Self::verify(ivk, input, &proof, None).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_verify_on_chain() {
+        let rng = &mut ark_std::test_rng();
+        let circuit = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let input = vec![Fr::one(), circuit.a.unwrap() * circuit.b.unwrap()];
+        let wrong_input = vec![Fr::one(), input[1] + Fr::one()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, circuit).unwrap();
+
+        for &make_zk in &[false, true] {
+            let proof = Nark::prove(&ipk, circuit, make_zk, None, Some(rng)).unwrap();
+            let on_chain = Nark::finalize_proof_for_chain(&proof);
+            for input in &[&input, &wrong_input] {
+                assert_eq!(
+                    Nark::verify_on_chain(&ivk, input, &on_chain),
+                    Nark::verify(&ivk, input, &proof, None).unwrap()
+                );
+            }
+            assert!(Nark::verify_on_chain(&ivk, &input, &on_chain));
+
+            let mut truncated = on_chain.clone();
+            truncated.bytes.pop();
+            assert!(!Nark::verify_on_chain(&ivk, &input, &truncated));
+
+            let mut extended = on_chain.clone();
+            extended.bytes.push(0);
+            assert!(!Nark::verify_on_chain(&ivk, &input, &extended));
+        }
+    }
+}
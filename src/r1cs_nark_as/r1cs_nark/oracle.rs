@@ -0,0 +1,204 @@
+use super::{
+    blake2b, FullAssignment, IndexProverKey, IndexVerifierKey, NarkError, Proof, R1CSNark,
+    R1CSResult,
+};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::Field;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::marker::PhantomData;
+use ark_std::rand::RngCore;
+
+/// A source of witnesses for [`R1CSNark::prove_with_oracle`], such as a remote service to which
+/// the witness computation is outsourced.
+pub trait WitnessOracle<G: AffineCurve>: Send + Sync {
+    /// Returns the full assignment of the circuit for the public `input`, which includes the
+    /// constant `1`.
+    fn compute_witness(
+        &self,
+        input: &[G::ScalarField],
+    ) -> Result<FullAssignment<G::ScalarField>, NarkError>;
+
+    /// Returns an identifier of the oracle, which is recorded in the receipts of the proofs of
+    /// the witnesses it computes.
+    fn oracle_id(&self) -> [u8; 32];
+}
+
+/// A record of the [`WitnessOracle`] that computed the witness of a proof. The identifier of the
+/// oracle is absorbed into the sponge before proving, so the proof only verifies with
+/// [`R1CSNark::verify_with_receipt`] under the receipt of that oracle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OracleReceipt {
+    /// The identifier of the oracle that computed the witness.
+    pub oracle_id: [u8; 32],
+}
+
+/// A [`WitnessOracle`] that computes witnesses by running a circuit locally. Its identifier is
+/// the Blake2b hash of a label chosen by its owner.
+pub struct LocalOracle<F: Field, CF> {
+    oracle_id: [u8; 32],
+    circuit_fn: CF,
+    _field: PhantomData<F>,
+}
+
+impl<F, C, CF> LocalOracle<F, CF>
+where
+    F: Field,
+    C: ConstraintSynthesizer<F>,
+    CF: Fn(&[F]) -> C + Send + Sync,
+{
+    /// Computes witnesses by running the circuit that `circuit_fn` returns for each public
+    /// input, and identifies itself by the hash of `label`.
+    pub fn new(label: &[u8], circuit_fn: CF) -> Self {
+        Self {
+            oracle_id: blake2b(label),
+            circuit_fn,
+            _field: PhantomData,
+        }
+    }
+}
+
+impl<G, C, CF> WitnessOracle<G> for LocalOracle<G::ScalarField, CF>
+where
+    G: AffineCurve,
+    C: ConstraintSynthesizer<G::ScalarField>,
+    CF: Fn(&[G::ScalarField]) -> C + Send + Sync,
+{
+    fn compute_witness(
+        &self,
+        input: &[G::ScalarField],
+    ) -> Result<FullAssignment<G::ScalarField>, NarkError> {
+        let cs = ConstraintSystem::new_ref();
+        (self.circuit_fn)(input).generate_constraints(cs.clone())?;
+
+        let cs = cs.borrow().unwrap();
+        Ok(FullAssignment {
+            input: cs.instance_assignment.clone(),
+            witness: cs.witness_assignment.clone(),
+        })
+    }
+
+    fn oracle_id(&self) -> [u8; 32] {
+        self.oracle_id
+    }
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Proves that some R1CS relation holds for the public `input`, with the witness computed by
+    /// `oracle`. The proof is bound to the identifier of the oracle, which is returned in a
+    /// receipt. The proof is zero knowledge if `rng` is provided.
+    ///
+    /// Returns [`NarkError::OracleInputMismatch`] if the oracle returns an assignment for another
+    /// public input, and the errors of [`R1CSNark::prove_lazy`] otherwise.
+    pub fn prove_with_oracle(
+        ipk: &IndexProverKey<G>,
+        input: &[G::ScalarField],
+        oracle: &dyn WitnessOracle<G>,
+        sponge: Option<S>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> R1CSResult<(Proof<G>, OracleReceipt)> {
+        let oracle_id = oracle.oracle_id();
+        let mut sponge = sponge.unwrap_or_else(S::new);
+        sponge.absorb(&oracle_id.as_ref());
+
+        let witness_fn = || {
+            let assignment = oracle.compute_witness(input)?;
+            if assignment.input != input {
+                return Err(NarkError::OracleInputMismatch { oracle_id });
+            }
+            Ok(assignment)
+        };
+        let proof = Self::prove_lazy(ipk, witness_fn, Some(sponge), rng)?;
+
+        Ok((proof, OracleReceipt { oracle_id }))
+    }
+
+    /// Verifies a proof produced by [`R1CSNark::prove_with_oracle`] with the witness computed by
+    /// the oracle of `receipt`. Returns false if the proof was computed with another oracle.
+    pub fn verify_with_receipt(
+        ivk: &IndexVerifierKey<G>,
+        input: &[G::ScalarField],
+        proof: &Proof<G>,
+        receipt: &OracleReceipt,
+        sponge: Option<S>,
+    ) -> R1CSResult<bool> {
+        let mut sponge = sponge.unwrap_or_else(S::new);
+        sponge.absorb(&receipt.oracle_id.as_ref());
+        Self::verify(ivk, input, proof, Some(sponge))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    // Returns the public input of its witness plus one, as a faulty remote oracle might.
+    struct FaultyOracle<O>(O);
+
+    impl<O: WitnessOracle<Affine>> WitnessOracle<Affine> for FaultyOracle<O> {
+        fn compute_witness(&self, input: &[Fr]) -> Result<FullAssignment<Fr>, NarkError> {
+            let mut assignment = self.0.compute_witness(input)?;
+            assignment.input[1] += Fr::one();
+            Ok(assignment)
+        }
+
+        fn oracle_id(&self) -> [u8; 32] {
+            blake2b(b"faulty")
+        }
+    }
+
+    #[test]
+    fn test_prove_with_oracle() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let oracle = LocalOracle::new(b"prover-1", move |_: &[Fr]| c);
+        let other_oracle = LocalOracle::new(b"prover-2", move |_: &[Fr]| c);
+
+        let (proof, receipt) =
+            Nark::prove_with_oracle(&ipk, &r1cs_input, &oracle, None, Some(rng)).unwrap();
+        assert_eq!(
+            receipt.oracle_id,
+            WitnessOracle::<Affine>::oracle_id(&oracle)
+        );
+        assert_eq!(receipt.oracle_id, blake2b(b"prover-1"));
+        assert!(Nark::verify_with_receipt(&ivk, &r1cs_input, &proof, &receipt, None).unwrap());
+
+        // The proof does not verify under the receipt of another oracle, nor without a receipt.
+        let (_, other_receipt) =
+            Nark::prove_with_oracle(&ipk, &r1cs_input, &other_oracle, None, Some(rng)).unwrap();
+        assert_ne!(receipt, other_receipt);
+        assert!(
+            !Nark::verify_with_receipt(&ivk, &r1cs_input, &proof, &other_receipt, None).unwrap()
+        );
+        assert!(!Nark::verify(&ivk, &r1cs_input, &proof, None).unwrap());
+
+        let faulty_oracle = FaultyOracle(oracle);
+        let result = Nark::prove_with_oracle(&ipk, &r1cs_input, &faulty_oracle, None, None);
+        assert!(matches!(
+            result,
+            Err(NarkError::OracleInputMismatch { oracle_id }) if oracle_id == blake2b(b"faulty")
+        ));
+    }
+}
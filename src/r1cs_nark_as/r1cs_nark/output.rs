@@ -0,0 +1,123 @@
+use super::{IndexProverKey, IndexVerifierKey, Proof, R1CSNark, R1CSResult};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::{BigInteger, PrimeField};
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_sponge::{absorb, Absorbable, CryptographicSponge};
+use ark_std::rand::RngCore;
+use ark_std::vec::Vec;
+
+const OUTPUT_DOMAIN: &[u8] = b"R1CS-NARK-2020-OUTPUT";
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Proves that some R1CS relation holds, and binds the proof to `output`, a value that is
+    /// derived from the witness rather than part of the public input. `output` is absorbed into
+    /// the sponge before the proof's challenge is computed, so the proof only verifies with
+    /// [`R1CSNark::verify_with_output`] for the same `output`.
+    ///
+    /// The proof is always zero knowledge, since the checks of a proof without zero knowledge do
+    /// not depend on the challenge, and so could not be bound to `output`.
+    pub fn prove_with_output<C: ConstraintSynthesizer<G::ScalarField>>(
+        ipk: &IndexProverKey<G>,
+        r1cs: C,
+        output: &[G::ScalarField],
+        sponge: Option<S>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> R1CSResult<Proof<G>> {
+        let sponge = Self::absorb_output(output, sponge);
+        Self::prove(ipk, r1cs, true, sponge, rng)
+    }
+
+    /// Verifies that some R1CS relation holds for a proof produced by
+    /// [`R1CSNark::prove_with_output`] with the same `output`. Proofs without zero knowledge are
+    /// rejected, as are malformed inputs and proofs.
+    pub fn verify_with_output(
+        ivk: &IndexVerifierKey<G>,
+        input: &[G::ScalarField],
+        output: &[G::ScalarField],
+        proof: &Proof<G>,
+        sponge: Option<S>,
+    ) -> bool {
+        if proof.first_msg.randomness.is_none() {
+            return false;
+        }
+
+        let sponge = Self::absorb_output(output, sponge);
+        Self::verify(ivk, input, proof, sponge).unwrap_or(false)
+    }
+
+    fn absorb_output(output: &[G::ScalarField], sponge: Option<S>) -> Option<S> {
+        let mut sponge = sponge.unwrap_or_else(|| S::new());
+        let output_bytes = output
+            .iter()
+            .flat_map(|out| out.into_repr().to_bytes_le())
+            .collect::<Vec<_>>();
+        absorb!(&mut sponge, OUTPUT_DOMAIN, output_bytes);
+        Some(sponge)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_output_binding() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+        let (five, six) = (vec![Fr::from(5u64)], vec![Fr::from(6u64)]);
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let proof = Nark::prove_with_output(&ipk, c, &five, None, Some(rng)).unwrap();
+        assert!(Nark::verify_with_output(
+            &ivk,
+            &r1cs_input,
+            &five,
+            &proof,
+            None
+        ));
+        assert!(!Nark::verify_with_output(
+            &ivk,
+            &r1cs_input,
+            &six,
+            &proof,
+            None
+        ));
+        assert!(!Nark::verify_with_output(
+            &ivk,
+            &r1cs_input,
+            &[],
+            &proof,
+            None
+        ));
+
+        // A proof without zero knowledge cannot be bound to an output.
+        let plain_proof = Nark::prove(&ipk, c, false, None, None).unwrap();
+        assert!(!Nark::verify_with_output(
+            &ivk,
+            &r1cs_input,
+            &five,
+            &plain_proof,
+            None
+        ));
+    }
+}
@@ -0,0 +1,138 @@
+use super::{
+    FullAssignment, IndexInfo, IndexProverKey, IndexVerifierKey, PublicParameters, R1CSNark,
+    R1CSResult,
+};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::Field;
+use ark_relations::r1cs::{ConstraintSynthesizer, Matrix, SynthesisError};
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::vec::Vec;
+
+/// Pads the witness of `assignment` with zeros so that the assignment has `target_size`
+/// variables in total, counting both the input and the witness.
+pub fn pad_assignment<F: Field>(assignment: &mut FullAssignment<F>, target_size: usize) {
+    let num_variables = assignment.input.len() + assignment.witness.len();
+    assert!(target_size >= num_variables);
+
+    assignment
+        .witness
+        .resize(target_size - assignment.input.len(), F::zero());
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Outputs a specialized prover and verifier key for some R1CS instance whose matrices are
+    /// padded with zero rows and columns to `num_constraints` rows and `num_variables` columns.
+    /// Witnesses are padded with zeros during proving. Returns an error if the instance is
+    /// larger than the requested size.
+    pub fn index_padded<C: ConstraintSynthesizer<G::ScalarField>>(
+        pp: &PublicParameters,
+        r1cs_instance: C,
+        num_variables: usize,
+        num_constraints: usize,
+    ) -> R1CSResult<(IndexProverKey<G>, IndexVerifierKey<G>)> {
+        let (ipk, _) = Self::index(pp, r1cs_instance)?;
+        let IndexInfo {
+            num_variables: unpadded_num_variables,
+            num_constraints: unpadded_num_constraints,
+            num_instance_variables,
+            num_pruned_constraints,
+            num_circuit_constraints,
+            ..
+        } = ipk.index_info;
+        if num_variables < unpadded_num_variables || num_constraints < unpadded_num_constraints {
+            return Err(SynthesisError::PolynomialDegreeTooLarge.into());
+        }
+
+        let IndexProverKey { a, b, c, .. } = ipk;
+        let (mut a, mut b, mut c) = (Matrix::from(a), Matrix::from(b), Matrix::from(c));
+        a.resize(num_constraints, Vec::new());
+        b.resize(num_constraints, Vec::new());
+        c.resize(num_constraints, Vec::new());
+
+        let (mut ipk, mut ivk) = Self::index_from_matrices(
+            pp,
+            a,
+            b,
+            c,
+            num_instance_variables,
+            num_variables - num_instance_variables,
+        )?;
+        ipk.index_info.num_pruned_constraints = num_pruned_constraints;
+        ivk.index_info.num_pruned_constraints = num_pruned_constraints;
+        ipk.index_info.num_circuit_constraints = num_circuit_constraints;
+        ivk.index_info.num_circuit_constraints = num_circuit_constraints;
+        ipk.index_info.is_padded = true;
+        ivk.index_info.is_padded = true;
+        Ok((ipk, ivk))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use crate::r1cs_nark_as::r1cs_nark::NarkError;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_padding() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 4,
+            num_constraints: 5,
+        };
+        let input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (unpadded_ipk, _) = Nark::index(&pp, c).unwrap();
+        assert_eq!(unpadded_ipk.index_info.num_variables, 5);
+
+        let (ipk, ivk) = Nark::index_padded(&pp, c, 8, 8).unwrap();
+        assert_eq!(ipk.index_info.num_variables, 8);
+        assert_eq!(ipk.index_info.num_constraints, 8);
+        assert_eq!(ipk.ck.supported_num_elems(), 8);
+
+        let (input_assignment, witness) = Nark::synthesize(&unpadded_ipk, c).unwrap();
+        let mut assignment = FullAssignment {
+            input: input_assignment,
+            witness,
+        };
+        assert!(Nark::validate_assignment(&unpadded_ipk, &assignment));
+        pad_assignment(&mut assignment, 8);
+        assert_eq!(assignment.witness.len(), 6);
+        assert!(Nark::validate_assignment(&ivk, &assignment));
+
+        for &make_zk in &[false, true] {
+            let proof = Nark::prove(&ipk, c, make_zk, None, Some(rng)).unwrap();
+            assert_eq!(proof.second_msg.blinded_witness.len(), 6);
+            assert!(Nark::verify(&ivk, &input, &proof, None).unwrap());
+        }
+
+        // Only the padded index accepts a circuit with fewer variables.
+        let small_c = DummyCircuit {
+            num_variables: 3,
+            ..c
+        };
+        assert!(Nark::prove(&ipk, small_c, false, None, None).is_ok());
+        assert!(matches!(
+            Nark::prove(&unpadded_ipk, small_c, false, None, None),
+            Err(NarkError::WitnessSizeMismatch { .. })
+        ));
+
+        assert!(Nark::index_padded(&pp, c, 4, 8).is_err());
+        assert!(Nark::index_padded(&pp, c, 8, 4).is_err());
+    }
+}
@@ -0,0 +1,353 @@
+use crate::r1cs_nark_as::CHALLENGE_SIZE;
+use crate::ConstraintF;
+
+use ark_ec::msm::VariableBaseMSM;
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+use ark_poly_commit::trivial_pc::{CommitterKey, PedersenCommitment};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_sponge::{absorb, Absorbable, CryptographicSponge, FieldElementSize};
+use ark_std::io::{Read, Write};
+use ark_std::marker::PhantomData;
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+const OPENING_DOMAIN: &[u8] = b"R1CS-NARK-2020-VECTOR-OPENING";
+const MASK_DOMAIN: &[u8] = b"R1CS-NARK-2020-VECTOR-OPENING-MASK";
+
+/// A proof that the value at one index of the vector in a [`PedersenVectorCommitment`] is some
+/// value, which reveals nothing else about the vector if the commitment is hiding.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct OpeningProof<G: AffineCurve> {
+    /// The commitment to the mask that hides the rest of the vector.
+    pub mask_comm: G,
+
+    /// The cross terms of the left halves of the scalars with the right halves of the
+    /// generators, one per round of the inner product argument.
+    pub l: Vec<G>,
+
+    /// The cross terms of the right halves of the scalars with the left halves of the
+    /// generators, one per round of the inner product argument.
+    pub r: Vec<G>,
+
+    /// The single scalar left after the last round.
+    pub response: G::ScalarField,
+}
+
+/// The Pedersen commitment that [`R1CSNark`][nark] uses for vectors, with openings of single
+/// elements. This allows some elements of a committed witness to be disclosed while the others
+/// stay hidden.
+///
+/// To open the value `v` at index `i`, the prover shows that it knows an opening of
+/// `comm - v * G_i` under the other generators, including the hiding generator. Since the
+/// generators have no known discrete logarithm relations, this implies that `v` is the value at
+/// index `i`. The opening is masked with a Schnorr-like step, and then proven with an inner
+/// product argument in the style of Bulletproofs. The proof has `O(log n)` group elements for a
+/// key of `n` generators.
+///
+/// [nark]: crate::r1cs_nark_as::r1cs_nark::R1CSNark
+pub struct PedersenVectorCommitment<G, S>
+where
+    G: AffineCurve,
+{
+    _affine: PhantomData<G>,
+    _sponge: PhantomData<S>,
+}
+
+impl<G, S> PedersenVectorCommitment<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Commits to `values`, exactly as [`PedersenCommitment::commit`] does.
+    pub fn commit(
+        ck: &CommitterKey<G>,
+        values: &[G::ScalarField],
+        blinding: Option<G::ScalarField>,
+    ) -> G {
+        PedersenCommitment::commit(ck, values, blinding)
+    }
+
+    /// Proves that the value at `index` of the vector committed to with `blinding` is
+    /// `values[index]`.
+    ///
+    /// The mask is derived from the committed vector and the blinding, so that opening the same
+    /// index twice produces the same proof. The other values are hidden exactly as well as they
+    /// are by the commitment, i.e. only if `blinding` is provided.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds, or if `ck` is too short for `values`.
+    pub fn open(
+        ck: &CommitterKey<G>,
+        values: &[G::ScalarField],
+        index: usize,
+        blinding: Option<G::ScalarField>,
+    ) -> OpeningProof<G> {
+        assert!(index < values.len(), "the index is out of bounds");
+        assert!(
+            values.len() <= ck.generators.len(),
+            "the commitment key is too short"
+        );
+
+        let comm = Self::commit(ck, values, blinding);
+        let mut sponge = Self::init_sponge(comm, index, values[index]);
+
+        // The opening of `comm - value * G_index` under the other generators.
+        let mut generators = other_generators(ck, index);
+        let mut opening = values.to_vec();
+        opening.remove(index);
+        opening.resize(ck.generators.len() - 1, G::ScalarField::zero());
+        opening.push(blinding.unwrap_or_else(G::ScalarField::zero));
+        opening.resize(generators.len(), G::ScalarField::zero());
+
+        let mask = Self::derive_mask(values, index, blinding, generators.len());
+        let mask_comm = msm(&generators, &mask).into_affine();
+        absorb!(&mut sponge, mask_comm);
+        let e = squeeze_challenge::<G, S>(&mut sponge);
+        let mut scalars: Vec<_> = mask
+            .into_iter()
+            .zip(opening)
+            .map(|(m, o)| m + e * o)
+            .collect();
+
+        let (mut l, mut r) = (Vec::new(), Vec::new());
+        while scalars.len() > 1 {
+            let half = scalars.len() / 2;
+            let (scalars_lo, scalars_hi) = scalars.split_at(half);
+            let (generators_lo, generators_hi) = generators.split_at(half);
+            let l_i = msm(generators_hi, scalars_lo).into_affine();
+            let r_i = msm(generators_lo, scalars_hi).into_affine();
+
+            absorb!(&mut sponge, l_i, r_i);
+            let x = squeeze_challenge::<G, S>(&mut sponge);
+            let x_inv = x.inverse().unwrap();
+
+            scalars = scalars_lo
+                .iter()
+                .zip(scalars_hi)
+                .map(|(lo, hi)| *lo * x + *hi * x_inv)
+                .collect();
+            generators = fold_generators(&generators, x, x_inv);
+            l.push(l_i);
+            r.push(r_i);
+        }
+
+        OpeningProof {
+            mask_comm,
+            l,
+            r,
+            response: scalars[0],
+        }
+    }
+
+    /// Verifies that `value` is the value at `index` of the vector committed to in `comm`.
+    /// Returns false if `index` is out of bounds for `ck`.
+    pub fn verify_opening(
+        ck: &CommitterKey<G>,
+        comm: G,
+        index: usize,
+        value: G::ScalarField,
+        proof: &OpeningProof<G>,
+    ) -> bool {
+        if index >= ck.generators.len() {
+            return false;
+        }
+
+        let mut generators = other_generators(ck, index);
+        let num_rounds = generators.len().trailing_zeros() as usize;
+        if proof.l.len() != num_rounds || proof.r.len() != num_rounds {
+            return false;
+        }
+
+        let mut sponge = Self::init_sponge(comm, index, value);
+        absorb!(&mut sponge, proof.mask_comm);
+        let e = squeeze_challenge::<G, S>(&mut sponge);
+        let mut target = proof.mask_comm.into_projective()
+            + (comm.into_projective() - ck.generators[index].mul(value)).mul(e.into_repr());
+
+        for (&l_i, &r_i) in proof.l.iter().zip(&proof.r) {
+            absorb!(&mut sponge, l_i, r_i);
+            let x = squeeze_challenge::<G, S>(&mut sponge);
+            let x_inv = match x.inverse() {
+                Some(x_inv) => x_inv,
+                None => return false,
+            };
+
+            target += l_i.mul(x.square()) + r_i.mul(x_inv.square());
+            generators = fold_generators(&generators, x, x_inv);
+        }
+
+        target == generators[0].mul(proof.response)
+    }
+
+    // Returns a sponge that has absorbed the statement of an opening.
+    fn init_sponge(comm: G, index: usize, value: G::ScalarField) -> S {
+        let mut sponge = S::new();
+        let index_bytes = (index as u64).to_le_bytes();
+        let value_bytes = value.into_repr().to_bytes_le();
+        absorb!(
+            &mut sponge,
+            OPENING_DOMAIN,
+            comm,
+            index_bytes.as_ref(),
+            value_bytes
+        );
+        sponge
+    }
+
+    // Derives the mask of an opening from the committed vector and its blinding, which only the
+    // prover knows.
+    fn derive_mask(
+        values: &[G::ScalarField],
+        index: usize,
+        blinding: Option<G::ScalarField>,
+        len: usize,
+    ) -> Vec<G::ScalarField> {
+        let mut sponge = S::new();
+        let secret_bytes = values
+            .iter()
+            .chain(&blinding)
+            .flat_map(|elem| elem.into_repr().to_bytes_le())
+            .collect::<Vec<_>>();
+        let index_bytes = (index as u64).to_le_bytes();
+        absorb!(&mut sponge, MASK_DOMAIN, secret_bytes, index_bytes.as_ref());
+        sponge.squeeze_nonnative_field_elements_with_sizes(&vec![FieldElementSize::Full; len])
+    }
+}
+
+// Returns the generators of `ck` other than the one at `index`, followed by the hiding
+// generator, padded with the identity to a power of two. The padding does not affect binding,
+// since the identity contributes nothing to a commitment.
+fn other_generators<G: AffineCurve>(ck: &CommitterKey<G>, index: usize) -> Vec<G> {
+    let mut generators = ck.generators.clone();
+    generators.remove(index);
+    generators.push(ck.hiding_generator);
+    generators.resize(generators.len().next_power_of_two(), G::zero());
+    generators
+}
+
+// Returns `<scalars, generators>`.
+fn msm<G: AffineCurve>(generators: &[G], scalars: &[G::ScalarField]) -> G::Projective {
+    let scalars = scalars.iter().map(|s| s.into_repr()).collect::<Vec<_>>();
+    VariableBaseMSM::multi_scalar_mul(generators, &scalars)
+}
+
+// Folds the two halves of `generators` into `x^-1 * lo + x * hi`.
+fn fold_generators<G: AffineCurve>(
+    generators: &[G],
+    x: G::ScalarField,
+    x_inv: G::ScalarField,
+) -> Vec<G> {
+    let (lo, hi) = generators.split_at(generators.len() / 2);
+    let folded = lo
+        .iter()
+        .zip(hi)
+        .map(|(lo, hi)| lo.mul(x_inv) + hi.mul(x))
+        .collect::<Vec<_>>();
+    G::Projective::batch_normalization_into_affine(&folded)
+}
+
+// Squeezes a challenge of the inner product argument, which is an integer of at most 128 bits.
+fn squeeze_challenge<G, S>(sponge: &mut S) -> G::ScalarField
+where
+    G: AffineCurve,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    sponge
+        .squeeze_nonnative_field_elements_with_sizes(&[FieldElementSize::Truncated(CHALLENGE_SIZE)])
+        .pop()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type VectorCommitment = PedersenVectorCommitment<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_open() {
+        let rng = &mut ark_std::test_rng();
+        let pp = PedersenCommitment::setup(10);
+        let ck = PedersenCommitment::trim(&pp, 10);
+
+        for &len in &[1, 6, 10] {
+            let values: Vec<_> = (0..len).map(|_| Fr::rand(rng)).collect();
+            for &blinding in &[None, Some(Fr::rand(rng))] {
+                let comm = VectorCommitment::commit(&ck, &values, blinding);
+                for index in 0..len {
+                    let proof = VectorCommitment::open(&ck, &values, index, blinding);
+                    assert!(VectorCommitment::verify_opening(
+                        &ck,
+                        comm,
+                        index,
+                        values[index],
+                        &proof
+                    ));
+
+                    let wrong_value = values[index] + Fr::one();
+                    assert!(!VectorCommitment::verify_opening(
+                        &ck,
+                        comm,
+                        index,
+                        wrong_value,
+                        &proof
+                    ));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_openings() {
+        let rng = &mut ark_std::test_rng();
+        let pp = PedersenCommitment::setup(8);
+        let ck = PedersenCommitment::trim(&pp, 8);
+        let values: Vec<_> = (0..8).map(|_| Fr::rand(rng)).collect();
+        let blinding = Some(Fr::rand(rng));
+        let comm = VectorCommitment::commit(&ck, &values, blinding);
+        let proof = VectorCommitment::open(&ck, &values, 3, blinding);
+        let verify = |index, value, proof: &OpeningProof<Affine>| {
+            VectorCommitment::verify_opening(&ck, comm, index, value, proof)
+        };
+        assert!(verify(3, values[3], &proof));
+
+        // The opening is bound to its position, even if the values at both positions are equal.
+        assert!(!verify(4, values[3], &proof));
+        let mut repeated = values.clone();
+        repeated[4] = repeated[3];
+        let repeated_comm = VectorCommitment::commit(&ck, &repeated, blinding);
+        let repeated_proof = VectorCommitment::open(&ck, &repeated, 3, blinding);
+        assert!(!VectorCommitment::verify_opening(
+            &ck,
+            repeated_comm,
+            4,
+            repeated[3],
+            &repeated_proof
+        ));
+
+        // The opening is bound to the commitment.
+        let other_comm = VectorCommitment::commit(&ck, &values, Some(Fr::rand(rng)));
+        assert!(!VectorCommitment::verify_opening(
+            &ck, other_comm, 3, values[3], &proof
+        ));
+
+        let mut tampered = proof.clone();
+        tampered.response += Fr::one();
+        assert!(!verify(3, values[3], &tampered));
+
+        let mut tampered = proof.clone();
+        tampered.l.swap(0, 1);
+        assert!(!verify(3, values[3], &tampered));
+
+        let mut truncated = proof;
+        truncated.r.pop();
+        assert!(!verify(3, values[3], &truncated));
+
+        assert!(!verify(8, values[3], &repeated_proof));
+    }
+}
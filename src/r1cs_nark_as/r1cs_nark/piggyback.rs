@@ -0,0 +1,412 @@
+use super::{
+    matrix_vec_mul, FirstRoundMessage, IndexProverKey, IndexVerifierKey, NarkError, Proof,
+    R1CSNark, R1CSResult, SecondRoundMessageRandomness,
+};
+use crate::r1cs_nark_as::CHALLENGE_SIZE;
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use ark_poly_commit::trivial_pc::PedersenCommitment;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_sponge::{absorb, Absorbable, CryptographicSponge, FieldElementSize};
+use ark_std::io::{Read, Write};
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+/// Several [`Proof`]s for the same R1CS instance, in which the second messages of the proofs are
+/// replaced by a single response. The first messages are kept, and the verifier checks random
+/// linear combinations of the equations of all the proofs:
+/// * the commitments to `A * z_i`, `B * z_i`, and `C * z_i` are combined with the coefficients
+///   `(mu * nu)^i`, and opened with `z_L = sum_i (mu * nu)^i * z_i`;
+/// * the commitments to `B * z_i` are also combined with the coefficients `nu^(n - 1 - i)`, and
+///   opened with `z_R = sum_i nu^(n - 1 - i) * z_i`;
+/// * the Hadamard products `(A * z_i) ○ (B * z_i)` are the coefficient of `X^(n - 1)` in
+///   `(sum_i (mu * X)^i * A * z_i) ○ (sum_i X^(n - 1 - i) * B * z_i)`, and the other coefficients
+///   are committed to in `cross_term_comms`, so that the commitment to
+///   `(A * z_L) ○ (B * z_R)` can be reconstructed.
+///
+/// Here `z_i` is the public input of proof `i` followed by its blinded witness, and `mu` and `nu`
+/// are Fiat-Shamir challenges.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PiggybackedProof<G: AffineCurve> {
+    /// The first messages of the proofs.
+    pub first_msgs: Vec<FirstRoundMessage<G>>,
+
+    /// The commitments to the coefficients of every power of `X` other than `X^(n - 1)` in the
+    /// combined Hadamard product, in increasing order.
+    pub cross_term_comms: Vec<G>,
+
+    /// The witness part of `z_L`.
+    pub left_witness: Vec<G::ScalarField>,
+
+    /// The witness part of `z_R`.
+    pub right_witness: Vec<G::ScalarField>,
+
+    /// The blinder of the commitment to `A * z_L`.
+    pub sigma_a: G::ScalarField,
+
+    /// The blinder of the commitment to `B * z_L`.
+    pub sigma_b: G::ScalarField,
+
+    /// The blinder of the commitment to `C * z_L`.
+    pub sigma_c: G::ScalarField,
+
+    /// The blinder of the commitment to `B * z_R`.
+    pub sigma_b_right: G::ScalarField,
+
+    /// The blinder of the commitment to `(A * z_L) ○ (B * z_R)`.
+    pub sigma_o: G::ScalarField,
+}
+
+// Returns `[1, x, ..., x^(n - 1)]`.
+fn powers<F: Field>(x: F, n: usize) -> Vec<F> {
+    let mut powers = Vec::with_capacity(n);
+    let mut power = F::one();
+    for _ in 0..n {
+        powers.push(power);
+        power *= x;
+    }
+
+    powers
+}
+
+// Returns `sum_i coeffs[i] * vectors[i]`.
+fn linear_combination<F: Field>(coeffs: &[F], vectors: &[&[F]]) -> Vec<F> {
+    let mut result = vec![F::zero(); vectors.first().map_or(0, |v| v.len())];
+    for (coeff, vector) in coeffs.iter().zip(vectors) {
+        for (r, v) in result.iter_mut().zip(vector.iter()) {
+            *r += *coeff * v;
+        }
+    }
+
+    result
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    // Squeezes a challenge from `sponge`.
+    fn squeeze_piggyback_challenge(sponge: &mut S) -> G::ScalarField {
+        sponge
+            .squeeze_nonnative_field_elements_with_sizes(&[FieldElementSize::Truncated(
+                CHALLENGE_SIZE,
+            )])
+            .pop()
+            .unwrap()
+    }
+
+    // Absorbs the public inputs and the first messages of the proofs, and returns the challenge
+    // `mu` that weighs their Hadamard products.
+    fn piggyback_mu(
+        ipk: &IndexProverKey<G>,
+        sub_inputs: &[&[G::ScalarField]],
+        first_msgs: &[FirstRoundMessage<G>],
+        sponge: &mut S,
+    ) -> G::ScalarField {
+        sponge.absorb(&ipk.index_info.matrices_hash.as_ref());
+        for (input, first_msg) in sub_inputs.iter().zip(first_msgs) {
+            let input_bytes = input
+                .iter()
+                .flat_map(|inp| inp.into_repr().to_bytes_le())
+                .collect::<Vec<_>>();
+            absorb!(sponge, input_bytes, first_msg);
+        }
+
+        Self::squeeze_piggyback_challenge(sponge)
+    }
+
+    // Absorbs the cross term commitments, and returns the challenge `nu` at which the combined
+    // vectors are evaluated.
+    fn piggyback_nu(cross_term_comms: &[G], sponge: &mut S) -> G::ScalarField {
+        for comm in cross_term_comms {
+            sponge.absorb(comm);
+        }
+
+        Self::squeeze_piggyback_challenge(sponge)
+    }
+
+    /// Combines proofs for the same R1CS instance into a [`PiggybackedProof`], which is smaller
+    /// than the proofs together and is verified with [`R1CSNark::piggyback_verify`]. Only the
+    /// proofs and their public inputs are needed, since the response is computed from the
+    /// blinded witnesses. Every proof must have been created without a custom sponge. Returns an
+    /// error if there are no proofs, if the inputs or the proofs are malformed, or if there is
+    /// not one input per proof.
+    pub fn piggyback_prove(
+        ipk: &IndexProverKey<G>,
+        sub_proofs: &[Proof<G>],
+        sub_inputs: &[&[G::ScalarField]],
+        sponge: Option<S>,
+    ) -> R1CSResult<PiggybackedProof<G>> {
+        let n = sub_proofs.len();
+        if n == 0 {
+            return Err(NarkError::MalformedProof(SerializationError::InvalidData));
+        }
+        if sub_inputs.len() != n {
+            return Err(NarkError::MalformedInput {
+                expected_len: n,
+                got: sub_inputs.len(),
+            });
+        }
+
+        let index_info = &ipk.index_info;
+        let witness_len = index_info.num_variables - index_info.num_instance_variables;
+        for (input, proof) in sub_inputs.iter().zip(sub_proofs) {
+            if input.len() != index_info.num_instance_variables {
+                return Err(NarkError::MalformedInput {
+                    expected_len: index_info.num_instance_variables,
+                    got: input.len(),
+                });
+            }
+            if proof.second_msg.blinded_witness.len() != witness_len {
+                return Err(NarkError::MalformedWitness {
+                    expected_len: witness_len,
+                    got: proof.second_msg.blinded_witness.len(),
+                });
+            }
+            if proof.first_msg.randomness.is_some() != proof.second_msg.randomness.is_some() {
+                return Err(NarkError::MalformedProof(SerializationError::InvalidData));
+            }
+        }
+
+        let init_time = start_timer!(|| "NARK::PiggybackProver");
+        let first_msgs: Vec<_> = sub_proofs.iter().map(|p| p.first_msg.clone()).collect();
+        let mut sponge = Self::init_sponge(ipk, sponge);
+        let mu = Self::piggyback_mu(ipk, sub_inputs, &first_msgs, &mut sponge);
+        let mu_powers = powers(mu, n);
+
+        let products: Vec<_> = sub_inputs
+            .iter()
+            .zip(sub_proofs)
+            .map(|(input, proof)| ipk.matrix_vec_muls(input, &proof.second_msg.blinded_witness))
+            .collect();
+
+        // The coefficient of `X^(i + n - 1 - j)` gets `mu^i * (A * z_i) ○ (B * z_j)`.
+        let mut cross_terms =
+            vec![vec![G::ScalarField::zero(); index_info.num_constraints]; 2 * n - 1];
+        for (i, (a_z, _, _)) in products.iter().enumerate() {
+            for (j, (_, b_z, _)) in products.iter().enumerate() {
+                let cross_term = &mut cross_terms[i + n - 1 - j];
+                for ((t, a), b) in cross_term.iter_mut().zip(a_z).zip(b_z) {
+                    *t += mu_powers[i] * a * b;
+                }
+            }
+        }
+        cross_terms.remove(n - 1);
+        let cross_term_comms: Vec<_> = cross_terms
+            .iter()
+            .map(|t| PedersenCommitment::commit(&ipk.ck, t, None))
+            .collect();
+
+        let nu = Self::piggyback_nu(&cross_term_comms, &mut sponge);
+        let nu_powers = powers(nu, n);
+        let left_coeffs: Vec<_> = mu_powers
+            .iter()
+            .zip(&nu_powers)
+            .map(|(m, v)| *m * v)
+            .collect();
+        let right_coeffs: Vec<_> = nu_powers.iter().rev().cloned().collect();
+
+        let witnesses: Vec<_> = sub_proofs
+            .iter()
+            .map(|p| p.second_msg.blinded_witness.as_slice())
+            .collect();
+        // Combines the blinders selected by `sigma` with `coeffs`, where a proof without zero
+        // knowledge has zero blinders.
+        let combine_sigmas = |coeffs: &[G::ScalarField],
+                              sigma: fn(
+            &SecondRoundMessageRandomness<G::ScalarField>,
+        ) -> G::ScalarField| {
+            coeffs
+                .iter()
+                .zip(sub_proofs)
+                .filter_map(|(coeff, p)| {
+                    p.second_msg.randomness.as_ref().map(|r| *coeff * sigma(r))
+                })
+                .sum::<G::ScalarField>()
+        };
+
+        let proof = PiggybackedProof {
+            first_msgs,
+            cross_term_comms,
+            left_witness: linear_combination(&left_coeffs, &witnesses),
+            right_witness: linear_combination(&right_coeffs, &witnesses),
+            sigma_a: combine_sigmas(&left_coeffs, |r| r.sigma_a),
+            sigma_b: combine_sigmas(&left_coeffs, |r| r.sigma_b),
+            sigma_c: combine_sigmas(&left_coeffs, |r| r.sigma_c),
+            sigma_b_right: combine_sigmas(&right_coeffs, |r| r.sigma_b),
+            sigma_o: nu_powers[n - 1] * combine_sigmas(&mu_powers, |r| r.sigma_o),
+        };
+
+        end_timer!(init_time);
+        Ok(proof)
+    }
+
+    /// Verifies a [`PiggybackedProof`] against the public inputs of the combined proofs, in the
+    /// order in which the proofs were combined. Returns `false` if the inputs or the proof are
+    /// malformed.
+    pub fn piggyback_verify(
+        ivk: &IndexVerifierKey<G>,
+        sub_inputs: &[&[G::ScalarField]],
+        proof: &PiggybackedProof<G>,
+        sponge: Option<S>,
+    ) -> bool {
+        let n = proof.first_msgs.len();
+        let index_info = &ivk.index_info;
+        let witness_len = index_info.num_variables - index_info.num_instance_variables;
+        if n == 0
+            || sub_inputs.len() != n
+            || proof.cross_term_comms.len() != 2 * n - 2
+            || proof.left_witness.len() != witness_len
+            || proof.right_witness.len() != witness_len
+            || sub_inputs
+                .iter()
+                .any(|input| input.len() != index_info.num_instance_variables)
+        {
+            return false;
+        }
+
+        let init_time = start_timer!(|| "NARK::PiggybackVerifier");
+        let mut sponge = Self::init_sponge(ivk, sponge);
+        let mu = Self::piggyback_mu(ivk, sub_inputs, &proof.first_msgs, &mut sponge);
+        let nu = Self::piggyback_nu(&proof.cross_term_comms, &mut sponge);
+        let mu_powers = powers(mu, n);
+        let nu_powers = powers(nu, 2 * n - 1);
+        let left_coeffs: Vec<_> = mu_powers
+            .iter()
+            .zip(&nu_powers)
+            .map(|(m, v)| *m * v)
+            .collect();
+        let right_coeffs: Vec<_> = nu_powers[..n].iter().rev().cloned().collect();
+
+        // The commitments to `A * z_i`, `B * z_i`, `C * z_i`, and their Hadamard product, as the
+        // verifier of each proof reconstructs them.
+        let mut comm_a = G::Projective::zero();
+        let mut comm_b = G::Projective::zero();
+        let mut comm_c = G::Projective::zero();
+        let mut comm_b_right = G::Projective::zero();
+        let mut comm_o = G::Projective::zero();
+        for (i, (input, first_msg)) in sub_inputs.iter().zip(&proof.first_msgs).enumerate() {
+            let gamma = Self::compute_challenge(
+                &index_info.matrices_hash,
+                input,
+                first_msg,
+                Self::init_sponge(ivk, None),
+            );
+
+            let (left, right) = (left_coeffs[i], right_coeffs[i]);
+            let had_prod = mu_powers[i] * nu_powers[n - 1];
+            comm_a += first_msg.comm_a.mul(left);
+            comm_b += first_msg.comm_b.mul(left);
+            comm_c += first_msg.comm_c.mul(left);
+            comm_b_right += first_msg.comm_b.mul(right);
+            comm_o += first_msg.comm_c.mul(had_prod);
+            if let Some(randomness) = first_msg.randomness.as_ref() {
+                comm_a += randomness.comm_r_a.mul(left * gamma);
+                comm_b += randomness.comm_r_b.mul(left * gamma);
+                comm_c += randomness.comm_r_c.mul(left * gamma);
+                comm_b_right += randomness.comm_r_b.mul(right * gamma);
+                comm_o += randomness.comm_1.mul(had_prod * gamma);
+                comm_o += randomness.comm_2.mul(had_prod * gamma.square());
+            }
+        }
+        for (k, comm) in proof.cross_term_comms.iter().enumerate() {
+            let power = if k < n - 1 { k } else { k + 1 };
+            comm_o += comm.mul(nu_powers[power]);
+        }
+
+        let left_input = linear_combination(&left_coeffs, sub_inputs);
+        let right_input = linear_combination(&right_coeffs, sub_inputs);
+        let (a_z, b_z, c_z) = ivk.matrix_vec_muls(&left_input, &proof.left_witness);
+        let b_z_right = matrix_vec_mul(&ivk.b, &right_input, &proof.right_witness);
+        let had_prod: Vec<_> = a_z.iter().zip(&b_z_right).map(|(a, b)| *a * b).collect();
+
+        let checks = [
+            (comm_a, &a_z, proof.sigma_a),
+            (comm_b, &b_z, proof.sigma_b),
+            (comm_c, &c_z, proof.sigma_c),
+            (comm_b_right, &b_z_right, proof.sigma_b_right),
+            (comm_o, &had_prod, proof.sigma_o),
+        ];
+        let result = checks.iter().all(|(comm, vector, sigma)| {
+            *comm == PedersenCommitment::commit(&ivk.ck, vector, Some(*sigma)).into_projective()
+        });
+
+        end_timer!(init_time);
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::UniformRand;
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_piggyback() {
+        let rng = &mut ark_std::test_rng();
+        let circuit = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, circuit).unwrap();
+
+        let mut inputs = Vec::new();
+        let mut proofs = Vec::new();
+        for i in 0..5 {
+            let c = DummyCircuit {
+                a: Some(Fr::rand(rng)),
+                b: Some(Fr::rand(rng)),
+                ..circuit
+            };
+            inputs.push(vec![Fr::one(), c.a.unwrap() * c.b.unwrap()]);
+            proofs.push(Nark::prove(&ipk, c, i % 2 == 1, None, Some(rng)).unwrap());
+        }
+        let sub_inputs: Vec<_> = inputs.iter().map(|input| input.as_slice()).collect();
+
+        let proof = Nark::piggyback_prove(&ipk, &proofs, &sub_inputs, None).unwrap();
+        assert!(Nark::piggyback_verify(&ivk, &sub_inputs, &proof, None));
+        let proofs_size: usize = proofs.iter().map(|p| p.serialized_size()).sum();
+        assert!(proof.serialized_size() < proofs_size);
+
+        let mut wrong_input = inputs[3].clone();
+        wrong_input[1] += Fr::one();
+        let mut wrong_inputs = sub_inputs.clone();
+        wrong_inputs[3] = wrong_input.as_slice();
+        assert!(!Nark::piggyback_verify(&ivk, &wrong_inputs, &proof, None));
+
+        let mut swapped_inputs = sub_inputs.clone();
+        swapped_inputs.swap(0, 1);
+        assert!(!Nark::piggyback_verify(&ivk, &swapped_inputs, &proof, None));
+
+        let mut wrong_proof = proof.clone();
+        wrong_proof.cross_term_comms[0] = wrong_proof.cross_term_comms[1];
+        assert!(!Nark::piggyback_verify(
+            &ivk,
+            &sub_inputs,
+            &wrong_proof,
+            None
+        ));
+
+        // A proof with an invalid witness spoils the combination.
+        let mut wrong_proofs = proofs.clone();
+        wrong_proofs[2].second_msg.blinded_witness[0] += Fr::one();
+        let proof = Nark::piggyback_prove(&ipk, &wrong_proofs, &sub_inputs, None).unwrap();
+        assert!(!Nark::piggyback_verify(&ivk, &sub_inputs, &proof, None));
+
+        assert!(Nark::piggyback_prove(&ipk, &proofs, &sub_inputs[1..], None).is_err());
+        assert!(Nark::piggyback_prove(&ipk, &[], &[], None).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use super::{IndexVerifierKey, Proof, R1CSNark};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::vec::Vec;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+
+const VERIFIER_KEY_DOMAIN: &[u8] = b"R1CS-NARK-2020-VERIFIER-KEY";
+
+/// A verifier key signed by some authority, so that verifiers that trust the authority's public
+/// key can use it without indexing the R1CS instance themselves.
+#[derive(Clone)]
+pub struct SignedVerifierKey<G: AffineCurve> {
+    /// The signed verifier key.
+    pub ivk: IndexVerifierKey<G>,
+
+    /// The signature over the serialized verifier key.
+    pub signature: Signature,
+
+    /// The public key of the signer.
+    pub signer: VerifyingKey,
+}
+
+/// Signs the serialized `ivk` with `signing_key`.
+pub fn sign_verifier_key<G: AffineCurve>(
+    ivk: IndexVerifierKey<G>,
+    signing_key: &SigningKey,
+) -> SignedVerifierKey<G> {
+    let signature = signing_key.sign(&signed_message(&ivk));
+    SignedVerifierKey {
+        ivk,
+        signature,
+        signer: signing_key.verifying_key(),
+    }
+}
+
+fn signed_message<G: AffineCurve>(ivk: &IndexVerifierKey<G>) -> Vec<u8> {
+    let mut message = VERIFIER_KEY_DOMAIN.to_vec();
+    message.extend(ivk.to_bytes());
+    message
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Verifies that some R1CS relation holds for a verifier key signed by `trusted_pubkey`.
+    /// Returns false if the key was signed by anyone else or its signature is invalid, before
+    /// the proof is looked at, and otherwise if the proof or input is malformed or the proof
+    /// does not verify.
+    pub fn verify_signed(
+        signed_ivk: &SignedVerifierKey<G>,
+        trusted_pubkey: &VerifyingKey,
+        input: &[G::ScalarField],
+        proof: &Proof<G>,
+        sponge: Option<S>,
+    ) -> bool {
+        if &signed_ivk.signer != trusted_pubkey {
+            return false;
+        }
+
+        let message = signed_message(&signed_ivk.ivk);
+        if trusted_pubkey
+            .verify_strict(&message, &signed_ivk.signature)
+            .is_err()
+        {
+            return false;
+        }
+
+        Self::verify(&signed_ivk.ivk, input, proof, sponge).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_verify_signed() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let authority = SigningKey::from_bytes(&[1u8; 32]);
+        let impostor = SigningKey::from_bytes(&[2u8; 32]);
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let proof = Nark::prove(&ipk, c, true, None, Some(rng)).unwrap();
+        let signed_ivk = sign_verifier_key(ivk, &authority);
+        let trusted_pubkey = authority.verifying_key();
+        assert!(Nark::verify_signed(
+            &signed_ivk,
+            &trusted_pubkey,
+            &r1cs_input,
+            &proof,
+            None
+        ));
+
+        // A key signed by someone other than the trusted authority is rejected.
+        let forged_ivk = sign_verifier_key(signed_ivk.ivk.clone(), &impostor);
+        assert!(!Nark::verify_signed(
+            &forged_ivk,
+            &trusted_pubkey,
+            &r1cs_input,
+            &proof,
+            None
+        ));
+
+        // So is a key claiming the trusted signer with someone else's signature.
+        let mut spoofed_ivk = forged_ivk;
+        spoofed_ivk.signer = trusted_pubkey;
+        assert!(!Nark::verify_signed(
+            &spoofed_ivk,
+            &trusted_pubkey,
+            &r1cs_input,
+            &proof,
+            None
+        ));
+
+        let wrong_input = vec![Fr::one(), Fr::rand(rng)];
+        assert!(!Nark::verify_signed(
+            &signed_ivk,
+            &trusted_pubkey,
+            &wrong_input,
+            &proof,
+            None
+        ));
+    }
+}
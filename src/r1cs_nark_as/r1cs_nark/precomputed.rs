@@ -0,0 +1,159 @@
+use super::{CsrMatrix, IndexVerifierKey, NarkError, Proof, R1CSNark};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::Field;
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::cfg_iter;
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+// A matrix stored in compressed sparse row form, with its rows sorted from longest to shortest.
+// Storing all entries contiguously improves cache efficiency, and processing the longest rows
+// first balances the work when rows are processed in parallel.
+#[derive(Clone)]
+struct SortedCsrMatrix<F: Field> {
+    // The index of each stored row in the original matrix.
+    row_indices: Vec<usize>,
+
+    // The offsets of the stored rows in `entries`.
+    row_offsets: Vec<usize>,
+
+    // The non-zero entries of the matrix and their columns.
+    entries: Vec<(F, usize)>,
+}
+
+impl<F: Field> SortedCsrMatrix<F> {
+    fn new(matrix: &CsrMatrix<F>) -> Self {
+        let mut row_indices = (0..matrix.len()).collect::<Vec<_>>();
+        row_indices.sort_by_key(|&i| ark_std::cmp::Reverse(matrix.row(i).len()));
+
+        let mut row_offsets = Vec::with_capacity(matrix.len() + 1);
+        let mut entries = Vec::new();
+        row_offsets.push(0);
+        for &i in &row_indices {
+            entries.extend(matrix.row(i).iter());
+            row_offsets.push(entries.len());
+        }
+
+        Self {
+            row_indices,
+            row_offsets,
+            entries,
+        }
+    }
+
+    // Computes `matrix * (input || witness)`.
+    fn mul(&self, input: &[F], witness: &[F]) -> Vec<F> {
+        let sorted_result: Vec<F> = cfg_iter!(self.row_offsets[..self.row_indices.len()])
+            .zip(&self.row_offsets[1..])
+            .map(|(&start, &end)| {
+                let mut acc = F::zero();
+                for &(ref coeff, i) in &self.entries[start..end] {
+                    let z = if i < input.len() {
+                        input[i]
+                    } else {
+                        witness[i - input.len()]
+                    };
+                    acc += &(*coeff * z);
+                }
+                acc
+            })
+            .collect();
+
+        let mut result = vec![F::zero(); sorted_result.len()];
+        for (&i, value) in self.row_indices.iter().zip(sorted_result) {
+            result[i] = value;
+        }
+        result
+    }
+}
+
+/// A verifier for [`R1CSNark`] that precomputes a cache-friendly layout of the matrices of an
+/// index, which speeds up verifying many proofs for the same R1CS instance.
+pub struct PrecomputedVerifier<G: AffineCurve> {
+    ivk: IndexVerifierKey<G>,
+    a: SortedCsrMatrix<G::ScalarField>,
+    b: SortedCsrMatrix<G::ScalarField>,
+    c: SortedCsrMatrix<G::ScalarField>,
+}
+
+impl<G> PrecomputedVerifier<G>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+{
+    /// Precomputes the verification tables for `ivk`.
+    pub fn new(ivk: IndexVerifierKey<G>) -> Self {
+        let a = SortedCsrMatrix::new(&ivk.a);
+        let b = SortedCsrMatrix::new(&ivk.b);
+        let c = SortedCsrMatrix::new(&ivk.c);
+        Self { ivk, a, b, c }
+    }
+
+    /// Verifies that the R1CS relation of the precomputed index holds. This is equivalent to
+    /// [`R1CSNark::verify`].
+    pub fn verify<S: CryptographicSponge<ConstraintF<G>>>(
+        &self,
+        input: &[G::ScalarField],
+        proof: &Proof<G>,
+        sponge: Option<S>,
+    ) -> Result<bool, NarkError> {
+        R1CSNark::<G, S>::verify_with_matrix_vec_muls(
+            &self.ivk,
+            input,
+            proof,
+            sponge,
+            |input, witness| {
+                (
+                    self.a.mul(input, witness),
+                    self.b.mul(input, witness),
+                    self.c.mul(input, witness),
+                )
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_precomputed_verifier() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let precomputed = PrecomputedVerifier::new(ivk.clone());
+
+        let proof = Nark::prove(&ipk, c, true, None, Some(rng)).unwrap();
+
+        assert!(Nark::verify(&ivk, &r1cs_input, &proof, None).unwrap());
+        assert!(precomputed
+            .verify::<PoseidonSponge<Fq>>(&r1cs_input, &proof, None)
+            .unwrap());
+
+        let mut wrong_input = r1cs_input.clone();
+        wrong_input[1] += Fr::one();
+        assert!(!precomputed
+            .verify::<PoseidonSponge<Fq>>(&wrong_input, &proof, None)
+            .unwrap());
+    }
+}
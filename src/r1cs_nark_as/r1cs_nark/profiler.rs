@@ -0,0 +1,209 @@
+use super::{
+    check_commitment_key, first_unsatisfied_row, IndexProverKey, InstrumentedCircuit, NarkConfig,
+    NarkError, Proof, R1CSNark, R1CSResult,
+};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::Field;
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::marker::PhantomData;
+use ark_std::rand::RngCore;
+use std::hint::black_box;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+/// The time each constraint of a circuit contributed to proving it, grouped by the sub-circuits
+/// instrumented with [`InstrumentedCircuit`], as measured by [`R1CSNark::prove_profiled`].
+///
+/// The cost of a constraint is the time taken to evaluate its rows of `A`, `B`, and `C` on the
+/// full assignment, which is the part of the prover's work that grows with each constraint.
+#[derive(Clone, Debug)]
+pub struct CircuitProfiler<F: Field> {
+    /// The cost of each constraint of the circuit, in the order in which they were added.
+    pub constraint_costs: Vec<Duration>,
+
+    // The name of each instrumented sub-circuit, with the range of constraints it added.
+    groups: Vec<(String, Range<usize>)>,
+
+    _field: PhantomData<F>,
+}
+
+impl<F: Field> CircuitProfiler<F> {
+    /// Returns the total cost of the constraints of each instrumented sub-circuit, for the `k`
+    /// most expensive sub-circuits, most expensive first. Nested sub-circuits are reported
+    /// separately as well as in the cost of the sub-circuits that enclose them.
+    pub fn top_k_constraints(&self, k: usize) -> Vec<(String, Duration)> {
+        let mut costs: Vec<_> = self
+            .groups
+            .iter()
+            .map(|(name, range)| {
+                let cost = self.constraint_costs[range.clone()].iter().sum();
+                (name.clone(), cost)
+            })
+            .collect();
+        costs.sort_by(|(_, cost_1), (_, cost_2)| cost_2.cmp(cost_1));
+        costs.truncate(k);
+        costs
+    }
+}
+
+// Returns the inner product of `row` with the full assignment `z`.
+fn eval_row<F: Field>(row: &[(F, usize)], z: &[F]) -> F {
+    row.iter().map(|(coeff, i)| *coeff * z[*i]).sum()
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Proves that some R1CS relation holds as [`R1CSNark::prove`] does, and measures the cost
+    /// of each of its constraints. The proof is zero knowledge if `rng` is provided.
+    ///
+    /// The constraints are grouped by the sub-circuits of `r1cs` instrumented with
+    /// [`InstrumentedCircuit::instrument`], which serve as namespaces.
+    pub fn prove_profiled<C: ConstraintSynthesizer<G::ScalarField> + Clone>(
+        ipk: &IndexProverKey<G>,
+        r1cs: InstrumentedCircuit<C>,
+        sponge: Option<S>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> R1CSResult<(Proof<G>, CircuitProfiler<G::ScalarField>)> {
+        check_commitment_key(ipk)?;
+
+        // The matrices of the circuit are needed in the order in which the constraints were
+        // added, before trivially satisfied constraints are removed.
+        let (a, b, c, _, _) = Self::constraint_matrices(r1cs.clone(), &NarkConfig::default())?;
+        let (input, witness) = Self::synthesize(ipk, r1cs.clone())?;
+        if let Some(row) = first_unsatisfied_row(&ipk.a, &ipk.b, &ipk.c, &input, &witness) {
+            return Err(NarkError::UnsatisfiedConstraint { row });
+        }
+
+        let z = [input.as_slice(), witness.as_slice()].concat();
+        let constraint_costs = a
+            .iter()
+            .zip(&b)
+            .zip(&c)
+            .map(|((a_row, b_row), c_row)| {
+                let start = Instant::now();
+                black_box(eval_row(a_row, &z) * eval_row(b_row, &z) - eval_row(c_row, &z));
+                start.elapsed()
+            })
+            .collect();
+
+        let groups = r1cs
+            .report()
+            .sections
+            .into_iter()
+            .map(|section| {
+                let end = section.first_constraint + section.num_constraints;
+                (section.name, section.first_constraint..end)
+            })
+            .collect();
+
+        let make_zk = rng.is_some();
+        let proof = Self::prove_with_assignment(ipk, input, witness, make_zk, sponge, rng);
+        let profiler = CircuitProfiler {
+            constraint_costs,
+            groups,
+            _field: PhantomData,
+        };
+        Ok((proof, profiler))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    // Enforces `x * x = y` for a secret `x` and a public `y`.
+    #[derive(Clone, Copy)]
+    struct SquareCircuit<F> {
+        x: F,
+    }
+
+    impl<F: Field> ConstraintSynthesizer<F> for SquareCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let x = cs.new_witness_variable(|| Ok(self.x))?;
+            let y = cs.new_input_variable(|| Ok(self.x.square()))?;
+            cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + y)
+        }
+    }
+
+    // Squares a secret `x` `len` times, keeping every power secret.
+    #[derive(Clone, Copy)]
+    struct ChainCircuit<F> {
+        x: F,
+        len: usize,
+    }
+
+    impl<F: Field> ConstraintSynthesizer<F> for ChainCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let mut value = self.x;
+            let mut var = cs.new_witness_variable(|| Ok(value))?;
+            for _ in 0..self.len {
+                value.square_in_place();
+                let next_var = cs.new_witness_variable(|| Ok(value))?;
+                cs.enforce_constraint(lc!() + var, lc!() + var, lc!() + next_var)?;
+                var = next_var;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[derive(Clone)]
+    struct ComposedCircuit<F> {
+        square: InstrumentedCircuit<SquareCircuit<F>>,
+        chain: InstrumentedCircuit<ChainCircuit<F>>,
+    }
+
+    impl<F: Field> ConstraintSynthesizer<F> for ComposedCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            self.square.generate_constraints(cs.clone())?;
+            self.chain.generate_constraints(cs)
+        }
+    }
+
+    #[test]
+    fn test_prove_profiled() {
+        let rng = &mut ark_std::test_rng();
+        let x = Fr::rand(rng);
+        let r1cs_input = vec![Fr::one(), x.square()];
+
+        let square = InstrumentedCircuit::new("square", SquareCircuit { x });
+        let chain = square.instrument("chain", ChainCircuit { x, len: 20 });
+        let circuit = square.instrument(
+            "composed",
+            ComposedCircuit {
+                square: square.clone(),
+                chain,
+            },
+        );
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, circuit.clone()).unwrap();
+        let (proof, profiler) = Nark::prove_profiled(&ipk, circuit, None, Some(rng)).unwrap();
+        assert!(Nark::verify(&ivk, &r1cs_input, &proof, None).unwrap());
+        assert!(proof.first_msg.randomness.is_some());
+        assert_eq!(profiler.constraint_costs.len(), 21);
+
+        let top = profiler.top_k_constraints(3);
+        assert_eq!(top.len(), 3);
+        assert_eq!(top[0].0, "composed");
+        for name in &["square", "chain"] {
+            assert!(top.iter().any(|(top_name, _)| top_name == name));
+        }
+        assert!(top.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert_eq!(profiler.top_k_constraints(1).len(), 1);
+    }
+}
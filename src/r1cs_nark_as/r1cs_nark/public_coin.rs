@@ -0,0 +1,582 @@
+use super::{
+    check_protocol_version, pad_assignment, CommitmentCheck, FailedCheck, FirstRoundMessage,
+    FirstRoundMessageRandomness, FullAssignment, IndexProverKey, IndexVerifierKey,
+    MatrixVecProducts, NarkError, SecondRoundMessage, SecondRoundMessageRandomness,
+};
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{Field, Zero};
+use ark_poly_commit::trivial_pc::{CommitterKey, PedersenCommitment};
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisMode,
+};
+use ark_std::rand::{Rng, RngCore};
+use ark_std::vec;
+use ark_std::vec::Vec;
+use ark_std::{cfg_into_iter, cfg_iter, marker::PhantomData, UniformRand};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use super::R1CSResult;
+
+// The randomness sampled by a zero-knowledge prover for its first message, which is needed to
+// compute its second message.
+struct ProverRandomness<F: Field> {
+    r: Vec<F>,
+    a_blinder: F,
+    b_blinder: F,
+    c_blinder: F,
+    r_a_blinder: F,
+    r_b_blinder: F,
+    r_c_blinder: F,
+    blinder_1: F,
+    blinder_2: F,
+}
+
+/// The state kept by the prover of [`PubCoinR1CSNark`] between its first and second messages.
+pub struct ProverState<G: AffineCurve> {
+    witness: Vec<G::ScalarField>,
+    randomness: Option<ProverRandomness<G::ScalarField>>,
+}
+
+/// The public-coin interactive protocol underlying [`R1CSNark`][nark], before it is made
+/// non-interactive with the Fiat-Shamir transform. The verifier's challenge is sampled at random
+/// instead of being derived from a sponge, which allows the protocol to be analyzed and tested
+/// one message at a time.
+///
+/// [nark]: crate::r1cs_nark_as::r1cs_nark::R1CSNark
+pub struct PubCoinR1CSNark<G: AffineCurve> {
+    _affine: PhantomData<G>,
+}
+
+impl<G: AffineCurve> PubCoinR1CSNark<G> {
+    /// Synthesizes `r1cs` and computes the prover's first message, which commits to the products
+    /// of the R1CS matrices with the assignment. `rng` is required if `make_zk` is set.
+    pub fn prover_first_msg<C: ConstraintSynthesizer<G::ScalarField>>(
+        ipk: &IndexProverKey<G>,
+        r1cs: C,
+        make_zk: bool,
+        rng: Option<&mut dyn RngCore>,
+    ) -> R1CSResult<(FirstRoundMessage<G>, ProverState<G>)> {
+        let (input, witness) = Self::synthesize(ipk, r1cs, OptimizationGoal::Constraints)?;
+        Ok(Self::first_msg_with_assignment(
+            ipk, &input, witness, make_zk, rng,
+        ))
+    }
+
+    // Generates the input and witness assignments of `r1cs`, synthesized with `optimization_goal`.
+    pub(crate) fn synthesize<C: ConstraintSynthesizer<G::ScalarField>>(
+        ipk: &IndexProverKey<G>,
+        r1cs: C,
+        optimization_goal: OptimizationGoal,
+    ) -> R1CSResult<(Vec<G::ScalarField>, Vec<G::ScalarField>)> {
+        check_protocol_version(&ipk.index_info)?;
+
+        let constraint_time = start_timer!(|| "Generating constraints and witnesses");
+        let pcs = ConstraintSystem::new_ref();
+        pcs.set_optimization_goal(optimization_goal);
+        pcs.set_mode(SynthesisMode::Prove {
+            construct_matrices: false,
+        });
+        r1cs.generate_constraints(pcs.clone())?;
+        end_timer!(constraint_time);
+
+        pcs.finalize();
+        let (input, witness, num_constraints) = {
+            let pcs = pcs.borrow().unwrap();
+            (
+                pcs.instance_assignment.as_slice().to_vec(),
+                pcs.witness_assignment.as_slice().to_vec(),
+                pcs.num_constraints,
+            )
+        };
+
+        // A padded index accepts smaller circuits, whose witnesses are padded with zeros. Any
+        // other index accepts only circuits of exactly its size.
+        let index_info = &ipk.index_info;
+        if input.len() != index_info.num_instance_variables {
+            return Err(NarkError::MalformedInput {
+                expected_len: index_info.num_instance_variables,
+                got: input.len(),
+            });
+        }
+        let num_variables = input.len() + witness.len();
+        if index_info.num_variables < num_variables
+            || (!index_info.is_padded && index_info.num_variables != num_variables)
+        {
+            return Err(NarkError::WitnessSizeMismatch {
+                expected: index_info.num_variables,
+                got: num_variables,
+                circuit_hash: index_info.matrices_hash,
+            });
+        }
+        let max_num_constraints = index_info.num_constraints + index_info.num_pruned_constraints;
+        if max_num_constraints < num_constraints {
+            return Err(NarkError::ConstraintCountMismatch {
+                expected: max_num_constraints,
+                got: num_constraints,
+                circuit_hash: index_info.matrices_hash,
+            });
+        }
+        if num_constraints < index_info.num_circuit_constraints {
+            return Err(NarkError::ModeMismatch {
+                index_constraints: index_info.num_circuit_constraints,
+                prove_constraints: num_constraints,
+            });
+        }
+        if !index_info.is_padded && num_constraints != max_num_constraints {
+            return Err(NarkError::ConstraintCountMismatch {
+                expected: max_num_constraints,
+                got: num_constraints,
+                circuit_hash: index_info.matrices_hash,
+            });
+        }
+
+        let mut assignment = FullAssignment { input, witness };
+        pad_assignment(&mut assignment, ipk.index_info.num_variables);
+
+        Ok((assignment.input, assignment.witness))
+    }
+
+    // Computes the prover's first message for an already generated input and witness.
+    pub(crate) fn first_msg_with_assignment(
+        ipk: &IndexProverKey<G>,
+        input: &[G::ScalarField],
+        witness: Vec<G::ScalarField>,
+        make_zk: bool,
+        mut rng: Option<&mut dyn RngCore>,
+    ) -> (FirstRoundMessage<G>, ProverState<G>) {
+        let num_input_variables = input.len();
+        let num_witness_variables = witness.len();
+
+        // Step 2 of the scheme's prover, as detailed in BCLMS20.
+        let r = if make_zk {
+            // Sample r
+            let randomizer_time = start_timer!(|| "Sampling randomizer r");
+
+            let rng = rng.as_mut().unwrap();
+            let mut r = Vec::with_capacity(num_witness_variables);
+            for _ in 0..num_witness_variables {
+                r.push(G::ScalarField::rand(rng))
+            }
+
+            end_timer!(randomizer_time);
+
+            Some(r)
+        } else {
+            None
+        };
+
+        // Step 3 of the scheme's prover, as detailed in BCLMS20.
+        let eval_z_m_time = start_timer!(|| "Evaluating z_M");
+        let (z_a, z_b, z_c) = ipk.matrix_vec_muls(input, &witness);
+        end_timer!(eval_z_m_time);
+
+        let (r_a, r_b, r_c) = if make_zk {
+            let r_ref = r.as_ref().unwrap();
+            let zeros = vec![G::ScalarField::zero(); num_input_variables];
+
+            // Compute r_a, r_b, r_c.
+            let eval_r_m_time = start_timer!(|| "Evaluating r_M");
+            let (r_a, r_b, r_c) = ipk.matrix_vec_muls(&zeros, r_ref);
+            end_timer!(eval_r_m_time);
+
+            (Some(r_a), Some(r_b), Some(r_c))
+        } else {
+            (None, None, None)
+        };
+
+        // Step 4 of the scheme's prover, as detailed in BCLMS20.
+        // Sample blinders for z_a, z_b, z_c.
+        let (mut a_blinder, mut b_blinder, mut c_blinder) = (None, None, None);
+        if make_zk {
+            let rng = rng.as_mut().unwrap();
+            a_blinder = Some(G::ScalarField::rand(rng));
+            b_blinder = Some(G::ScalarField::rand(rng));
+            c_blinder = Some(G::ScalarField::rand(rng));
+        }
+
+        let commit_time = start_timer!(|| "Committing to z_A, z_B, and z_C");
+        // Compute hiding commitments to z_a, z_b, z_c.
+        let (comm_a, comm_b, comm_c) =
+            commit_concurrently(&ipk.ck, &z_a, &z_b, &z_c, [a_blinder, b_blinder, c_blinder]);
+
+        end_timer!(commit_time);
+
+        let (mut r_a_blinder, mut r_b_blinder, mut r_c_blinder) = (None, None, None);
+        let (mut blinder_1, mut blinder_2) = (None, None);
+        let first_round_randomness = if make_zk {
+            let rng = rng.as_mut().unwrap();
+
+            // Sample blinders for r_a, r_b, r_c.
+            r_a_blinder = Some(G::ScalarField::rand(rng));
+            r_b_blinder = Some(G::ScalarField::rand(rng));
+            r_c_blinder = Some(G::ScalarField::rand(rng));
+
+            // Commit to r_a, r_b, r_c.
+            let commit_time = start_timer!(|| "Committing to r_A, r_B, r_C");
+            let (comm_r_a, comm_r_b, comm_r_c) = commit_concurrently(
+                &ipk.ck,
+                r_a.as_ref().unwrap(),
+                r_b.as_ref().unwrap(),
+                r_c.as_ref().unwrap(),
+                [r_a_blinder, r_b_blinder, r_c_blinder],
+            );
+            end_timer!(commit_time);
+
+            // Step 5 of the scheme's prover, as detailed in BCLMS20.
+            // Commit to z_a ○ r_b + z_b ○ r_a.
+            let cross_prod_time = start_timer!(|| "Computing cross product z_a ○ r_b + z_b ○ r_a");
+            let z_a_times_r_b = cfg_iter!(z_a).zip(r_b.as_ref().unwrap());
+            let z_b_times_r_a = cfg_iter!(z_b).zip(r_a.as_ref().unwrap());
+            let cross_product: Vec<_> = z_a_times_r_b
+                .zip(z_b_times_r_a)
+                .map(|((z_a, r_b), (z_b, r_a))| *z_a * r_b + *z_b * r_a)
+                .collect();
+            end_timer!(cross_prod_time);
+            blinder_1 = Some(G::ScalarField::rand(rng));
+            let commit_time = start_timer!(|| "Committing to cross product");
+            let comm_1 = PedersenCommitment::commit(&ipk.ck, &cross_product, blinder_1);
+            end_timer!(commit_time);
+
+            // Commit to r_a ○ r_b.
+            let commit_time = start_timer!(|| "Committing to r_a ○ r_b");
+            let r_a_r_b_product: Vec<_> = cfg_iter!(r_a.as_ref().unwrap())
+                .zip(r_b.unwrap())
+                .map(|(r_a, r_b)| r_b * r_a)
+                .collect();
+            blinder_2 = Some(G::ScalarField::rand(rng));
+            let comm_2 = PedersenCommitment::commit(&ipk.ck, &r_a_r_b_product, blinder_2);
+            end_timer!(commit_time);
+
+            Some(FirstRoundMessageRandomness {
+                comm_r_a,
+                comm_r_b,
+                comm_r_c,
+                comm_1,
+                comm_2,
+            })
+        } else {
+            None
+        };
+
+        // Step 6 of the scheme's prover, as detailed in BCLMS20.
+        let first_msg = FirstRoundMessage {
+            comm_a,
+            comm_b,
+            comm_c,
+            randomness: first_round_randomness,
+        };
+
+        let randomness = r.map(|r| ProverRandomness {
+            r,
+            a_blinder: a_blinder.unwrap(),
+            b_blinder: b_blinder.unwrap(),
+            c_blinder: c_blinder.unwrap(),
+            r_a_blinder: r_a_blinder.unwrap(),
+            r_b_blinder: r_b_blinder.unwrap(),
+            r_c_blinder: r_c_blinder.unwrap(),
+            blinder_1: blinder_1.unwrap(),
+            blinder_2: blinder_2.unwrap(),
+        });
+
+        (
+            first_msg,
+            ProverState {
+                witness,
+                randomness,
+            },
+        )
+    }
+
+    /// Computes the prover's second message, which opens the committed products blinded by the
+    /// verifier's challenge.
+    pub fn prover_second_msg(
+        state: ProverState<G>,
+        challenge: G::ScalarField,
+    ) -> SecondRoundMessage<G::ScalarField> {
+        let gamma = challenge;
+        let mut blinded_witness = state.witness;
+        let second_round_randomness = state.randomness.map(|randomness| {
+            // Step 8 of the scheme's prover, as detailed in BCLMS20.
+            ark_std::cfg_iter_mut!(blinded_witness)
+                .zip(randomness.r)
+                .for_each(|(s, r)| *s += gamma * r);
+
+            // Step 9 of the scheme's prover, as detailed in BCLMS20.
+            let sigma_a = randomness.a_blinder + gamma * randomness.r_a_blinder;
+            let sigma_b = randomness.b_blinder + gamma * randomness.r_b_blinder;
+            let sigma_c = randomness.c_blinder + gamma * randomness.r_c_blinder;
+
+            // Step 10 of the scheme's prover, as detailed in BCLMS20.
+            let sigma_o = randomness.c_blinder
+                + gamma * randomness.blinder_1
+                + gamma.square() * randomness.blinder_2;
+
+            SecondRoundMessageRandomness {
+                sigma_a,
+                sigma_b,
+                sigma_c,
+                sigma_o,
+            }
+        });
+
+        // Step 11 of the scheme's prover, as detailed in BCLMS20.
+        SecondRoundMessage {
+            blinded_witness,
+            randomness: second_round_randomness,
+        }
+    }
+
+    /// Samples the verifier's challenge. Like the challenge of [`R1CSNark`][nark], it is an
+    /// integer of at most 128 bits.
+    ///
+    /// [nark]: crate::r1cs_nark_as::r1cs_nark::R1CSNark
+    pub fn verifier_challenge(rng: &mut dyn RngCore) -> G::ScalarField {
+        G::ScalarField::from(rng.gen::<u128>())
+    }
+
+    /// Decides whether the verifier accepts the transcript of an interaction with the prover on
+    /// `input`, where `challenge` was sampled by [`PubCoinR1CSNark::verifier_challenge`].
+    pub fn verifier_decide(
+        ivk: &IndexVerifierKey<G>,
+        input: &[G::ScalarField],
+        first_msg: &FirstRoundMessage<G>,
+        second_msg: &SecondRoundMessage<G::ScalarField>,
+        challenge: G::ScalarField,
+    ) -> bool {
+        let index_info = &ivk.index_info;
+        if input.len() != index_info.num_instance_variables
+            || input.len() + second_msg.blinded_witness.len() != index_info.num_variables
+        {
+            return false;
+        }
+
+        Self::find_failed_check(
+            ivk,
+            input,
+            first_msg,
+            second_msg,
+            challenge,
+            |input, witness| ivk.matrix_vec_muls(input, witness),
+        )
+        .is_none()
+    }
+
+    // Runs the checks of the verifier with challenge `gamma` in order and returns the first one
+    // that fails, if any. The input and the witness must have the lengths expected by `ivk`.
+    pub(crate) fn find_failed_check(
+        ivk: &IndexVerifierKey<G>,
+        input: &[G::ScalarField],
+        first_msg: &FirstRoundMessage<G>,
+        second_msg: &SecondRoundMessage<G::ScalarField>,
+        gamma: G::ScalarField,
+        matrix_vec_muls: impl Fn(
+            &[G::ScalarField],
+            &[G::ScalarField],
+        ) -> MatrixVecProducts<G::ScalarField>,
+    ) -> Option<FailedCheck<G>> {
+        if first_msg.randomness.is_some() != second_msg.randomness.is_some() {
+            return Some(FailedCheck::Randomness);
+        }
+
+        // Step 3 of the scheme's verifier, as detailed in BCLMS20.
+        let mat_vec_mul_time = start_timer!(|| "Computing M * blinded_witness");
+        let (a_times_blinded_witness, b_times_blinded_witness, c_times_blinded_witness) =
+            matrix_vec_muls(input, &second_msg.blinded_witness);
+        end_timer!(mat_vec_mul_time);
+
+        // Step 4 of the scheme's verifier, as detailed in BCLMS20.
+        let mut comm_a = first_msg.comm_a.into_projective();
+        let mut comm_b = first_msg.comm_b.into_projective();
+        let mut comm_c = first_msg.comm_c.into_projective();
+        if let Some(first_msg_randomness) = first_msg.randomness.as_ref() {
+            comm_a += first_msg_randomness.comm_r_a.mul(gamma);
+            comm_b += first_msg_randomness.comm_r_b.mul(gamma);
+            comm_c += first_msg_randomness.comm_r_c.mul(gamma);
+        }
+
+        // Each check returns early on a mismatch, so invalid proofs are rejected as soon as
+        // possible.
+        let commit_time = start_timer!(|| "Reconstructing c_A, c_B, c_C commitments");
+        let second_msg_randomness = second_msg.randomness.as_ref();
+        let checks = [
+            (
+                CommitmentCheck::A,
+                comm_a,
+                &a_times_blinded_witness,
+                second_msg_randomness.map(|r| r.sigma_a),
+            ),
+            (
+                CommitmentCheck::B,
+                comm_b,
+                &b_times_blinded_witness,
+                second_msg_randomness.map(|r| r.sigma_b),
+            ),
+            (
+                CommitmentCheck::C,
+                comm_c,
+                &c_times_blinded_witness,
+                second_msg_randomness.map(|r| r.sigma_c),
+            ),
+        ];
+        for (check, comm, m_times_blinded_witness, sigma) in checks.iter() {
+            let reconstructed_comm =
+                PedersenCommitment::commit(&ivk.ck, m_times_blinded_witness, *sigma);
+            if *comm != reconstructed_comm.into_projective() {
+                end_timer!(commit_time);
+                return Some(FailedCheck::Commitment(
+                    *check,
+                    comm.into_affine(),
+                    reconstructed_comm,
+                ));
+            }
+        }
+        drop(c_times_blinded_witness);
+        end_timer!(commit_time);
+
+        // Step 5 of the scheme's verifier, as detailed in BCLMS20.
+        let had_prod_time = start_timer!(|| "Computing Hadamard product and commitment to it");
+        let had_prod: Vec<_> = cfg_into_iter!(a_times_blinded_witness)
+            .zip(b_times_blinded_witness)
+            .map(|(a, b)| a * b)
+            .collect();
+        let reconstructed_had_prod_comm = PedersenCommitment::commit(
+            &ivk.ck,
+            &had_prod,
+            second_msg_randomness.map(|r| r.sigma_o),
+        );
+        end_timer!(had_prod_time);
+
+        let mut had_prod_comm = first_msg.comm_c.into_projective();
+        if let Some(first_msg_randomness) = first_msg.randomness.as_ref() {
+            had_prod_comm += first_msg_randomness.comm_1.mul(gamma);
+            had_prod_comm += first_msg_randomness.comm_2.mul(gamma.square());
+        }
+        let had_prod_equal = had_prod_comm == reconstructed_had_prod_comm.into_projective();
+        add_to_trace!(|| "Verifier result", || format!(
+            "Hadamard Product equal: {}",
+            had_prod_equal
+        ));
+        if had_prod_equal {
+            None
+        } else {
+            Some(FailedCheck::HadamardProduct(
+                had_prod_comm.into_affine(),
+                reconstructed_had_prod_comm,
+            ))
+        }
+    }
+}
+
+// Commits to `a`, `b`, and `c` under the same key with three separate multi-scalar
+// multiplications, which run concurrently when the `parallel` feature is enabled. This does no
+// less work than three calls to `PedersenCommitment::commit`; it only overlaps them.
+pub(crate) fn commit_concurrently<G: AffineCurve>(
+    ck: &CommitterKey<G>,
+    a: &[G::ScalarField],
+    b: &[G::ScalarField],
+    c: &[G::ScalarField],
+    blinders: [Option<G::ScalarField>; 3],
+) -> (G, G, G) {
+    let [a_blinder, b_blinder, c_blinder] = blinders;
+    let commit_a = || PedersenCommitment::commit(ck, a, a_blinder);
+    let commit_b = || PedersenCommitment::commit(ck, b, b_blinder);
+    let commit_c = || PedersenCommitment::commit(ck, c, c_blinder);
+
+    #[cfg(feature = "parallel")]
+    {
+        let (comm_a, (comm_b, comm_c)) = rayon::join(commit_a, || rayon::join(commit_b, commit_c));
+        (comm_a, comm_b, comm_c)
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        (commit_a(), commit_b(), commit_c())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use crate::r1cs_nark_as::r1cs_nark::R1CSNark;
+    use ark_ff::One;
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+    type PubCoinNark = PubCoinR1CSNark<Affine>;
+
+    #[test]
+    fn test_interactive_protocol() {
+        let rng = &mut ark_std::test_rng();
+        let circuit = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let input = vec![Fr::one(), circuit.a.unwrap() * circuit.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, circuit).unwrap();
+
+        for &make_zk in &[false, true] {
+            let (first_msg, state) =
+                PubCoinNark::prover_first_msg(&ipk, circuit, make_zk, Some(rng)).unwrap();
+            assert_eq!(first_msg.randomness.is_some(), make_zk);
+
+            let challenge = PubCoinNark::verifier_challenge(rng);
+            let second_msg = PubCoinNark::prover_second_msg(state, challenge);
+            assert!(PubCoinNark::verifier_decide(
+                &ivk,
+                &input,
+                &first_msg,
+                &second_msg,
+                challenge
+            ));
+
+            if make_zk {
+                assert!(!PubCoinNark::verifier_decide(
+                    &ivk,
+                    &input,
+                    &first_msg,
+                    &second_msg,
+                    challenge + Fr::one()
+                ));
+            }
+
+            let wrong_input = vec![Fr::one(), input[1] + Fr::one()];
+            assert!(!PubCoinNark::verifier_decide(
+                &ivk,
+                &wrong_input,
+                &first_msg,
+                &second_msg,
+                challenge
+            ));
+        }
+    }
+
+    #[test]
+    fn test_commit_concurrently() {
+        let rng = &mut ark_std::test_rng();
+        let pp = PedersenCommitment::<Affine>::setup(16);
+        let ck = PedersenCommitment::trim(&pp, 16);
+        let sample = |rng: &mut ark_std::rand::rngs::StdRng| {
+            (0..16).map(|_| Fr::rand(rng)).collect::<Vec<_>>()
+        };
+        let (a, b, c) = (sample(rng), sample(rng), sample(rng));
+        let blinders = [Some(Fr::rand(rng)), None, Some(Fr::rand(rng))];
+
+        assert_eq!(
+            commit_concurrently(&ck, &a, &b, &c, blinders),
+            (
+                PedersenCommitment::commit(&ck, &a, blinders[0]),
+                PedersenCommitment::commit(&ck, &b, blinders[1]),
+                PedersenCommitment::commit(&ck, &c, blinders[2]),
+            )
+        );
+    }
+}
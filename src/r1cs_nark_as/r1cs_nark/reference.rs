@@ -0,0 +1,34 @@
+//! An unoptimized reference implementation of the R1CS matrix arithmetic used by [`R1CSNark`],
+//! for differential testing.
+//!
+//! [`R1CSNark`]: crate::r1cs_nark_as::r1cs_nark::R1CSNark
+
+use ark_ff::Field;
+use ark_relations::r1cs::Matrix;
+use ark_std::vec::Vec;
+
+/// Computes `matrix * (input || witness)` one row at a time.
+pub(crate) fn reference_matrix_vec_mul<F: Field>(
+    matrix: &Matrix<F>,
+    input: &[F],
+    witness: &[F],
+) -> Vec<F> {
+    let mut z = Vec::new();
+    z.extend_from_slice(input);
+    z.extend_from_slice(witness);
+
+    let mut result = Vec::new();
+    for row in matrix {
+        result.push(reference_inner_prod(row, &z));
+    }
+    result
+}
+
+/// Computes the inner product of a sparse `row` and `z`.
+pub(crate) fn reference_inner_prod<F: Field>(row: &[(F, usize)], z: &[F]) -> F {
+    let mut acc = F::zero();
+    for (coeff, i) in row {
+        acc = acc + *coeff * z[*i];
+    }
+    acc
+}
@@ -0,0 +1,89 @@
+use super::IndexProverKey;
+
+use ark_ec::AffineCurve;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::vec::Vec;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_with::hex::Hex;
+use serde_with::{DeserializeAs, SerializeAs};
+
+/// A [`serde_with`] adapter that serializes a curve point or field element as the hex encoding of
+/// its compressed [`CanonicalSerialize`] bytes.
+pub struct CanonicalHex;
+
+impl<T: CanonicalSerialize> SerializeAs<T> for CanonicalHex {
+    fn serialize_as<S: Serializer>(source: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut bytes = Vec::with_capacity(source.serialized_size());
+        CanonicalSerialize::serialize(source, &mut bytes).map_err(serde::ser::Error::custom)?;
+        <Hex as SerializeAs<Vec<u8>>>::serialize_as(&bytes, serializer)
+    }
+}
+
+impl<'de, T: CanonicalDeserialize> DeserializeAs<'de, T> for CanonicalHex {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        let bytes: Vec<u8> = Hex::deserialize_as(deserializer)?;
+        T::deserialize(bytes.as_slice()).map_err(D::Error::custom)
+    }
+}
+
+/// Serializes the key as the hex encoding of [`IndexProverKey::to_bytes`].
+impl<G: AffineCurve> Serialize for IndexProverKey<G> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        CanonicalHex::serialize_as(self, serializer)
+    }
+}
+
+impl<'de, G: AffineCurve> Deserialize<'de> for IndexProverKey<G> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        CanonicalHex::deserialize_as(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use crate::r1cs_nark_as::r1cs_nark::{FullAssignment, IndexVerifierKey, Proof, R1CSNark};
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_serde_round_trip() {
+        let rng = &mut ark_std::test_rng();
+        let circuit = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let input = vec![Fr::one(), circuit.a.unwrap() * circuit.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, circuit).unwrap();
+
+        let ivk_json = serde_json::to_string(&ivk).unwrap();
+        let ivk: IndexVerifierKey<Affine> = serde_json::from_str(&ivk_json).unwrap();
+
+        for &make_zk in &[false, true] {
+            let proof = Nark::prove(&ipk, circuit, make_zk, None, Some(rng)).unwrap();
+            let proof_json = serde_json::to_string(&proof).unwrap();
+            let proof: Proof<Affine> = serde_json::from_str(&proof_json).unwrap();
+            assert!(Nark::verify(&ivk, &input, &proof, None).unwrap());
+        }
+
+        let assignment = FullAssignment {
+            input: input.clone(),
+            witness: vec![Fr::rand(rng); 3],
+        };
+        let assignment_json = serde_json::to_string(&assignment).unwrap();
+        assert_eq!(
+            serde_json::from_str::<FullAssignment<Fr>>(&assignment_json).unwrap(),
+            assignment
+        );
+
+        assert!(serde_json::from_str::<Proof<Affine>>("{}").is_err());
+    }
+}
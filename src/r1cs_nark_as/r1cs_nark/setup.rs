@@ -0,0 +1,387 @@
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{PrimeField, Zero};
+use ark_poly_commit::trivial_pc::{PedersenCommitment, UniversalParams};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::io::{Read, Write};
+use ark_std::iter::FromIterator;
+use ark_std::rand::RngCore;
+use ark_std::vec::{IntoIter, Vec};
+use ark_std::UniformRand;
+use blake2::{digest::VariableOutput, VarBlake2b};
+
+/// A universal Pedersen commitment key that supports committing to vectors of up to `max_degree`
+/// elements. A single key can be trimmed for every R1CS instance with at most `max_degree`
+/// constraints.
+#[derive(Clone)]
+pub struct CommitmentKey<G: AffineCurve> {
+    /// The Pedersen commitment parameters.
+    pub(crate) pp: UniversalParams<G>,
+
+    /// The maximum number of elements that can be committed to.
+    pub(crate) max_degree: usize,
+}
+
+impl<G: AffineCurve> CommitmentKey<G> {
+    /// Returns the maximum number of elements that can be committed to with this key.
+    pub fn max_degree(&self) -> usize {
+        self.max_degree
+    }
+
+    /// Returns the number of generators in the key, excluding the hiding generator.
+    pub fn len(&self) -> usize {
+        self.pp.generators.len()
+    }
+
+    /// Returns whether the key has no generators, excluding the hiding generator.
+    pub fn is_empty(&self) -> bool {
+        self.pp.generators.is_empty()
+    }
+
+    /// Returns an iterator over the generators of the key in chunks of `chunk_size`, excluding
+    /// the hiding generator.
+    pub fn chunks(&self, chunk_size: usize) -> impl Iterator<Item = &[G]> {
+        self.pp.generators.chunks(chunk_size)
+    }
+}
+
+/// Yields the generators of the key, followed by the hiding generator.
+impl<G: AffineCurve> IntoIterator for CommitmentKey<G> {
+    type Item = G;
+    type IntoIter = IntoIter<G>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut elems = self.pp.generators;
+        elems.push(self.pp.hiding_generator);
+        elems.into_iter()
+    }
+}
+
+/// Collects a key from its generators followed by the hiding generator, which is the order
+/// produced by [`CommitmentKey::into_iter`]. Panics if the iterator is empty.
+impl<G: AffineCurve> FromIterator<G> for CommitmentKey<G> {
+    fn from_iter<I: IntoIterator<Item = G>>(iter: I) -> Self {
+        let mut generators = iter.into_iter().collect::<Vec<_>>();
+        let hiding_generator = generators
+            .pop()
+            .expect("a commitment key requires a hiding generator");
+        let max_degree = generators.len();
+
+        CommitmentKey {
+            pp: UniversalParams {
+                generators,
+                hiding_generator,
+            },
+            max_degree,
+        }
+    }
+}
+
+/// Generates a universal Pedersen commitment key of length `max_degree`.
+///
+/// The setup is transparent: the generators are derived deterministically by
+/// [`PedersenCommitment::setup`], so no randomness is used, there are no trapdoors, and anyone can
+/// recompute the key from `max_degree` alone.
+pub fn universal_setup<G: AffineCurve>(max_degree: usize) -> CommitmentKey<G> {
+    let setup_time = start_timer!(|| "Universal setup");
+    let pp = PedersenCommitment::setup(max_degree);
+    end_timer!(setup_time);
+
+    CommitmentKey { pp, max_degree }
+}
+
+/// Generates a Pedersen commitment key of length `max_degree` whose generators are derived from
+/// `Hash(seed || circuit_hash || index)`, so anyone can recompute the key from public data.
+///
+/// Note that, unlike [`universal_setup`], the key is bound to a specific circuit and seed, so its
+/// generators differ from the default Pedersen generators. Proofs can only be verified with a key
+/// produced from the same circuit hash and seed.
+pub fn transparent_setup<G: AffineCurve>(
+    circuit_hash: [u8; 32],
+    seed: &[u8],
+    max_degree: usize,
+) -> CommitmentKey<G> {
+    let setup_time = start_timer!(|| "Transparent setup");
+    let generators = (0..=max_degree as u64)
+        .map(|index| hash_to_generator::<G>(seed, &circuit_hash, index))
+        .collect::<Vec<_>>();
+    let mut generators = G::Projective::batch_normalization_into_affine(&generators);
+    end_timer!(setup_time);
+
+    let hiding_generator = generators.pop().unwrap();
+    let pp = UniversalParams {
+        generators,
+        hiding_generator,
+    };
+
+    CommitmentKey { pp, max_degree }
+}
+
+// The circuit hash under which beacon setups derive their generators, to separate them from
+// those of transparent setups for actual circuits.
+#[cfg(feature = "beacon-setup")]
+const BEACON_DOMAIN: [u8; 32] = *b"R1CS-NARK-2020-BEACON-SETUP\0\0\0\0\0";
+
+/// Generates a Pedersen commitment key of length `max_degree` whose generators are derived from
+/// the output of a public randomness beacon, e.g. a drand round, as [`transparent_setup`] derives
+/// them from a seed. Since the beacon output is externally verifiable, anyone can check with
+/// [`verify_beacon_setup`] that the key was not chosen by the party that generated it.
+#[cfg(feature = "beacon-setup")]
+pub fn setup_with_beacon<G: AffineCurve>(
+    beacon_output: &[u8; 64],
+    max_degree: usize,
+) -> CommitmentKey<G> {
+    transparent_setup(BEACON_DOMAIN, beacon_output, max_degree)
+}
+
+/// Checks that `ck` was generated by [`setup_with_beacon`] from `beacon_output`, by deriving the
+/// key again and comparing the generators.
+#[cfg(feature = "beacon-setup")]
+pub fn verify_beacon_setup<G: AffineCurve>(
+    ck: &CommitmentKey<G>,
+    beacon_output: &[u8; 64],
+) -> bool {
+    let expected = setup_with_beacon::<G>(beacon_output, ck.max_degree);
+    ck.pp.generators == expected.pp.generators
+        && ck.pp.hiding_generator == expected.pp.hiding_generator
+}
+
+// Hashes to a group element using try-and-increment.
+fn hash_to_generator<G: AffineCurve>(
+    seed: &[u8],
+    circuit_hash: &[u8; 32],
+    index: u64,
+) -> G::Projective {
+    let mut attempt = 0u64;
+    loop {
+        let mut hasher = VarBlake2b::new(64).unwrap();
+        digest::Update::update(&mut hasher, seed);
+        digest::Update::update(&mut hasher, circuit_hash);
+        digest::Update::update(&mut hasher, &index.to_le_bytes());
+        digest::Update::update(&mut hasher, &attempt.to_le_bytes());
+
+        let mut bytes = [0u8; 64];
+        hasher.finalize_variable(|res| bytes.copy_from_slice(res));
+
+        if let Some(g) = G::from_random_bytes(&bytes) {
+            let g = g.mul_by_cofactor_to_projective();
+            if !g.is_zero() {
+                return g;
+            }
+        }
+
+        attempt += 1;
+    }
+}
+
+/// A proof that a [`CommitmentKey`] was obtained from another by multiplying every generator,
+/// including the hiding generator, by the same secret scalar. The proof does not reveal the
+/// scalar.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct UpdateProof<G: AffineCurve> {
+    /// The commitment to the prover's nonce.
+    pub nonce_commitment: G,
+
+    /// The response to the challenge.
+    pub response: G::ScalarField,
+}
+
+/// Updates a commitment key by multiplying every generator by a freshly sampled secret scalar
+/// `s`, and proves that the update is consistent. After a chain of updates, the generators are
+/// scaled by the product of all the secret scalars.
+///
+/// An update has no effect on the security of the key. The commitments are binding as long as no
+/// discrete logarithm relation between the generators is known, and scaling every generator by
+/// the same scalar preserves all such relations, so a party that knows a relation for the old key
+/// knows it for the new key as well. Keys from [`universal_setup`] have no trapdoor and need no
+/// updates.
+pub fn update_srs<G: AffineCurve>(
+    old_ck: CommitmentKey<G>,
+    rng: &mut dyn RngCore,
+) -> (CommitmentKey<G>, UpdateProof<G>) {
+    let update_time = start_timer!(|| "Updating commitment key");
+    let secret = G::ScalarField::rand(rng);
+    let max_degree = old_ck.max_degree;
+    let old_generators = old_ck.into_iter().collect::<Vec<_>>();
+    let new_generators = old_generators
+        .iter()
+        .map(|g| g.mul(secret))
+        .collect::<Vec<_>>();
+    let new_generators = G::Projective::batch_normalization_into_affine(&new_generators);
+
+    // Prove that log_a(b) = s, where a and b are the same random combinations of the old and new
+    // generators. This shows that every new generator is the old one scaled by s.
+    let (a, b, seed) = combine_generators(&old_generators, &new_generators);
+    let nonce = G::ScalarField::rand(rng);
+    let nonce_commitment = a.mul(nonce).into_affine();
+    let challenge = update_challenge::<G>(&seed, &a, &b, &nonce_commitment);
+    let response = nonce + challenge * secret;
+
+    let mut new_ck = CommitmentKey::from_iter(new_generators);
+    new_ck.max_degree = max_degree;
+    end_timer!(update_time);
+
+    (
+        new_ck,
+        UpdateProof {
+            nonce_commitment,
+            response,
+        },
+    )
+}
+
+/// Checks that `new_ck` was obtained from `old_ck` by an update that produced `proof`.
+pub fn verify_update<G: AffineCurve>(
+    old_ck: &CommitmentKey<G>,
+    new_ck: &CommitmentKey<G>,
+    proof: &UpdateProof<G>,
+) -> bool {
+    if old_ck.max_degree != new_ck.max_degree || old_ck.len() != new_ck.len() {
+        return false;
+    }
+
+    let old_generators = old_ck.clone().into_iter().collect::<Vec<_>>();
+    let new_generators = new_ck.clone().into_iter().collect::<Vec<_>>();
+    if new_generators.iter().any(|g| g.is_zero()) {
+        return false;
+    }
+
+    let (a, b, seed) = combine_generators(&old_generators, &new_generators);
+    let challenge = update_challenge::<G>(&seed, &a, &b, &proof.nonce_commitment);
+    a.mul(proof.response) == proof.nonce_commitment.into_projective() + b.mul(challenge)
+}
+
+// Combines the old and new generators with coefficients derived from both keys. Returns the two
+// combinations and the hash of the keys from which the coefficients were derived.
+fn combine_generators<G: AffineCurve>(old: &[G], new: &[G]) -> (G, G, [u8; 64]) {
+    let mut hasher = VarBlake2b::new(64).unwrap();
+    for g in old.iter().chain(new) {
+        let mut bytes = Vec::new();
+        g.serialize(&mut bytes).unwrap();
+        digest::Update::update(&mut hasher, &bytes);
+    }
+    let mut seed = [0u8; 64];
+    hasher.finalize_variable(|res| seed.copy_from_slice(res));
+
+    let mut a = G::Projective::zero();
+    let mut b = G::Projective::zero();
+    for (index, (old, new)) in old.iter().zip(new).enumerate() {
+        let coeff =
+            hash_to_scalar::<G::ScalarField>(&[&seed[..], &(index as u64).to_le_bytes()[..]]);
+        a += old.mul(coeff);
+        b += new.mul(coeff);
+    }
+
+    (a.into_affine(), b.into_affine(), seed)
+}
+
+// Derives the challenge of an update proof.
+fn update_challenge<G: AffineCurve>(
+    seed: &[u8; 64],
+    a: &G,
+    b: &G,
+    nonce_commitment: &G,
+) -> G::ScalarField {
+    let mut bytes = Vec::new();
+    a.serialize(&mut bytes).unwrap();
+    b.serialize(&mut bytes).unwrap();
+    nonce_commitment.serialize(&mut bytes).unwrap();
+    hash_to_scalar(&[&seed[..], &bytes[..]])
+}
+
+// Hashes the concatenation of `parts` to a scalar.
+fn hash_to_scalar<F: PrimeField>(parts: &[&[u8]]) -> F {
+    let mut hasher = VarBlake2b::new(64).unwrap();
+    for part in parts {
+        digest::Update::update(&mut hasher, part);
+    }
+
+    let mut bytes = [0u8; 64];
+    hasher.finalize_variable(|res| bytes.copy_from_slice(res));
+    F::from_le_bytes_mod_order(&bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_pallas::{Affine, Fr};
+
+    #[test]
+    fn test_transparent_setup() {
+        let circuit_hash = [7u8; 32];
+
+        let ck_1 = transparent_setup::<Affine>(circuit_hash, b"seed", 16);
+        let ck_2 = transparent_setup::<Affine>(circuit_hash, b"seed", 16);
+        assert_eq!(ck_1.max_degree(), 16);
+        assert_eq!(ck_1.pp.generators.len(), 16);
+        assert_eq!(ck_1.pp.generators, ck_2.pp.generators);
+        assert_eq!(ck_1.pp.hiding_generator, ck_2.pp.hiding_generator);
+
+        let ck_3 = transparent_setup::<Affine>(circuit_hash, b"another seed", 16);
+        assert_ne!(ck_1.pp.generators, ck_3.pp.generators);
+
+        let ck_4 = transparent_setup::<Affine>([8u8; 32], b"seed", 16);
+        assert_ne!(ck_1.pp.generators, ck_4.pp.generators);
+    }
+
+    #[cfg(feature = "beacon-setup")]
+    #[test]
+    fn test_setup_with_beacon() {
+        let beacon_output = [3u8; 64];
+
+        let ck_1 = setup_with_beacon::<Affine>(&beacon_output, 16);
+        let ck_2 = setup_with_beacon::<Affine>(&beacon_output, 16);
+        assert_eq!(ck_1.max_degree(), 16);
+        assert_eq!(ck_1.pp.generators, ck_2.pp.generators);
+        assert_eq!(ck_1.pp.hiding_generator, ck_2.pp.hiding_generator);
+        assert!(verify_beacon_setup(&ck_1, &beacon_output));
+
+        let other_output = [4u8; 64];
+        let ck_3 = setup_with_beacon::<Affine>(&other_output, 16);
+        assert_ne!(ck_1.pp.generators, ck_3.pp.generators);
+        assert!(!verify_beacon_setup(&ck_3, &beacon_output));
+
+        let seeded_ck = transparent_setup::<Affine>([7u8; 32], &beacon_output, 16);
+        assert!(!verify_beacon_setup(&seeded_ck, &beacon_output));
+    }
+
+    #[test]
+    fn test_commitment_key_iter() {
+        let ck = transparent_setup::<Affine>([7u8; 32], b"seed", 10);
+        assert_eq!(ck.len(), 10);
+        assert!(!ck.is_empty());
+
+        let chunks = ck.chunks(4).collect::<Vec<_>>();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2].len(), 2);
+        assert_eq!(chunks.concat(), ck.pp.generators);
+
+        let round_trip = CommitmentKey::from_iter(ck.clone().into_iter());
+        assert_eq!(round_trip.max_degree(), ck.max_degree());
+        assert_eq!(round_trip.pp.generators, ck.pp.generators);
+        assert_eq!(round_trip.pp.hiding_generator, ck.pp.hiding_generator);
+    }
+
+    #[test]
+    fn test_update_srs() {
+        let rng = &mut ark_std::test_rng();
+        let ck_0 = transparent_setup::<Affine>([7u8; 32], b"seed", 16);
+
+        let (ck_1, proof_1) = update_srs(ck_0.clone(), rng);
+        let (ck_2, proof_2) = update_srs(ck_1.clone(), rng);
+        let (ck_3, proof_3) = update_srs(ck_2.clone(), rng);
+        assert!(verify_update(&ck_0, &ck_1, &proof_1));
+        assert!(verify_update(&ck_1, &ck_2, &proof_2));
+        assert!(verify_update(&ck_2, &ck_3, &proof_3));
+        assert_eq!(ck_3.max_degree(), ck_0.max_degree());
+        assert_ne!(ck_3.pp.generators, ck_0.pp.generators);
+
+        // A proof does not verify for a different update.
+        assert!(!verify_update(&ck_0, &ck_2, &proof_1));
+        assert!(!verify_update(&ck_1, &ck_2, &proof_1));
+
+        // Scaling a single generator differently is rejected.
+        let mut tampered = ck_1.clone();
+        tampered.pp.generators[3] = tampered.pp.generators[3].mul(Fr::from(2u64)).into_affine();
+        assert!(!verify_update(&ck_0, &tampered, &proof_1));
+    }
+}
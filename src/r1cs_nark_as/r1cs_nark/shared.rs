@@ -0,0 +1,166 @@
+use super::{first_unsatisfied_row, IndexProverKey, NarkError, Proof, R1CSNark, R1CSResult};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::{Field, Zero};
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::rand::RngCore;
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+/// One party's additive share of an R1CS witness. The witness is the sum of the shares of all
+/// `total_parties` parties.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SharedWitness<F: Field> {
+    /// The party's share of the witness.
+    pub share: Vec<F>,
+
+    /// The index of the party, in `0..total_parties`.
+    pub index: usize,
+
+    /// The number of parties that hold a share of the witness.
+    pub total_parties: usize,
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Reconstructs a witness that is additively shared among several parties, and proves that
+    /// the R1CS relation holds for it with a zero-knowledge proof.
+    ///
+    /// This is a reconstruction helper, not a distributed prover: the caller learns the full
+    /// witness, so the shares are only hidden from the verifier. The prover cannot work on the
+    /// shares alone, since the Hadamard product of `A * z` and `B * z` is not linear in `z`.
+    ///
+    /// Returns an error if the shares are inconsistent or the reconstructed witness does not
+    /// satisfy the R1CS instance.
+    pub fn prove_from_shares(
+        ipk: &IndexProverKey<G>,
+        input: &[G::ScalarField],
+        shares: Vec<SharedWitness<G::ScalarField>>,
+        sponge: Option<S>,
+        rng: &mut dyn RngCore,
+    ) -> R1CSResult<Proof<G>> {
+        let index_info = &ipk.index_info;
+        let witness_len = index_info.num_variables - index_info.num_instance_variables;
+        if input.len() != index_info.num_instance_variables {
+            return Err(NarkError::MalformedInput {
+                expected_len: index_info.num_instance_variables,
+                got: input.len(),
+            });
+        }
+
+        let mut seen = vec![false; shares.len()];
+        for (position, share) in shares.iter().enumerate() {
+            if share.share.len() != witness_len {
+                return Err(NarkError::MalformedShare {
+                    position,
+                    expected_len: witness_len,
+                    got: share.share.len(),
+                });
+            }
+
+            if share.total_parties != shares.len()
+                || share.index >= shares.len()
+                || seen[share.index]
+            {
+                return Err(NarkError::InconsistentShares { position });
+            }
+
+            seen[share.index] = true;
+        }
+
+        let mut witness = vec![G::ScalarField::zero(); witness_len];
+        for share in &shares {
+            for (w, s) in witness.iter_mut().zip(&share.share) {
+                *w += s;
+            }
+        }
+
+        if let Some(row) = first_unsatisfied_row(&ipk.a, &ipk.b, &ipk.c, input, &witness) {
+            return Err(NarkError::UnsatisfiedConstraint { row });
+        }
+
+        Ok(Self::prove_with_assignment(
+            ipk,
+            input.to_vec(),
+            witness,
+            true,
+            sponge,
+            Some(rng),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_prove_from_shares() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let (input, witness) = Nark::synthesize(&ipk, c).unwrap();
+
+        // Split the witness into three random shares that sum to it.
+        let total_parties = 3;
+        let mut shares = (0..total_parties - 1)
+            .map(|index| SharedWitness {
+                share: (0..witness.len()).map(|_| Fr::rand(rng)).collect(),
+                index,
+                total_parties,
+            })
+            .collect::<Vec<_>>();
+        let last_share = witness
+            .iter()
+            .enumerate()
+            .map(|(i, w)| *w - shares.iter().map(|s| s.share[i]).sum::<Fr>())
+            .collect();
+        shares.push(SharedWitness {
+            share: last_share,
+            index: total_parties - 1,
+            total_parties,
+        });
+
+        let proof = Nark::prove_from_shares(&ipk, &input, shares.clone(), None, rng).unwrap();
+        assert!(Nark::verify(&ivk, &input, &proof, None).unwrap());
+
+        let mut wrong_shares = shares.clone();
+        wrong_shares[0].share[0] += Fr::one();
+        assert!(matches!(
+            Nark::prove_from_shares(&ipk, &input, wrong_shares, None, rng),
+            Err(NarkError::UnsatisfiedConstraint { .. })
+        ));
+
+        let mut short_shares = shares.clone();
+        short_shares[2].share.pop();
+        assert!(matches!(
+            Nark::prove_from_shares(&ipk, &input, short_shares, None, rng),
+            Err(NarkError::MalformedShare { position: 2, .. })
+        ));
+
+        let mut duplicate_shares = shares;
+        duplicate_shares[1].index = 0;
+        assert!(matches!(
+            Nark::prove_from_shares(&ipk, &input, duplicate_shares, None, rng),
+            Err(NarkError::InconsistentShares { position: 1 })
+        ));
+    }
+}
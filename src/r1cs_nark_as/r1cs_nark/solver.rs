@@ -0,0 +1,204 @@
+use super::{first_unsatisfied_row, CsrMatrix, FullAssignment};
+
+use ark_ff::{BigInteger, FpParameters, PrimeField};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisMode};
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+/// The largest number of witness variables that [`find_witness`] accepts.
+pub const MAX_SEARCH_VARS: usize = 30;
+
+// The largest field over which every witness variable ranges over the whole field.
+const SMALL_FIELD_BITS: u32 = 8;
+
+/// Finds a witness that satisfies `circuit` for the public input `input`, which includes the
+/// leading constant `1`.
+///
+/// If `circuit` computes its own witness and that witness is satisfying, it is returned.
+/// Otherwise, all assignments of the witness variables are tried. Each witness variable ranges
+/// over `{0, 1}`, or over the whole field if the field has at most 256 elements. Returns `None`
+/// if no assignment satisfies the circuit, or if the circuit has more than `max_vars` witness
+/// variables.
+///
+/// Panics if `max_vars` exceeds [`MAX_SEARCH_VARS`].
+pub fn find_witness<F, C>(circuit: C, input: &[F], max_vars: usize) -> Option<FullAssignment<F>>
+where
+    F: PrimeField,
+    C: ConstraintSynthesizer<F> + Clone,
+{
+    assert!(
+        max_vars <= MAX_SEARCH_VARS,
+        "the witness search supports at most {} variables",
+        MAX_SEARCH_VARS
+    );
+
+    if let Some(witness) = generated_witness(circuit.clone(), input) {
+        return Some(FullAssignment {
+            input: input.to_vec(),
+            witness,
+        });
+    }
+
+    let cs = ConstraintSystem::new_ref();
+    cs.set_mode(SynthesisMode::Setup);
+    circuit.generate_constraints(cs.clone()).ok()?;
+    cs.finalize();
+
+    let num_witness_variables = cs.num_witness_variables();
+    if cs.num_instance_variables() != input.len() || num_witness_variables > max_vars {
+        return None;
+    }
+    let matrices = cs.to_matrices()?;
+    let (a, b, c) = (
+        CsrMatrix::from(matrices.a),
+        CsrMatrix::from(matrices.b),
+        CsrMatrix::from(matrices.c),
+    );
+
+    let values = candidate_values::<F>();
+    let mut indices = vec![0usize; num_witness_variables];
+    loop {
+        let witness = indices.iter().map(|&i| values[i]).collect::<Vec<_>>();
+        if first_unsatisfied_row(&a, &b, &c, input, &witness).is_none() {
+            return Some(FullAssignment {
+                input: input.to_vec(),
+                witness,
+            });
+        }
+
+        // Advance to the next assignment, treating `indices` as a little-endian counter.
+        let position = indices.iter().position(|&i| i + 1 < values.len())?;
+        indices[position] += 1;
+        indices[..position].iter_mut().for_each(|i| *i = 0);
+    }
+}
+
+// Synthesizes `circuit` in proving mode and returns its witness if the circuit assigned all of its
+// variables, its public input is `input`, and it is satisfied.
+fn generated_witness<F, C>(circuit: C, input: &[F]) -> Option<Vec<F>>
+where
+    F: PrimeField,
+    C: ConstraintSynthesizer<F>,
+{
+    let cs = ConstraintSystem::new_ref();
+    circuit.generate_constraints(cs.clone()).ok()?;
+    cs.finalize();
+
+    let cs = cs.into_inner()?;
+    if cs.instance_assignment != input || !cs.is_satisfied().ok()? {
+        return None;
+    }
+
+    Some(cs.witness_assignment)
+}
+
+// The values over which each witness variable ranges.
+fn candidate_values<F: PrimeField>() -> Vec<F> {
+    let modulus = F::Params::MODULUS;
+    if modulus.num_bits() > SMALL_FIELD_BITS {
+        return vec![F::zero(), F::one()];
+    }
+
+    let size = modulus.as_ref()[0];
+    (0..size).map(F::from).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::{biginteger::BigInteger64, FftParameters, Fp64, Fp64Parameters, One};
+    use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+    // The field with 11 elements, with constants in Montgomery form for `R = 2^64`.
+    struct F11Parameters;
+
+    type F11 = Fp64<F11Parameters>;
+
+    impl Fp64Parameters for F11Parameters {}
+
+    impl FftParameters for F11Parameters {
+        type BigInt = BigInteger64;
+
+        const TWO_ADICITY: u32 = 1;
+        const TWO_ADIC_ROOT_OF_UNITY: BigInteger64 = BigInteger64([6]);
+    }
+
+    impl FpParameters for F11Parameters {
+        const MODULUS: BigInteger64 = BigInteger64([11]);
+        const MODULUS_BITS: u32 = 4;
+        const CAPACITY: u32 = 3;
+        const REPR_SHAVE_BITS: u32 = 60;
+        const R: BigInteger64 = BigInteger64([5]);
+        const R2: BigInteger64 = BigInteger64([3]);
+        const INV: u64 = 15092790605762360413;
+        const GENERATOR: BigInteger64 = BigInteger64([10]);
+        const MODULUS_MINUS_ONE_DIV_TWO: BigInteger64 = BigInteger64([5]);
+        const T: BigInteger64 = BigInteger64([5]);
+        const T_MINUS_ONE_DIV_TWO: BigInteger64 = BigInteger64([2]);
+    }
+
+    // Enforces `a * b = c`, where `c` is public and `a` and `b` are witnesses that are only
+    // assigned if they are known.
+    #[derive(Clone, Copy)]
+    struct ProductCircuit<F: PrimeField> {
+        a: Option<F>,
+        b: Option<F>,
+        c: F,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for ProductCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let c = cs.new_input_variable(|| Ok(self.c))?;
+            let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+            let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+            cs.enforce_constraint(
+                ark_relations::lc!() + a,
+                ark_relations::lc!() + b,
+                ark_relations::lc!() + c,
+            )
+        }
+    }
+
+    #[test]
+    fn test_find_witness() {
+        let six = F11::from(6u64);
+        let input = vec![F11::one(), six];
+
+        let circuit = ProductCircuit {
+            a: None,
+            b: None,
+            c: six,
+        };
+        let assignment = find_witness(circuit, &input, 2).unwrap();
+        assert_eq!(assignment.input, input);
+        assert_eq!(assignment.witness[0] * assignment.witness[1], six);
+
+        // A circuit that computes its own witness is not searched.
+        let circuit = ProductCircuit {
+            a: Some(F11::from(3u64)),
+            b: Some(F11::from(2u64)),
+            c: six,
+        };
+        let assignment = find_witness(circuit, &input, 0).unwrap();
+        assert_eq!(assignment.witness, vec![F11::from(3u64), F11::from(2u64)]);
+
+        // The circuit has more witness variables than allowed.
+        let circuit = ProductCircuit {
+            a: None,
+            b: None,
+            c: six,
+        };
+        assert!(find_witness(circuit, &input, 1).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_find_witness_too_many_vars() {
+        let circuit = ProductCircuit {
+            a: None,
+            b: None,
+            c: F11::one(),
+        };
+        find_witness(circuit, &[F11::one(), F11::one()], MAX_SEARCH_VARS + 1);
+    }
+}
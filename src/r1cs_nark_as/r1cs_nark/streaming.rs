@@ -0,0 +1,323 @@
+use super::{
+    check_proof_format_version, CsrMatrix, CsrRow, FirstRoundMessage, IndexVerifierKey, NarkError,
+    Proof, R1CSNark, SecondRoundMessage, SecondRoundMessageRandomness,
+};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::{Field, Zero};
+use ark_serialize::{CanonicalDeserialize, SerializationError};
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::io::ErrorKind;
+use ark_std::marker::PhantomData;
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+// The part of the proof that the streaming verifier expects next.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    FormatVersion,
+    FirstMessage,
+    WitnessLength,
+    Witness,
+    SecondMessageRandomness,
+    Done,
+}
+
+/// A verifier for [`R1CSNark`] that receives the canonical serialization of a [`Proof`] in
+/// pieces, e.g. as it arrives over a network.
+///
+/// Each component of the proof is decoded as soon as its bytes are available, and each element of
+/// the blinded witness is multiplied into `A * witness`, `B * witness`, and `C * witness` as soon
+/// as it arrives, so little work remains once the last byte has been fed.
+pub struct StreamingVerifier<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    ivk: IndexVerifierKey<G>,
+    stage: Stage,
+    buffer: Vec<u8>,
+
+    first_msg: Option<FirstRoundMessage<G>>,
+    witness_len: usize,
+    witness: Vec<G::ScalarField>,
+    second_msg_randomness: Option<SecondRoundMessageRandomness<G::ScalarField>>,
+
+    // The entries of the witness columns of `A`, `B`, and `C`, as `(coefficient, row)` pairs.
+    witness_columns: [Vec<Vec<(G::ScalarField, usize)>>; 3],
+    // The products of `A`, `B`, and `C` with the part of the witness received so far.
+    witness_products: [Vec<G::ScalarField>; 3],
+
+    _sponge: PhantomData<S>,
+}
+
+impl<G, S> StreamingVerifier<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Creates a verifier for proofs for the index of `ivk`.
+    pub fn new(ivk: IndexVerifierKey<G>) -> Self {
+        let index_info = &ivk.index_info;
+        let num_instance_variables = index_info.num_instance_variables;
+        let witness_len = index_info.num_variables - num_instance_variables;
+        let num_constraints = index_info.num_constraints;
+
+        let witness_columns = [
+            witness_columns(&ivk.a, num_instance_variables, witness_len),
+            witness_columns(&ivk.b, num_instance_variables, witness_len),
+            witness_columns(&ivk.c, num_instance_variables, witness_len),
+        ];
+        let witness_products = [
+            vec![G::ScalarField::zero(); num_constraints],
+            vec![G::ScalarField::zero(); num_constraints],
+            vec![G::ScalarField::zero(); num_constraints],
+        ];
+
+        Self {
+            ivk,
+            stage: Stage::FormatVersion,
+            buffer: Vec::new(),
+            first_msg: None,
+            witness_len,
+            witness: Vec::with_capacity(witness_len),
+            second_msg_randomness: None,
+            witness_columns,
+            witness_products,
+            _sponge: PhantomData,
+        }
+    }
+
+    /// Feeds the next bytes of the proof to the verifier. Returns an error if the bytes received
+    /// so far are not the prefix of a proof for the index.
+    pub fn feed(&mut self, bytes: &[u8]) -> Result<(), NarkError> {
+        let mut buffer = ark_std::mem::take(&mut self.buffer);
+        buffer.extend_from_slice(bytes);
+
+        let mut consumed = 0;
+        let result = loop {
+            let mut reader = &buffer[consumed..];
+            let available = reader.len();
+            match self.decode_next(&mut reader) {
+                Ok(true) => consumed += available - reader.len(),
+                Ok(false) => break Ok(()),
+                Err(err) => break Err(err),
+            }
+        };
+
+        buffer.drain(..consumed);
+        self.buffer = buffer;
+        result
+    }
+
+    /// Verifies the proof that has been fed against the public input `input`. Returns `false` if
+    /// the proof is incomplete or invalid.
+    pub fn finalize(&mut self, input: &[G::ScalarField]) -> bool {
+        if self.stage != Stage::Done {
+            return false;
+        }
+
+        let proof = Proof {
+            first_msg: self.first_msg.clone().unwrap(),
+            second_msg: SecondRoundMessage {
+                blinded_witness: self.witness.clone(),
+                randomness: self.second_msg_randomness.clone(),
+            },
+        };
+
+        let ivk = &self.ivk;
+        let witness_products = &self.witness_products;
+        R1CSNark::<G, S>::verify_with_matrix_vec_muls(ivk, input, &proof, None, |input, _| {
+            let mut products = witness_products.clone();
+            for (matrix, products) in [&ivk.a, &ivk.b, &ivk.c].iter().zip(&mut products) {
+                for (row, product) in matrix.rows().zip(products.iter_mut()) {
+                    *product += input_inner_product(row, input);
+                }
+            }
+
+            let [z_a, z_b, z_c] = products;
+            (z_a, z_b, z_c)
+        })
+        .unwrap_or(false)
+    }
+
+    // Decodes the next component of the proof from `reader`. Returns `false` if `reader` does
+    // not hold all of its bytes yet.
+    fn decode_next(&mut self, reader: &mut &[u8]) -> Result<bool, NarkError> {
+        match self.stage {
+            Stage::FormatVersion => {
+                if let Some(version) = try_decode::<u8>(reader)? {
+                    check_proof_format_version(version)?;
+                    self.stage = Stage::FirstMessage;
+                    return Ok(true);
+                }
+            }
+            Stage::FirstMessage => {
+                if let Some(first_msg) = try_decode::<FirstRoundMessage<G>>(reader)? {
+                    self.first_msg = Some(first_msg);
+                    self.stage = Stage::WitnessLength;
+                    return Ok(true);
+                }
+            }
+            Stage::WitnessLength => {
+                if let Some(len) = try_decode::<u64>(reader)? {
+                    let len = len as usize;
+                    if len != self.witness_len {
+                        return Err(NarkError::MalformedWitness {
+                            expected_len: self.witness_len,
+                            got: len,
+                        });
+                    }
+
+                    self.stage = if len == 0 {
+                        Stage::SecondMessageRandomness
+                    } else {
+                        Stage::Witness
+                    };
+                    return Ok(true);
+                }
+            }
+            Stage::Witness => {
+                if let Some(elem) = try_decode::<G::ScalarField>(reader)? {
+                    let column = self.witness.len();
+                    let columns_and_products =
+                        self.witness_columns.iter().zip(&mut self.witness_products);
+                    for (columns, products) in columns_and_products {
+                        for &(coeff, row) in columns.get(column).into_iter().flatten() {
+                            products[row] += coeff * elem;
+                        }
+                    }
+
+                    self.witness.push(elem);
+                    if self.witness.len() == self.witness_len {
+                        self.stage = Stage::SecondMessageRandomness;
+                    }
+                    return Ok(true);
+                }
+            }
+            Stage::SecondMessageRandomness => {
+                if let Some(randomness) = try_decode(reader)? {
+                    self.second_msg_randomness = randomness;
+                    self.stage = Stage::Done;
+                    return Ok(true);
+                }
+            }
+            Stage::Done => {
+                if !reader.is_empty() {
+                    return Err(NarkError::MalformedProof(SerializationError::InvalidData));
+                }
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+// Decodes a `T` from the front of `reader`, leaving `reader` unchanged and returning `None` if it
+// ends before the encoding does.
+fn try_decode<T: CanonicalDeserialize>(reader: &mut &[u8]) -> Result<Option<T>, NarkError> {
+    let mut lookahead = *reader;
+    match T::deserialize(&mut lookahead) {
+        Ok(value) => {
+            *reader = lookahead;
+            Ok(Some(value))
+        }
+        Err(SerializationError::IoError(err)) if err.kind() == ErrorKind::UnexpectedEof => Ok(None),
+        Err(err) => Err(NarkError::MalformedProof(err)),
+    }
+}
+
+// Transposes the witness columns of `matrix`, whose variables are ordered as `input || witness`.
+fn witness_columns<F: Field>(
+    matrix: &CsrMatrix<F>,
+    num_instance_variables: usize,
+    witness_len: usize,
+) -> Vec<Vec<(F, usize)>> {
+    let mut columns = vec![Vec::new(); witness_len];
+    for (row, entries) in matrix.rows().enumerate() {
+        for (coeff, column) in entries.iter() {
+            if column >= num_instance_variables {
+                columns[column - num_instance_variables].push((coeff, row));
+            }
+        }
+    }
+
+    columns
+}
+
+// Computes the inner product of the input columns of `row` with `input`.
+fn input_inner_product<F: Field>(row: CsrRow<'_, F>, input: &[F]) -> F {
+    row.iter()
+        .filter(|(_, column)| *column < input.len())
+        .map(|(coeff, column)| coeff * input[column])
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_serialize::CanonicalSerialize;
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    const CHUNK_SIZE: usize = 1024;
+
+    #[test]
+    fn test_streaming_verifier() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 1000,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+        let wrong_input = vec![Fr::one(), Fr::rand(rng)];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+
+        for &make_zk in &[false, true] {
+            let proof = Nark::prove(&ipk, c, make_zk, None, Some(rng)).unwrap();
+            let mut bytes = Vec::new();
+            proof.serialize(&mut bytes).unwrap();
+            assert!(bytes.len() > CHUNK_SIZE);
+
+            let mut verifier = StreamingVerifier::<Affine, PoseidonSponge<Fq>>::new(ivk.clone());
+            for chunk in bytes.chunks(CHUNK_SIZE) {
+                assert!(!verifier.finalize(&r1cs_input));
+                verifier.feed(chunk).unwrap();
+            }
+
+            let expected = Nark::verify(&ivk, &r1cs_input, &proof, None).unwrap();
+            assert!(expected);
+            assert_eq!(verifier.finalize(&r1cs_input), expected);
+            assert!(!verifier.finalize(&wrong_input));
+
+            // Any bytes after the end of the proof are rejected.
+            assert!(matches!(
+                verifier.feed(&[0u8]),
+                Err(NarkError::MalformedProof(_))
+            ));
+        }
+
+        // The base-case proof of IVC is only accepted by `R1CSNark::verify_ivc_step`.
+        let proof = Proof::<Affine>::base_case(&r1cs_input);
+        let mut bytes = Vec::new();
+        proof.serialize(&mut bytes).unwrap();
+
+        let mut verifier = StreamingVerifier::<Affine, PoseidonSponge<Fq>>::new(ivk);
+        assert!(matches!(
+            verifier.feed(&bytes),
+            Err(NarkError::MalformedWitness { .. })
+        ));
+        assert!(!verifier.finalize(&r1cs_input));
+    }
+}
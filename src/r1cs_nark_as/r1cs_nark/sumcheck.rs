@@ -0,0 +1,305 @@
+use super::R1CSNark;
+use crate::r1cs_nark_as::CHALLENGE_SIZE;
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_sponge::{Absorbable, CryptographicSponge, FieldElementSize};
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+/// A sumcheck proof that `a_z ∘ b_z = c_z`, produced by [`R1CSNark::prove_hadamard_sumcheck`].
+///
+/// The vectors are padded with zeros to a power of two `2^k` and read as the evaluations of
+/// multilinear polynomials `a`, `b`, and `c` over the hypercube `{0, 1}^k`. For a random point
+/// `tau`, the prover shows that `sum_x eq(tau, x) * (a(x) * b(x) - c(x)) = 0`, which holds for
+/// all but a negligible fraction of the points only if the Hadamard product holds. The sumcheck
+/// reduces the sum to a single evaluation of `a`, `b`, and `c` at a random point.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HadamardSumcheckProof<F: Field> {
+    /// The message of each of the `k` rounds: the evaluations at `0`, `1`, `2`, and `3` of the
+    /// univariate polynomial of the round, which has degree 3 in the bound variable since `eq`
+    /// is linear in it and `a * b` is quadratic.
+    pub round_polys: Vec<[F; 4]>,
+}
+
+/// Evaluates at `point` the multilinear extension of `values`, padded with zeros to
+/// `2^point.len()` elements. The first coordinate of `point` is the most significant bit of the
+/// index of `values`, as in [`HadamardSumcheckProof`].
+///
+/// # Panics
+/// Panics if `values` has more than `2^point.len()` elements.
+pub fn evaluate_multilinear_extension<F: Field>(values: &[F], point: &[F]) -> F {
+    let mut evals = pad_to_hypercube(values, point.len());
+    for r in point {
+        evals = fold(&evals, *r);
+    }
+    evals[0]
+}
+
+// Returns the number of variables of the hypercube that holds `len` elements.
+fn num_variables(len: usize) -> usize {
+    len.next_power_of_two().trailing_zeros() as usize
+}
+
+// Returns `values` padded with zeros to `2^num_variables` elements.
+fn pad_to_hypercube<F: Field>(values: &[F], num_variables: usize) -> Vec<F> {
+    let len = 1 << num_variables;
+    assert!(values.len() <= len, "too many values for the hypercube");
+    let mut padded = values.to_vec();
+    padded.resize(len, F::zero());
+    padded
+}
+
+// Binds the first variable of the multilinear polynomial with evaluations `evals` to `r`.
+fn fold<F: Field>(evals: &[F], r: F) -> Vec<F> {
+    let (lo, hi) = evals.split_at(evals.len() / 2);
+    lo.iter()
+        .zip(hi)
+        .map(|(lo, hi)| *lo + r * (*hi - lo))
+        .collect()
+}
+
+// Returns the evaluations of `eq(tau, x)` over the hypercube. The last coordinate of `tau` is
+// bound first, so that the first one ends up as the most significant bit.
+fn eq_evaluations<F: Field>(tau: &[F]) -> Vec<F> {
+    let mut evals = vec![F::one()];
+    for t in tau.iter().rev() {
+        let one_minus_t = F::one() - t;
+        let mut next: Vec<F> = evals.iter().map(|e| *e * one_minus_t).collect();
+        next.extend(evals.iter().map(|e| *e * t));
+        evals = next;
+    }
+    evals
+}
+
+// Returns `eq(tau, r)`, in time linear in the number of variables.
+fn eq_eval<F: Field>(tau: &[F], r: &[F]) -> F {
+    tau.iter().zip(r).fold(F::one(), |acc, (t, r)| {
+        acc * (*t * r + (F::one() - t) * (F::one() - r))
+    })
+}
+
+// Evaluates at `r` the polynomial of degree 3 with evaluations `evals` at `0`, `1`, `2`, and
+// `3`, by Lagrange interpolation.
+fn interpolate_cubic<F: Field>(evals: &[F; 4], r: F) -> F {
+    let one = F::one();
+    let two = one + one;
+    let three = two + one;
+    let two_inv = two.inverse().unwrap();
+    let six_inv = (two * three).inverse().unwrap();
+
+    let (r_0, r_1, r_2, r_3) = (r, r - one, r - two, r - three);
+    let l_0 = -(r_1 * r_2 * r_3) * six_inv;
+    let l_1 = r_0 * r_2 * r_3 * two_inv;
+    let l_2 = -(r_0 * r_1 * r_3) * two_inv;
+    let l_3 = r_0 * r_1 * r_2 * six_inv;
+    evals[0] * l_0 + evals[1] * l_1 + evals[2] * l_2 + evals[3] * l_3
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Proves that `a_z ∘ b_z = c_z` with a sumcheck of `O(log n)` rounds, where `n` is the
+    /// length of the vectors. The prover runs in time `O(n)`.
+    ///
+    /// The challenges are derived from `sponge`, which should already have absorbed commitments
+    /// to the vectors, since the proof does not bind to them by itself.
+    ///
+    /// # Panics
+    /// Panics if the vectors do not have the same length.
+    pub fn prove_hadamard_sumcheck<F: PrimeField>(
+        a_z: &[F],
+        b_z: &[F],
+        c_z: &[F],
+        sponge: &mut S,
+    ) -> HadamardSumcheckProof<F> {
+        assert!(
+            a_z.len() == b_z.len() && b_z.len() == c_z.len(),
+            "the vectors must have the same length"
+        );
+
+        let num_variables = num_variables(a_z.len());
+        let tau = Self::sumcheck_tau::<F>(num_variables, sponge);
+
+        let mut a = pad_to_hypercube(a_z, num_variables);
+        let mut b = pad_to_hypercube(b_z, num_variables);
+        let mut c = pad_to_hypercube(c_z, num_variables);
+        let mut eq = eq_evaluations(&tau);
+
+        let mut round_polys = Vec::with_capacity(num_variables);
+        for _ in 0..num_variables {
+            // Evaluates the round polynomial at `0, 1, 2, 3` by extending each table linearly
+            // in the bound variable.
+            let half = a.len() / 2;
+            let mut round_poly = [F::zero(); 4];
+            for x in 0..half {
+                let step = |table: &[F]| (table[x], table[x + half] - table[x]);
+                let (mut a_t, a_step) = step(&a);
+                let (mut b_t, b_step) = step(&b);
+                let (mut c_t, c_step) = step(&c);
+                let (mut eq_t, eq_step) = step(&eq);
+                for eval in round_poly.iter_mut() {
+                    *eval += eq_t * (a_t * b_t - c_t);
+                    a_t += a_step;
+                    b_t += b_step;
+                    c_t += c_step;
+                    eq_t += eq_step;
+                }
+            }
+
+            let r = Self::sumcheck_round_challenge(&round_poly, sponge);
+            a = fold(&a, r);
+            b = fold(&b, r);
+            c = fold(&c, r);
+            eq = fold(&eq, r);
+            round_polys.push(round_poly);
+        }
+
+        HadamardSumcheckProof { round_polys }
+    }
+
+    /// Verifies a proof produced by [`R1CSNark::prove_hadamard_sumcheck`] for vectors of length
+    /// `len`, with a sponge in the same state as the prover's.
+    ///
+    /// The verifier runs in time `O(log n)`, apart from a single call to `evaluations`, which
+    /// must return the evaluations at the given point of the multilinear extensions of `a_z`,
+    /// `b_z`, and `c_z`, e.g. with [`evaluate_multilinear_extension`] or from an evaluation of
+    /// the R1CS matrices at a random point.
+    pub fn verify_hadamard_sumcheck<F: PrimeField>(
+        len: usize,
+        proof: &HadamardSumcheckProof<F>,
+        sponge: &mut S,
+        evaluations: impl FnOnce(&[F]) -> (F, F, F),
+    ) -> bool {
+        let num_variables = num_variables(len);
+        if proof.round_polys.len() != num_variables {
+            return false;
+        }
+
+        let tau = Self::sumcheck_tau::<F>(num_variables, sponge);
+
+        let mut claim = F::zero();
+        let mut point = Vec::with_capacity(num_variables);
+        for round_poly in &proof.round_polys {
+            if round_poly[0] + round_poly[1] != claim {
+                return false;
+            }
+
+            let r = Self::sumcheck_round_challenge(round_poly, sponge);
+            claim = interpolate_cubic(round_poly, r);
+            point.push(r);
+        }
+
+        let (a, b, c) = evaluations(&point);
+        claim == eq_eval(&tau, &point) * (a * b - c)
+    }
+
+    // Absorbs the number of variables and squeezes the point `tau` at which the Hadamard
+    // product is checked.
+    fn sumcheck_tau<F: PrimeField>(num_variables: usize, sponge: &mut S) -> Vec<F> {
+        sponge.absorb(&(num_variables as u64).to_le_bytes().as_ref());
+        let sizes = vec![FieldElementSize::Truncated(CHALLENGE_SIZE); num_variables];
+        sponge.squeeze_nonnative_field_elements_with_sizes(&sizes)
+    }
+
+    // Absorbs the message of a round and squeezes the value to which its variable is bound.
+    fn sumcheck_round_challenge<F: PrimeField>(round_poly: &[F; 4], sponge: &mut S) -> F {
+        let round_poly_bytes = round_poly
+            .iter()
+            .flat_map(|eval| eval.into_repr().to_bytes_le())
+            .collect::<Vec<_>>();
+        sponge.absorb(&round_poly_bytes);
+        sponge
+            .squeeze_nonnative_field_elements_with_sizes(&[FieldElementSize::Truncated(
+                CHALLENGE_SIZE,
+            )])
+            .pop()
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::{One, UniformRand, Zero};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    fn verify(a_z: &[Fr], b_z: &[Fr], c_z: &[Fr], proof: &HadamardSumcheckProof<Fr>) -> bool {
+        Nark::verify_hadamard_sumcheck(a_z.len(), proof, &mut PoseidonSponge::new(), |point| {
+            (
+                evaluate_multilinear_extension(a_z, point),
+                evaluate_multilinear_extension(b_z, point),
+                evaluate_multilinear_extension(c_z, point),
+            )
+        })
+    }
+
+    #[test]
+    fn test_evaluate_multilinear_extension() {
+        let rng = &mut ark_std::test_rng();
+        let values: Vec<_> = (0..5).map(|_| Fr::rand(rng)).collect();
+        let (zero, one) = (Fr::zero(), Fr::one());
+
+        // On the hypercube, the first coordinate is the most significant bit of the index.
+        assert_eq!(
+            evaluate_multilinear_extension(&values, &[zero, zero, one]),
+            values[1]
+        );
+        assert_eq!(
+            evaluate_multilinear_extension(&values, &[one, zero, zero]),
+            values[4]
+        );
+        assert_eq!(
+            evaluate_multilinear_extension(&values, &[one, one, zero]),
+            zero
+        );
+
+        let tau: Vec<_> = (0..3).map(|_| Fr::rand(rng)).collect();
+        let r: Vec<_> = (0..3).map(|_| Fr::rand(rng)).collect();
+        assert_eq!(
+            evaluate_multilinear_extension(&eq_evaluations(&tau), &r),
+            eq_eval(&tau, &r)
+        );
+    }
+
+    #[test]
+    fn test_hadamard_sumcheck() {
+        let rng = &mut ark_std::test_rng();
+        for &len in &[1, 2, 5, 8, 100] {
+            let a_z: Vec<_> = (0..len).map(|_| Fr::rand(rng)).collect();
+            let b_z: Vec<_> = (0..len).map(|_| Fr::rand(rng)).collect();
+            let c_z: Vec<_> = a_z.iter().zip(&b_z).map(|(a, b)| *a * b).collect();
+
+            let proof = Nark::prove_hadamard_sumcheck(&a_z, &b_z, &c_z, &mut PoseidonSponge::new());
+            assert_eq!(proof.round_polys.len(), num_variables(len));
+            assert!(verify(&a_z, &b_z, &c_z, &proof));
+
+            // A single wrong entry of the product is caught.
+            let mut wrong_c_z = c_z.clone();
+            wrong_c_z[len / 2] += Fr::one();
+            let wrong_proof =
+                Nark::prove_hadamard_sumcheck(&a_z, &b_z, &wrong_c_z, &mut PoseidonSponge::new());
+            assert!(!verify(&a_z, &b_z, &wrong_c_z, &wrong_proof));
+
+            // The proof is bound to the vectors through their evaluations.
+            assert!(!verify(&a_z, &b_z, &wrong_c_z, &proof));
+
+            if len > 1 {
+                let mut tampered = proof.clone();
+                tampered.round_polys[0][2] += Fr::one();
+                assert!(!verify(&a_z, &b_z, &c_z, &tampered));
+
+                let mut truncated = proof;
+                truncated.round_polys.pop();
+                assert!(!verify(&a_z, &b_z, &c_z, &truncated));
+            }
+        }
+    }
+}
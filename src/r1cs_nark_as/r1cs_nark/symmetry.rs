@@ -0,0 +1,145 @@
+use super::{CsrMatrix, IndexProverKey};
+
+use ark_ec::AffineCurve;
+use ark_ff::Field;
+
+/// Structure detected in the matrices of an R1CS instance when it is indexed, which the prover
+/// and verifier exploit to skip matrix-vector multiplications.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MatrixSymmetries {
+    /// Whether `A == B`, in which case `B * z` is not computed, since it equals `A * z`.
+    pub a_equals_b: bool,
+
+    /// Whether row `i` of `C` has the single entry `1` at column `i` for every `i`, in which case
+    /// `C * z` is not computed, since it is the first `num_constraints` entries of `z`.
+    pub c_is_identity: bool,
+}
+
+impl MatrixSymmetries {
+    /// Detects the symmetries of the matrices `a`, `b`, and `c`.
+    pub fn detect<F: Field>(a: &CsrMatrix<F>, b: &CsrMatrix<F>, c: &CsrMatrix<F>) -> Self {
+        let c_is_identity = (0..c.len()).all(|i| {
+            let row = c.row(i);
+            row.len() == 1 && row.iter().next() == Some((F::one(), i))
+        });
+
+        Self {
+            a_equals_b: a == b,
+            c_is_identity,
+        }
+    }
+}
+
+impl<G: AffineCurve> IndexProverKey<G> {
+    /// Returns the symmetries detected in the matrices of the R1CS instance.
+    pub fn matrix_symmetries(&self) -> MatrixSymmetries {
+        self.index_info.matrix_symmetries
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use crate::r1cs_nark_as::r1cs_nark::R1CSNark;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+    use ark_sponge::poseidon::PoseidonSponge;
+    use ark_std::vec::Vec;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    // Enforces `x_i * x_i = y_i` for secret `x_i` and public `y_i`, so that `A == B`.
+    #[derive(Clone)]
+    struct SquaresCircuit<F> {
+        xs: Vec<F>,
+    }
+
+    impl<F: Field> ConstraintSynthesizer<F> for SquaresCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            for x in self.xs {
+                let y = cs.new_input_variable(|| Ok(x.square()))?;
+                let x = cs.new_witness_variable(|| Ok(x))?;
+                cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + y)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_a_equals_b() {
+        let rng = &mut ark_std::test_rng();
+        let circuit = SquaresCircuit {
+            xs: (0..10).map(|_| Fr::rand(rng)).collect(),
+        };
+        let mut r1cs_input = vec![Fr::one()];
+        r1cs_input.extend(circuit.xs.iter().map(|x| x.square()));
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, circuit.clone()).unwrap();
+        assert_eq!(
+            ivk.matrix_symmetries(),
+            MatrixSymmetries {
+                a_equals_b: true,
+                c_is_identity: false,
+            }
+        );
+
+        for &make_zk in &[false, true] {
+            let proof = Nark::prove(&ipk, circuit.clone(), make_zk, None, Some(rng)).unwrap();
+            assert!(Nark::verify(&ivk, &r1cs_input, &proof, None).unwrap());
+
+            let mut wrong_input = r1cs_input.clone();
+            wrong_input[1] += Fr::one();
+            assert!(!Nark::verify(&ivk, &wrong_input, &proof, None).unwrap());
+        }
+
+        // The symmetries are detected again when the key is deserialized.
+        let ivk = IndexProverKey::<Affine>::from_bytes(&ivk.to_bytes()).unwrap();
+        assert!(ivk.matrix_symmetries().a_equals_b);
+
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let (ipk, _) = Nark::index(&pp, c).unwrap();
+        assert_eq!(ipk.matrix_symmetries(), MatrixSymmetries::default());
+    }
+
+    #[test]
+    fn test_c_is_identity() {
+        // With `z = (1, 2, 4)`: `1 * 1 = 1`, `2 * 1 = 2`, and `2 * 2 = 4`.
+        let one = Fr::one();
+        let two = one + one;
+        let a = vec![vec![(one, 0)], vec![(two, 0)], vec![(one, 1)]];
+        let b = vec![vec![(one, 0)], vec![(one, 0)], vec![(one, 1)]];
+        let c = vec![vec![(one, 0)], vec![(one, 1)], vec![(one, 2)]];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index_from_matrices(&pp, a, b, c, 1, 2).unwrap();
+        assert_eq!(
+            ivk.matrix_symmetries(),
+            MatrixSymmetries {
+                a_equals_b: false,
+                c_is_identity: true,
+            }
+        );
+
+        let (input, witness) = (vec![one], vec![two, two.square()]);
+        let (_, _, z_c) = ivk.matrix_vec_muls(&input, &witness);
+        assert_eq!(z_c, vec![one, two, two.square()]);
+
+        let proof = Nark::prove_with_assignment(&ipk, input.clone(), witness, false, None, None);
+        assert!(Nark::verify(&ivk, &input, &proof, None).unwrap());
+
+        let wrong_witness = vec![two, two];
+        let proof =
+            Nark::prove_with_assignment(&ipk, input.clone(), wrong_witness, false, None, None);
+        assert!(!Nark::verify(&ivk, &input, &proof, None).unwrap());
+    }
+}
@@ -0,0 +1,72 @@
+use super::{IndexProverKey, Proof, R1CSNark, R1CSResult};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_sponge::{Absorbable, CryptographicSponge};
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Proves that some R1CS relation holds as [`R1CSNark::prove`] does, with randomness drawn
+    /// from a new [`ark_std::test_rng`]. Proving the same circuit twice produces byte-identical
+    /// proofs, even with zero knowledge, which makes the proofs usable in snapshot tests.
+    ///
+    /// **This must never be used in production.** The seed of the test rng is a public
+    /// constant, so the randomness of a proof can be recomputed and the proof reveals the
+    /// witness whether or not `make_zk` is set.
+    pub fn prove_with_test_rng<C: ConstraintSynthesizer<G::ScalarField>>(
+        ipk: &IndexProverKey<G>,
+        r1cs: C,
+        make_zk: bool,
+        sponge: Option<S>,
+    ) -> R1CSResult<Proof<G>> {
+        let mut rng = ark_std::test_rng();
+        Self::prove(ipk, r1cs, make_zk, sponge, Some(&mut rng))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_serialize::CanonicalSerialize;
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_prove_with_test_rng() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let to_bytes = |proof: &Proof<Affine>| {
+            let mut bytes = Vec::new();
+            proof.serialize(&mut bytes).unwrap();
+            bytes
+        };
+
+        let proof = Nark::prove_with_test_rng(&ipk, c, true, None).unwrap();
+        assert!(Nark::verify(&ivk, &r1cs_input, &proof, None).unwrap());
+        let other_proof = Nark::prove_with_test_rng(&ipk, c, true, None).unwrap();
+        assert_eq!(to_bytes(&proof), to_bytes(&other_proof));
+
+        // Proofs made with a continuing rng differ.
+        let proof = Nark::prove(&ipk, c, true, None, Some(rng)).unwrap();
+        let other_proof = Nark::prove(&ipk, c, true, None, Some(rng)).unwrap();
+        assert_ne!(to_bytes(&proof), to_bytes(&other_proof));
+    }
+}
@@ -0,0 +1,284 @@
+use super::reference::reference_matrix_vec_mul;
+use super::test::DummyCircuit;
+use super::{
+    matrix_triple_mul, matrix_vec_mul, matrix_vec_mul_dispatch, CsrMatrix, FirstRoundMessage,
+    FirstRoundMessageRandomness, IndexVerifierKey, MatrixRepr, Proof, PubCoinR1CSNark, R1CSNark,
+    SecondRoundMessage, SecondRoundMessageRandomness,
+};
+use crate::ConstraintF;
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{Field, One, PrimeField, UniformRand, Zero};
+use ark_pallas::{Affine, Fr};
+use ark_poly_commit::trivial_pc::PedersenCommitment;
+use ark_relations::r1cs::{ConstraintSynthesizer, Matrix};
+use ark_serialize::CanonicalSerialize;
+use ark_sponge::poseidon::PoseidonSponge;
+use ark_sponge::Absorbable;
+use ark_std::rand::{rngs::StdRng, RngCore, SeedableRng};
+use ark_std::vec::Vec;
+use proptest::prelude::*;
+
+// Samples a random `num_rows x num_columns` matrix in which roughly `density` percent of the
+// entries are non-zero, along with a random `(input, witness)` pair.
+fn sample_instance(
+    num_rows: usize,
+    num_columns: usize,
+    num_inputs: usize,
+    density: u32,
+    seed: u64,
+) -> (Matrix<Fr>, Vec<Fr>, Vec<Fr>) {
+    let rng = &mut StdRng::seed_from_u64(seed);
+    let matrix = (0..num_rows)
+        .map(|_| {
+            (0..num_columns)
+                .filter(|_| rng.next_u32() % 100 < density)
+                .map(|i| (Fr::rand(rng), i))
+                .collect()
+        })
+        .collect();
+
+    let input = (0..num_inputs).map(|_| Fr::rand(rng)).collect();
+    let witness = (num_inputs..num_columns).map(|_| Fr::rand(rng)).collect();
+
+    (matrix, input, witness)
+}
+
+prop_compose! {
+    fn instances()(num_rows in 1usize..=20, num_columns in 1usize..=20)(
+        num_rows in Just(num_rows),
+        num_columns in Just(num_columns),
+        num_inputs in 1..=num_columns,
+        density in 0u32..=100,
+        seed in any::<u64>(),
+    ) -> (Matrix<Fr>, Vec<Fr>, Vec<Fr>) {
+        sample_instance(num_rows, num_columns, num_inputs, density, seed)
+    }
+}
+
+proptest! {
+    // `matrix_vec_mul` runs in parallel when the `parallel` feature is enabled, so running this
+    // test with and without the feature compares both code paths against the reference.
+    #[test]
+    fn matrix_vec_mul_matches_reference((matrix, input, witness) in instances()) {
+        let expected = reference_matrix_vec_mul(&matrix, &input, &witness);
+        prop_assert_eq!(matrix_vec_mul(&CsrMatrix::from(matrix), &input, &witness), expected);
+    }
+
+    #[test]
+    fn dense_matrix_vec_mul_matches_reference((matrix, input, witness) in instances()) {
+        let expected = reference_matrix_vec_mul(&matrix, &input, &witness);
+        let num_columns = input.len() + witness.len();
+
+        let dense = matrix
+            .iter()
+            .map(|row| {
+                let mut dense_row = vec![Fr::zero(); num_columns];
+                for (coeff, i) in row {
+                    dense_row[*i] += coeff;
+                }
+                dense_row
+            })
+            .collect();
+        let dense = MatrixRepr::Dense(dense, num_columns);
+
+        prop_assert_eq!(matrix_vec_mul_dispatch(&dense, &input, &witness), expected);
+    }
+
+    #[test]
+    fn matrix_triple_mul_matches_reference(
+        (a, input, witness) in instances(),
+        seed in any::<u64>(),
+    ) {
+        let num_columns = input.len() + witness.len();
+        let (b, _, _) = sample_instance(a.len(), num_columns, input.len(), 50, seed);
+        let (c, _, _) =
+            sample_instance(a.len(), num_columns, input.len(), 50, seed.wrapping_add(1));
+
+        let (a_z, b_z, c_z) = matrix_triple_mul(
+            &CsrMatrix::from(a.clone()),
+            &CsrMatrix::from(b.clone()),
+            &CsrMatrix::from(c.clone()),
+            &input,
+            &witness,
+        );
+        prop_assert_eq!(a_z, reference_matrix_vec_mul(&a, &input, &witness));
+        prop_assert_eq!(b_z, reference_matrix_vec_mul(&b, &input, &witness));
+        prop_assert_eq!(c_z, reference_matrix_vec_mul(&c, &input, &witness));
+    }
+}
+
+// Simulates a proof that the public-coin verifier accepts on `input` with the returned
+// challenge, without a witness. The second message and the challenge are sampled first, and the
+// first message is then solved for from the checks of the verifier.
+fn simulate_proof<G: AffineCurve>(
+    ivk: &IndexVerifierKey<G>,
+    input: &[G::ScalarField],
+    rng: &mut dyn RngCore,
+) -> (Proof<G>, G::ScalarField) {
+    let index_info = &ivk.index_info;
+    let witness_len = index_info.num_variables - index_info.num_instance_variables;
+    let gamma = PubCoinR1CSNark::<G>::verifier_challenge(rng);
+
+    let blinded_witness: Vec<_> = (0..witness_len)
+        .map(|_| G::ScalarField::rand(rng))
+        .collect();
+    let sigma_a = G::ScalarField::rand(rng);
+    let sigma_b = G::ScalarField::rand(rng);
+    let sigma_c = G::ScalarField::rand(rng);
+    let sigma_o = G::ScalarField::rand(rng);
+
+    let (a_z, b_z, c_z) = ivk.matrix_vec_muls(input, &blinded_witness);
+    let had_prod: Vec<_> = a_z.iter().zip(&b_z).map(|(a, b)| *a * b).collect();
+    let commit = |values: &[G::ScalarField], sigma| {
+        PedersenCommitment::commit(&ivk.ck, values, Some(sigma)).into_projective()
+    };
+
+    let comm_r_a = G::Projective::rand(rng).into_affine();
+    let comm_r_b = G::Projective::rand(rng).into_affine();
+    let comm_r_c = G::Projective::rand(rng).into_affine();
+    let comm_1 = G::Projective::rand(rng).into_affine();
+
+    let comm_a = commit(&a_z, sigma_a) - comm_r_a.mul(gamma);
+    let comm_b = commit(&b_z, sigma_b) - comm_r_b.mul(gamma);
+    let comm_c = commit(&c_z, sigma_c) - comm_r_c.mul(gamma);
+    let comm_2 = (commit(&had_prod, sigma_o) - comm_c - comm_1.mul(gamma))
+        .into_affine()
+        .mul(gamma.square().inverse().unwrap());
+
+    let proof = Proof {
+        first_msg: FirstRoundMessage {
+            comm_a: comm_a.into_affine(),
+            comm_b: comm_b.into_affine(),
+            comm_c: comm_c.into_affine(),
+            randomness: Some(FirstRoundMessageRandomness {
+                comm_r_a,
+                comm_r_b,
+                comm_r_c,
+                comm_1,
+                comm_2: comm_2.into_affine(),
+            }),
+        },
+        second_msg: SecondRoundMessage {
+            blinded_witness,
+            randomness: Some(SecondRoundMessageRandomness {
+                sigma_a,
+                sigma_b,
+                sigma_c,
+                sigma_o,
+            }),
+        },
+    };
+    (proof, gamma)
+}
+
+// The low four bits of every element of a zero-knowledge proof, which behaves like an element of
+// a field of size 16. Points contribute the low bits of their serialized x-coordinate.
+fn low_nibbles<G: AffineCurve>(proof: &Proof<G>) -> Vec<usize> {
+    let first_msg = &proof.first_msg;
+    let first_randomness = first_msg.randomness.as_ref().unwrap();
+    let points = [
+        first_msg.comm_a,
+        first_msg.comm_b,
+        first_msg.comm_c,
+        first_randomness.comm_r_a,
+        first_randomness.comm_r_b,
+        first_randomness.comm_r_c,
+        first_randomness.comm_1,
+        first_randomness.comm_2,
+    ];
+    let second_randomness = proof.second_msg.randomness.as_ref().unwrap();
+    let scalars = proof.second_msg.blinded_witness.iter().chain(&[
+        second_randomness.sigma_a,
+        second_randomness.sigma_b,
+        second_randomness.sigma_c,
+        second_randomness.sigma_o,
+    ]);
+
+    let point_nibbles = points.iter().map(|point| {
+        let mut bytes = Vec::new();
+        point.serialize(&mut bytes).unwrap();
+        (bytes[0] & 0xf) as usize
+    });
+    let scalar_nibbles = scalars.map(|scalar| (scalar.into_repr().as_ref()[0] & 0xf) as usize);
+    point_nibbles.chain(scalar_nibbles).collect()
+}
+
+// Checks that proofs of `circuit` are zero knowledge by comparing them with simulated proofs.
+// Both must be accepted, and the distributions of their elements, reduced to a field of size 16,
+// must pass a two-sample chi-squared test.
+fn test_zero_knowledge_via_simulation<G, C>(
+    circuit: C,
+    input: &[G::ScalarField],
+    rng: &mut dyn RngCore,
+) where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    C: ConstraintSynthesizer<G::ScalarField> + Clone,
+{
+    type Nark<G> = R1CSNark<G, PoseidonSponge<ConstraintF<G>>>;
+    const NUM_PROOFS: usize = 64;
+    const NUM_BINS: usize = 16;
+    // The critical value of the chi-squared distribution with 15 degrees of freedom at a
+    // significance level of about 2.5e-7, so that the test is not flaky.
+    const CRITICAL_VALUE: f64 = 60.0;
+
+    let pp = Nark::<G>::setup();
+    let (ipk, ivk) = Nark::<G>::index(&pp, circuit.clone()).unwrap();
+
+    let mut real_counts = [0u64; NUM_BINS];
+    let mut simulated_counts = [0u64; NUM_BINS];
+    for _ in 0..NUM_PROOFS {
+        let real = Nark::<G>::prove(&ipk, circuit.clone(), true, None, Some(&mut *rng)).unwrap();
+        assert!(Nark::<G>::verify(&ivk, input, &real, None).unwrap());
+
+        // The simulated proof is only accepted with the programmed challenge.
+        let (simulated, gamma) = simulate_proof(&ivk, input, rng);
+        assert!(PubCoinR1CSNark::verifier_decide(
+            &ivk,
+            input,
+            &simulated.first_msg,
+            &simulated.second_msg,
+            gamma
+        ));
+        assert!(!Nark::<G>::verify(&ivk, input, &simulated, None).unwrap());
+
+        low_nibbles(&real)
+            .into_iter()
+            .for_each(|nibble| real_counts[nibble] += 1);
+        low_nibbles(&simulated)
+            .into_iter()
+            .for_each(|nibble| simulated_counts[nibble] += 1);
+    }
+
+    let statistic: f64 = real_counts
+        .iter()
+        .zip(&simulated_counts)
+        .filter(|(r, s)| **r + **s > 0)
+        .map(|(r, s)| {
+            let diff = *r as f64 - *s as f64;
+            diff * diff / (*r + *s) as f64
+        })
+        .sum();
+    assert!(
+        statistic < CRITICAL_VALUE,
+        "real and simulated proofs are distinguishable: chi-squared statistic {}",
+        statistic
+    );
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(4))]
+
+    #[test]
+    fn zero_knowledge_via_simulation(seed in any::<u64>()) {
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let circuit = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 5,
+            num_constraints: 4,
+        };
+        let input = [Fr::one(), circuit.a.unwrap() * circuit.b.unwrap()];
+        test_zero_knowledge_via_simulation::<Affine, _>(circuit, &input, rng);
+    }
+}
@@ -0,0 +1,132 @@
+use super::{IndexProverKey, NarkError, Proof, R1CSNark, R1CSResult};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::rand::RngCore;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>> + Send + 'static,
+{
+    /// Proves that some R1CS relation holds as [`R1CSNark::prove`] does, on a separate thread.
+    /// Returns [`NarkError::ProverTimeout`] if no proof is produced within `timeout`.
+    ///
+    /// A thread cannot be interrupted, so a prover that times out is detached rather than
+    /// joined: it runs to completion in the background, and its result is dropped.
+    pub fn prove_with_timeout<C, R>(
+        ipk: &IndexProverKey<G>,
+        r1cs: C,
+        make_zk: bool,
+        timeout: Duration,
+        sponge: Option<S>,
+        rng: Option<R>,
+    ) -> R1CSResult<Proof<G>>
+    where
+        C: ConstraintSynthesizer<G::ScalarField> + Send + 'static,
+        R: RngCore + Send + 'static,
+    {
+        let start = Instant::now();
+        let (sender, receiver) = mpsc::channel();
+        let ipk = ipk.clone();
+        thread::spawn(move || {
+            let mut rng = rng;
+            let rng = rng.as_mut().map(|rng| rng as &mut dyn RngCore);
+            // The receiver is gone if the caller timed out, in which case the result is dropped.
+            let _ = sender.send(Self::prove(&ipk, r1cs, make_zk, sponge, rng));
+        });
+
+        match receiver.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => Err(NarkError::ProverTimeout {
+                elapsed: start.elapsed(),
+            }),
+            Err(RecvTimeoutError::Disconnected) => panic!("the prover thread panicked"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, PrimeField, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+    use ark_sponge::poseidon::PoseidonSponge;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    // Enforces `x_{i - 1} * x_{i - 1} = x_i` for `n` powers of a secret `x`, but computes every
+    // power from `x` again, so that generating the witness takes quadratic time. Indexing does
+    // not generate the witness and is fast.
+    #[derive(Clone, Copy)]
+    struct SlowCircuit<F> {
+        x: F,
+        n: usize,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for SlowCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let mut var = cs.new_witness_variable(|| Ok(self.x))?;
+            for i in 1..=self.n {
+                let next_var = cs.new_witness_variable(|| {
+                    let mut value = self.x;
+                    for _ in 0..i {
+                        value.square_in_place();
+                    }
+                    Ok(value)
+                })?;
+                cs.enforce_constraint(lc!() + var, lc!() + var, lc!() + next_var)?;
+                var = next_var;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_prove_with_timeout() {
+        let rng = &mut ark_std::test_rng();
+        let pp = Nark::setup();
+
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let proof = Nark::prove_with_timeout(
+            &ipk,
+            c,
+            true,
+            Duration::from_secs(600),
+            None,
+            Some(StdRng::seed_from_u64(0)),
+        )
+        .unwrap();
+        assert!(Nark::verify(&ivk, &r1cs_input, &proof, None).unwrap());
+
+        let slow = SlowCircuit {
+            x: Fr::rand(rng),
+            n: 1 << 12,
+        };
+        let (ipk, _) = Nark::index(&pp, slow).unwrap();
+        let timeout = Duration::from_millis(10);
+        let result = Nark::prove_with_timeout(&ipk, slow, false, timeout, None, None::<StdRng>);
+        assert!(matches!(
+            result,
+            Err(NarkError::ProverTimeout { elapsed }) if elapsed >= timeout
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+use super::{
+    check_commitment_key, FirstRoundMessage, IndexProverKey, IndexVerifierKey, Proof, R1CSNark,
+    R1CSResult, SecondRoundMessage,
+};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::Field;
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::rand::RngCore;
+
+/// The messages of a proof generated by [`R1CSNark::prove_with_transcript`], together with the
+/// Fiat-Shamir challenge, for replay-based testing.
+#[derive(Clone)]
+pub struct ProofTranscript<G: AffineCurve, F: Field> {
+    /// The first message of the prover.
+    pub first_msg: FirstRoundMessage<G>,
+
+    /// The challenge that the prover derived from the first message and used for the second.
+    pub challenge: F,
+
+    /// The second message of the prover.
+    pub second_msg: SecondRoundMessage<F>,
+
+    /// The challenge recomputed from the finished proof, as the verifier derives it.
+    pub computed_gamma: F,
+}
+
+/// The messages of a proof verified by [`R1CSNark::verify_with_transcript`], together with the
+/// Fiat-Shamir challenge that the verifier derived.
+#[derive(Clone)]
+pub struct VerifierTranscript<G: AffineCurve, F: Field> {
+    /// The first message of the proof.
+    pub first_msg: FirstRoundMessage<G>,
+
+    /// The challenge that the verifier derived from the first message.
+    pub gamma: F,
+
+    /// The second message of the proof.
+    pub second_msg: SecondRoundMessage<F>,
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Proves that some R1CS relation holds as [`R1CSNark::prove`] does, and records the messages
+    /// and challenge of the proof in a [`ProofTranscript`].
+    pub fn prove_with_transcript<C: ConstraintSynthesizer<G::ScalarField>>(
+        ipk: &IndexProverKey<G>,
+        r1cs: C,
+        make_zk: bool,
+        sponge: Option<S>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> R1CSResult<(Proof<G>, ProofTranscript<G, G::ScalarField>)> {
+        check_commitment_key(ipk)?;
+
+        let (input, witness) = Self::synthesize(ipk, r1cs)?;
+        let computed_gamma_sponge = Self::init_sponge(ipk, sponge.clone());
+        let (proof, challenge) = Self::prove_with_assignment_and_challenge(
+            ipk,
+            input.clone(),
+            witness,
+            make_zk,
+            sponge,
+            rng,
+        );
+        let computed_gamma = Self::compute_challenge(
+            &ipk.index_info.matrices_hash,
+            &input,
+            &proof.first_msg,
+            computed_gamma_sponge,
+        );
+
+        let transcript = ProofTranscript {
+            first_msg: proof.first_msg.clone(),
+            challenge,
+            second_msg: proof.second_msg.clone(),
+            computed_gamma,
+        };
+        Ok((proof, transcript))
+    }
+
+    /// Verifies that some R1CS relation holds as [`R1CSNark::verify`] does, and records the
+    /// messages of the proof and the challenge of the verifier in a [`VerifierTranscript`]. A
+    /// malformed input or proof is rejected rather than reported as an error.
+    pub fn verify_with_transcript(
+        ivk: &IndexVerifierKey<G>,
+        input: &[G::ScalarField],
+        proof: &Proof<G>,
+        sponge: Option<S>,
+    ) -> (bool, VerifierTranscript<G, G::ScalarField>) {
+        let gamma = Self::compute_challenge(
+            &ivk.index_info.matrices_hash,
+            input,
+            &proof.first_msg,
+            Self::init_sponge(ivk, sponge.clone()),
+        );
+        let result = Self::verify(ivk, input, proof, sponge).unwrap_or(false);
+
+        let transcript = VerifierTranscript {
+            first_msg: proof.first_msg.clone(),
+            gamma,
+            second_msg: proof.second_msg.clone(),
+        };
+        (result, transcript)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    #[test]
+    fn test_transcripts() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        for &make_zk in &[false, true] {
+            let (proof, prover_transcript) =
+                Nark::prove_with_transcript(&ipk, c, make_zk, None, Some(rng)).unwrap();
+            assert_eq!(
+                prover_transcript.challenge,
+                prover_transcript.computed_gamma
+            );
+
+            let (result, verifier_transcript) =
+                Nark::verify_with_transcript(&ivk, &r1cs_input, &proof, None);
+            assert!(result);
+            assert_eq!(prover_transcript.challenge, verifier_transcript.gamma);
+            assert_eq!(
+                prover_transcript.second_msg.blinded_witness,
+                verifier_transcript.second_msg.blinded_witness
+            );
+
+            // A verifier with another sponge state derives another challenge and rejects.
+            let mut sponge = PoseidonSponge::<Fq>::new();
+            sponge.absorb(&b"another state".as_ref());
+            let (result, verifier_transcript) =
+                Nark::verify_with_transcript(&ivk, &r1cs_input, &proof, Some(sponge));
+            assert!(!result);
+            assert_ne!(prover_transcript.challenge, verifier_transcript.gamma);
+        }
+    }
+}
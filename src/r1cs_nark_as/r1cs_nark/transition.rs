@@ -0,0 +1,163 @@
+use super::{IndexProverKey, IndexVerifierKey, NarkError, Proof, R1CSNark, R1CSResult};
+use crate::ConstraintF;
+
+use ark_ec::AffineCurve;
+use ark_ff::Field;
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_sponge::{Absorbable, CryptographicSponge};
+use ark_std::rand::RngCore;
+use ark_std::vec::Vec;
+
+/// A proof of one step of a state machine, which is bound to the state before and after the
+/// step. Both states are part of the public input of the step, which the verifier absorbs into
+/// the sponge, so no separate commitment to them is needed.
+#[derive(Clone)]
+pub struct StateTransitionProof<G: AffineCurve> {
+    /// The proof that the step is valid.
+    pub proof: Proof<G>,
+}
+
+// Returns the public input of a step, `1 || input_state || output_state`.
+fn transition_input<F: Field>(input_state: &[F], output_state: &[F]) -> Vec<F> {
+    let mut input = Vec::with_capacity(1 + input_state.len() + output_state.len());
+    input.push(F::one());
+    input.extend_from_slice(input_state);
+    input.extend_from_slice(output_state);
+    input
+}
+
+impl<G, S> R1CSNark<G, S>
+where
+    G: AffineCurve + Absorbable<ConstraintF<G>>,
+    ConstraintF<G>: Absorbable<ConstraintF<G>>,
+    S: CryptographicSponge<ConstraintF<G>>,
+{
+    /// Proves one step of a state machine from `input_state` to `output_state`. The public input
+    /// of `r1cs` must be `input_state` followed by `output_state`, after the leading constant
+    /// `1`, so the proof only verifies for this transition. Returns an error if the states do
+    /// not have the length of the public input of `ipk`. The proof is zero knowledge if `rng` is
+    /// provided.
+    pub fn prove_transition<C: ConstraintSynthesizer<G::ScalarField>>(
+        ipk: &IndexProverKey<G>,
+        r1cs: C,
+        input_state: &[G::ScalarField],
+        output_state: &[G::ScalarField],
+        sponge: Option<S>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> R1CSResult<StateTransitionProof<G>> {
+        let input = transition_input(input_state, output_state);
+        if ipk.index_info.num_instance_variables != input.len() {
+            return Err(NarkError::MalformedInput {
+                expected_len: ipk.index_info.num_instance_variables,
+                got: input.len(),
+            });
+        }
+
+        let make_zk = rng.is_some();
+        let proof = Self::prove(ipk, r1cs, make_zk, sponge, rng)?;
+        Ok(StateTransitionProof { proof })
+    }
+
+    /// Verifies a proof of one step of a state machine from `input_state` to `output_state`, as
+    /// produced by [`R1CSNark::prove_transition`].
+    pub fn verify_transition(
+        ivk: &IndexVerifierKey<G>,
+        input_state: &[G::ScalarField],
+        output_state: &[G::ScalarField],
+        proof: &StateTransitionProof<G>,
+        sponge: Option<S>,
+    ) -> bool {
+        let input = transition_input(input_state, output_state);
+        Self::verify(ivk, &input, &proof.proof, sponge).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError, Variable};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    // One step of the state machine `x -> x^2 + 1`, with the states as public inputs.
+    #[derive(Clone, Copy)]
+    struct StepCircuit<F> {
+        x: F,
+    }
+
+    impl<F: Field> StepCircuit<F> {
+        fn next_state(&self) -> F {
+            self.x.square() + F::one()
+        }
+    }
+
+    impl<F: Field> ConstraintSynthesizer<F> for StepCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let x = cs.new_input_variable(|| Ok(self.x))?;
+            let y = cs.new_input_variable(|| Ok(self.next_state()))?;
+            let x_squared = cs.new_witness_variable(|| Ok(self.x.square()))?;
+            cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + x_squared)?;
+            cs.enforce_constraint(
+                lc!() + x_squared + Variable::One,
+                lc!() + Variable::One,
+                lc!() + y,
+            )
+        }
+    }
+
+    #[test]
+    fn test_prove_transition() {
+        let rng = &mut ark_std::test_rng();
+        let step_0 = StepCircuit { x: Fr::rand(rng) };
+        let step_1 = StepCircuit {
+            x: step_0.next_state(),
+        };
+        let states = [step_0.x, step_1.x, step_1.next_state()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, step_0).unwrap();
+        let proof_0 =
+            Nark::prove_transition(&ipk, step_0, &states[0..1], &states[1..2], None, Some(rng))
+                .unwrap();
+        let proof_1 =
+            Nark::prove_transition(&ipk, step_1, &states[1..2], &states[2..3], None, Some(rng))
+                .unwrap();
+
+        assert!(Nark::verify_transition(
+            &ivk,
+            &states[0..1],
+            &states[1..2],
+            &proof_0,
+            None
+        ));
+        assert!(Nark::verify_transition(
+            &ivk,
+            &states[1..2],
+            &states[2..3],
+            &proof_1,
+            None
+        ));
+
+        // Swapping the input and output states fails.
+        assert!(!Nark::verify_transition(
+            &ivk,
+            &states[1..2],
+            &states[0..1],
+            &proof_0,
+            None
+        ));
+
+        // The proof of one step is not a proof of another.
+        assert!(!Nark::verify_transition(
+            &ivk,
+            &states[1..2],
+            &states[2..3],
+            &proof_0,
+            None
+        ));
+    }
+}
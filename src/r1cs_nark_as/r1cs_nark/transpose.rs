@@ -0,0 +1,151 @@
+use super::{CsrMatrix, IndexVerifierKey};
+
+use ark_ec::AffineCurve;
+use ark_ff::Field;
+use ark_relations::r1cs::Matrix;
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Returns the transpose of `mat`, which has `num_cols` columns. The rows of the transpose are
+/// sorted by column, as those of the matrices produced by a constraint system are.
+pub fn transpose<F: Field>(mat: &Matrix<F>, num_cols: usize) -> Matrix<F> {
+    let mut mat_t = vec![Vec::new(); num_cols];
+    for (i, row) in mat.iter().enumerate() {
+        for (coeff, j) in row {
+            mat_t[*j].push((*coeff, i));
+        }
+    }
+
+    mat_t
+}
+
+/// Computes `A^T * v`, given the transpose `mat_t` of `A` as computed by [`transpose`]. `v` must
+/// have one entry per row of `A`.
+pub fn transposed_matrix_vec_mul<F: Field>(mat_t: &Matrix<F>, v: &[F]) -> Vec<F> {
+    ark_std::cfg_iter!(mat_t)
+        .map(|row| row.iter().map(|(coeff, i)| *coeff * v[*i]).sum())
+        .collect()
+}
+
+// Computes the transposes of the `A`, `B`, and `C` matrices of an index with `num_variables`
+// variables.
+pub(crate) fn transpose_matrices<F: Field>(
+    a: &CsrMatrix<F>,
+    b: &CsrMatrix<F>,
+    c: &CsrMatrix<F>,
+    num_variables: usize,
+) -> [CsrMatrix<F>; 3] {
+    let transpose_csr =
+        |mat: &CsrMatrix<F>| CsrMatrix::from(transpose(&Matrix::from(mat.clone()), num_variables));
+    [transpose_csr(a), transpose_csr(b), transpose_csr(c)]
+}
+
+impl<G: AffineCurve> IndexVerifierKey<G> {
+    /// Computes `A^T * v`, `B^T * v`, and `C^T * v` with the transposes stored in the key, where
+    /// `v` has one entry per constraint. The result has one entry per variable.
+    pub fn transposed_matrix_vec_muls(
+        &self,
+        v: &[G::ScalarField],
+    ) -> (
+        Vec<G::ScalarField>,
+        Vec<G::ScalarField>,
+        Vec<G::ScalarField>,
+    ) {
+        let mul = |mat_t: &CsrMatrix<G::ScalarField>| -> Vec<G::ScalarField> {
+            (0..mat_t.len())
+                .map(|j| mat_t.row(j).iter().map(|(coeff, i)| coeff * v[i]).sum())
+                .collect()
+        };
+        let [a_t, b_t, c_t] = &self.transposes;
+        (mul(a_t), mul(b_t), mul(c_t))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::r1cs_nark_as::r1cs_nark::test::DummyCircuit;
+    use crate::r1cs_nark_as::r1cs_nark::R1CSNark;
+    use ark_ff::{One, UniformRand};
+    use ark_pallas::{Affine, Fq, Fr};
+    use ark_sponge::poseidon::PoseidonSponge;
+
+    type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+    fn inner_product(x: &[Fr], y: &[Fr]) -> Fr {
+        x.iter().zip(y).map(|(x, y)| *x * y).sum()
+    }
+
+    #[test]
+    fn test_transpose() {
+        let rng = &mut ark_std::test_rng();
+        let num_cols = 6;
+        let a: Matrix<Fr> = (0..4)
+            .map(|i| {
+                (0..num_cols)
+                    .filter(|j| (i + j) % 3 != 0)
+                    .map(|j| (Fr::rand(rng), j))
+                    .collect()
+            })
+            .collect();
+        let a_t = transpose(&a, num_cols);
+        assert_eq!(a_t.len(), num_cols);
+        assert_eq!(transpose(&a_t, a.len()), a);
+
+        let z: Vec<_> = (0..num_cols).map(|_| Fr::rand(rng)).collect();
+        let a_times_z: Vec<Fr> = a
+            .iter()
+            .map(|row| row.iter().map(|(coeff, j)| *coeff * z[*j]).sum())
+            .collect();
+
+        // Row `i` of `A` is column `i` of `A^T`, so `(A * z)[i]` is the product of that column
+        // with `z`, which is `A^T * e_i` dotted with `z`.
+        for (i, a_times_z_i) in a_times_z.iter().enumerate() {
+            let mut e_i = vec![Fr::from(0u64); a.len()];
+            e_i[i] = Fr::one();
+            let column_i = transposed_matrix_vec_mul(&a_t, &e_i);
+            assert_eq!(*a_times_z_i, inner_product(&column_i, &z));
+        }
+
+        let v: Vec<_> = (0..a.len()).map(|_| Fr::rand(rng)).collect();
+        assert_eq!(
+            inner_product(&v, &a_times_z),
+            inner_product(&transposed_matrix_vec_mul(&a_t, &v), &z)
+        );
+    }
+
+    #[test]
+    fn test_key_transposes() {
+        let rng = &mut ark_std::test_rng();
+        let c = DummyCircuit {
+            a: Some(Fr::rand(rng)),
+            b: Some(Fr::rand(rng)),
+            num_variables: 10,
+            num_constraints: 100,
+        };
+        let r1cs_input = vec![Fr::one(), c.a.unwrap() * c.b.unwrap()];
+
+        let pp = Nark::setup();
+        let (ipk, ivk) = Nark::index(&pp, c).unwrap();
+        let proof = Nark::prove(&ipk, c, false, None, None).unwrap();
+        let witness = &proof.second_msg.blinded_witness;
+        let z: Vec<_> = r1cs_input.iter().chain(witness).cloned().collect();
+
+        let v: Vec<_> = (0..ivk.index_info.num_constraints)
+            .map(|_| Fr::rand(rng))
+            .collect();
+        let (a_z, b_z, c_z) = ivk.matrix_vec_muls(&r1cs_input, witness);
+        let (a_t_v, b_t_v, c_t_v) = ivk.transposed_matrix_vec_muls(&v);
+        assert_eq!(a_t_v.len(), ivk.index_info.num_variables);
+        assert_eq!(inner_product(&v, &a_z), inner_product(&a_t_v, &z));
+        assert_eq!(inner_product(&v, &b_z), inner_product(&b_t_v, &z));
+        assert_eq!(inner_product(&v, &c_z), inner_product(&c_t_v, &z));
+
+        // The transposes are recomputed when the key is deserialized.
+        let ivk = IndexVerifierKey::<Affine>::from_bytes(&ivk.to_bytes()).unwrap();
+        assert_eq!(ivk.transposed_matrix_vec_muls(&v), (a_t_v, b_t_v, c_t_v));
+    }
+}
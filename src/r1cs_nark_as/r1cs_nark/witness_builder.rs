@@ -0,0 +1,178 @@
+use super::FullAssignment;
+
+use ark_ff::{Field, Zero};
+use ark_relations::r1cs::{LinearCombination, Matrix, Variable};
+use ark_std::vec;
+use ark_std::vec::Vec;
+
+/// Builds an R1CS instance and its assignment directly, without a [`ConstraintSynthesizer`][cs]
+/// or a constraint system. The matrices that [`WitnessBuilder::build`] outputs are the ones that
+/// `ConstraintSystem::to_matrices` outputs for the same variables and constraints.
+///
+/// # Example
+/// ```
+/// use ark_accumulation::r1cs_nark_as::r1cs_nark::WitnessBuilder;
+/// use ark_ff::One;
+/// use ark_pallas::Fr;
+/// use ark_relations::lc;
+///
+/// // Prove knowledge of a square root `x` of the public `y = 4`.
+/// let mut builder = WitnessBuilder::new();
+/// let y = builder.add_input(Fr::from(4u64));
+/// let x = builder.add_witness(Fr::from(2u64));
+/// builder.assert_r1cs(lc!() + x, lc!() + x, lc!() + y);
+///
+/// let (assignment, a, b, c) = builder.build();
+/// assert_eq!(assignment.input, vec![Fr::one(), Fr::from(4u64)]);
+/// assert_eq!(a, vec![vec![(Fr::one(), 2)]]);
+/// ```
+///
+/// [cs]: ark_relations::r1cs::ConstraintSynthesizer
+#[derive(Clone)]
+pub struct WitnessBuilder<F: Field> {
+    input: Vec<F>,
+    witness: Vec<F>,
+    constraints: Vec<[LinearCombination<F>; 3]>,
+}
+
+impl<F: Field> WitnessBuilder<F> {
+    /// Creates a builder with no constraints, whose only variable is the constant `1`.
+    pub fn new() -> Self {
+        Self {
+            input: vec![F::one()],
+            witness: Vec::new(),
+            constraints: Vec::new(),
+        }
+    }
+
+    /// Adds a public input variable with the value `val`.
+    pub fn add_input(&mut self, val: F) -> Variable {
+        self.input.push(val);
+        Variable::Instance(self.input.len() - 1)
+    }
+
+    /// Adds a witness variable with the value `val`.
+    pub fn add_witness(&mut self, val: F) -> Variable {
+        self.witness.push(val);
+        Variable::Witness(self.witness.len() - 1)
+    }
+
+    /// Adds the constraint `a * b = c`. The linear combinations may only refer to the constant
+    /// `1`, to [`Variable::Zero`], and to variables added to this builder.
+    pub fn assert_r1cs(
+        &mut self,
+        a: LinearCombination<F>,
+        b: LinearCombination<F>,
+        c: LinearCombination<F>,
+    ) {
+        self.constraints.push([a, b, c]);
+    }
+
+    /// Outputs the assignment to the variables and the matrices `A`, `B`, and `C`, with one row
+    /// per constraint in the order they were added.
+    ///
+    /// Panics if a constraint refers to a variable that was not added to this builder.
+    pub fn build(self) -> (FullAssignment<F>, Matrix<F>, Matrix<F>, Matrix<F>) {
+        let num_instance_variables = self.input.len();
+        let num_variables = num_instance_variables + self.witness.len();
+
+        let mut a = Vec::with_capacity(self.constraints.len());
+        let mut b = Vec::with_capacity(self.constraints.len());
+        let mut c = Vec::with_capacity(self.constraints.len());
+        for [a_lc, b_lc, c_lc] in self.constraints {
+            a.push(make_row(a_lc, num_instance_variables, num_variables));
+            b.push(make_row(b_lc, num_instance_variables, num_variables));
+            c.push(make_row(c_lc, num_instance_variables, num_variables));
+        }
+
+        let assignment = FullAssignment {
+            input: self.input,
+            witness: self.witness,
+        };
+        (assignment, a, b, c)
+    }
+}
+
+impl<F: Field> Default for WitnessBuilder<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Converts `lc` to a matrix row as `ConstraintSystem::to_matrices` does: terms on the same
+// variable are merged, terms are ordered by variable, and zero terms are dropped.
+fn make_row<F: Field>(
+    mut lc: LinearCombination<F>,
+    num_instance_variables: usize,
+    num_variables: usize,
+) -> Vec<(F, usize)> {
+    lc.compactify();
+    lc.0.into_iter()
+        .filter(|(coeff, var)| !coeff.is_zero() && *var != Variable::Zero)
+        .map(|(coeff, var)| {
+            let index = var
+                .get_index_unchecked(num_instance_variables)
+                .filter(|&index| index < num_variables)
+                .expect("the constraint refers to a variable that was not added to the builder");
+            (coeff, index)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_pallas::Fr;
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSystem, SynthesisMode};
+
+    #[test]
+    fn test_witness_builder() {
+        let rng = &mut ark_std::test_rng();
+        let (x, y) = (Fr::rand(rng), Fr::rand(rng));
+        let z = x * y;
+
+        // The circuit `x * y = z` for secret `x` and `y` and a public `z`, with a second
+        // constraint `(x + y + x) * 1 = 2x + y` whose terms are repeated and out of order.
+        let mut builder = WitnessBuilder::new();
+        let z_var = builder.add_input(z);
+        let x_var = builder.add_witness(x);
+        let y_var = builder.add_witness(y);
+        builder.assert_r1cs(lc!() + x_var, lc!() + y_var, lc!() + z_var);
+        builder.assert_r1cs(
+            lc!() + y_var + x_var + x_var,
+            lc!() + Variable::One,
+            lc!() + y_var + (Fr::from(2u64), x_var),
+        );
+        let (assignment, a, b, c) = builder.build();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        cs.set_mode(SynthesisMode::Prove {
+            construct_matrices: true,
+        });
+        let cs_z = cs.new_input_variable(|| Ok(z)).unwrap();
+        let cs_x = cs.new_witness_variable(|| Ok(x)).unwrap();
+        let cs_y = cs.new_witness_variable(|| Ok(y)).unwrap();
+        assert_eq!((cs_z, cs_x, cs_y), (z_var, x_var, y_var));
+        cs.enforce_constraint(lc!() + cs_x, lc!() + cs_y, lc!() + cs_z)
+            .unwrap();
+        cs.enforce_constraint(
+            lc!() + cs_y + cs_x + cs_x,
+            lc!() + Variable::One,
+            lc!() + cs_y + (Fr::from(2u64), cs_x),
+        )
+        .unwrap();
+        cs.finalize();
+        assert!(cs.is_satisfied().unwrap());
+
+        let matrices = cs.to_matrices().unwrap();
+        assert_eq!(a, matrices.a);
+        assert_eq!(b, matrices.b);
+        assert_eq!(c, matrices.c);
+
+        let cs = cs.borrow().unwrap();
+        assert_eq!(assignment.input, cs.instance_assignment);
+        assert_eq!(assignment.witness, cs.witness_assignment);
+    }
+}
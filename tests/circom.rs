@@ -0,0 +1,48 @@
+use ark_accumulation::r1cs_nark_as::r1cs_nark::R1CSNark;
+use ark_ff::One;
+use ark_pallas::{Affine, Fq, Fr};
+use ark_sponge::poseidon::PoseidonSponge;
+use std::path::PathBuf;
+
+type Nark = R1CSNark<Affine, PoseidonSponge<Fq>>;
+
+// The `Multiplier` circuit, which enforces `c <== a * b` for the private inputs `a` and `b` and
+// the public output `c`, over the scalar field of Pallas. The witness has `a = 3` and `b = 11`.
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("data")
+        .join(name)
+}
+
+#[test]
+fn test_index_from_circom() {
+    let pp = Nark::setup();
+    let (ipk, ivk) = Nark::index_from_circom(&pp, &fixture("multiplier.r1cs")).unwrap();
+    let mut assignment = Nark::load_wtns(&ipk, &fixture("multiplier.wtns")).unwrap();
+    assert_eq!(assignment.input, vec![Fr::one(), Fr::from(33u64)]);
+    assert_eq!(assignment.witness, vec![Fr::from(3u64), Fr::from(11u64)]);
+    assert!(Nark::validate_assignment(&ivk, &assignment));
+
+    assignment.input[1] += Fr::one();
+    assert!(!Nark::validate_assignment(&ivk, &assignment));
+}
+
+#[test]
+fn test_malformed_circom_files() {
+    let pp = Nark::setup();
+    assert!(Nark::index_from_circom(&pp, &fixture("multiplier.wtns")).is_err());
+    assert!(Nark::index_from_circom(&pp, &fixture("missing.r1cs")).is_err());
+
+    let (ipk, _) = Nark::index_from_circom(&pp, &fixture("multiplier.r1cs")).unwrap();
+    assert!(Nark::load_wtns(&ipk, &fixture("multiplier.r1cs")).is_err());
+
+    // The witness of the multiplier has too many signals for `x_1 * 1 = w_1`.
+    let (a, b, c) = (
+        vec![vec![(Fr::one(), 1)]],
+        vec![vec![(Fr::one(), 0)]],
+        vec![vec![(Fr::one(), 2)]],
+    );
+    let (smaller_ipk, _) = Nark::index_from_matrices(&pp, a, b, c, 2, 1).unwrap();
+    assert!(Nark::load_wtns(&smaller_ipk, &fixture("multiplier.wtns")).is_err());
+}